@@ -0,0 +1,341 @@
+use bevy::prelude::*;
+use boid_wars_shared::*;
+use std::collections::VecDeque;
+
+use crate::LocalPlayer;
+
+// Snapshots kept per entity; at 30Hz this covers ~1s
+const MAX_SNAPSHOTS: usize = 32;
+
+/// One replicated state, stamped with its local arrival time
+#[derive(Debug, Clone, Copy)]
+pub struct Snapshot {
+    pub time: f64,
+    pub position: Vec2,
+    pub rotation: f32,
+    pub velocity: Vec2,
+}
+
+/// Recent snapshots for a remote entity, oldest first
+#[derive(Component, Debug, Default)]
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl SnapshotBuffer {
+    pub fn push(&mut self, snapshot: Snapshot) {
+        // Replication can deliver several changes in one frame; keep the latest
+        if let Some(last) = self.snapshots.back_mut() {
+            if snapshot.time <= last.time {
+                *last = snapshot;
+                return;
+            }
+        }
+
+        self.snapshots.push_back(snapshot);
+        while self.snapshots.len() > MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.snapshots.back()
+    }
+
+    /// Position and rotation at `render_time`.
+    ///
+    /// Interpolates between the snapshots around `render_time`; past the newest
+    /// snapshot it extrapolates with its velocity for at most `max_extrapolation`.
+    pub fn sample(&self, render_time: f64, max_extrapolation: f32) -> Option<(Vec2, f32)> {
+        let newest = self.snapshots.back()?;
+
+        if render_time >= newest.time {
+            let ahead = ((render_time - newest.time) as f32).min(max_extrapolation);
+            return Some((newest.position + newest.velocity * ahead, newest.rotation));
+        }
+
+        let after_index = self
+            .snapshots
+            .iter()
+            .position(|snapshot| snapshot.time > render_time)?;
+        let after = &self.snapshots[after_index];
+        let Some(before) = after_index
+            .checked_sub(1)
+            .map(|index| &self.snapshots[index])
+        else {
+            // Older than anything buffered
+            return Some((after.position, after.rotation));
+        };
+
+        let span = (after.time - before.time) as f32;
+        let t = if span > 0.0 {
+            ((render_time - before.time) as f32 / span).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let rotation = before.rotation + shortest_angle_delta(before.rotation, after.rotation) * t;
+
+        Some((before.position.lerp(after.position, t), rotation))
+    }
+
+    /// Drop snapshots no longer needed to render at `render_time`
+    pub fn prune(&mut self, render_time: f64) {
+        while self.snapshots.len() > 2 && self.snapshots[1].time <= render_time {
+            self.snapshots.pop_front();
+        }
+    }
+}
+
+/// Interpolation delay tuning, adapted from measured snapshot jitter
+#[derive(Resource, Debug, Clone)]
+pub struct InterpolationSettings {
+    /// Current delay behind the newest snapshots (seconds)
+    pub delay: f32,
+    pub min_delay: f32,
+    pub max_delay: f32,
+    /// Delay = mean interval + this many jitter deviations
+    pub jitter_multiplier: f32,
+    /// Longest time to extrapolate with velocity when snapshots are late
+    pub max_extrapolation: f32,
+    /// Smoothed snapshot interval (seconds)
+    pub mean_interval: f32,
+    /// Smoothed deviation of the snapshot interval (seconds)
+    pub jitter: f32,
+}
+
+impl Default for InterpolationSettings {
+    fn default() -> Self {
+        Self {
+            delay: 0.1,
+            min_delay: 0.05,
+            max_delay: 0.3,
+            jitter_multiplier: 2.0,
+            max_extrapolation: 0.25,
+            mean_interval: 1.0 / 30.0, // Server replication rate
+            jitter: 0.0,
+        }
+    }
+}
+
+impl InterpolationSettings {
+    /// Fold a measured snapshot interval into the jitter estimate
+    pub fn record_interval(&mut self, interval: f32) {
+        // Ignore gaps from entities that stopped changing
+        if interval <= 0.0 || interval > self.max_delay * 2.0 {
+            return;
+        }
+
+        const ALPHA: f32 = 0.05;
+        let deviation = (interval - self.mean_interval).abs();
+        self.mean_interval += (interval - self.mean_interval) * ALPHA;
+        self.jitter += (deviation - self.jitter) * ALPHA;
+    }
+
+    /// Delay the estimate asks for
+    pub fn target_delay(&self) -> f32 {
+        (self.mean_interval + self.jitter * self.jitter_multiplier)
+            .clamp(self.min_delay, self.max_delay)
+    }
+}
+
+type RemoteEntity = (Or<(With<Boid>, With<Player>)>, Without<LocalPlayer>);
+
+/// Record replicated state changes of remote entities
+#[allow(clippy::type_complexity)]
+fn record_snapshots(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &Position,
+            Option<&Rotation>,
+            Option<&Velocity>,
+            Option<&Player>,
+            Option<&mut SnapshotBuffer>,
+        ),
+        (
+            RemoteEntity,
+            Or<(Changed<Position>, Changed<Rotation>, Changed<Velocity>)>,
+        ),
+    >,
+    mut settings: ResMut<InterpolationSettings>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for (entity, position, rotation, velocity, player, buffer) in query.iter_mut() {
+        let velocity = velocity.map(|v| v.0).unwrap_or(Vec2::ZERO);
+
+        // Boids face where they fly; players face where they aim
+        let rotation = match rotation {
+            _ if player.is_none() && velocity.length_squared() > 0.1 => aim_angle(velocity),
+            Some(rotation) => rotation.angle,
+            None => buffer
+                .as_ref()
+                .and_then(|buffer| buffer.latest())
+                .map(|latest| latest.rotation)
+                .unwrap_or(0.0),
+        };
+
+        let snapshot = Snapshot {
+            time: now,
+            position: position.0,
+            rotation,
+            velocity,
+        };
+
+        match buffer {
+            Some(mut buffer) => {
+                if let Some(latest) = buffer.latest() {
+                    settings.record_interval((now - latest.time) as f32);
+                }
+                buffer.push(snapshot);
+            }
+            None => {
+                let mut buffer = SnapshotBuffer::default();
+                buffer.push(snapshot);
+                commands.entity(entity).insert(buffer);
+            }
+        }
+    }
+}
+
+/// Ease the delay towards what the measured jitter needs
+fn adapt_interpolation_delay(mut settings: ResMut<InterpolationSettings>, time: Res<Time>) {
+    let target = settings.target_delay();
+    let rate = (time.delta_secs() * 0.5).min(1.0);
+    settings.delay += (target - settings.delay) * rate;
+}
+
+/// Render remote entities a fixed delay in the past
+fn interpolate_snapshots(
+    mut query: Query<(&mut SnapshotBuffer, &mut Transform), Without<LocalPlayer>>,
+    settings: Res<InterpolationSettings>,
+    time: Res<Time>,
+) {
+    let render_time = time.elapsed_secs_f64() - settings.delay as f64;
+
+    for (mut buffer, mut transform) in query.iter_mut() {
+        let Some((position, rotation)) = buffer.sample(render_time, settings.max_extrapolation)
+        else {
+            continue;
+        };
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        transform.rotation = Quat::from_rotation_z(rotation);
+
+        buffer.prune(render_time);
+    }
+}
+
+/// Snapshot interpolation for boids and remote players
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InterpolationSettings>().add_systems(
+            Update,
+            (
+                record_snapshots,
+                adapt_interpolation_delay,
+                interpolate_snapshots,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(time: f64, x: f32, rotation: f32) -> Snapshot {
+        Snapshot {
+            time,
+            position: Vec2::new(x, 0.0),
+            rotation,
+            velocity: Vec2::new(100.0, 0.0),
+        }
+    }
+
+    fn buffer(snapshots: &[Snapshot]) -> SnapshotBuffer {
+        let mut buffer = SnapshotBuffer::default();
+        for snapshot in snapshots {
+            buffer.push(*snapshot);
+        }
+        buffer
+    }
+
+    /// One snapshot a second, at x = time
+    fn buffer_of(count: usize) -> SnapshotBuffer {
+        let snapshots: Vec<_> = (0..count)
+            .map(|i| snapshot(i as f64, i as f32, 0.0))
+            .collect();
+        buffer(&snapshots)
+    }
+
+    #[test]
+    fn test_sample_interpolates_between_snapshots() {
+        let buffer = buffer(&[snapshot(1.0, 0.0, 0.0), snapshot(1.1, 10.0, 0.4)]);
+
+        let (position, rotation) = buffer.sample(1.025, 0.25).unwrap();
+        assert!((position.x - 2.5).abs() < 0.001);
+        assert!((rotation - 0.1).abs() < 0.001);
+
+        // Before anything buffered, hold the oldest snapshot
+        let (position, _) = buffer.sample(0.5, 0.25).unwrap();
+        assert_eq!(position.x, 0.0);
+    }
+
+    #[test]
+    fn test_sample_turns_the_short_way_round() {
+        use std::f32::consts::PI;
+
+        let buffer = buffer(&[snapshot(1.0, 0.0, PI - 0.1), snapshot(1.1, 0.0, -PI + 0.1)]);
+        let (_, rotation) = buffer.sample(1.05, 0.25).unwrap();
+        assert!((rotation - PI).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_sample_extrapolates_for_a_limited_time() {
+        let buffer = buffer(&[snapshot(1.0, 0.0, 0.0), snapshot(1.1, 10.0, 0.0)]);
+
+        let (position, _) = buffer.sample(1.2, 0.25).unwrap();
+        assert!((position.x - 20.0).abs() < 0.001);
+
+        // Past the limit the entity stops where the limit left it
+        let (position, _) = buffer.sample(3.0, 0.25).unwrap();
+        assert!((position.x - 35.0).abs() < 0.001);
+
+        assert!(SnapshotBuffer::default().sample(1.0, 0.25).is_none());
+    }
+
+    #[test]
+    fn test_push_keeps_the_latest_of_a_frame() {
+        let buffer = buffer(&[snapshot(1.0, 0.0, 0.0), snapshot(1.0, 5.0, 0.0)]);
+        assert_eq!(buffer.snapshots.len(), 1);
+        assert_eq!(buffer.latest().unwrap().position.x, 5.0);
+
+        let buffer = buffer_of(MAX_SNAPSHOTS + 3);
+        assert_eq!(buffer.snapshots.len(), MAX_SNAPSHOTS);
+        assert_eq!(buffer.snapshots.front().unwrap().time, 3.0);
+    }
+
+    #[test]
+    fn test_prune_keeps_the_pair_around_render_time() {
+        let mut buffer = buffer_of(5);
+
+        buffer.prune(2.5);
+        let times: Vec<f64> = buffer.snapshots.iter().map(|s| s.time).collect();
+        assert_eq!(times, vec![2.0, 3.0, 4.0]);
+
+        // Still enough to sample where it was pruned
+        let (position, _) = buffer.sample(2.5, 0.25).unwrap();
+        assert!((position.x - 2.5).abs() < 0.001);
+
+        // Never below two, so extrapolation has something to go on
+        buffer.prune(100.0);
+        assert_eq!(buffer.snapshots.len(), 2);
+    }
+}
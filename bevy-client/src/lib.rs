@@ -1,4 +1,5 @@
 use bevy::asset::AssetMetaCheck;
+use bevy::diagnostic::DiagnosticsStore;
 use bevy::prelude::*;
 use boid_wars_shared::*;
 use lightyear::client::message::ReceiveMessage;
@@ -10,6 +11,16 @@ use wasm_bindgen::prelude::*;
 
 mod health_events;
 use health_events::HealthEventsPlugin;
+mod interpolation;
+use interpolation::{InterpolationPlugin, SnapshotBuffer};
+mod prediction;
+use prediction::{InputHistory, PredictionPlugin};
+mod spectator;
+use spectator::SpectatorPlugin;
+mod swarm;
+use swarm::SwarmRenderPlugin;
+mod zone;
+use zone::ZoneRenderPlugin;
 
 // Constants
 const PLAYER_SPRITE_SIZE: f32 = 64.0; // Actual sprite size after optimization
@@ -21,20 +32,15 @@ const PROJECTILE_SPRITE_SIZE: f32 = 18.0; // Actual projectile sprite size
 struct LocalPlayer;
 
 // Connection state tracking
-#[derive(Resource, Debug, Clone)]
+#[derive(Resource, Debug, Clone, Default)]
 enum ConnectionState {
+    #[default]
     Connecting,
     Connected,
     Disconnected { #[allow(dead_code)] reason: String },
     ServerFull { message: String },
 }
 
-impl Default for ConnectionState {
-    fn default() -> Self {
-        ConnectionState::Connecting
-    }
-}
-
 // UI Components
 #[derive(Component)]
 struct ServerFullUI;
@@ -42,13 +48,21 @@ struct ServerFullUI;
 #[derive(Component)]
 struct LobbyUI;
 
+#[derive(Component)]
+struct ResultsUI;
+
+// Results of the most recent round, shown until the next lobby
+#[derive(Resource, Default)]
+struct LastMatchResults(Option<MatchResults>);
+
 // Game state tracking
 #[derive(Resource)]
 struct ClientGameState {
     phase: boid_wars_shared::GamePhase,
     player_count: u8,
-    player1_ready: bool,
-    player2_ready: bool,
+    max_players: u8,
+    players: Vec<LobbyPlayerStatus>,
+    spectator_count: u8,
 }
 
 impl Default for ClientGameState {
@@ -56,8 +70,9 @@ impl Default for ClientGameState {
         Self {
             phase: boid_wars_shared::GamePhase::WaitingForPlayers,
             player_count: 0,
-            player1_ready: false,
-            player2_ready: false,
+            max_players: GAME_CONFIG.max_players,
+            players: Vec::new(),
+            spectator_count: 0,
         }
     }
 }
@@ -85,20 +100,6 @@ struct HealthBarLink {
     fill: Entity,
 }
 
-// Client-side smooth interpolation component
-#[derive(Component)]
-struct SmoothTransform {
-    previous_position: Vec2,
-    target_position: Vec2,
-    previous_rotation: f32,
-    target_rotation: f32,
-    interpolation_speed: f32,
-    // Enhanced smoothing
-    velocity: Vec2,
-    angular_velocity: f32,
-    smoothing_factor: f32,
-}
-
 // Client-side projectile tracking
 #[derive(Resource, Default)]
 struct ClientProjectileTracker {
@@ -155,6 +156,11 @@ struct ClientProjectile {
 pub fn run() {
     console_error_panic_hook::set_once();
 
+    // Report bad settings up front, before the first read of a global config
+    if let Err(errors) = install_from_env() {
+        panic!("Invalid configuration:\n{}", error_report(&errors));
+    }
+
     let mut app = App::new();
 
     // Add Bevy plugins optimized for WASM
@@ -188,6 +194,7 @@ pub fn run() {
     app.insert_resource(MyClientId(client_id));
     app.insert_resource(ConnectionState::default());
     app.insert_resource(ClientGameState::default());
+    app.init_resource::<LastMatchResults>();
     app.add_plugins(ClientPlugins::new(lightyear_config));
 
     // Add shared protocol
@@ -196,6 +203,21 @@ pub fn run() {
     // Add health events handling
     app.add_plugins(HealthEventsPlugin);
 
+    // Draw the battle-royale safe zone
+    app.add_plugins(ZoneRenderPlugin);
+
+    // Spectator role for dead players and overflow connections
+    app.add_plugins(SpectatorPlugin);
+
+    // Predict the local ship instead of waiting for the server round trip
+    app.add_plugins(PredictionPlugin);
+
+    // Render boids and remote players from a jitter-adaptive snapshot buffer
+    app.add_plugins(InterpolationPlugin);
+
+    // Stand-in swarms for distant groups sent as a single summary
+    app.add_plugins(SwarmRenderPlugin);
+
     // Initialize performance timer
     let client_settings = &*CLIENT_CONFIG;
     app.insert_resource(PerformanceTimer(Timer::from_seconds(
@@ -223,7 +245,9 @@ pub fn run() {
             handle_connection_events,
             handle_server_full_message,
             handle_game_state_updates,
+            handle_match_results,
             mark_local_player,
+            report_bandwidth,
         ),
     );
     
@@ -259,6 +283,7 @@ pub fn run() {
             cleanup_health_bars,
             server_full_ui,
             lobby_ui_system,
+            results_ui_system,
             handle_lobby_input,
         ),
     );
@@ -267,7 +292,6 @@ pub fn run() {
     app.add_systems(
         Update,
         (
-            smooth_interpolation_system,
             toggle_debug_display,
             debug_collision_system,
         ),
@@ -642,23 +666,44 @@ fn handle_server_full_message(
 fn handle_game_state_updates(
     mut message_events: EventReader<ReceiveMessage<GameStateUpdate>>,
     mut game_state: ResMut<ClientGameState>,
+    mut movement: ResMut<MovementRules>,
 ) {
     for message_event in message_events.read() {
         let update = &message_event.message;
+        // Predict with the rules the server simulates with
+        movement.set_if_neq(update.movement);
         game_state.phase = update.phase.clone();
         game_state.player_count = update.player_count;
-        game_state.player1_ready = update.player1_ready;
-        game_state.player2_ready = update.player2_ready;
-        
-        info!("Game state update: {:?}, players: {}/2, P1 ready: {}, P2 ready: {}", 
-              game_state.phase, game_state.player_count, 
-              game_state.player1_ready, game_state.player2_ready);
+        game_state.max_players = update.max_players;
+        game_state.players = update.players.clone();
+        game_state.spectator_count = update.spectator_count;
+
+        let ready_count = game_state.players.iter().filter(|p| p.ready).count();
+        info!(
+            "Game state update: {:?}, players: {}/{}, ready: {}",
+            game_state.phase, game_state.player_count, game_state.max_players, ready_count
+        );
         
         // Force change detection to trigger UI update
         game_state.set_changed();
     }
 }
 
+/// Store end-of-round results from the server
+fn handle_match_results(
+    mut message_events: EventReader<ReceiveMessage<MatchResults>>,
+    mut last_results: ResMut<LastMatchResults>,
+) {
+    for message_event in message_events.read() {
+        let results = &message_event.message;
+        info!(
+            "Round over ({:?}) after {:.0}s, winner: {:?}",
+            results.reason, results.duration_secs, results.winner
+        );
+        last_results.0 = Some(results.clone());
+    }
+}
+
 /// Display server full UI when needed
 fn server_full_ui(
     mut commands: Commands,
@@ -793,11 +838,10 @@ fn render_networked_entities(
             ));
         }
 
-        // Choose sprite based on player number
+        // Choose sprite based on player number, alternating between the two ship skins
         let sprite_handle = match player_number {
-            Some(PlayerNumber::Player1) => player_sprite.0.clone(),
-            Some(PlayerNumber::Player2) => player2_sprite.0.clone(),
-            None => player_sprite.0.clone(), // Default to player 1 sprite if no number
+            Some(PlayerNumber(number)) if number % 2 == 0 => player2_sprite.0.clone(),
+            _ => player_sprite.0.clone(), // Odd numbers (and missing numbers) use player 1 sprite
         };
 
         // Add visual components
@@ -950,140 +994,32 @@ fn render_networked_entities(
     }
 }
 
-/// Sync Position and Rotation components to Transform for rendering
+/// Sync Position to Transform for entities that are not interpolated
+///
+/// Boids and remote players are rendered from snapshot buffers (see
+/// `interpolation`), and the local ship from prediction.
 #[allow(clippy::type_complexity)]
 fn sync_position_to_transform(
-    mut commands: Commands,
     mut query: Query<
+        (&Position, Option<&Velocity>, &mut Transform, Option<&Projectile>),
         (
-            Entity,
-            &Position,
-            Option<&Rotation>,
-            Option<&Velocity>,
-            &mut Transform,
-            Option<&Player>,
-            Option<&Projectile>,
-            Option<&mut SmoothTransform>,
-            Option<&LocalPlayer>,
+            Without<SnapshotBuffer>,
+            Without<LocalPlayer>,
+            Without<ReplicatedGroup>,
+            Or<(Changed<Position>, Changed<Velocity>)>,
         ),
-        Or<(Changed<Position>, Changed<Rotation>, Changed<Velocity>)>,
     >,
 ) {
-    for (
-        entity,
-        position,
-        rotation,
-        velocity,
-        mut transform,
-        player,
-        projectile,
-        smooth_transform,
-        local_player,
-    ) in query.iter_mut()
-    {
-        // Check if this entity needs smooth interpolation (only boids, not players or projectiles)
-        let needs_smoothing = player.is_none() && projectile.is_none();
-
-        if needs_smoothing {
-            // Handle smooth interpolation for boids only
-            if let Some(mut smooth) = smooth_transform {
-                // Update targets
-                smooth.previous_position = smooth.target_position;
-                smooth.target_position = position.0;
-
-                // Calculate target rotation
-                let target_rotation = if let Some(vel) = velocity {
-                    if vel.length_squared() > 0.1 {
-                        vel.y.atan2(vel.x) - std::f32::consts::FRAC_PI_2
-                    } else if let Some(rot) = rotation {
-                        rot.angle
-                    } else {
-                        smooth.target_rotation
-                    }
-                } else if let Some(rot) = rotation {
-                    rot.angle
-                } else {
-                    smooth.target_rotation
-                };
-
-                smooth.previous_rotation = smooth.target_rotation;
-                smooth.target_rotation = target_rotation;
-            } else {
-                // First time seeing this boid - add SmoothTransform component
-                let target_rotation = if let Some(vel) = velocity {
-                    if vel.length_squared() > 0.1 {
-                        vel.y.atan2(vel.x) - std::f32::consts::FRAC_PI_2
-                    } else if let Some(rot) = rotation {
-                        rot.angle
-                    } else {
-                        0.0
-                    }
-                } else if let Some(rot) = rotation {
-                    rot.angle
-                } else {
-                    0.0
-                };
-
-                commands.entity(entity).insert(SmoothTransform {
-                    previous_position: position.0,
-                    target_position: position.0,
-                    previous_rotation: target_rotation,
-                    target_rotation,
-                    interpolation_speed: 8.0, // Slower for more smoothness
-                    velocity: Vec2::ZERO,
-                    angular_velocity: 0.0,
-                    smoothing_factor: 0.85, // Higher = more smoothing
-                });
-            }
-        } else {
-            // Players and projectiles get position updates
-            if player.is_some() {
-                // Players get light interpolation for 30Hz updates
-                let target_pos = position.0;
-                let current_pos = transform.translation.truncate();
-
-                // Smooth position interpolation (10% per frame)
-                let smoothed_pos = current_pos.lerp(target_pos, 0.1);
-                transform.translation.x = smoothed_pos.x;
-                transform.translation.y = smoothed_pos.y;
-            } else {
-                // Local players and projectiles get direct updates
-                transform.translation.x = position.x;
-                transform.translation.y = position.y;
-            }
-
-            // Handle rotation for projectiles
-            if projectile.is_some() {
-                // Use velocity direction for projectile rotation
-                if let Some(vel) = velocity {
-                    if vel.length_squared() > 0.1 {
-                        let angle = vel.y.atan2(vel.x) - std::f32::consts::FRAC_PI_2;
-                        transform.rotation = Quat::from_rotation_z(angle);
-                    }
-                }
-            }
-
-            // Sync rotation from server for players
-            // (Local player rotation is handled by update_player_rotation_to_mouse)
-            if let Some(rot) = rotation {
-                if player.is_some() && local_player.is_none() {
-                    // Players get light interpolation for smoother 30Hz updates
-                    let target_rotation = rot.angle;
-                    let current_rotation = transform.rotation.to_euler(EulerRot::ZYX).0;
-
-                    // Smooth rotation interpolation using shortest angular path (15% per frame)
-                    let mut angle_diff = target_rotation - current_rotation;
-                    // Normalize to [-π, π] using the shortest path (same as server)
-                    while angle_diff > std::f32::consts::PI {
-                        angle_diff -= 2.0 * std::f32::consts::PI;
-                    }
-                    while angle_diff < -std::f32::consts::PI {
-                        angle_diff += 2.0 * std::f32::consts::PI;
-                    }
-                    
-                    let interpolated_angle = current_rotation + angle_diff * 0.15;
-
-                    transform.rotation = Quat::from_rotation_z(interpolated_angle);
+    for (position, velocity, mut transform, projectile) in query.iter_mut() {
+        // Projectiles and obstacles get direct updates
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+
+        // Use velocity direction for projectile rotation
+        if projectile.is_some() {
+            if let Some(vel) = velocity {
+                if vel.length_squared() > 0.1 {
+                    transform.rotation = Quat::from_rotation_z(aim_angle(vel.0));
                 }
             }
         }
@@ -1093,7 +1029,7 @@ fn sync_position_to_transform(
 /// Update player sprite rotation to face mouse cursor
 #[allow(clippy::type_complexity)]
 fn update_player_rotation_to_mouse(
-    mut players: Query<(&Position, &mut Transform), (With<Player>, With<LocalPlayer>)>,
+    mut players: Query<&mut Transform, (With<Player>, With<LocalPlayer>)>,
     windows: Query<&Window>,
     cameras: Query<(&Camera, &GlobalTransform)>,
 ) {
@@ -1102,12 +1038,13 @@ fn update_player_rotation_to_mouse(
             // Convert cursor position to world coordinates
             if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) {
                 // Update rotation for local player only
-                for (position, mut transform) in players.iter_mut() {
-                    // Calculate direction from player to mouse
-                    let direction = (world_pos - position.0).normalize_or_zero();
+                for mut transform in players.iter_mut() {
+                    // Calculate direction from the predicted ship to mouse
+                    let direction =
+                        (world_pos - transform.translation.truncate()).normalize_or_zero();
                     if direction.length() > 0.1 {
                         // Calculate angle and apply sprite offset
-                        let angle = direction.y.atan2(direction.x) - std::f32::consts::FRAC_PI_2;
+                        let angle = aim_angle(direction);
                         transform.rotation = Quat::from_rotation_z(angle);
                     }
                 }
@@ -1117,13 +1054,15 @@ fn update_player_rotation_to_mouse(
 }
 
 /// Send player input to server
+#[allow(clippy::too_many_arguments)]
 fn send_player_input(
     mut connection: ResMut<ConnectionManager>,
     keys: Res<ButtonInput<KeyCode>>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     windows: Query<&Window>,
     cameras: Query<(&Camera, &GlobalTransform)>,
-    players: Query<&Position, (With<Player>, With<LocalPlayer>)>,
+    players: Query<&Transform, (With<Player>, With<LocalPlayer>)>,
+    mut history: ResMut<InputHistory>,
     mut input_timer: Local<f32>,
     time: Res<Time>,
 ) {
@@ -1156,10 +1095,11 @@ fn send_player_input(
         if let Some(cursor_pos) = window.cursor_position() {
             // Convert cursor position to world coordinates
             if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) {
-                // Get local player position
-                if let Ok(player_pos) = players.single() {
+                // Get local player position (predicted, so aim matches what's on screen)
+                if let Ok(player_transform) = players.single() {
                     // Calculate direction from player to mouse
-                    let direction = (world_pos - player_pos.0).normalize_or_zero();
+                    let direction =
+                        (world_pos - player_transform.translation.truncate()).normalize_or_zero();
                     if direction.length() > 0.1 {
                         aim = direction;
                     }
@@ -1178,13 +1118,37 @@ fn send_player_input(
     if *input_timer >= 0.04 {
         *input_timer = 0.0;
 
-        let input = PlayerInput::new(movement, aim, fire);
+        // Only tag inputs while we have a ship to predict
+        let sequence = if players.is_empty() {
+            0
+        } else {
+            history.push(movement)
+        };
+        let input = PlayerInput::new(movement, aim, fire).with_sequence(sequence);
 
         // Send input to server as a message
         let _ = connection.send_message::<UnreliableChannel, PlayerInput>(&input);
     }
 }
 
+/// Tell the server how much bandwidth this connection uses, for its metrics
+fn report_bandwidth(
+    mut connection: ResMut<ConnectionManager>,
+    diagnostics: Res<DiagnosticsStore>,
+    time: Res<Time>,
+    mut since_report: Local<f32>,
+) {
+    *since_report += time.delta_secs();
+    if *since_report < BANDWIDTH_REPORT_INTERVAL_SECS {
+        return;
+    }
+    *since_report = 0.0;
+
+    if let Some(bandwidth) = ClientBandwidth::from_diagnostics(&diagnostics) {
+        let _ = connection.send_message::<UnreliableChannel, _>(&bandwidth);
+    }
+}
+
 /// Debug system to count players and their positions
 fn debug_player_count(
     all_players: Query<(&Player, &Position, &Transform), With<Player>>,
@@ -1204,6 +1168,7 @@ fn debug_player_count(
 }
 
 /// Update health bars based on entity health
+#[allow(clippy::type_complexity)]
 fn update_health_bars(
     // Query for player health bars
     mut player_fill_query: Query<(&mut Sprite, &PlayerHealthBar), With<HealthBarFill>>,
@@ -1311,64 +1276,6 @@ fn cleanup_health_bars(
     }
 }
 
-/// Enhanced smooth interpolation system for boids with velocity-based smoothing
-#[allow(clippy::type_complexity)]
-fn smooth_interpolation_system(
-    mut query: Query<
-        (&mut Transform, &mut SmoothTransform),
-        (With<SmoothTransform>, Without<Player>),
-    >,
-    time: Res<Time>,
-) {
-    let delta_time = time.delta_secs();
-
-    for (mut transform, mut smooth) in query.iter_mut() {
-        let current_pos = Vec2::new(transform.translation.x, transform.translation.y);
-        let current_rotation = transform.rotation.to_euler(EulerRot::ZYX).0;
-
-        // Calculate distance to target for adaptive smoothing
-        let distance_to_target = (smooth.target_position - current_pos).length();
-
-        // Adaptive smoothing - less smoothing when far from target, more when close
-        let adaptive_factor = (distance_to_target / 100.0).clamp(0.2, 1.0);
-        let base_lerp_factor = smooth.interpolation_speed * delta_time * adaptive_factor;
-
-        // Velocity-based smoothing for position
-        let target_velocity = (smooth.target_position - current_pos) * smooth.interpolation_speed;
-        smooth.velocity = smooth.velocity.lerp(target_velocity, base_lerp_factor);
-
-        // Apply velocity with additional smoothing
-        let velocity_factor = smooth.smoothing_factor;
-        let new_pos = current_pos + smooth.velocity * delta_time * velocity_factor;
-
-        // Additional exponential smoothing towards target
-        let final_pos = new_pos.lerp(smooth.target_position, base_lerp_factor * 0.3);
-
-        transform.translation.x = final_pos.x;
-        transform.translation.y = final_pos.y;
-
-        // Enhanced rotation smoothing with angular velocity
-        let mut rotation_diff = smooth.target_rotation - current_rotation;
-
-        // Handle rotation wrap-around (shortest path between angles)
-        while rotation_diff > std::f32::consts::PI {
-            rotation_diff -= 2.0 * std::f32::consts::PI;
-        }
-        while rotation_diff < -std::f32::consts::PI {
-            rotation_diff += 2.0 * std::f32::consts::PI;
-        }
-
-        // Angular velocity smoothing
-        let target_angular_velocity = rotation_diff * smooth.interpolation_speed;
-        smooth.angular_velocity = smooth.angular_velocity * smooth.smoothing_factor
-            + target_angular_velocity * (1.0 - smooth.smoothing_factor);
-
-        // Apply angular velocity with damping
-        let new_rotation = current_rotation + smooth.angular_velocity * delta_time * 0.7;
-        transform.rotation = Quat::from_rotation_z(new_rotation);
-    }
-}
-
 // Handle camera zoom with mouse wheel (DISABLED)
 /*
 fn handle_camera_zoom(
@@ -1668,9 +1575,18 @@ fn lobby_ui_system(
                 // Player count
                 let player_text = match game_state.player_count {
                     0 => "Waiting for players...".to_string(),
-                    1 => "1/2 Players - Waiting for another player...".to_string(),
-                    2 => "2/2 Players - Press R when ready!".to_string(),
-                    _ => format!("{}/2 Players", game_state.player_count),
+                    count if game_state.phase == boid_wars_shared::GamePhase::Lobby => format!(
+                        "{}/{} Players - Press R when ready!",
+                        count, game_state.max_players
+                    ),
+                    count => format!(
+                        "{}/{} Players - Waiting for more players...",
+                        count, game_state.max_players
+                    ),
+                };
+                let player_text = match game_state.spectator_count {
+                    0 => player_text,
+                    spectators => format!("{player_text}\n{spectators} spectating"),
                 };
                 
                 parent.spawn((
@@ -1688,12 +1604,21 @@ fn lobby_ui_system(
                 
                 // Ready status
                 if game_state.phase == boid_wars_shared::GamePhase::Lobby {
+                    let ready_text = game_state
+                        .players
+                        .iter()
+                        .map(|status| {
+                            format!(
+                                "Player {}: {}",
+                                status.player_number,
+                                if status.ready { "READY ✓" } else { "Not Ready" }
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+
                     parent.spawn((
-                        Text::new(format!(
-                            "Player 1: {}\nPlayer 2: {}",
-                            if game_state.player1_ready { "READY ✓" } else { "Not Ready" },
-                            if game_state.player2_ready { "READY ✓" } else { "Not Ready" }
-                        )),
+                        Text::new(ready_text),
                         TextFont {
                             font_size: 24.0,
                             ..default()
@@ -1729,6 +1654,131 @@ fn lobby_ui_system(
     }
 }
 
+/// Display the game over banner and end-of-round results
+fn results_ui_system(
+    mut commands: Commands,
+    game_state: Res<ClientGameState>,
+    last_results: Res<LastMatchResults>,
+    my_client_id: Res<MyClientId>,
+    query: Query<Entity, With<ResultsUI>>,
+) {
+    let should_show = matches!(
+        game_state.phase,
+        boid_wars_shared::GamePhase::GameOver | boid_wars_shared::GamePhase::Results
+    );
+
+    if !should_show {
+        for entity in query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    if !game_state.is_changed() && !last_results.is_changed() && !query.is_empty() {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let results = last_results
+        .0
+        .as_ref()
+        .filter(|_| game_state.phase == boid_wars_shared::GamePhase::Results);
+
+    let title = match results.and_then(|results| results.winner) {
+        Some(winner) if winner == my_client_id.0 => "VICTORY".to_string(),
+        Some(winner) => {
+            let number = results
+                .and_then(|results| results.players.iter().find(|p| p.player_id == winner))
+                .map(|p| p.player_number)
+                .unwrap_or_default();
+            format!("PLAYER {number} WINS")
+        }
+        None => "ROUND OVER".to_string(),
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            ResultsUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(title),
+                TextFont {
+                    font_size: 48.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.85, 0.3)),
+                Node {
+                    margin: UiRect::all(Val::Px(20.0)),
+                    ..default()
+                },
+            ));
+
+            let Some(results) = results else {
+                return;
+            };
+
+            let table = results
+                .players
+                .iter()
+                .map(|p| {
+                    format!(
+                        "#{}  Player {}{}  -  {} kills  {:.0} damage  {:.0}s",
+                        p.placement,
+                        p.player_number,
+                        if p.player_id == my_client_id.0 { " (you)" } else { "" },
+                        p.kills,
+                        p.damage_dealt,
+                        p.survival_secs
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            parent.spawn((
+                Text::new(table),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                Node {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+            ));
+
+            parent.spawn((
+                Text::new(format!(
+                    "Returning to lobby in {:.0}s",
+                    results.next_round_in
+                )),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.5, 0.5, 0.8)),
+                Node {
+                    margin: UiRect::top(Val::Px(30.0)),
+                    ..default()
+                },
+            ));
+        });
+}
+
 /// Handle lobby input (R key for ready)
 fn handle_lobby_input(
     keys: Res<ButtonInput<KeyCode>>,
@@ -0,0 +1,313 @@
+use bevy::prelude::*;
+use boid_wars_shared::*;
+use std::collections::VecDeque;
+
+use crate::LocalPlayer;
+
+// Inputs kept for replay, ~2.5s at the 25Hz send rate
+const MAX_PENDING_INPUTS: usize = 64;
+// Corrections larger than this snap instead of blending (teleports, respawns)
+const SNAP_DISTANCE: f32 = 150.0;
+// How fast leftover correction error fades out (per second)
+const CORRECTION_DECAY: f32 = 12.0;
+
+/// An input sent to the server and how long we have predicted with it
+#[derive(Debug, Clone, Copy)]
+struct PendingInput {
+    sequence: u32,
+    movement: Vec2,
+    duration: f32,
+}
+
+/// Inputs the server has not acknowledged yet, oldest first
+#[derive(Resource, Default)]
+pub struct InputHistory {
+    next_sequence: u32,
+    pending: VecDeque<PendingInput>,
+}
+
+impl InputHistory {
+    /// Record a new input and return the sequence number to send with it
+    pub fn push(&mut self, movement: Vec2) -> u32 {
+        self.next_sequence += 1;
+        self.pending.push_back(PendingInput {
+            sequence: self.next_sequence,
+            movement,
+            duration: 0.0,
+        });
+        while self.pending.len() > MAX_PENDING_INPUTS {
+            self.pending.pop_front();
+        }
+        self.next_sequence
+    }
+
+    /// Movement of the input currently in effect
+    pub fn current_movement(&self) -> Option<Vec2> {
+        self.pending.back().map(|input| input.movement)
+    }
+
+    /// Account `dt` seconds of prediction to the current input
+    pub fn advance(&mut self, dt: f32) {
+        if let Some(input) = self.pending.back_mut() {
+            input.duration += dt;
+        }
+    }
+
+    /// Forget inputs older than the acknowledged one.
+    ///
+    /// The acknowledged input stays so its unsimulated remainder can be replayed.
+    pub fn acknowledge(&mut self, sequence: u32) {
+        while self
+            .pending
+            .front()
+            .is_some_and(|input| input.sequence < sequence)
+        {
+            self.pending.pop_front();
+        }
+    }
+
+    /// Re-run unacknowledged movement on top of an authoritative state
+    pub fn replay(&self, ack: &InputAck, authoritative: Vec2, rules: &MovementRules) -> Vec2 {
+        self.pending
+            .iter()
+            .filter(|input| input.sequence >= ack.sequence)
+            .fold(authoritative, |position, input| {
+                let duration = if input.sequence == ack.sequence {
+                    (input.duration - ack.applied_secs).max(0.0)
+                } else {
+                    input.duration
+                };
+                rules.step_player_position(position, input.movement, duration)
+            })
+    }
+}
+
+/// Locally predicted state of our own ship
+#[derive(Component, Debug)]
+pub struct PredictedShip {
+    pub position: Vec2,
+    /// Visual offset left over from the last correction, fades to zero
+    pub correction: Vec2,
+}
+
+/// Start predicting once the local ship is identified
+#[allow(clippy::type_complexity)]
+fn init_prediction(
+    mut commands: Commands,
+    ships: Query<(Entity, &Position), (With<LocalPlayer>, Without<PredictedShip>)>,
+    mut history: ResMut<InputHistory>,
+) {
+    for (entity, position) in ships.iter() {
+        history.pending.clear();
+        commands.entity(entity).insert(PredictedShip {
+            position: position.0,
+            correction: Vec2::ZERO,
+        });
+    }
+}
+
+/// Rewind to the server state and replay unacknowledged inputs
+#[allow(clippy::type_complexity)]
+fn reconcile_local_ship(
+    mut ships: Query<
+        (&Position, &InputAck, &mut PredictedShip),
+        (
+            With<LocalPlayer>,
+            Or<(Changed<Position>, Changed<InputAck>)>,
+        ),
+    >,
+    mut history: ResMut<InputHistory>,
+    rules: Res<MovementRules>,
+) {
+    for (position, ack, mut ship) in ships.iter_mut() {
+        history.acknowledge(ack.sequence);
+        let corrected = history.replay(ack, position.0, &rules);
+
+        let error = ship.position - corrected;
+        ship.correction = if error.length() > SNAP_DISTANCE {
+            Vec2::ZERO
+        } else {
+            ship.correction + error
+        };
+        ship.position = corrected;
+    }
+}
+
+/// Move the local ship immediately with the input we just sent
+fn predict_local_ship(
+    mut ships: Query<&mut PredictedShip, With<LocalPlayer>>,
+    mut history: ResMut<InputHistory>,
+    rules: Res<MovementRules>,
+    time: Res<Time>,
+) {
+    let Some(movement) = history.current_movement() else {
+        return;
+    };
+    let dt = time.delta_secs();
+    history.advance(dt);
+
+    for mut ship in ships.iter_mut() {
+        ship.position = rules.step_player_position(ship.position, movement, dt);
+    }
+}
+
+/// Render the predicted position, blending out correction error
+fn apply_predicted_transform(
+    mut ships: Query<(&mut PredictedShip, &mut Transform), With<LocalPlayer>>,
+    time: Res<Time>,
+) {
+    let decay = (-CORRECTION_DECAY * time.delta_secs()).exp();
+
+    for (mut ship, mut transform) in ships.iter_mut() {
+        ship.correction *= decay;
+        let rendered = ship.position + ship.correction;
+        transform.translation.x = rendered.x;
+        transform.translation.y = rendered.y;
+    }
+}
+
+/// Client-side prediction and server reconciliation for the local ship
+pub struct PredictionPlugin;
+
+impl Plugin for PredictionPlugin {
+    fn build(&self, app: &mut App) {
+        // Replaced by the server's rules with its first game state update
+        app.init_resource::<InputHistory>()
+            .init_resource::<MovementRules>()
+            .add_systems(
+                Update,
+                (
+                    init_prediction,
+                    reconcile_local_ship,
+                    predict_local_ship,
+                    apply_predicted_transform,
+                )
+                    .chain()
+                    .after(crate::send_player_input),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ack(sequence: u32, applied_secs: f32) -> InputAck {
+        InputAck {
+            sequence,
+            applied_secs,
+        }
+    }
+
+    fn sequences(history: &InputHistory) -> Vec<u32> {
+        history.pending.iter().map(|input| input.sequence).collect()
+    }
+
+    #[test]
+    fn test_acknowledge_keeps_the_acked_input() {
+        let mut history = InputHistory::default();
+        for _ in 0..3 {
+            history.push(Vec2::X);
+        }
+        assert_eq!(sequences(&history), vec![1, 2, 3]);
+
+        history.acknowledge(2);
+        assert_eq!(sequences(&history), vec![2, 3]);
+
+        // Acks can arrive late; an older one changes nothing
+        history.acknowledge(1);
+        assert_eq!(sequences(&history), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_push_drops_the_oldest_past_the_limit() {
+        let mut history = InputHistory::default();
+        for _ in 0..MAX_PENDING_INPUTS + 5 {
+            history.push(Vec2::X);
+        }
+        assert_eq!(history.pending.len(), MAX_PENDING_INPUTS);
+        assert_eq!(history.pending.front().unwrap().sequence, 6);
+    }
+
+    #[test]
+    fn test_replay_skips_what_the_server_already_simulated() {
+        let rules = MovementRules::default();
+        let mut history = InputHistory::default();
+        history.push(Vec2::X);
+        history.advance(0.5);
+        history.push(Vec2::Y);
+        history.advance(0.25);
+
+        // The server is 0.2s into the first input
+        let start = Vec2::new(400.0, 300.0);
+        let replayed = history.replay(&ack(1, 0.2), start, &rules);
+        let expected = rules.step_player_position(
+            rules.step_player_position(start, Vec2::X, 0.3),
+            Vec2::Y,
+            0.25,
+        );
+        assert!(replayed.distance(expected) < 0.001);
+
+        // Fully applied input leaves only the newer one to replay
+        let replayed = history.replay(&ack(1, 0.5), start, &rules);
+        let expected = rules.step_player_position(start, Vec2::Y, 0.25);
+        assert!(replayed.distance(expected) < 0.001);
+    }
+
+    fn reconcile_app(predicted: Vec2, server: Vec2, ack: InputAck) -> (App, Entity) {
+        let mut app = App::new();
+        app.init_resource::<InputHistory>()
+            .init_resource::<MovementRules>()
+            .add_systems(Update, reconcile_local_ship);
+        let ship = app
+            .world_mut()
+            .spawn((
+                LocalPlayer,
+                Position(server),
+                ack,
+                PredictedShip {
+                    position: predicted,
+                    correction: Vec2::ZERO,
+                },
+            ))
+            .id();
+        (app, ship)
+    }
+
+    #[test]
+    fn test_reconcile_replays_onto_the_server_position() {
+        let rules = MovementRules::default();
+        let server = Vec2::new(400.0, 300.0);
+        let (mut app, ship) = reconcile_app(Vec2::new(450.0, 300.0), server, ack(2, 0.1));
+        {
+            let mut history = app.world_mut().resource_mut::<InputHistory>();
+            history.push(Vec2::Y);
+            history.advance(0.2);
+            history.push(Vec2::X);
+            history.advance(0.3);
+        }
+
+        app.update();
+
+        let expected = rules.step_player_position(server, Vec2::X, 0.2);
+        let predicted = app.world().get::<PredictedShip>(ship).unwrap();
+        assert!(predicted.position.distance(expected) < 0.001);
+        // The jump is hidden behind a correction that fades out
+        let error = Vec2::new(450.0, 300.0) - expected;
+        assert!(predicted.correction.distance(error) < 0.001);
+        assert_eq!(sequences(app.world().resource::<InputHistory>()), vec![2]);
+    }
+
+    #[test]
+    fn test_reconcile_snaps_large_errors() {
+        let server = Vec2::new(400.0, 300.0);
+        let far = server + Vec2::X * (SNAP_DISTANCE + 50.0);
+        let (mut app, ship) = reconcile_app(far, server, ack(0, 0.0));
+
+        app.update();
+
+        let predicted = app.world().get::<PredictedShip>(ship).unwrap();
+        assert_eq!(predicted.position, server);
+        assert_eq!(predicted.correction, Vec2::ZERO);
+    }
+}
@@ -0,0 +1,245 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use boid_wars_shared::*;
+use lightyear::client::message::ReceiveMessage;
+use tracing::info;
+
+// Free camera pan speed in world units per second at zoom 1.0
+const FREE_CAMERA_SPEED: f32 = 600.0;
+// How quickly the follow camera catches up with its target
+const FOLLOW_SMOOTHING: f32 = 8.0;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+
+/// How the spectator camera moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpectatorCamera {
+    /// Track a living player, cycled with Tab
+    #[default]
+    Follow,
+    /// Pan with WASD / arrow keys
+    Free,
+}
+
+/// Local spectator state, driven by `SpectatorStatus` from the server
+#[derive(Resource, Default)]
+pub struct SpectatorState {
+    /// Why we are spectating, `None` while playing
+    pub reason: Option<SpectateReason>,
+    pub camera: SpectatorCamera,
+    /// Player ID currently followed
+    pub target: Option<u64>,
+}
+
+impl SpectatorState {
+    pub fn is_spectating(&self) -> bool {
+        self.reason.is_some()
+    }
+}
+
+#[derive(Component)]
+struct SpectatorHud;
+
+/// Apply spectator status changes from the server
+fn handle_spectator_status(
+    mut message_events: EventReader<ReceiveMessage<SpectatorStatus>>,
+    mut state: ResMut<SpectatorState>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    for message_event in message_events.read() {
+        let reason = message_event.message.reason;
+        info!("Spectator status: {:?}", reason);
+
+        state.reason = reason;
+        state.target = None;
+
+        // Back to the fixed arena view when we get a ship again
+        if reason.is_none() {
+            let game_config = &*GAME_CONFIG;
+            for mut transform in cameras.iter_mut() {
+                transform.translation.x = game_config.game_width / 2.0;
+                transform.translation.y = game_config.game_height / 2.0;
+                transform.scale = Vec3::ONE;
+            }
+        }
+    }
+}
+
+/// Tab cycles follow targets, F toggles the free camera, mouse wheel zooms
+fn spectator_controls(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut state: ResMut<SpectatorState>,
+    players: Query<&Player>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    if !state.is_spectating() {
+        scroll_events.clear();
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyF) {
+        state.camera = match state.camera {
+            SpectatorCamera::Follow => SpectatorCamera::Free,
+            SpectatorCamera::Free => SpectatorCamera::Follow,
+        };
+    }
+
+    if keys.just_pressed(KeyCode::Tab) {
+        let mut ids: Vec<u64> = players.iter().map(|player| player.id).collect();
+        ids.sort_unstable();
+
+        let next = match state
+            .target
+            .and_then(|target| ids.iter().position(|id| *id == target))
+        {
+            Some(index) => ids.get((index + 1) % ids.len()).copied(),
+            None => ids.first().copied(),
+        };
+        state.target = next;
+        state.camera = SpectatorCamera::Follow;
+    }
+
+    for event in scroll_events.read() {
+        for mut transform in cameras.iter_mut() {
+            let scale = (transform.scale.x - event.y * 0.1).clamp(MIN_ZOOM, MAX_ZOOM);
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+}
+
+/// Move the camera for the current spectator mode
+fn spectator_camera_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<SpectatorState>,
+    players: Query<(&Player, &Transform), Without<Camera2d>>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+    time: Res<Time>,
+) {
+    if !state.is_spectating() {
+        return;
+    }
+
+    let Ok(mut camera) = cameras.single_mut() else {
+        return;
+    };
+    let dt = time.delta_secs();
+
+    match state.camera {
+        SpectatorCamera::Follow => {
+            // Fall back to the lowest ID if the target died or left
+            let target = state
+                .target
+                .and_then(|id| players.iter().find(|(player, _)| player.id == id))
+                .or_else(|| players.iter().min_by_key(|(player, _)| player.id));
+
+            let Some((player, transform)) = target else {
+                return;
+            };
+            state.target = Some(player.id);
+
+            let goal = transform.translation.truncate();
+            let current = camera.translation.truncate();
+            let t = (FOLLOW_SMOOTHING * dt).min(1.0);
+            let position = current.lerp(goal, t);
+            camera.translation.x = position.x;
+            camera.translation.y = position.y;
+        }
+        SpectatorCamera::Free => {
+            let mut direction = Vec2::ZERO;
+            if keys.pressed(KeyCode::KeyW) || keys.pressed(KeyCode::ArrowUp) {
+                direction.y += 1.0;
+            }
+            if keys.pressed(KeyCode::KeyS) || keys.pressed(KeyCode::ArrowDown) {
+                direction.y -= 1.0;
+            }
+            if keys.pressed(KeyCode::KeyA) || keys.pressed(KeyCode::ArrowLeft) {
+                direction.x -= 1.0;
+            }
+            if keys.pressed(KeyCode::KeyD) || keys.pressed(KeyCode::ArrowRight) {
+                direction.x += 1.0;
+            }
+
+            let game_config = &*GAME_CONFIG;
+            let step = direction.normalize_or_zero() * FREE_CAMERA_SPEED * camera.scale.x * dt;
+            camera.translation.x =
+                (camera.translation.x + step.x).clamp(0.0, game_config.game_width);
+            camera.translation.y =
+                (camera.translation.y + step.y).clamp(0.0, game_config.game_height);
+        }
+    }
+}
+
+/// Banner showing who we are watching and the controls
+fn spectator_hud(
+    mut commands: Commands,
+    state: Res<SpectatorState>,
+    players: Query<(&Player, Option<&PlayerNumber>)>,
+    mut hud: Query<(Entity, &mut Text), With<SpectatorHud>>,
+) {
+    let Some(reason) = state.reason else {
+        for (entity, _) in hud.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+
+    let heading = match reason {
+        SpectateReason::ServerFull => "SPECTATING - server full, waiting for a slot",
+        SpectateReason::MatchInProgress => "SPECTATING - you will join the next round",
+        SpectateReason::Eliminated => "ELIMINATED - spectating",
+    };
+    let watching = match state.camera {
+        SpectatorCamera::Free => "Free camera".to_string(),
+        SpectatorCamera::Follow => state
+            .target
+            .and_then(|id| players.iter().find(|(player, _)| player.id == id))
+            .map(|(player, number)| match number {
+                Some(number) => format!("Following Player {}", number.0),
+                None => format!("Following {}", player.name),
+            })
+            .unwrap_or_else(|| "No players alive".to_string()),
+    };
+    let text = format!("{heading}\n{watching}  |  Tab: next player  F: free camera  Wheel: zoom");
+
+    if let Ok((_, mut hud_text)) = hud.single_mut() {
+        if hud_text.0 != text {
+            hud_text.0 = text;
+        }
+        return;
+    }
+
+    commands.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        SpectatorHud,
+    ));
+}
+
+/// Plugin for watching the match without a ship
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpectatorState>().add_systems(
+            Update,
+            (
+                handle_spectator_status,
+                spectator_controls,
+                spectator_camera_system,
+                spectator_hud,
+            )
+                .chain(),
+        );
+    }
+}
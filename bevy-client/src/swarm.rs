@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use boid_wars_shared::*;
+
+use crate::{EnemySprite, EnemySprite2, EnemySprite3, BOID_SPRITE_SIZE};
+
+// How quickly a stand-in swarm catches up with its replicated center
+const SWARM_FOLLOW_SMOOTHING: f32 = 4.0;
+// How quickly the swarm turns to face its direction of travel
+const SWARM_TURN_SMOOTHING: f32 = 3.0;
+// Idle drift of each stand-in boid around its formation slot
+const SWARM_WOBBLE: f32 = 4.0;
+
+/// Layout a stand-in swarm was built for
+#[derive(Component)]
+struct SwarmLayout {
+    formation: Formation,
+    member_count: u32,
+    /// Last replicated center, used to derive the heading
+    last_position: Vec2,
+    /// Angle the formation should face
+    heading: f32,
+}
+
+/// A synthesized boid sprite inside a distant group
+#[derive(Component)]
+struct SwarmMember {
+    slot: usize,
+    offset: Vec2,
+}
+
+/// Same sizes the server gives each archetype's boids
+fn archetype_scale(archetype: &GroupArchetype) -> f32 {
+    match archetype {
+        GroupArchetype::Assault { .. } => 1.2,
+        GroupArchetype::Defensive { .. } => 1.4,
+        GroupArchetype::Recon { .. } => 0.8,
+    }
+}
+
+/// Build or rebuild swarms when a group summary arrives or its layout changes
+#[allow(clippy::type_complexity)]
+fn update_swarm_layouts(
+    mut commands: Commands,
+    mut groups: Query<
+        (Entity, &ReplicatedGroup, Option<&mut SwarmLayout>),
+        Changed<ReplicatedGroup>,
+    >,
+    enemy_sprite: Res<EnemySprite>,
+    enemy_sprite2: Res<EnemySprite2>,
+    enemy_sprite3: Res<EnemySprite3>,
+) {
+    for (entity, group, layout) in groups.iter_mut() {
+        let rebuild = match layout {
+            Some(mut layout) => {
+                let travel = group.position - layout.last_position;
+                if travel.length_squared() > 1.0 {
+                    layout.heading = aim_angle(travel);
+                    layout.last_position = group.position;
+                }
+
+                let changed = layout.formation != group.formation
+                    || layout.member_count != group.member_count;
+                if changed {
+                    layout.formation = group.formation.clone();
+                    layout.member_count = group.member_count;
+                    commands.entity(entity).despawn_related::<Children>();
+                }
+                changed
+            }
+            None => {
+                commands.entity(entity).insert((
+                    SwarmLayout {
+                        formation: group.formation.clone(),
+                        member_count: group.member_count,
+                        last_position: group.position,
+                        heading: 0.0,
+                    },
+                    Transform::from_translation(group.position.extend(13.0)),
+                    Visibility::default(),
+                ));
+                true
+            }
+        };
+
+        if !rebuild {
+            continue;
+        }
+
+        // Same skins as individually replicated boids of this group
+        let image = match group.id {
+            1 => enemy_sprite2.0.clone(),
+            2 => enemy_sprite3.0.clone(),
+            _ => enemy_sprite.0.clone(),
+        };
+        let size = Vec2::splat(BOID_SPRITE_SIZE * archetype_scale(&group.archetype));
+
+        let offsets = calculate_formation_positions(&group.formation, group.member_count as usize);
+        commands.entity(entity).with_children(|parent| {
+            for (slot, offset) in offsets.into_iter().enumerate() {
+                parent.spawn((
+                    Sprite {
+                        image: image.clone(),
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    Transform::from_translation(offset.extend(0.0)),
+                    SwarmMember { slot, offset },
+                ));
+            }
+        });
+    }
+}
+
+/// Glide swarms towards their replicated center and keep the members drifting
+fn animate_swarms(
+    mut groups: Query<(&ReplicatedGroup, &SwarmLayout, &mut Transform), Without<SwarmMember>>,
+    mut members: Query<(&SwarmMember, &mut Transform), Without<SwarmLayout>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+
+    for (group, layout, mut transform) in groups.iter_mut() {
+        let current = transform.translation.truncate();
+        let position = current.lerp(group.position, (SWARM_FOLLOW_SMOOTHING * dt).min(1.0));
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+
+        let angle = transform.rotation.to_euler(EulerRot::ZYX).0;
+        let turn = shortest_angle_delta(angle, layout.heading);
+        transform.rotation =
+            Quat::from_rotation_z(angle + turn * (SWARM_TURN_SMOOTHING * dt).min(1.0));
+    }
+
+    let t = time.elapsed_secs();
+    for (member, mut transform) in members.iter_mut() {
+        let phase = member.slot as f32 * 0.7;
+        let wobble = Vec2::new((t * 1.3 + phase).sin(), (t * 1.1 + phase * 1.3).cos());
+        let position = member.offset + wobble * SWARM_WOBBLE;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+/// Stand-in rendering for boid groups the server only sends as a summary
+pub struct SwarmRenderPlugin;
+
+impl Plugin for SwarmRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (update_swarm_layouts, animate_swarms).chain());
+    }
+}
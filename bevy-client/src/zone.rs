@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use boid_wars_shared::*;
+
+// Ring thickness in world units
+const ZONE_EDGE_WIDTH: f32 = 4.0;
+const NEXT_ZONE_EDGE_WIDTH: f32 = 2.0;
+
+/// Which part of the zone a visual represents
+#[derive(Component, Clone, Copy, PartialEq)]
+enum ZoneVisual {
+    /// Shaded area outside the safe circle
+    Outside,
+    /// Edge of the current safe circle
+    Edge,
+    /// Edge of the circle the zone is shrinking to
+    NextEdge,
+}
+
+/// Draw the replicated safe zone
+fn update_zone_visuals(
+    mut commands: Commands,
+    zones: Query<&SafeZone, Changed<SafeZone>>,
+    mut visuals: Query<(Entity, &ZoneVisual, &mut Transform, &mut Mesh2d)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let Some(zone) = zones.iter().next() else {
+        return;
+    };
+
+    let game_config = &*GAME_CONFIG;
+    // Far enough out to cover the arena from any zone center
+    let outer_radius = Vec2::new(game_config.game_width, game_config.game_height).length() * 2.0;
+
+    let rings = [
+        (
+            ZoneVisual::Outside,
+            zone.center,
+            Annulus::new(zone.radius, zone.radius.max(1.0) + outer_radius),
+            Color::srgba(0.8, 0.1, 0.1, 0.25),
+            12.0,
+        ),
+        (
+            ZoneVisual::Edge,
+            zone.center,
+            Annulus::new((zone.radius - ZONE_EDGE_WIDTH).max(0.0), zone.radius),
+            Color::srgba(1.0, 0.3, 0.3, 0.9),
+            12.1,
+        ),
+        (
+            ZoneVisual::NextEdge,
+            zone.next_center,
+            Annulus::new(
+                (zone.next_radius - NEXT_ZONE_EDGE_WIDTH).max(0.0),
+                zone.next_radius,
+            ),
+            Color::srgba(1.0, 1.0, 1.0, 0.6),
+            12.1,
+        ),
+    ];
+
+    for (kind, center, shape, color, z) in rings {
+        let existing = visuals
+            .iter_mut()
+            .find(|(_, visual, _, _)| **visual == kind);
+
+        if let Some((_, _, mut transform, mut mesh)) = existing {
+            transform.translation = center.extend(z);
+            mesh.0 = meshes.add(shape);
+        } else {
+            commands.spawn((
+                kind,
+                Mesh2d(meshes.add(shape)),
+                MeshMaterial2d(materials.add(color)),
+                Transform::from_translation(center.extend(z)),
+            ));
+        }
+    }
+}
+
+/// Remove zone visuals once the server clears the zone
+fn cleanup_zone_visuals(
+    mut commands: Commands,
+    mut removed: RemovedComponents<SafeZone>,
+    zones: Query<(), With<SafeZone>>,
+    visuals: Query<Entity, With<ZoneVisual>>,
+) {
+    if removed.read().count() == 0 || !zones.is_empty() {
+        return;
+    }
+
+    for entity in visuals.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Plugin to render the battle-royale safe zone on the client
+pub struct ZoneRenderPlugin;
+
+impl Plugin for ZoneRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (update_zone_visuals, cleanup_zone_visuals).chain());
+    }
+}
@@ -0,0 +1,375 @@
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::diagnostic::DiagnosticsStore;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use boid_wars_shared::*;
+use lightyear::client::message::ReceiveMessage;
+use lightyear::connection::client::NetClient;
+use lightyear::prelude::client::*;
+use lightyear::prelude::SharedConfig;
+use rand::Rng;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::stats::{BotStatus, InputLossTracker, StatsHandle};
+
+// Same input rate as the browser client (25Hz)
+const INPUT_INTERVAL: f32 = 0.04;
+// How often a bot publishes its stats to the reporter
+const STATS_INTERVAL: f32 = 0.5;
+// Inputs unacknowledged after this long count as lost
+const LOSS_SETTLE_SECS: f64 = 1.0;
+// Movement patterns, matching the server-side AI players
+const CIRCLE_RADIUS: f32 = 100.0;
+const CIRCLE_SPEED: f32 = 1.0;
+const BOUNCE_RETARGET_SECS: f32 = 3.0;
+const CHASE_SHOOT_DISTANCE: f32 = 300.0;
+
+/// Input pattern a bot plays with, mirroring the server's `AIType`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotBehavior {
+    Circler,
+    Bouncer,
+    Shooter,
+    Chaser,
+}
+
+impl BotBehavior {
+    pub const ALL: [BotBehavior; 4] = [
+        BotBehavior::Circler,
+        BotBehavior::Bouncer,
+        BotBehavior::Shooter,
+        BotBehavior::Chaser,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "circler" => Some(BotBehavior::Circler),
+            "bouncer" => Some(BotBehavior::Bouncer),
+            "shooter" => Some(BotBehavior::Shooter),
+            "chaser" => Some(BotBehavior::Chaser),
+            _ => None,
+        }
+    }
+}
+
+/// Identity and behavior of one bot connection
+#[derive(Resource, Debug, Clone)]
+pub struct BotConfig {
+    pub index: usize,
+    pub client_id: u64,
+    pub behavior: BotBehavior,
+    pub server_addr: SocketAddr,
+}
+
+/// Per-bot AI and session state
+#[derive(Resource, Default)]
+struct BotBrain {
+    behavior_timer: f32,
+    shoot_timer: f32,
+    input_timer: f32,
+    stats_timer: f32,
+    /// First position of the ship, the center Circlers orbit
+    anchor: Option<Vec2>,
+    target: Vec2,
+    sequence: u32,
+    ready_sent: bool,
+    status: BotStatus,
+    losses: InputLossTracker,
+}
+
+/// Netcode client over WebSocket, the same transport the browser uses
+pub fn client_config(
+    server_addr: SocketAddr,
+    client_id: u64,
+) -> lightyear::prelude::client::ClientConfig {
+    let network_config = &*NETWORK_CONFIG;
+
+    let transport = ClientTransport::WebSocketClient { server_addr };
+    let io = IoConfig::from_transport(transport);
+
+    let net_config = NetConfig::Netcode {
+        config: NetcodeConfig::default(),
+        io,
+        auth: Authentication::Manual {
+            server_addr,
+            client_id,
+            private_key: network_config.dev_key,
+            protocol_id: network_config.protocol_id,
+        },
+    };
+
+    lightyear::prelude::client::ClientConfig {
+        shared: SharedConfig::default(),
+        net: net_config,
+        replication: Default::default(),
+        packet: Default::default(),
+        ping: Default::default(),
+        interpolation: Default::default(),
+        prediction: Default::default(),
+        sync: Default::default(),
+    }
+}
+
+/// Headless app for one bot; run it on its own thread
+pub fn build_bot_app(config: BotConfig, stats: StatsHandle) -> App {
+    let mut app = App::new();
+    app.add_plugins(
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1.0 / 60.0,
+        ))),
+    )
+    .add_plugins(StatesPlugin)
+    .add_plugins(ClientPlugins::new(client_config(
+        config.server_addr,
+        config.client_id,
+    )))
+    .add_plugins(ProtocolPlugin)
+    .add_plugins(BotPlugin { config, stats });
+    app
+}
+
+/// Connects, readies up and plays with a scripted input pattern
+pub struct BotPlugin {
+    pub config: BotConfig,
+    pub stats: StatsHandle,
+}
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        if let Ok(mut stats) = self.stats.0.lock() {
+            stats.bot = self.config.index;
+            stats.client_id = self.config.client_id;
+        }
+
+        app.insert_resource(self.config.clone())
+            .insert_resource(self.stats.clone())
+            .init_resource::<BotBrain>()
+            .add_systems(Startup, connect_bot)
+            .add_systems(
+                Update,
+                (
+                    handle_bot_connection,
+                    handle_bot_game_state,
+                    track_input_acks,
+                    drive_bot,
+                    publish_bot_stats,
+                    report_bandwidth,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn connect_bot(mut commands: Commands) {
+    commands.queue(|world: &mut World| {
+        world.connect_client();
+    });
+}
+
+fn handle_bot_connection(
+    mut connect_events: EventReader<ConnectEvent>,
+    mut disconnect_events: EventReader<DisconnectEvent>,
+    mut brain: ResMut<BotBrain>,
+    config: Res<BotConfig>,
+) {
+    for _ in connect_events.read() {
+        info!("Bot {} connected", config.index);
+        brain.status = BotStatus::Lobby;
+    }
+    for event in disconnect_events.read() {
+        warn!("Bot {} disconnected: {:?}", config.index, event.reason);
+        brain.status = BotStatus::Disconnected;
+    }
+}
+
+/// Ready up in every lobby and follow the match phase
+fn handle_bot_game_state(
+    mut state_events: EventReader<ReceiveMessage<GameStateUpdate>>,
+    mut spectator_events: EventReader<ReceiveMessage<SpectatorStatus>>,
+    mut brain: ResMut<BotBrain>,
+    mut connection: ResMut<ConnectionManager>,
+) {
+    for event in spectator_events.read() {
+        brain.status = match event.message.reason {
+            Some(_) => BotStatus::Spectating,
+            None => BotStatus::Lobby,
+        };
+    }
+
+    for event in state_events.read() {
+        let phase = event.message.phase.clone();
+        if brain.status == BotStatus::Spectating {
+            continue;
+        }
+
+        if phase == GamePhase::Lobby {
+            brain.status = BotStatus::Lobby;
+            if !brain.ready_sent {
+                match connection.send_message::<ReliableChannel, _>(&PlayerReady) {
+                    Ok(()) => brain.ready_sent = true,
+                    Err(e) => warn!("Failed to send ready message: {:?}", e),
+                }
+            }
+        } else {
+            brain.ready_sent = false;
+            if phase == GamePhase::InGame {
+                brain.status = BotStatus::Playing;
+            }
+        }
+    }
+}
+
+fn track_input_acks(
+    ships: Query<(&Player, &InputAck), Changed<InputAck>>,
+    mut brain: ResMut<BotBrain>,
+    config: Res<BotConfig>,
+) {
+    for (player, ack) in ships.iter() {
+        if player.id == config.client_id {
+            brain.losses.record_ack(ack.sequence);
+        }
+    }
+}
+
+/// Send inputs following the bot's behavior pattern
+fn drive_bot(
+    ships: Query<(&Player, &Position)>,
+    mut brain: ResMut<BotBrain>,
+    mut connection: ResMut<ConnectionManager>,
+    config: Res<BotConfig>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    brain.behavior_timer += dt;
+    brain.shoot_timer += dt;
+    brain.input_timer += dt;
+    if brain.input_timer < INPUT_INTERVAL {
+        return;
+    }
+    brain.input_timer = 0.0;
+
+    let Some(own) = ships
+        .iter()
+        .find(|(player, _)| player.id == config.client_id)
+        .map(|(_, position)| position.0)
+    else {
+        brain.anchor = None;
+        return;
+    };
+    let anchor = *brain.anchor.get_or_insert(own);
+
+    let (movement, aim, fire) = match config.behavior {
+        BotBehavior::Circler => {
+            let angle = brain.behavior_timer * CIRCLE_SPEED;
+            let target = anchor + Vec2::from_angle(angle) * CIRCLE_RADIUS;
+            let direction = (target - own).normalize_or_zero();
+            (direction, direction, false)
+        }
+        BotBehavior::Bouncer => {
+            if brain.behavior_timer > BOUNCE_RETARGET_SECS || brain.target == Vec2::ZERO {
+                brain.behavior_timer = 0.0;
+                let game_config = &*GAME_CONFIG;
+                let mut rng = rand::thread_rng();
+                brain.target = Vec2::new(
+                    rng.gen_range(0.1..0.9) * game_config.game_width,
+                    rng.gen_range(0.1..0.9) * game_config.game_height,
+                );
+            }
+            let direction = (brain.target - own).normalize_or_zero();
+            (direction, direction, rand::random::<f32>() < 0.05)
+        }
+        BotBehavior::Shooter => {
+            let aim = Vec2::from_angle(brain.behavior_timer * 2.0);
+            let fire = brain.shoot_timer > 0.1;
+            if fire {
+                brain.shoot_timer = 0.0;
+            }
+            (Vec2::ZERO, aim, fire)
+        }
+        BotBehavior::Chaser => {
+            let nearest = ships
+                .iter()
+                .filter(|(player, _)| player.id != config.client_id)
+                .map(|(_, position)| position.0)
+                .min_by(|a, b| a.distance(own).total_cmp(&b.distance(own)));
+
+            match nearest {
+                Some(target) => {
+                    let direction = (target - own).normalize_or_zero();
+                    let fire =
+                        own.distance(target) < CHASE_SHOOT_DISTANCE && brain.shoot_timer > 0.3;
+                    if fire {
+                        brain.shoot_timer = 0.0;
+                    }
+                    (direction, direction, fire)
+                }
+                None => {
+                    let direction = Vec2::from_angle(brain.behavior_timer * 0.5);
+                    (direction, direction, false)
+                }
+            }
+        }
+    };
+
+    brain.sequence += 1;
+    let sequence = brain.sequence;
+    let input = PlayerInput::new(movement, aim, fire).with_sequence(sequence);
+    if connection
+        .send_message::<UnreliableChannel, PlayerInput>(&input)
+        .is_ok()
+    {
+        brain.losses.record_sent(sequence, time.elapsed_secs_f64());
+    }
+}
+
+/// Report this bot's bandwidth to the server, as the browser client does
+fn report_bandwidth(
+    mut connection: ResMut<ConnectionManager>,
+    diagnostics: Res<DiagnosticsStore>,
+    time: Res<Time>,
+    mut since_report: Local<f32>,
+) {
+    *since_report += time.delta_secs();
+    if *since_report < BANDWIDTH_REPORT_INTERVAL_SECS {
+        return;
+    }
+    *since_report = 0.0;
+
+    if let Some(bandwidth) = ClientBandwidth::from_diagnostics(&diagnostics) {
+        let _ = connection.send_message::<UnreliableChannel, _>(&bandwidth);
+    }
+}
+
+/// Copy RTT, bandwidth and loss into the shared stats
+fn publish_bot_stats(
+    mut brain: ResMut<BotBrain>,
+    connection: Res<ConnectionManager>,
+    client: Res<ClientConnection>,
+    stats: Res<StatsHandle>,
+    time: Res<Time>,
+) {
+    brain.stats_timer += time.delta_secs();
+    if brain.stats_timer < STATS_INTERVAL {
+        return;
+    }
+    brain.stats_timer = 0.0;
+
+    brain
+        .losses
+        .settle(time.elapsed_secs_f64(), LOSS_SETTLE_SECS);
+
+    let Ok(mut stats) = stats.0.lock() else {
+        return;
+    };
+    stats.status = brain.status;
+    stats.rtt_ms = connection.ping_manager.rtt().as_secs_f32() * 1000.0;
+    if let Some(io) = client.io() {
+        stats.bytes_sent = io.stats().bytes_sent as u64;
+        stats.bytes_received = io.stats().bytes_received as u64;
+    }
+    stats.inputs_sent = brain.sequence as u64;
+    stats.inputs_settled = brain.losses.settled;
+    stats.inputs_lost = brain.losses.lost;
+}
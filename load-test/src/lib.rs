@@ -0,0 +1,3 @@
+// Expose modules for the load-test binary and its tests
+pub mod bot;
+pub mod stats;
@@ -0,0 +1,176 @@
+//! Headless load-test client.
+//!
+//! Spawns N bot connections against a running server, each in its own
+//! headless Bevy app, and prints per-connection RTT, bandwidth and input loss.
+//!
+//! ```text
+//! cargo run -p boid-wars-load-test --release -- --bots 50 --behavior mixed --duration 120
+//! ```
+
+use boid_wars_load_test::bot::{build_bot_app, BotBehavior, BotConfig};
+use boid_wars_load_test::stats::{format_report, BotStats, StatsHandle};
+use boid_wars_shared::{error_report, install_from_env, NETWORK_CONFIG};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Delay between bot connections so the server isn't hit by one burst
+const CONNECT_STAGGER: Duration = Duration::from_millis(50);
+
+struct Args {
+    bots: usize,
+    server_addr: SocketAddr,
+    /// `None` cycles through every behavior
+    behavior: Option<BotBehavior>,
+    duration: Option<Duration>,
+    report_interval: Duration,
+}
+
+const USAGE: &str = "usage: boid-wars-load-test [--bots N] [--server ADDR] \
+[--behavior circler|bouncer|shooter|chaser|mixed] [--duration SECS] [--report-interval SECS]";
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        bots: 10,
+        server_addr: NETWORK_CONFIG
+            .client_connect_addr
+            .parse()
+            .map_err(|e| format!("invalid default server address: {e}"))?,
+        behavior: None,
+        duration: None,
+        report_interval: Duration::from_secs(2),
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(flag) = iter.next() {
+        if flag == "--help" || flag == "-h" {
+            return Err(USAGE.to_string());
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}\n{USAGE}"))?;
+        let secs = |value: &str| {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|secs| *secs > 0.0)
+                .map(Duration::from_secs_f64)
+                .ok_or_else(|| format!("invalid seconds for {flag}: {value}"))
+        };
+
+        match flag.as_str() {
+            "--bots" => {
+                args.bots = value
+                    .parse()
+                    .map_err(|_| format!("invalid bot count: {value}"))?
+            }
+            "--server" => {
+                args.server_addr = value
+                    .parse()
+                    .map_err(|_| format!("invalid server address: {value}"))?
+            }
+            "--behavior" => {
+                args.behavior = match value.as_str() {
+                    "mixed" => None,
+                    name => Some(
+                        BotBehavior::parse(name)
+                            .ok_or_else(|| format!("unknown behavior: {name}"))?,
+                    ),
+                }
+            }
+            "--duration" => args.duration = Some(secs(&value)?),
+            "--report-interval" => args.report_interval = secs(&value)?,
+            _ => return Err(format!("unknown argument: {flag}\n{USAGE}")),
+        }
+    }
+
+    Ok(args)
+}
+
+fn main() {
+    // Report bad settings up front, before the first read of a global config
+    if let Err(errors) = install_from_env() {
+        eprintln!("Invalid configuration:\n{}", error_report(&errors));
+        std::process::exit(2);
+    }
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(2);
+        }
+    };
+
+    tracing_subscriber::fmt::init();
+
+    println!(
+        "Starting {} bots against {} ({})",
+        args.bots,
+        args.server_addr,
+        args.behavior
+            .map_or("mixed".to_string(), |behavior| format!("{behavior:?}"))
+    );
+
+    // Client ids only need to be unique per server run
+    let id_base = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default();
+
+    let handles: Vec<StatsHandle> = (0..args.bots)
+        .map(|index| {
+            StatsHandle(Arc::new(Mutex::new(BotStats {
+                bot: index,
+                client_id: id_base.wrapping_add(index as u64),
+                ..Default::default()
+            })))
+        })
+        .collect();
+    let spawn_handles = handles.clone();
+    let (server_addr, behavior) = (args.server_addr, args.behavior);
+    thread::spawn(move || {
+        for (index, stats) in spawn_handles.into_iter().enumerate() {
+            let config = BotConfig {
+                index,
+                client_id: id_base.wrapping_add(index as u64),
+                behavior: behavior.unwrap_or(BotBehavior::ALL[index % BotBehavior::ALL.len()]),
+                server_addr,
+            };
+            let spawned = thread::Builder::new()
+                .name(format!("bot-{index}"))
+                .spawn(move || {
+                    build_bot_app(config, stats).run();
+                });
+            if let Err(e) = spawned {
+                eprintln!("Failed to start bot {index}: {e}");
+            }
+            thread::sleep(CONNECT_STAGGER);
+        }
+    });
+
+    let started = Instant::now();
+    let mut previous: Vec<BotStats> = Vec::new();
+    loop {
+        thread::sleep(args.report_interval);
+
+        let current: Vec<BotStats> = handles.iter().map(StatsHandle::snapshot).collect();
+        println!(
+            "\n[{:.0}s]\n{}",
+            started.elapsed().as_secs_f32(),
+            format_report(&current, &previous, args.report_interval.as_secs_f32())
+        );
+        previous = current;
+
+        if args
+            .duration
+            .is_some_and(|duration| started.elapsed() >= duration)
+        {
+            break;
+        }
+    }
+
+    // Bot apps run until the process exits
+    std::process::exit(0);
+}
@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Where a bot is in its session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BotStatus {
+    #[default]
+    Connecting,
+    Lobby,
+    Playing,
+    Spectating,
+    Disconnected,
+}
+
+/// Latest measurements for one bot connection
+#[derive(Debug, Clone, Default)]
+pub struct BotStats {
+    pub bot: usize,
+    pub client_id: u64,
+    pub status: BotStatus,
+    pub rtt_ms: f32,
+    /// Totals since the bot started
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub inputs_sent: u64,
+    /// Inputs old enough to judge, and how many of those the server never acked
+    pub inputs_settled: u64,
+    pub inputs_lost: u64,
+}
+
+impl BotStats {
+    pub fn loss_percent(&self) -> f32 {
+        if self.inputs_settled == 0 {
+            0.0
+        } else {
+            self.inputs_lost as f32 / self.inputs_settled as f32 * 100.0
+        }
+    }
+}
+
+/// Stats shared between a bot's app thread and the reporter
+#[derive(Resource, Clone, Default)]
+pub struct StatsHandle(pub Arc<Mutex<BotStats>>);
+
+impl StatsHandle {
+    pub fn snapshot(&self) -> BotStats {
+        self.0.lock().map(|stats| stats.clone()).unwrap_or_default()
+    }
+}
+
+/// Estimates input loss from the `InputAck` sequences echoed by the server.
+///
+/// Inputs go out on the unreliable channel and the server acks the latest one it
+/// applied. `InputAck` is replicated state, so only the newest sequence of each
+/// replication update reaches the bot. An ack of N therefore counts every input up
+/// to N as delivered. An input still above the highest ack after the settle time
+/// counts as lost. An input dropped just before a later one got through goes
+/// unnoticed, so this is a lower bound.
+#[derive(Debug, Default)]
+pub struct InputLossTracker {
+    pending: VecDeque<(u32, f64)>,
+    highest_acked: Option<u32>,
+    pub settled: u64,
+    pub lost: u64,
+}
+
+impl InputLossTracker {
+    pub fn record_sent(&mut self, sequence: u32, now: f64) {
+        self.pending.push_back((sequence, now));
+    }
+
+    pub fn record_ack(&mut self, sequence: u32) {
+        self.highest_acked = self.highest_acked.max(Some(sequence));
+    }
+
+    /// Judge inputs sent more than `settle_secs` ago
+    pub fn settle(&mut self, now: f64, settle_secs: f64) {
+        while let Some(&(sequence, sent_at)) = self.pending.front() {
+            if now - sent_at < settle_secs {
+                break;
+            }
+            self.pending.pop_front();
+            self.settled += 1;
+            if self.highest_acked.is_none_or(|acked| sequence > acked) {
+                self.lost += 1;
+            }
+        }
+    }
+}
+
+/// Per-connection table plus totals.
+///
+/// Bandwidth is the byte difference from `previous` over `interval_secs`.
+pub fn format_report(current: &[BotStats], previous: &[BotStats], interval_secs: f32) -> String {
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "{:>4} {:>20} {:>12} {:>8} {:>10} {:>10} {:>8} {:>7}",
+        "bot", "client", "status", "rtt ms", "up kB/s", "down kB/s", "inputs", "loss %"
+    );
+
+    let rate = |now: u64, before: u64| (now.saturating_sub(before)) as f32 / 1024.0 / interval_secs;
+    let mut total_up = 0.0;
+    let mut total_down = 0.0;
+    let mut rtts = Vec::new();
+
+    for stats in current {
+        let before = previous.iter().find(|prev| prev.bot == stats.bot);
+        let up = rate(stats.bytes_sent, before.map_or(0, |prev| prev.bytes_sent));
+        let down = rate(
+            stats.bytes_received,
+            before.map_or(0, |prev| prev.bytes_received),
+        );
+        total_up += up;
+        total_down += down;
+        if stats.status != BotStatus::Connecting && stats.status != BotStatus::Disconnected {
+            rtts.push(stats.rtt_ms);
+        }
+
+        let _ = writeln!(
+            report,
+            "{:>4} {:>20} {:>12} {:>8.1} {:>10.1} {:>10.1} {:>8} {:>7.2}",
+            stats.bot,
+            stats.client_id,
+            format!("{:?}", stats.status),
+            stats.rtt_ms,
+            up,
+            down,
+            stats.inputs_sent,
+            stats.loss_percent()
+        );
+    }
+
+    let settled: u64 = current.iter().map(|stats| stats.inputs_settled).sum();
+    let lost: u64 = current.iter().map(|stats| stats.inputs_lost).sum();
+    let mean_rtt = if rtts.is_empty() {
+        0.0
+    } else {
+        rtts.iter().sum::<f32>() / rtts.len() as f32
+    };
+    let max_rtt = rtts.iter().copied().fold(0.0, f32::max);
+    let _ = writeln!(
+        report,
+        "total: {} connected | rtt mean {:.1} ms max {:.1} ms | up {:.1} kB/s down {:.1} kB/s | loss {:.2}%",
+        rtts.len(),
+        mean_rtt,
+        max_rtt,
+        total_up,
+        total_down,
+        if settled == 0 {
+            0.0
+        } else {
+            lost as f32 / settled as f32 * 100.0
+        }
+    );
+
+    report
+}
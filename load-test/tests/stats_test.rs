@@ -0,0 +1,66 @@
+use boid_wars_load_test::bot::BotBehavior;
+use boid_wars_load_test::stats::{format_report, BotStats, BotStatus, InputLossTracker};
+
+#[test]
+fn test_unacked_inputs_count_as_lost_after_settling() {
+    let mut tracker = InputLossTracker::default();
+    for sequence in 1..=4 {
+        tracker.record_sent(sequence, sequence as f64 * 0.04);
+    }
+    tracker.record_ack(2);
+    tracker.record_ack(1);
+
+    // Nothing is judged before the settle time
+    tracker.settle(0.5, 1.0);
+    assert_eq!((tracker.settled, tracker.lost), (0, 0));
+
+    tracker.settle(2.0, 1.0);
+    assert_eq!((tracker.settled, tracker.lost), (4, 2));
+}
+
+#[test]
+fn test_coalesced_acks_deliver_earlier_inputs() {
+    let mut tracker = InputLossTracker::default();
+    for sequence in 1..=6 {
+        tracker.record_sent(sequence, sequence as f64 * 0.04);
+    }
+    // One replication update carried only the newest applied input
+    tracker.record_ack(3);
+    tracker.record_ack(6);
+
+    tracker.settle(2.0, 1.0);
+    assert_eq!((tracker.settled, tracker.lost), (6, 0));
+}
+
+#[test]
+fn test_report_rates_use_previous_totals() {
+    let previous = vec![BotStats {
+        bot: 0,
+        bytes_sent: 1024,
+        bytes_received: 2048,
+        ..Default::default()
+    }];
+    let current = vec![BotStats {
+        bot: 0,
+        client_id: 7,
+        status: BotStatus::Playing,
+        rtt_ms: 42.0,
+        bytes_sent: 3072,
+        bytes_received: 10240,
+        inputs_sent: 50,
+        inputs_settled: 40,
+        inputs_lost: 2,
+    }];
+
+    let report = format_report(&current, &previous, 2.0);
+    assert!(report.contains("Playing"));
+    assert!(report.contains("rtt mean 42.0 ms"));
+    assert!(report.contains("up 1.0 kB/s down 4.0 kB/s"));
+    assert!(report.contains("loss 5.00%"));
+}
+
+#[test]
+fn test_behavior_names_parse() {
+    assert_eq!(BotBehavior::parse("Chaser"), Some(BotBehavior::Chaser));
+    assert_eq!(BotBehavior::parse("mixed"), None);
+}
@@ -1,9 +1,11 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use boid_wars_server::flocking::{update_flocking, FlockingConfig};
 use boid_wars_server::physics::{PhysicsPlugin, ProjectilePool};
 use boid_wars_server::position_sync::PositionSyncPlugin;
-use boid_wars_shared::{Boid, Player, Position, Velocity};
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use boid_wars_server::spatial_grid::{LayerMask, SpatialGrid};
+use boid_wars_shared::{Boid, BoidGroupMember, BoidRole, Position, Velocity};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 
 fn spawn_entities_benchmark(c: &mut Criterion) {
     c.bench_function("spawn_1000_entities", |b| {
@@ -112,35 +114,81 @@ fn position_sync_benchmark(c: &mut Criterion) {
 fn projectile_pool_benchmark(c: &mut Criterion) {
     c.bench_function("projectile_pool_spawn_despawn", |b| {
         let mut app = create_test_app();
-        let pool_resource = app.world().resource::<ProjectilePool>().clone();
+        // Let startup pre-spawn the pool
+        app.update();
 
         b.iter(|| {
-            // Simulate spawning and despawning 100 projectiles
-            let mut spawned = Vec::new();
-
-            // Spawn from pool
-            for _ in 0..100 {
-                if let Some(entity) = pool_resource.available.last() {
-                    spawned.push(entity);
-                }
-            }
+            let mut pool = app.world_mut().resource_mut::<ProjectilePool>();
 
-            // Return to pool
-            for entity in spawned {
-                // Simulate returning to pool
-                black_box(entity);
+            // Acquire and release 100 projectiles
+            let acquired: Vec<_> = (0..100).filter_map(|_| pool.acquire()).collect();
+            for pooled in acquired {
+                black_box(pool.release(pooled));
             }
         });
     });
 }
 
+fn flocking_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("flocking");
+    group.sample_size(20);
+
+    for count in [1000, 5000, 10000] {
+        // Threshold past the boid count forces the serial path
+        for (mode, parallel_threshold) in [("serial", usize::MAX), ("parallel", 0)] {
+            let mut app = create_flocking_app(count, parallel_threshold);
+            group.bench_with_input(BenchmarkId::new(mode, count), &count, |b, _| {
+                b.iter(|| {
+                    app.update();
+                    black_box(&app);
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Boids spread over a 4000x3000 arena in groups of 100, grid filled once
+fn create_flocking_app(count: usize, parallel_threshold: usize) -> App {
+    let (width, height) = (4000.0, 3000.0);
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(FlockingConfig {
+            parallel_threshold,
+            ..default()
+        })
+        .add_systems(Update, update_flocking);
+
+    let mut grid = SpatialGrid::new(width, height, 100.0);
+    for i in 0..count {
+        let position = Vec2::new((i as f32 * 17.0) % width, (i as f32 * 23.0) % height);
+        let entity = app
+            .world_mut()
+            .spawn((
+                Boid { id: i as u32 },
+                Position(position),
+                Velocity(Vec2::new(1.0, 0.0)),
+                BoidGroupMember {
+                    group_entity: Entity::PLACEHOLDER,
+                    group_id: (i / 100) as u32,
+                    formation_slot: None,
+                    role_in_group: BoidRole::Support,
+                },
+            ))
+            .id();
+        grid.insert(entity, position, LayerMask::BOID);
+    }
+    app.insert_resource(grid);
+    app
+}
+
 fn create_test_app() -> App {
     let mut app = App::new();
     app.add_plugins(MinimalPlugins);
     app.add_plugins(TransformPlugin);
-    app.add_plugins(PhysicsPlugin);
+    app.add_plugins(PhysicsPlugin::default());
     app.add_plugins(PositionSyncPlugin);
-    app.init_resource::<ProjectilePool>();
     app
 }
 
@@ -149,6 +197,7 @@ criterion_group!(
     spawn_entities_benchmark,
     physics_update_benchmark,
     position_sync_benchmark,
-    projectile_pool_benchmark
+    projectile_pool_benchmark,
+    flocking_benchmark
 );
 criterion_main!(benches);
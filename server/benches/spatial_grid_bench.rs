@@ -2,7 +2,18 @@ use bevy::prelude::*;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 // Import from the server crate
 extern crate boid_wars_server;
-use boid_wars_server::spatial_grid::SpatialGrid;
+use boid_wars_server::spatial_grid::{LayerMask, SpatialGrid};
+
+/// Mostly boids, with a player every 10th entity and an obstacle every 50th
+fn layer_for(i: usize) -> LayerMask {
+    if i % 50 == 0 {
+        LayerMask::OBSTACLE
+    } else if i % 10 == 0 {
+        LayerMask::PLAYER
+    } else {
+        LayerMask::BOID
+    }
+}
 
 fn setup_grid_with_entities(count: usize) -> (SpatialGrid, Vec<(Entity, Vec2)>) {
     let mut grid = SpatialGrid::new(2000.0, 1500.0, 100.0);
@@ -15,7 +26,7 @@ fn setup_grid_with_entities(count: usize) -> (SpatialGrid, Vec<(Entity, Vec2)>)
         let y = (i as f32 * 23.0) % 1500.0;
         let pos = Vec2::new(x, y);
 
-        grid.insert(entity, pos);
+        grid.insert(entity, pos, layer_for(i));
         entities.push((entity, pos));
     }
 
@@ -29,7 +40,7 @@ fn bench_spatial_grid_queries(c: &mut Criterion) {
     for count in [1000, 5000, 10000].iter() {
         let (grid, entities) = setup_grid_with_entities(*count);
 
-        group.bench_function(format!("query_{}_entities", count), |b| {
+        group.bench_function(format!("query_{count}_entities"), |b| {
             let mut i = 0;
             b.iter(|| {
                 // Query from different positions
@@ -52,7 +63,8 @@ fn bench_spatial_grid_insert(c: &mut Criterion) {
         b.iter(|| {
             let entity = Entity::from_raw(i);
             let pos = Vec2::new((i as f32 * 17.0) % 2000.0, (i as f32 * 23.0) % 1500.0);
-            black_box(grid.insert(entity, pos));
+            grid.insert(entity, pos, LayerMask::BOID);
+            black_box(());
             i = i.wrapping_add(1);
 
             // Clear periodically to prevent unbounded growth
@@ -68,21 +80,94 @@ fn bench_spatial_grid_clear(c: &mut Criterion) {
         let (mut grid, _) = setup_grid_with_entities(10000);
 
         b.iter(|| {
-            black_box(grid.clear());
+            grid.clear();
+            black_box(());
             // Re-populate for next iteration
             for i in 0..10000 {
                 let entity = Entity::from_raw(i as u32);
                 let pos = Vec2::new((i as f32 * 17.0) % 2000.0, (i as f32 * 23.0) % 1500.0);
-                grid.insert(entity, pos);
+                grid.insert(entity, pos, layer_for(i));
+            }
+        });
+    });
+}
+
+fn bench_spatial_grid_layered_queries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spatial_grid_layers");
+
+    for count in [1000, 5000, 10000] {
+        let (grid, entities) = setup_grid_with_entities(count);
+
+        group.bench_function(format!("players_within_300_of_{count}"), |b| {
+            let mut i = 0;
+            b.iter(|| {
+                let pos = entities[i % entities.len()].1;
+                i += 1;
+                black_box(grid.within_radius(pos, 300.0, LayerMask::PLAYER).count())
+            });
+        });
+
+        group.bench_function(format!("k_nearest_8_of_{count}"), |b| {
+            let mut i = 0;
+            b.iter(|| {
+                let pos = entities[i % entities.len()].1;
+                i += 1;
+                black_box(grid.k_nearest(pos, 8, 500.0, LayerMask::BOID))
+            });
+        });
+
+        group.bench_function(format!("raycast_600_through_{count}"), |b| {
+            let mut i = 0;
+            b.iter(|| {
+                let pos = entities[i % entities.len()].1;
+                let direction = Vec2::from_angle(i as f32 * 0.7);
+                i += 1;
+                black_box(grid.raycast(pos, direction, 600.0, 8.0, LayerMask::PLAYER))
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_spatial_grid_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spatial_grid_update_10k");
+
+    // Small moves mostly stay in the same cell and take the in-place path
+    group.bench_function("incremental", |b| {
+        let (mut grid, entities) = setup_grid_with_entities(10000);
+        let mut tick = 0u32;
+        b.iter(|| {
+            let offset = Vec2::splat((tick % 4) as f32);
+            for (i, (entity, pos)) in entities.iter().enumerate() {
+                grid.insert(*entity, *pos + offset, layer_for(i));
             }
+            tick = tick.wrapping_add(1);
         });
     });
+
+    group.bench_function("rebuild", |b| {
+        let (mut grid, entities) = setup_grid_with_entities(10000);
+        let mut tick = 0u32;
+        b.iter(|| {
+            let offset = Vec2::splat((tick % 4) as f32);
+            grid.clear();
+            for (i, (entity, pos)) in entities.iter().enumerate() {
+                grid.insert(*entity, *pos + offset, layer_for(i));
+            }
+            tick = tick.wrapping_add(1);
+        });
+    });
+
+    group.finish();
 }
 
 criterion_group!(
     benches,
     bench_spatial_grid_queries,
     bench_spatial_grid_insert,
-    bench_spatial_grid_clear
+    bench_spatial_grid_clear,
+    bench_spatial_grid_layered_queries,
+    bench_spatial_grid_update
 );
 criterion_main!(benches);
@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use boid_wars_shared::wire::{
+    position_delta, velocity_delta, write_angle, write_position, write_velocity, PositionDelta,
+    VelocityDelta,
+};
+use boid_wars_shared::{Position, Rotation, Velocity, GAME_CONFIG};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+// One server tick at 60Hz
+const TICK_SECS: f32 = 1.0 / 60.0;
+
+#[derive(Clone, Copy)]
+struct BoidState {
+    position: Vec2,
+    velocity: Vec2,
+    rotation: f32,
+}
+
+/// Boids spread over the arena at typical flocking speeds
+fn boid_states(count: usize) -> Vec<BoidState> {
+    let game_config = &*GAME_CONFIG;
+    let mut rng = StdRng::seed_from_u64(42);
+
+    (0..count)
+        .map(|_| {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let speed = rng.gen_range(50.0..200.0);
+            BoidState {
+                position: Vec2::new(
+                    rng.gen_range(0.0..game_config.game_width),
+                    rng.gen_range(0.0..game_config.game_height),
+                ),
+                velocity: Vec2::from_angle(angle) * speed,
+                rotation: angle,
+            }
+        })
+        .collect()
+}
+
+/// The same boids one tick later, steering slightly
+fn step(states: &[BoidState]) -> Vec<BoidState> {
+    states
+        .iter()
+        .enumerate()
+        .map(|(i, state)| {
+            let turn = if i % 2 == 0 { 0.02 } else { -0.02 };
+            let velocity = Vec2::from_angle(turn).rotate(state.velocity);
+            BoidState {
+                position: state.position + velocity * TICK_SECS,
+                velocity,
+                rotation: state.rotation + turn,
+            }
+        })
+        .collect()
+}
+
+/// Append `value` the way lightyear serializes serde messages and components
+fn write_bincode(buffer: &mut Vec<u8>, value: &impl Serialize) {
+    bincode::serde::encode_into_std_write(value, buffer, bincode::config::standard()).unwrap();
+}
+
+/// Plain serde encoding: the three components as raw `f32`s
+fn full_f32_bytes(states: &[BoidState]) -> usize {
+    let mut buffer = Vec::with_capacity(states.len() * 20);
+    for state in states {
+        write_bincode(&mut buffer, &Position(state.position));
+        write_bincode(&mut buffer, &Velocity(state.velocity));
+        write_bincode(
+            &mut buffer,
+            &Rotation {
+                angle: state.rotation,
+            },
+        );
+    }
+    buffer.len()
+}
+
+/// What the registered `SerializeFns` write for full updates
+fn quantized_bytes(states: &[BoidState]) -> usize {
+    let mut buffer = Vec::with_capacity(states.len() * 10);
+    for state in states {
+        write_position(&mut buffer, state.position).unwrap();
+        write_velocity(&mut buffer, state.velocity).unwrap();
+        write_angle(&mut buffer, state.rotation).unwrap();
+    }
+    buffer.len()
+}
+
+/// Quantized rotation plus position/velocity delta messages against the acked tick
+fn delta_bytes(acked: &[BoidState], states: &[BoidState]) -> usize {
+    let mut buffer = Vec::with_capacity(states.len() * 8);
+    for (base, state) in acked.iter().zip(states) {
+        write_bincode(
+            &mut buffer,
+            &PositionDelta(position_delta(base.position, state.position)),
+        );
+        write_bincode(
+            &mut buffer,
+            &VelocityDelta(velocity_delta(base.velocity, state.velocity)),
+        );
+        write_angle(&mut buffer, state.rotation).unwrap();
+    }
+    buffer.len()
+}
+
+fn bench_wire_encoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wire_encoding");
+
+    for count in [1000, 5000, 10000] {
+        let acked = boid_states(count);
+        let current = step(&acked);
+
+        let full = full_f32_bytes(&current);
+        let quantized = quantized_bytes(&current);
+        let delta = delta_bytes(&acked, &current);
+        println!(
+            "{count} boids, bytes per tick: f32 {full} | quantized {quantized} ({:.0}%) | delta {delta} ({:.0}%)",
+            quantized as f32 / full as f32 * 100.0,
+            delta as f32 / full as f32 * 100.0,
+        );
+
+        group.bench_function(format!("f32_{count}_boids"), |b| {
+            b.iter(|| black_box(full_f32_bytes(&current)))
+        });
+        group.bench_function(format!("quantized_{count}_boids"), |b| {
+            b.iter(|| black_box(quantized_bytes(&current)))
+        });
+        group.bench_function(format!("delta_{count}_boids"), |b| {
+            b.iter(|| black_box(delta_bytes(&acked, &current)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_wire_encoding);
+criterion_main!(benches);
@@ -0,0 +1,490 @@
+//! Admin console for playtests: one command per stdin line, run against the live world.
+//!
+//! ```text
+//! clients                          list players and spectators
+//! kick <client>                    disconnect a client
+//! start                            start the match now, ready or not
+//! spawn <archetype> <x> <y> [n]    spawn a boid group (assault, defensive, recon)
+//! god <client>                     toggle invulnerability for a player's ship
+//! counts                           entity counts by kind
+//! groups                           each boid group's behavior, leader and deciding tree node
+//! profile                          per-stage tick timings
+//! trace <ticks> [file]             write the next ticks as a Chrome trace
+//! ```
+
+use crate::config::PhysicsConfig;
+use crate::groups::behavior_tree::{ActiveBehaviorNode, BehaviorKind};
+use crate::groups::roles::GroupLeader;
+use crate::groups::{self, BoidGroupConfig, BoidIdCounter, GroupIdCounter};
+use crate::lobby::{ForceStart, GameState, PlayerSlots, Spectators};
+use crate::map_file::MAX_GROUP_SIZE;
+use crate::physics::{self, GodMode, Projectile, ProjectilePool};
+use crate::profiler::{TickProfiler, DEFAULT_TRACE_PATH};
+use crate::rng::GameRng;
+use bevy::prelude::*;
+use boid_wars_shared::{
+    ArenaZone, Boid, BoidGroup, GamePhase, GroupArchetype, Position, TerritoryData,
+};
+use lightyear::connection::id::ClientId;
+use lightyear::prelude::server::*;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Boids in a console-spawned group when no size is given
+const DEFAULT_SPAWN_SIZE: u32 = 10;
+
+pub const HELP: &str = "commands: clients | kick <client> | start | \
+spawn <assault|defensive|recon> <x> <y> [size] | god <client> | counts | groups | profile | \
+trace <ticks> [file] | help";
+
+/// A parsed console command
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum AdminCommand {
+    Help,
+    /// List connected players and spectators
+    Clients,
+    /// Disconnect a client by id
+    Kick(u64),
+    /// Start the match without waiting for ready checks
+    Start,
+    /// Spawn a boid group at a point
+    Spawn {
+        archetype: GroupArchetype,
+        position: Vec2,
+        size: u32,
+    },
+    /// Toggle god mode on a player's ship, by client id
+    God(u64),
+    /// Log entity counts
+    Counts,
+    /// Log every group's behavior and the tree node that chose it
+    Groups,
+    /// Log rolling tick-stage percentiles
+    Profile,
+    /// Capture the next ticks as a Chrome trace
+    Trace {
+        ticks: u32,
+        path: PathBuf,
+    },
+}
+
+impl AdminCommand {
+    /// Parse one console line; blank lines are `Ok(None)`
+    pub fn parse(line: &str) -> Result<Option<Self>, String> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(None);
+        };
+        let args: Vec<&str> = words.collect();
+
+        let command = match (name, args.as_slice()) {
+            ("help" | "?", []) => AdminCommand::Help,
+            ("clients", []) => AdminCommand::Clients,
+            ("kick", [client]) => AdminCommand::Kick(parse_number(client, "client id")?),
+            ("start", []) => AdminCommand::Start,
+            ("spawn", [archetype, x, y, rest @ ..]) if rest.len() <= 1 => AdminCommand::Spawn {
+                archetype: parse_archetype(archetype)?,
+                position: Vec2::new(parse_number(x, "x")?, parse_number(y, "y")?),
+                size: match rest {
+                    [size] => parse_group_size(size)?,
+                    _ => DEFAULT_SPAWN_SIZE,
+                },
+            },
+            ("god", [client]) => AdminCommand::God(parse_number(client, "client id")?),
+            ("counts", []) => AdminCommand::Counts,
+            ("groups", []) => AdminCommand::Groups,
+            ("profile", []) => AdminCommand::Profile,
+            ("trace", [ticks, rest @ ..]) if rest.len() <= 1 => AdminCommand::Trace {
+                ticks: parse_number(ticks, "tick count")?,
+                path: PathBuf::from(rest.first().copied().unwrap_or(DEFAULT_TRACE_PATH)),
+            },
+            (
+                "help" | "?" | "clients" | "kick" | "start" | "spawn" | "god" | "counts" | "groups"
+                | "profile" | "trace",
+                _,
+            ) => {
+                return Err(format!("wrong arguments for '{name}'; {HELP}"));
+            }
+            _ => return Err(format!("unknown command '{name}'; {HELP}")),
+        };
+        Ok(Some(command))
+    }
+
+    /// Whether the command changes game state, so recordings must include it.
+    /// `kick` only does through the disconnect, which is recorded on its own.
+    pub fn affects_simulation(&self) -> bool {
+        matches!(
+            self,
+            AdminCommand::Start | AdminCommand::Spawn { .. } | AdminCommand::God(_)
+        )
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(word: &str, what: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("invalid {what}: '{word}'"))
+}
+
+/// Group sizes are held to the same limit as map files
+fn parse_group_size(word: &str) -> Result<u32, String> {
+    let size: u32 = parse_number(word, "size")?;
+    if size == 0 || size > MAX_GROUP_SIZE {
+        return Err(format!(
+            "size must be between 1 and {MAX_GROUP_SIZE}, got {size}"
+        ));
+    }
+    Ok(size)
+}
+
+/// Archetypes with the same tuning as the default match groups
+fn parse_archetype(word: &str) -> Result<GroupArchetype, String> {
+    match word {
+        "assault" => Ok(GroupArchetype::Assault {
+            aggression_multiplier: 1.0,
+            preferred_range: 150.0,
+        }),
+        "defensive" => Ok(GroupArchetype::Defensive {
+            protection_radius: 400.0,
+            retreat_threshold: 0.4,
+        }),
+        "recon" => Ok(GroupArchetype::Recon {
+            detection_range: 400.0,
+            flee_speed_bonus: 1.3,
+        }),
+        _ => Err(format!(
+            "unknown archetype '{word}', expected assault, defensive or recon"
+        )),
+    }
+}
+
+fn archetype_name(archetype: &GroupArchetype) -> &'static str {
+    match archetype {
+        GroupArchetype::Assault { .. } => "assault",
+        GroupArchetype::Defensive { .. } => "defensive",
+        GroupArchetype::Recon { .. } => "recon",
+    }
+}
+
+/// Lines read from stdin on a background thread
+#[derive(Resource)]
+struct ConsoleInput(Mutex<Receiver<String>>);
+
+/// Admin commands, from stdin when `read_stdin` is set or as `AdminCommand` events
+pub struct AdminConsolePlugin {
+    pub read_stdin: bool,
+}
+
+impl Plugin for AdminConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AdminCommand>().init_resource::<GameRng>();
+
+        if self.read_stdin {
+            let (sender, lines) = channel();
+            std::thread::Builder::new()
+                .name("admin-console".to_string())
+                .spawn(move || {
+                    for line in std::io::stdin().lock().lines() {
+                        let Ok(line) = line else { break };
+                        if sender.send(line).is_err() {
+                            break;
+                        }
+                    }
+                })
+                .expect("failed to start admin console thread");
+
+            app.insert_resource(ConsoleInput(Mutex::new(lines)))
+                .add_systems(Update, read_console_input.before(AdminSet));
+            info!("🛠️  Admin console ready - type 'help' for commands");
+        }
+
+        app.add_systems(
+            Update,
+            (
+                admin_help,
+                admin_list_clients,
+                admin_kick,
+                admin_force_start,
+                admin_spawn_group,
+                admin_god_mode,
+                admin_counts,
+                admin_list_groups,
+                admin_profile,
+            )
+                .in_set(AdminSet),
+        );
+    }
+}
+
+/// Systems that carry out admin commands
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AdminSet;
+
+fn read_console_input(input: Res<ConsoleInput>, mut commands: EventWriter<AdminCommand>) {
+    let Ok(lines) = input.0.lock() else {
+        return;
+    };
+    for line in lines.try_iter() {
+        match AdminCommand::parse(&line) {
+            Ok(Some(command)) => {
+                commands.write(command);
+            }
+            Ok(None) => {}
+            Err(message) => warn!("{}", message),
+        }
+    }
+}
+
+fn admin_help(mut commands: EventReader<AdminCommand>) {
+    for command in commands.read() {
+        if *command == AdminCommand::Help {
+            info!("{}", HELP);
+        }
+    }
+}
+
+fn admin_list_clients(
+    mut commands: EventReader<AdminCommand>,
+    game_state: Res<GameState>,
+    player_slots: Res<PlayerSlots>,
+    spectators: Res<Spectators>,
+) {
+    for command in commands.read() {
+        if *command != AdminCommand::Clients {
+            continue;
+        }
+
+        info!(
+            "{:?}: {} players, {} spectators",
+            game_state.phase,
+            player_slots.len(),
+            spectators.len()
+        );
+        for (client_id, slot) in player_slots.iter_ordered() {
+            info!(
+                "  client {} - player {} ({})",
+                client_id.to_bits(),
+                slot.number.0,
+                if slot.ready { "ready" } else { "not ready" }
+            );
+        }
+        for client_id in spectators.clients() {
+            info!(
+                "  client {} - spectating ({:?})",
+                client_id.to_bits(),
+                spectators.reason(client_id)
+            );
+        }
+    }
+}
+
+fn admin_kick(mut commands: Commands, mut events: EventReader<AdminCommand>) {
+    for event in events.read() {
+        if let AdminCommand::Kick(client) = *event {
+            info!("Kicking client {}", client);
+            // Every client connects over netcode
+            commands.disconnect(ClientId::Netcode(client));
+        }
+    }
+}
+
+fn admin_force_start(
+    mut commands: EventReader<AdminCommand>,
+    mut game_state: ResMut<GameState>,
+    mut force_start: ResMut<ForceStart>,
+    player_slots: Res<PlayerSlots>,
+) {
+    for command in commands.read() {
+        if *command != AdminCommand::Start {
+            continue;
+        }
+
+        if player_slots.is_empty() {
+            warn!("Cannot start: no players connected");
+        } else if matches!(game_state.phase, GamePhase::InGame | GamePhase::GameOver) {
+            warn!("Cannot start: a match is already running");
+        } else if game_state.phase == GamePhase::Results {
+            // The round resets the arena and slots once the results are done
+            warn!("Cannot start: the last match's results are still showing");
+        } else {
+            // The lobby picks this up in check_start_game
+            game_state.phase = GamePhase::Lobby;
+            force_start.0 = true;
+        }
+    }
+}
+
+fn admin_spawn_group(
+    mut commands: Commands,
+    mut events: EventReader<AdminCommand>,
+    group_config: Res<BoidGroupConfig>,
+    physics_config: Res<PhysicsConfig>,
+    mut group_id_counter: ResMut<GroupIdCounter>,
+    mut boid_id_counter: ResMut<BoidIdCounter>,
+    mut rng: ResMut<GameRng>,
+) {
+    for event in events.read() {
+        let AdminCommand::Spawn {
+            archetype,
+            position,
+            size,
+        } = *event
+        else {
+            continue;
+        };
+
+        // Patrol a small square around the spawn point
+        let radius = group_config.territory_radius;
+        let half = radius * 0.5;
+        let territory = TerritoryData {
+            center: position,
+            radius,
+            zone: ArenaZone::Middle,
+            patrol_points: vec![
+                position + Vec2::new(-half, -half),
+                position + Vec2::new(half, -half),
+                position + Vec2::new(half, half),
+                position + Vec2::new(-half, half),
+            ],
+            neighboring_territories: vec![],
+        };
+
+        let entity = groups::spawn_boid_group(
+            &mut commands,
+            archetype,
+            size,
+            territory,
+            &mut group_id_counter,
+            &mut boid_id_counter,
+            &physics_config,
+            &mut rng,
+        );
+        info!(
+            "Spawned {:?} group {:?} of {} boids at ({}, {})",
+            archetype, entity, size, position.x, position.y
+        );
+    }
+}
+
+fn admin_god_mode(
+    mut commands: Commands,
+    mut events: EventReader<AdminCommand>,
+    players: Query<(Entity, &physics::Player, Has<GodMode>)>,
+) {
+    for event in events.read() {
+        let AdminCommand::God(client) = *event else {
+            continue;
+        };
+
+        let Some((entity, _, enabled)) = players
+            .iter()
+            .find(|(_, player, _)| player.player_id == client)
+        else {
+            warn!("No ship for client {} (has the match started?)", client);
+            continue;
+        };
+
+        if enabled {
+            commands.entity(entity).remove::<GodMode>();
+        } else {
+            commands.entity(entity).insert(GodMode);
+        }
+        info!(
+            "God mode {} for client {}",
+            if enabled { "off" } else { "on" },
+            client
+        );
+    }
+}
+
+fn admin_counts(
+    mut commands: EventReader<AdminCommand>,
+    entities: Query<Entity>,
+    players: Query<(), With<physics::Player>>,
+    boids: Query<(), With<Boid>>,
+    groups: Query<(), With<BoidGroup>>,
+    projectiles: Query<(), With<Projectile>>,
+    projectile_pool: Res<ProjectilePool>,
+) {
+    for command in commands.read() {
+        if *command != AdminCommand::Counts {
+            continue;
+        }
+
+        let pool = projectile_pool.status();
+        info!(
+            "Entities: {} total | {} players | {} boids in {} groups | {} projectiles ({} active, {} pooled)",
+            entities.iter().count(),
+            players.iter().count(),
+            boids.iter().count(),
+            groups.iter().count(),
+            projectiles.iter().count(),
+            pool.active,
+            pool.available
+        );
+    }
+}
+
+fn admin_list_groups(
+    mut commands: EventReader<AdminCommand>,
+    groups: Query<(
+        &BoidGroup,
+        &Position,
+        Option<&ActiveBehaviorNode>,
+        Option<&GroupLeader>,
+    )>,
+    boids: Query<&Boid>,
+) {
+    for command in commands.read() {
+        if *command != AdminCommand::Groups {
+            continue;
+        }
+
+        let mut groups: Vec<_> = groups.iter().collect();
+        groups.sort_by_key(|(group, _, _, _)| group.id);
+        info!("{} boid groups", groups.len());
+        for (group, position, active, leader) in groups {
+            let leader = leader
+                .and_then(|leader| boids.get(leader.0).ok())
+                .map(|boid| format!("boid {}", boid.id))
+                .unwrap_or_else(|| "-".to_string());
+            info!(
+                "  group {} ({}) - {:?} at ({:.0}, {:.0}) led by {} <- {}",
+                group.id,
+                archetype_name(&group.archetype),
+                BehaviorKind::of(&group.behavior_state),
+                position.0.x,
+                position.0.y,
+                leader,
+                active
+                    .map(|node| node.0.as_str())
+                    .filter(|node| !node.is_empty())
+                    .unwrap_or("-")
+            );
+        }
+    }
+}
+
+fn admin_profile(mut commands: EventReader<AdminCommand>, profiler: Option<ResMut<TickProfiler>>) {
+    let Some(mut profiler) = profiler else {
+        commands.clear();
+        return;
+    };
+
+    for command in commands.read() {
+        match command {
+            AdminCommand::Profile => info!("Tick ms p50/p95/p99: {}", profiler.summary()),
+            AdminCommand::Trace { ticks, path } => {
+                if profiler.is_capturing() {
+                    warn!("A trace is already being captured");
+                } else {
+                    info!("Tracing the next {} ticks to {}", ticks, path.display());
+                    profiler.start_capture(*ticks, path.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
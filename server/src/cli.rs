@@ -0,0 +1,141 @@
+//! Server command line.
+//!
+//! Flags that correspond to a setting (`--bind`, `--max-players`, `--health-bind`) are turned into
+//! `--set` overrides, so they take part in the usual settings layering and validation.
+
+use crate::settings::parse_override;
+use clap::{Parser, ValueEnum};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Boid Wars game server
+#[derive(Parser, Debug, Clone)]
+#[command(name = "boid-wars-server", version)]
+pub struct ServerArgs {
+    /// Address to listen on [default: network.server_bind_addr]
+    #[arg(long, value_name = "ADDR")]
+    pub bind: Option<SocketAddr>,
+
+    /// Transport clients connect over
+    #[arg(long, value_enum, default_value_t = Transport::WebSocket)]
+    pub transport: Transport,
+
+    /// Arena layout and boid groups from a RON/JSON map file
+    #[arg(long, value_name = "FILE")]
+    pub map: Option<PathBuf>,
+
+    /// Boid group behavior trees from a RON/JSON file [default: built-in trees]
+    #[arg(long, value_name = "FILE")]
+    pub behaviors: Option<PathBuf>,
+
+    /// Seed for the match RNG; random when not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Health and metrics endpoint; empty to disable [default: server.health_bind_addr]
+    #[arg(long, value_name = "ADDR")]
+    pub health_bind: Option<String>,
+
+    /// Player slots per match [default: game.max_players]
+    #[arg(long, value_name = "N")]
+    pub max_players: Option<u8>,
+
+    /// Fixed simulation ticks per second [default: 64]
+    #[arg(long, value_name = "HZ", value_parser = parse_tick_rate)]
+    pub tick_rate: Option<f64>,
+
+    /// Log line format
+    #[arg(long, value_enum, default_value_t = LogFormat::Full)]
+    pub log_format: LogFormat,
+
+    /// Settings file (TOML)
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Override one setting, e.g. `--set flocking.max_speed=450`
+    #[arg(
+        long = "set",
+        value_name = "SECTION.FIELD=VALUE",
+        value_parser = parse_override
+    )]
+    pub overrides: Vec<(String, String)>,
+
+    /// Record the match to a replay file
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Play a recorded match back headless and verify it
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Don't read admin commands from stdin
+    #[arg(long)]
+    pub no_console: bool,
+}
+
+impl ServerArgs {
+    /// `--set` overrides plus the ones implied by dedicated flags, which win
+    pub fn setting_overrides(&self) -> Vec<(String, String)> {
+        let mut overrides = self.overrides.clone();
+        if let Some(bind) = self.bind {
+            overrides.push(("network.server_bind_addr".to_string(), bind.to_string()));
+        }
+        if let Some(max_players) = self.max_players {
+            overrides.push(("game.max_players".to_string(), max_players.to_string()));
+        }
+        if let Some(health_bind) = &self.health_bind {
+            overrides.push(("server.health_bind_addr".to_string(), health_bind.clone()));
+        }
+        overrides
+    }
+
+    /// Fixed timestep for `--tick-rate`, if given
+    pub fn timestep(&self) -> Option<Duration> {
+        self.tick_rate.map(|hz| Duration::from_secs_f64(1.0 / hz))
+    }
+}
+
+/// Network transport the server listens with
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// WebSocket, what the browser client uses
+    #[value(name = "websocket")]
+    WebSocket,
+    /// Plain UDP, for native clients and bots
+    #[value(name = "udp")]
+    Udp,
+}
+
+/// How log lines are written
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable, one event per line with all fields
+    Full,
+    /// Shorter human-readable lines
+    Compact,
+    /// Newline-delimited JSON for log collectors
+    Json,
+}
+
+impl LogFormat {
+    /// Install the global tracing subscriber
+    pub fn init(self) {
+        let builder = tracing_subscriber::fmt();
+        match self {
+            LogFormat::Full => builder.init(),
+            LogFormat::Compact => builder.compact().init(),
+            LogFormat::Json => builder.json().init(),
+        }
+    }
+}
+
+fn parse_tick_rate(value: &str) -> Result<f64, String> {
+    let hz: f64 = value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+    if !(1.0..=1000.0).contains(&hz) {
+        return Err(format!("must be between 1 and 1000 Hz, got {hz}"));
+    }
+    Ok(hz)
+}
@@ -0,0 +1,134 @@
+//! Who can hurt whom: faction damage rules, friendly fire and ship ramming.
+//!
+//! Rapier reports every contact; `collision_system` asks [`CombatConfig`] whether the
+//! attacker's faction may damage the target's before applying anything. Shots that are
+//! not allowed to hurt their target pass through it.
+
+use bevy::prelude::*;
+use boid_wars_shared::{ConfigSection, Validator, PLAYER_MOVE_SPEED};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// The side an attacker or a target fights for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Faction {
+    /// A player ship, by player id. Every ship is its own side in battle royale.
+    Player(u64),
+    /// A boid, by group id; ungrouped boids and shots whose boid has died have none
+    Swarm(Option<u32>),
+}
+
+/// Damage rules between factions and ramming, the `[combat]` section of the settings file
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CombatConfig {
+    // Faction rules
+    pub player_vs_player: bool,
+    pub player_vs_swarm: bool,
+    pub swarm_vs_player: bool,
+    /// Boid shots hurt boids of other groups
+    pub swarm_vs_swarm: bool,
+    /// Boid shots hurt boids of their own group
+    pub swarm_friendly_fire: bool,
+
+    // Ramming
+    /// Ship speed below which touching a boid is a bump rather than a ram
+    pub ram_min_speed: f32,
+    pub ram_damage_to_boid: f32,
+    pub ram_damage_to_player: f32,
+    /// How long the same ship and boid must wait before ramming each other again
+    #[serde(with = "crate::config::duration_secs")]
+    pub ram_cooldown: Duration,
+}
+
+impl Default for CombatConfig {
+    fn default() -> Self {
+        Self {
+            // Faction rules
+            player_vs_player: true,
+            player_vs_swarm: true,
+            swarm_vs_player: true,
+            swarm_vs_swarm: false,
+            swarm_friendly_fire: false,
+
+            // Ramming
+            // Well under a ship's damped top speed, or full-speed rams would not count
+            ram_min_speed: PLAYER_MOVE_SPEED * 0.75,
+            ram_damage_to_boid: 50.0, // Two rams kill a standard boid
+            ram_damage_to_player: 10.0,
+            ram_cooldown: Duration::from_millis(500),
+        }
+    }
+}
+
+impl ConfigSection for CombatConfig {
+    const SECTION: &'static str = "combat";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        check.non_negative("ram_min_speed", self.ram_min_speed);
+        check.non_negative("ram_damage_to_boid", self.ram_damage_to_boid);
+        check.non_negative("ram_damage_to_player", self.ram_damage_to_player);
+    }
+}
+
+impl CombatConfig {
+    /// Whether `attacker` may damage `target`
+    pub fn can_damage(&self, attacker: Faction, target: Faction) -> bool {
+        match (attacker, target) {
+            (Faction::Player(_), Faction::Player(_)) => self.player_vs_player,
+            (Faction::Player(_), Faction::Swarm(_)) => self.player_vs_swarm,
+            (Faction::Swarm(_), Faction::Player(_)) => self.swarm_vs_player,
+            (Faction::Swarm(Some(a)), Faction::Swarm(Some(b))) if a == b => {
+                self.swarm_friendly_fire
+            }
+            (Faction::Swarm(_), Faction::Swarm(_)) => self.swarm_vs_swarm,
+        }
+    }
+
+    /// Damage a ship moving at `ship_speed` deals to and takes from a boid it hits,
+    /// as `(to_boid, to_player)`, or `None` if it is too slow to count as a ram
+    pub fn ram_damage(&self, ship_speed: f32) -> Option<(f32, f32)> {
+        (ship_speed >= self.ram_min_speed)
+            .then_some((self.ram_damage_to_boid, self.ram_damage_to_player))
+    }
+}
+
+/// When each ship last rammed each boid, so one long scrape is not a ram every tick
+#[derive(Resource, Debug, Default)]
+pub struct RamCooldowns {
+    last_ram: HashMap<(Entity, Entity), Duration>,
+}
+
+impl RamCooldowns {
+    /// Record a ram between `ship` and `boid` at `now` unless they rammed within `cooldown`
+    pub fn try_ram(
+        &mut self,
+        ship: Entity,
+        boid: Entity,
+        now: Duration,
+        cooldown: Duration,
+    ) -> bool {
+        match self.last_ram.get(&(ship, boid)) {
+            Some(&last) if now.saturating_sub(last) < cooldown => false,
+            _ => {
+                self.last_ram.insert((ship, boid), now);
+                true
+            }
+        }
+    }
+
+    /// Forget rams older than `cooldown`
+    pub fn expire(&mut self, now: Duration, cooldown: Duration) {
+        self.last_ram
+            .retain(|_, last| now.saturating_sub(*last) < cooldown);
+    }
+
+    pub fn len(&self) -> usize {
+        self.last_ram.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.last_ram.is_empty()
+    }
+}
@@ -1,8 +1,11 @@
 use bevy::prelude::*;
+use boid_wars_shared::{ConfigSection, Validator};
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-/// Physics configuration
-#[derive(Resource)]
+/// Physics configuration, the `[physics]` section of the settings file
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PhysicsConfig {
     // Player physics
     pub player_thrust_force: f32,
@@ -16,6 +19,7 @@ pub struct PhysicsConfig {
 
     // Projectile physics
     pub projectile_speed: f32,
+    #[serde(with = "duration_secs")]
     pub projectile_lifetime: Duration,
     pub projectile_damage: f32,
     pub projectile_fire_rate: f32,
@@ -25,6 +29,7 @@ pub struct PhysicsConfig {
     // Pool configuration
     pub projectile_pool_size: usize,
     pub projectile_pool_initial_spawn: usize,
+    #[serde(skip)]
     pub projectile_pool_offscreen_position: Vec3,
 
     // Arena configuration
@@ -41,6 +46,7 @@ pub struct PhysicsConfig {
     pub boid_radius: f32, // Radius for ball collider (12.0 for 24x24 sprite)
 
     // Boid aggression
+    #[serde(with = "duration_secs")]
     pub boid_aggression_memory_duration: Duration,
     pub boid_aggression_alert_radius: f32,
 }
@@ -55,8 +61,9 @@ impl Default for PhysicsConfig {
             player_max_speed: 800.0,
             player_acceleration: 800.0,
             player_deceleration: 400.0,
-            player_collider_size: 31.2, // Half of 62.4x62.4 sprite size
-            player_damping_factor: 0.98,
+            // Half of 62.4x62.4 sprite size, which is also how close clients predict to the walls
+            player_collider_size: boid_wars_shared::PLAYER_WALL_MARGIN,
+            player_damping_factor: boid_wars_shared::PLAYER_DAMPING_FACTOR,
 
             // Projectile physics
             projectile_speed: 900.0, // Increased from 600.0 for faster bullets
@@ -91,8 +98,59 @@ impl Default for PhysicsConfig {
     }
 }
 
-/// Performance monitoring configuration
-#[derive(Resource)]
+impl ConfigSection for PhysicsConfig {
+    const SECTION: &'static str = "physics";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        check.positive("player_turn_rate", self.player_turn_rate);
+        check.positive("player_max_speed", self.player_max_speed);
+        check.positive("player_collider_size", self.player_collider_size);
+        check.check(
+            "player_damping_factor",
+            (0.0..=1.0).contains(&self.player_damping_factor),
+            format!(
+                "must be between 0 and 1, got {}",
+                self.player_damping_factor
+            ),
+        );
+        check.positive("projectile_speed", self.projectile_speed);
+        check.check(
+            "projectile_lifetime",
+            !self.projectile_lifetime.is_zero(),
+            "must be longer than 0 seconds",
+        );
+        check.non_negative("projectile_damage", self.projectile_damage);
+        check.positive("projectile_fire_rate", self.projectile_fire_rate);
+        check.positive(
+            "projectile_collider_radius",
+            self.projectile_collider_radius,
+        );
+        check.check(
+            "projectile_pool_size",
+            self.projectile_pool_size > 0,
+            "must not be 0",
+        );
+        check.check(
+            "projectile_pool_initial_spawn",
+            self.projectile_pool_initial_spawn <= self.projectile_pool_size,
+            format!(
+                "must not exceed projectile_pool_size ({}), got {}",
+                self.projectile_pool_size, self.projectile_pool_initial_spawn
+            ),
+        );
+        check.non_negative("arena_wall_thickness", self.arena_wall_thickness);
+        check.positive("ai_shoot_interval", self.ai_shoot_interval);
+        check.positive("boid_radius", self.boid_radius);
+        check.non_negative(
+            "boid_aggression_alert_radius",
+            self.boid_aggression_alert_radius,
+        );
+    }
+}
+
+/// Performance monitoring configuration, the `[monitoring]` section of the settings file
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MonitoringConfig {
     pub pool_health_check_interval: f32,
     pub pool_low_threshold: usize,
@@ -112,3 +170,39 @@ impl Default for MonitoringConfig {
         }
     }
 }
+
+impl ConfigSection for MonitoringConfig {
+    const SECTION: &'static str = "monitoring";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        check.positive(
+            "pool_health_check_interval",
+            self.pool_health_check_interval,
+        );
+        check.check(
+            "pool_high_utilization_threshold",
+            (0.0..=100.0).contains(&self.pool_high_utilization_threshold),
+            format!(
+                "must be a percentage, got {}",
+                self.pool_high_utilization_threshold
+            ),
+        );
+        check.positive("status_log_interval", self.status_log_interval);
+        check.positive("projectile_log_interval", self.projectile_log_interval);
+    }
+}
+
+/// Durations as (fractional) seconds in settings files
+pub(crate) mod duration_secs {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+    }
+}
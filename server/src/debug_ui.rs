@@ -45,6 +45,7 @@ fn setup_debug_camera(mut commands: Commands) {
 }
 
 #[cfg(all(debug_assertions, feature = "debug-ui"))]
+#[allow(clippy::too_many_arguments)]
 fn debug_ui_system(
     mut contexts: EguiContexts,
     mut flocking_config: ResMut<FlockingConfig>,
@@ -52,6 +53,10 @@ fn debug_ui_system(
     time: Res<Time>,
     boids: Query<&boid_wars_shared::Velocity, With<boid_wars_shared::Boid>>,
     _spatial_grid: Res<crate::spatial_grid::SpatialGrid>,
+    groups: Query<(
+        &boid_wars_shared::BoidGroup,
+        Option<&crate::groups::behavior_tree::ActiveBehaviorNode>,
+    )>,
     mut clipboard_feedback: Local<Option<std::time::Instant>>,
 ) {
     let ctx = match contexts.ctx_mut() {
@@ -479,6 +484,20 @@ fn debug_ui_system(
                     flocking_config.player_avoidance_radius = 50.0;
                     flocking_config.player_avoidance_weight = 1.0;
                 }
+
+                // Which behavior-tree node is driving each group
+                ui.separator();
+                ui.heading("🧠 Group AI");
+                let mut groups: Vec<_> = groups.iter().collect();
+                groups.sort_by_key(|(group, _)| group.id);
+                for (group, active) in groups {
+                    ui.label(format!(
+                        "Group {}: {:?}",
+                        group.id,
+                        crate::groups::behavior_tree::BehaviorKind::of(&group.behavior_state)
+                    ));
+                    ui.small(active.map_or("-", |node| node.0.as_str()));
+                }
             });
         });
 
@@ -1,12 +1,20 @@
-use crate::spatial_grid::SpatialGrid;
+use crate::groups::roles::{engagement_range, flank_point, flank_side};
+use crate::navigation::Navigation;
+use crate::profiler::TickStage;
+use crate::spatial_grid::{LayerMask, SpatialGrid};
+use crate::zone::ZoneState;
 use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, ParallelSlice, TaskPool};
 use boid_wars_shared::{
-    Boid, BoidGroup, BoidGroupMember, BoidRole, GroupArchetype, GroupBehavior, Player, Position,
-    Velocity,
+    Boid, BoidGroup, BoidGroupMember, BoidRole, ConfigSection, GroupArchetype, GroupBehavior,
+    Player, Position, Validator, Velocity,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-/// Simplified configuration for flocking behavior
-#[derive(Resource, Debug, Clone)]
+/// Simplified configuration for flocking behavior, the `[flocking]` section of the settings file
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FlockingConfig {
     // Detection radii
     pub separation_radius: f32,
@@ -40,12 +48,36 @@ pub struct FlockingConfig {
     pub inter_group_separation_radius: f32,
     pub inter_group_separation_weight: f32,
 
+    // Soft separation at high density
+    /// Neighbours inside the separation radius beyond which a boid counts as crowded
+    pub crowding_threshold: usize,
+    /// Extra separation per threshold's worth of neighbours over it
+    pub crowding_separation_boost: f32,
+
+    // Safe zone containment
+    pub zone_avoidance_weight: f32,
+
+    // Routing around obstacles toward the group's goal
+    pub path_following_weight: f32,
+
+    // Group roles
+    /// Pull on members keeping their place behind the group's leader
+    pub leader_following_weight: f32,
+    pub leader_follow_distance: f32,
+    /// Support boids hang back at this multiple of their group's engagement range
+    pub support_standoff: f32,
+    /// How far ahead of the leader scouts range
+    pub scout_lead_distance: f32,
+
     // Avoidance thresholds and constants
     pub obstacle_danger_zone: f32,
     pub collision_threshold: f32,
     pub corner_boost_multiplier: f32,
     pub wall_prediction_time: f32,
     pub min_velocity_threshold: f32,
+
+    /// Boid count from which steering is spread over the compute task pool
+    pub parallel_threshold: usize,
 }
 
 impl Default for FlockingConfig {
@@ -83,32 +115,93 @@ impl Default for FlockingConfig {
             inter_group_separation_radius: 150.0, // Keep groups well separated
             inter_group_separation_weight: 2.0,   // Strong enough to prevent merging
 
+            // Soft separation at high density
+            crowding_threshold: 8,
+            crowding_separation_boost: 0.5,
+
+            // Safe zone containment
+            zone_avoidance_weight: 3.0, // Below wall avoidance so walls still win
+
+            // Routing around obstacles toward the group's goal
+            path_following_weight: 1.5, // Below obstacle avoidance so close calls still swerve
+
+            // Group roles
+            leader_following_weight: 1.2,
+            leader_follow_distance: 60.0,
+            support_standoff: 1.4,
+            scout_lead_distance: 180.0,
+
             // Avoidance thresholds and constants
             obstacle_danger_zone: 40.0,
             collision_threshold: 30.0,
             corner_boost_multiplier: 1.5,
             wall_prediction_time: 1.0,
             min_velocity_threshold: 0.1,
+
+            // Below this, task overhead outweighs the work
+            parallel_threshold: 256,
         }
     }
 }
 
+impl ConfigSection for FlockingConfig {
+    const SECTION: &'static str = "flocking";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        for (field, value) in [
+            ("separation_radius", self.separation_radius),
+            ("alignment_radius", self.alignment_radius),
+            ("cohesion_radius", self.cohesion_radius),
+            ("separation_weight", self.separation_weight),
+            ("alignment_weight", self.alignment_weight),
+            ("cohesion_weight", self.cohesion_weight),
+            ("boundary_margin", self.boundary_margin),
+            ("wall_avoidance_weight", self.wall_avoidance_weight),
+            ("obstacle_avoidance_radius", self.obstacle_avoidance_radius),
+            ("obstacle_avoidance_weight", self.obstacle_avoidance_weight),
+            ("player_avoidance_radius", self.player_avoidance_radius),
+            ("player_avoidance_weight", self.player_avoidance_weight),
+            (
+                "inter_group_separation_radius",
+                self.inter_group_separation_radius,
+            ),
+            (
+                "inter_group_separation_weight",
+                self.inter_group_separation_weight,
+            ),
+            ("zone_avoidance_weight", self.zone_avoidance_weight),
+            ("path_following_weight", self.path_following_weight),
+            ("leader_following_weight", self.leader_following_weight),
+            ("leader_follow_distance", self.leader_follow_distance),
+            ("scout_lead_distance", self.scout_lead_distance),
+            ("crowding_separation_boost", self.crowding_separation_boost),
+        ] {
+            check.non_negative(field, value);
+        }
+        check.positive("max_speed", self.max_speed);
+        check.positive("max_force", self.max_force);
+        check.positive("support_standoff", self.support_standoff);
+    }
+}
+
 /// Simple flocking system that updates boid velocities
-#[allow(clippy::type_complexity)]
+///
+/// Steering is computed from a snapshot of every boid taken at the start of the tick,
+/// in parallel on the compute task pool once there are enough boids, and the new
+/// velocities are written back afterwards.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn update_flocking(
     mut boids: Query<(Entity, &Position, &mut Velocity, Option<&BoidGroupMember>), With<Boid>>,
-    obstacle_query: Query<(&Position, &boid_wars_shared::Obstacle), Without<Boid>>,
-    player_query: Query<
-        (&Position, &Velocity, &Player),
-        (With<boid_wars_shared::Player>, Without<Boid>),
-    >,
-    group_query: Query<&BoidGroup>,
+    obstacle_query: ObstacleQuery,
+    player_query: PlayerQuery,
+    group_query: Query<&'static BoidGroup>,
     spatial_grid: Res<SpatialGrid>,
     config: Res<FlockingConfig>,
+    zone: Option<Res<ZoneState>>,
+    navigation: Option<Res<Navigation>>,
     time: Res<Time>,
 ) {
     let game_config = &*boid_wars_shared::GAME_CONFIG;
-    let delta = time.delta_secs();
 
     // Use largest radius for spatial query - include avoidance radii
     let search_radius = config
@@ -119,22 +212,146 @@ pub fn update_flocking(
         .max(config.player_avoidance_radius)
         .max(config.inter_group_separation_radius);
 
-    // Collect all boid data first to avoid borrow checker issues
-    // Now includes group_id for inter-group separation
-    let boid_data: Vec<(Entity, Vec2, Vec2, Option<u32>)> = boids
+    // Snapshot every boid so neighbours can be read while velocities are computed
+    let states: Vec<BoidState> = boids
         .iter()
-        .map(|(entity, pos, vel, group_member)| {
-            let group_id = group_member.as_ref().map(|m| m.group_id);
-            (entity, pos.0, vel.0, group_id)
+        .map(|(entity, pos, vel, group_member)| BoidState {
+            entity,
+            position: pos.0,
+            velocity: vel.0,
+            member: group_member.cloned(),
         })
         .collect();
+    let index: HashMap<Entity, usize> = states
+        .iter()
+        .enumerate()
+        .map(|(i, state)| (state.entity, i))
+        .collect();
+    // Each group's leader, by group entity
+    let leaders: HashMap<Entity, usize> = states
+        .iter()
+        .enumerate()
+        .filter_map(|(i, state)| {
+            let member = state.member.as_ref()?;
+            (member.role_in_group == BoidRole::Leader).then_some((member.group_entity, i))
+        })
+        .collect();
+
+    let steering = Steering {
+        config: &config,
+        grid: &spatial_grid,
+        states: &states,
+        index: &index,
+        leaders: &leaders,
+        obstacles: &obstacle_query,
+        players: &player_query,
+        groups: &group_query,
+        navigation: navigation.as_deref(),
+        // Active safe zone, if any (center, radius)
+        safe_zone: zone
+            .as_deref()
+            .filter(|zone| zone.is_active())
+            .map(|zone| (zone.center, zone.radius)),
+        arena: Vec2::new(game_config.game_width, game_config.game_height),
+        search_radius,
+        delta: time.delta_secs(),
+    };
+
+    // Each boid only reads the snapshot, so the order of evaluation doesn't matter
+    let velocities: Vec<Vec2> = if states.len() >= config.parallel_threshold {
+        let pool = ComputeTaskPool::get_or_init(TaskPool::default);
+        let chunk_size = states.len().div_ceil(pool.thread_num().max(1) * 4).max(1);
+        states
+            .par_chunk_map(pool, chunk_size, |_, chunk| {
+                chunk
+                    .iter()
+                    .map(|boid| steering.steer(boid))
+                    .collect::<Vec<_>>()
+            })
+            .into_iter()
+            .flatten()
+            .collect()
+    } else {
+        states.iter().map(|boid| steering.steer(boid)).collect()
+    };
+
+    // Write back
+    for (entity, _, mut vel, _) in boids.iter_mut() {
+        if let Some(&i) = index.get(&entity) {
+            vel.0 = velocities[i];
+        }
+    }
+}
+
+/// A boid as it was at the start of the tick
+#[derive(Debug, Clone)]
+pub struct BoidState {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub member: Option<BoidGroupMember>,
+}
+
+type ObstacleQuery<'w, 's> =
+    Query<'w, 's, (&'static Position, &'static boid_wars_shared::Obstacle), Without<Boid>>;
+type PlayerQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Position, &'static Velocity, &'static Player),
+    (With<boid_wars_shared::Player>, Without<Boid>),
+>;
+
+/// Everything steering reads, shared by all worker threads
+struct Steering<'a, 'w, 's> {
+    config: &'a FlockingConfig,
+    grid: &'a SpatialGrid,
+    states: &'a [BoidState],
+    index: &'a HashMap<Entity, usize>,
+    leaders: &'a HashMap<Entity, usize>,
+    obstacles: &'a ObstacleQuery<'w, 's>,
+    players: &'a PlayerQuery<'w, 's>,
+    groups: &'a Query<'w, 's, &'static BoidGroup>,
+    navigation: Option<&'a Navigation>,
+    safe_zone: Option<(Vec2, f32)>,
+    arena: Vec2,
+    search_radius: f32,
+    delta: f32,
+}
 
-    // Create a HashSet for O(1) boid lookups
-    let boid_entities: std::collections::HashSet<Entity> =
-        boid_data.iter().map(|(e, _, _, _)| *e).collect();
+impl Steering<'_, '_, '_> {
+    fn neighbor(&self, entity: Entity) -> Option<&BoidState> {
+        self.index.get(&entity).map(|&i| &self.states[i])
+    }
+
+    fn leader(&self, group: Entity) -> Option<&BoidState> {
+        self.leaders.get(&group).map(|&i| &self.states[i])
+    }
+
+    /// Which way `group`'s leader is going: where it's moving, or where its route
+    /// points while it's standing still
+    fn leader_heading(&self, group: Entity, leader: &BoidState) -> Vec2 {
+        if leader.velocity.length() > self.config.min_velocity_threshold {
+            return leader.velocity.normalize();
+        }
+        self.navigation
+            .and_then(|navigation| navigation.heading(group, leader.position))
+            .unwrap_or(Vec2::ZERO)
+    }
+
+    /// Direction from `position` toward `goal` for a member of `group`,
+    /// routed around obstacles when the straight line is blocked
+    fn heading(&self, group: Entity, position: Vec2, goal: Vec2) -> Vec2 {
+        self.navigation
+            .and_then(|navigation| navigation.heading(group, position))
+            .unwrap_or_else(|| (goal - position).normalize_or_zero())
+    }
+
+    /// New velocity for one boid
+    fn steer(&self, boid: &BoidState) -> Vec2 {
+        let config = self.config;
+        let (position, velocity) = (boid.position, boid.velocity);
+        let group_member = boid.member.as_ref();
 
-    // Update each boid
-    for (entity, pos, mut vel, group_member) in boids.iter_mut() {
         let mut separation = Vec2::ZERO;
         let mut alignment = Vec2::ZERO;
         let mut cohesion = Vec2::ZERO;
@@ -147,26 +364,25 @@ pub fn update_flocking(
         // Get this boid's group ID
         let my_group_id = group_member.as_ref().map(|m| m.group_id);
 
-        // Get nearby entities from spatial grid
-        let nearby = spatial_grid.get_nearby_entities(pos.0, search_radius);
-
-        // Calculate flocking forces from neighbors
-        for &other_entity in &nearby {
-            if other_entity == entity {
+        // Calculate flocking forces from neighboring boids
+        for entry in self
+            .grid
+            .nearby(position, self.search_radius, LayerMask::BOID)
+        {
+            if entry.entity == boid.entity {
                 continue;
             }
 
-            // Find the other boid's data in our collected data
-            if let Some((_, other_pos, other_vel, other_group_id)) =
-                boid_data.iter().find(|(e, _, _, _)| *e == other_entity)
-            {
-                let diff = pos.0 - *other_pos;
+            // Look the other boid up in the snapshot
+            if let Some(other) = self.neighbor(entry.entity) {
+                let other_group_id = other.member.as_ref().map(|m| m.group_id);
+                let diff = position - other.position;
                 let distance = diff.length();
 
                 // Check if boids are in the same group
                 let same_group = my_group_id.is_some()
                     && other_group_id.is_some()
-                    && my_group_id == *other_group_id;
+                    && my_group_id == other_group_id;
 
                 // Separation: avoid crowding (applies to all nearby boids)
                 if distance > 0.0 && distance < config.separation_radius {
@@ -179,13 +395,13 @@ pub fn update_flocking(
                 if same_group {
                     // Alignment: match velocity
                     if distance < config.alignment_radius {
-                        alignment += *other_vel;
+                        alignment += other.velocity;
                         align_count += 1;
                     }
 
                     // Cohesion: move toward center
                     if distance < config.cohesion_radius {
-                        cohesion += *other_pos;
+                        cohesion += other.position;
                         cohesion_count += 1;
                     }
                 } else if my_group_id.is_some() && other_group_id.is_some() {
@@ -205,11 +421,11 @@ pub fn update_flocking(
         // Apply separation with archetype-specific preferences
         if sep_count > 0 {
             separation = (separation / sep_count as f32).normalize_or_zero() * config.max_speed;
-            separation = (separation - vel.0).clamp_length_max(config.max_force);
+            separation = (separation - velocity).clamp_length_max(config.max_force);
 
             // Archetype-specific separation behavior
             let separation_multiplier = if let Some(member) = group_member {
-                if let Ok(group) = group_query.get(member.group_entity) {
+                if let Ok(group) = self.groups.get(member.group_entity) {
                     match &group.archetype {
                         GroupArchetype::Assault { .. } => 0.7, // Prefer tighter formations for aggressive attacks
                         GroupArchetype::Defensive { .. } => 1.3, // Want more personal space for defensive positioning
@@ -222,17 +438,27 @@ pub fn update_flocking(
                 1.0
             };
 
-            acceleration += separation * config.separation_weight * separation_multiplier;
+            // Packed boids push apart harder than the averaged separation alone manages
+            let crowding = if config.crowding_threshold > 0 && sep_count > config.crowding_threshold
+            {
+                let excess = (sep_count - config.crowding_threshold) as f32;
+                1.0 + config.crowding_separation_boost * excess / config.crowding_threshold as f32
+            } else {
+                1.0
+            };
+
+            acceleration +=
+                separation * config.separation_weight * separation_multiplier * crowding;
         }
 
         // Apply alignment with archetype-specific coordination
         if align_count > 0 {
             alignment = (alignment / align_count as f32).normalize_or_zero() * config.max_speed;
-            alignment = (alignment - vel.0).clamp_length_max(config.max_force);
+            alignment = (alignment - velocity).clamp_length_max(config.max_force);
 
             // Archetype-specific alignment behavior
             let alignment_multiplier = if let Some(member) = group_member {
-                if let Ok(group) = group_query.get(member.group_entity) {
+                if let Ok(group) = self.groups.get(member.group_entity) {
                     match &group.archetype {
                         GroupArchetype::Assault { .. } => 1.4, // Strong coordination for unified attacks
                         GroupArchetype::Defensive { .. } => 1.2, // Good coordination for defensive lines
@@ -251,12 +477,12 @@ pub fn update_flocking(
         // Apply cohesion with reduced weight for Defensive groups
         if cohesion_count > 0 {
             let center = cohesion / cohesion_count as f32;
-            let desired = (center - pos.0).normalize_or_zero() * config.max_speed;
-            cohesion = (desired - vel.0).clamp_length_max(config.max_force);
+            let desired = (center - position).normalize_or_zero() * config.max_speed;
+            cohesion = (desired - velocity).clamp_length_max(config.max_force);
 
             // Reduce cohesion for Defensive groups to make them spread farther apart
             let cohesion_multiplier = if let Some(member) = group_member {
-                if let Ok(group) = group_query.get(member.group_entity) {
+                if let Ok(group) = self.groups.get(member.group_entity) {
                     match &group.archetype {
                         GroupArchetype::Defensive { .. } => 0.3, // Much weaker cohesion for spreading
                         _ => 1.0,                                // Normal cohesion for other groups
@@ -277,37 +503,24 @@ pub fn update_flocking(
                 .normalize_or_zero()
                 * config.max_speed;
             inter_group_separation =
-                (inter_group_separation - vel.0).clamp_length_max(config.max_force);
+                (inter_group_separation - velocity).clamp_length_max(config.max_force);
             acceleration += inter_group_separation * config.inter_group_separation_weight;
         }
 
-        // Early exit optimization: if only boids nearby, skip avoidance calculations
-        let mut non_boid_count = 0;
-        for &other_entity in &nearby {
-            if other_entity != entity && !boid_entities.contains(&other_entity) {
-                non_boid_count += 1;
-                break; // Found at least one non-boid, proceed with avoidance
-            }
-        }
-
         let mut obstacle_force = Vec2::ZERO;
         let mut player_force = Vec2::ZERO;
         let mut obstacle_count = 0;
         let mut player_count = 0;
 
-        // Only calculate avoidance if there are non-boid entities nearby
-        if non_boid_count > 0 {
-            for &other_entity in &nearby {
-                // Skip self and other boids (handled by flocking)
-                if other_entity == entity || boid_entities.contains(&other_entity) {
-                    continue;
-                }
-
-                // Check for obstacles
-                if let Ok((obs_pos, obs)) = obstacle_query.get(other_entity) {
+        // Avoid nearby obstacles and players; other boids are handled by flocking
+        let avoid = LayerMask::OBSTACLE | LayerMask::PLAYER;
+        for entry in self.grid.nearby(position, self.search_radius, avoid) {
+            // Check for obstacles
+            if entry.layers.contains(LayerMask::OBSTACLE) {
+                if let Ok((obs_pos, obs)) = self.obstacles.get(entry.entity) {
                     let force = calculate_obstacle_avoidance(
-                        pos.0,
-                        vel.0,
+                        position,
+                        velocity,
                         obs_pos.0,
                         Vec2::new(obs.width / 2.0, obs.height / 2.0),
                         config.obstacle_prediction_time,
@@ -316,14 +529,16 @@ pub fn update_flocking(
                     obstacle_force += force;
                     obstacle_count += 1;
                 }
+            }
 
-                // Check for players
-                if let Ok((player_pos, player_vel, _player)) = player_query.get(other_entity) {
-                    let distance = pos.0.distance(player_pos.0);
+            // Check for players
+            if entry.layers.contains(LayerMask::PLAYER) {
+                if let Ok((player_pos, player_vel, _player)) = self.players.get(entry.entity) {
+                    let distance = position.distance(player_pos.0);
                     if distance < config.player_avoidance_radius {
                         let force = calculate_dynamic_avoidance(
-                            pos.0,
-                            vel.0,
+                            position,
+                            velocity,
                             player_pos.0,
                             player_vel.0,
                             config.player_avoidance_radius,
@@ -340,26 +555,73 @@ pub fn update_flocking(
         let mut pursuit_force = Vec2::ZERO;
         let mut is_pursuing = false;
         let mut is_defensive_keeping_distance = false;
+        let mut path_force = Vec2::ZERO;
+        let mut follow_force = Vec2::ZERO;
 
         if let Some(member) = group_member {
-            if let Ok(group) = group_query.get(member.group_entity) {
+            if let Ok(group) = self.groups.get(member.group_entity) {
+                // Whoever leads this boid's group, unless it's this boid
+                let leader = self
+                    .leader(member.group_entity)
+                    .filter(|leader| leader.entity != boid.entity);
+
                 match &group.behavior_state {
                     GroupBehavior::Retreating {
                         rally_point,
                         speed_multiplier,
                     } => {
                         // Move toward rally point with enhanced speed
-                        let direction = (*rally_point - pos.0).normalize_or_zero();
+                        let direction = self.heading(member.group_entity, position, *rally_point);
                         pursuit_force = direction * config.max_speed * speed_multiplier;
                         is_pursuing = true;
                     }
                     GroupBehavior::Engaging { primary_target, .. } => {
                         // Find the target player
-                        for (target_pos, _, target_player) in player_query.iter() {
-                            if target_player.id as u32 == *primary_target {
-                                let distance_to_target = pos.0.distance(target_pos.0);
-
-                                match &group.archetype {
+                        if let Some((target_pos, _, _)) = self
+                            .players
+                            .iter()
+                            .find(|(_, _, player)| player.id as u32 == *primary_target)
+                        {
+                            let distance_to_target = position.distance(target_pos.0);
+                            let range = engagement_range(&group.archetype);
+
+                            match member.role_in_group {
+                                BoidRole::Flanker => {
+                                    // Swing round to the target's side, then keep circling it
+                                    let anchor = leader.map_or(position, |leader| leader.position);
+                                    let side = flank_side(boid.entity);
+                                    let to_flank =
+                                        flank_point(anchor, target_pos.0, side, range) - position;
+                                    if to_flank.length() > range * 0.25 {
+                                        pursuit_force =
+                                            to_flank.normalize() * config.max_speed * 1.2;
+                                    } else {
+                                        let around = (position - target_pos.0).perp() * side;
+                                        pursuit_force =
+                                            around.normalize_or_zero() * config.max_speed * 0.6;
+                                    }
+                                    is_pursuing = true;
+                                }
+                                BoidRole::Support => {
+                                    // Hang back behind the fight and shoot from there
+                                    let standoff = range * config.support_standoff;
+                                    if distance_to_target < standoff {
+                                        let direction =
+                                            (position - target_pos.0).normalize_or_zero();
+                                        pursuit_force = direction * config.max_speed * 0.5;
+                                        is_defensive_keeping_distance = true;
+                                    } else if distance_to_target > standoff * 1.3 {
+                                        let direction = self.heading(
+                                            member.group_entity,
+                                            position,
+                                            target_pos.0,
+                                        );
+                                        pursuit_force = direction * config.max_speed * 0.8;
+                                        is_pursuing = true;
+                                    }
+                                }
+                                // Leaders and scouts press the attack the group's way
+                                BoidRole::Leader | BoidRole::Scout => match &group.archetype {
                                     GroupArchetype::Defensive {
                                         protection_radius, ..
                                     } => {
@@ -369,13 +631,16 @@ pub fn update_flocking(
                                         if distance_to_target < preferred_distance {
                                             // Too close - back away while maintaining sight (very slow)
                                             let direction =
-                                                (pos.0 - target_pos.0).normalize_or_zero();
+                                                (position - target_pos.0).normalize_or_zero();
                                             pursuit_force = direction * config.max_speed * 0.4; // Much slower retreat
                                             is_defensive_keeping_distance = true;
                                         } else if distance_to_target > preferred_distance * 1.8 {
                                             // Too far - move closer very slowly
-                                            let direction =
-                                                (target_pos.0 - pos.0).normalize_or_zero();
+                                            let direction = self.heading(
+                                                member.group_entity,
+                                                position,
+                                                target_pos.0,
+                                            );
                                             pursuit_force = direction * config.max_speed * 0.3; // Very slow approach
                                             is_pursuing = true;
                                         }
@@ -384,42 +649,20 @@ pub fn update_flocking(
                                     GroupArchetype::Assault {
                                         preferred_range, ..
                                     } => {
-                                        // Assault groups: Direct aggressive pursuit with flanking
+                                        // Assault groups: Direct aggressive pursuit
                                         if distance_to_target > *preferred_range {
-                                            let mut direction =
-                                                (target_pos.0 - pos.0).normalize_or_zero();
-
-                                            // Add flanking behavior based on boid role
-                                            if let Some(member) = group_member {
-                                                match member.role_in_group {
-                                                    BoidRole::Flanker => {
-                                                        // Flankers approach from sides
-                                                        let perpendicular =
-                                                            Vec2::new(-direction.y, direction.x);
-                                                        let side_offset =
-                                                            if (pos.0.x + pos.0.y) % 2.0 > 1.0 {
-                                                                1.0
-                                                            } else {
-                                                                -1.0
-                                                            };
-                                                        direction +=
-                                                            perpendicular * side_offset * 0.3;
-                                                        direction = direction.normalize_or_zero();
-                                                    }
-                                                    BoidRole::Leader => {
-                                                        // Leaders charge directly
-                                                        pursuit_force =
-                                                            direction * config.max_speed * 1.4;
-                                                        // Extra aggressive
-                                                    }
-                                                    _ => {}
-                                                }
-                                            }
-
-                                            if pursuit_force.length_squared() == 0.0 {
-                                                pursuit_force = direction * config.max_speed * 1.2;
-                                                // Default aggressive pursuit
-                                            }
+                                            let direction = self.heading(
+                                                member.group_entity,
+                                                position,
+                                                target_pos.0,
+                                            );
+
+                                            // Leaders charge in ahead of the rest
+                                            let charge = match member.role_in_group {
+                                                BoidRole::Leader => 1.4, // Extra aggressive
+                                                _ => 1.2, // Default aggressive pursuit
+                                            };
+                                            pursuit_force = direction * config.max_speed * charge;
                                             is_pursuing = true;
                                         }
                                     }
@@ -429,14 +672,17 @@ pub fn update_flocking(
                                         // Recon groups: Hit-and-run with circling patterns
                                         if distance_to_target > 250.0 {
                                             // Too far - approach for harassment
-                                            let direction =
-                                                (target_pos.0 - pos.0).normalize_or_zero();
+                                            let direction = self.heading(
+                                                member.group_entity,
+                                                position,
+                                                target_pos.0,
+                                            );
                                             pursuit_force =
                                                 direction * config.max_speed * flee_speed_bonus;
                                             is_pursuing = true;
                                         } else if distance_to_target < 180.0 {
                                             // Too close - circle strafe
-                                            let to_target = target_pos.0 - pos.0;
+                                            let to_target = target_pos.0 - position;
                                             let perpendicular =
                                                 Vec2::new(-to_target.y, to_target.x)
                                                     .normalize_or_zero();
@@ -449,12 +695,47 @@ pub fn update_flocking(
                                         }
                                         // If in optimal range (180-250), maintain position
                                     }
+                                },
+                            }
+                        }
+                    }
+                    GroupBehavior::Patrolling { .. } | GroupBehavior::Defending { .. } => {
+                        match (member.role_in_group, leader) {
+                            // Scouts range out ahead of the leader, spread to either side
+                            (BoidRole::Scout, Some(leader)) => {
+                                let heading = self.leader_heading(member.group_entity, leader);
+                                let side = flank_side(boid.entity);
+                                let spot = leader.position
+                                    + (heading + heading.perp() * side * 0.5)
+                                        * config.scout_lead_distance;
+                                let to_spot = spot - position;
+                                if heading != Vec2::ZERO
+                                    && to_spot.length() > config.separation_radius
+                                {
+                                    path_force = to_spot.normalize() * config.max_speed;
+                                }
+                            }
+                            // Everyone else keeps behind the leader
+                            (_, Some(leader)) => {
+                                let heading = self.leader_heading(member.group_entity, leader);
+                                let spot =
+                                    leader.position - heading * config.leader_follow_distance;
+                                let to_spot = spot - position;
+                                if to_spot.length() > config.leader_follow_distance * 0.5 {
+                                    follow_force = to_spot.normalize() * config.max_speed;
+                                }
+                            }
+                            // The leader, or anyone in a leaderless group, follows the route
+                            // to the next waypoint or the defended position
+                            (_, None) => {
+                                if let Some(direction) = self.navigation.and_then(|navigation| {
+                                    navigation.heading(member.group_entity, position)
+                                }) {
+                                    path_force = direction * config.max_speed;
                                 }
-                                break;
                             }
                         }
                     }
-                    _ => {} // Other group behaviors (Patrolling, Defending, etc.)
                 }
             }
         }
@@ -463,11 +744,11 @@ pub fn update_flocking(
         if obstacle_count > 0 {
             let avg_force =
                 (obstacle_force / obstacle_count as f32).normalize_or_zero() * config.max_speed;
-            let steering = (avg_force - vel.0).clamp_length_max(config.max_force);
+            let steering = (avg_force - velocity).clamp_length_max(config.max_force);
 
             // Archetype-specific obstacle avoidance
             let obstacle_multiplier = if let Some(member) = group_member {
-                if let Ok(group) = group_query.get(member.group_entity) {
+                if let Ok(group) = self.groups.get(member.group_entity) {
                     match &group.archetype {
                         GroupArchetype::Assault { .. } => 0.8, // More aggressive, less cautious around obstacles
                         GroupArchetype::Defensive { .. } => 1.4, // Very cautious, avoid obstacles early
@@ -487,28 +768,40 @@ pub fn update_flocking(
         if player_count > 0 && !is_pursuing && !is_defensive_keeping_distance {
             let avg_force =
                 (player_force / player_count as f32).normalize_or_zero() * config.max_speed;
-            let steering = (avg_force - vel.0).clamp_length_max(config.max_force);
+            let steering = (avg_force - velocity).clamp_length_max(config.max_force);
             acceleration += steering * config.player_avoidance_weight;
         }
 
         // Apply pursuit force if pursuing
         if is_pursuing {
-            let steering = (pursuit_force - vel.0).clamp_length_max(config.max_force);
+            let steering = (pursuit_force - velocity).clamp_length_max(config.max_force);
             acceleration += steering * 2.0; // Strong pursuit force
         }
 
         // Apply defensive positioning force if keeping distance
         if is_defensive_keeping_distance {
-            let steering = (pursuit_force - vel.0).clamp_length_max(config.max_force);
+            let steering = (pursuit_force - velocity).clamp_length_max(config.max_force);
             acceleration += steering * 1.5; // Moderate retreat force
         }
 
+        // Apply path following toward patrol waypoints and defended positions
+        if path_force != Vec2::ZERO {
+            let steering = (path_force - velocity).clamp_length_max(config.max_force);
+            acceleration += steering * config.path_following_weight;
+        }
+
+        // Apply leader following for members keeping formation behind the leader
+        if follow_force != Vec2::ZERO {
+            let steering = (follow_force - velocity).clamp_length_max(config.max_force);
+            acceleration += steering * config.leader_following_weight;
+        }
+
         // Apply enhanced wall avoidance
         let wall_force = calculate_wall_avoidance(
-            pos.0,
-            vel.0,
-            game_config.game_width,
-            game_config.game_height,
+            position,
+            velocity,
+            self.arena.x,
+            self.arena.y,
             config.boundary_margin,
             config.wall_prediction_time,
             config.corner_boost_multiplier,
@@ -516,13 +809,23 @@ pub fn update_flocking(
         );
         if wall_force.length_squared() > 0.0 {
             let desired_vel = wall_force * config.max_speed;
-            let steering = (desired_vel - vel.0).clamp_length_max(config.max_force);
+            let steering = (desired_vel - velocity).clamp_length_max(config.max_force);
             acceleration += steering * config.wall_avoidance_weight;
         }
 
+        // Steer back inside the safe zone
+        if let Some((zone_center, zone_radius)) = self.safe_zone {
+            let to_center = zone_center - position;
+            if to_center.length() > zone_radius {
+                let desired_vel = to_center.normalize_or_zero() * config.max_speed;
+                let steering = (desired_vel - velocity).clamp_length_max(config.max_force);
+                acceleration += steering * config.zone_avoidance_weight;
+            }
+        }
+
         // Update velocity with archetype-specific speed and agility
         let (speed_multiplier, agility_multiplier) = if let Some(member) = group_member {
-            if let Ok(group) = group_query.get(member.group_entity) {
+            if let Ok(group) = self.groups.get(member.group_entity) {
                 match &group.archetype {
                     GroupArchetype::Assault { .. } => (1.1, 1.3), // Fast and agile for aggressive maneuvers
                     GroupArchetype::Defensive { .. } => (0.6, 0.7), // Much slower and less agile, methodical
@@ -535,8 +838,8 @@ pub fn update_flocking(
             (1.0, 1.0)
         };
 
-        vel.0 += acceleration * delta * agility_multiplier;
-        vel.0 = vel.0.clamp_length_max(config.max_speed * speed_multiplier);
+        let velocity = velocity + acceleration * self.delta * agility_multiplier;
+        velocity.clamp_length_max(config.max_speed * speed_multiplier)
     }
 }
 
@@ -572,7 +875,8 @@ impl Plugin for FlockingPlugin {
                 update_flocking.in_set(crate::spatial_grid::SpatialGridSet::Read),
                 sync_boid_velocities.after(update_flocking),
                 // Physics will move the boids
-            ),
+            )
+                .in_set(TickStage::Flocking),
         );
 
         // Sync positions after physics
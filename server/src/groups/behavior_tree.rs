@@ -0,0 +1,604 @@
+//! Data-driven decisions for boid groups.
+//!
+//! Each archetype has a behavior tree, loaded from RON (the bundled
+//! `behaviors/default.ron` unless `--behaviors` names another file). Every tick a group
+//! gathers its [`GroupFacts`], walks its tree and the first [`Action`] reached switches
+//! its [`GroupBehavior`]. The path to that action is kept in [`ActiveBehaviorNode`] for
+//! the admin console's `groups` command and the debug UI.
+
+use crate::groups::BoidGroupConfig;
+use crate::map_file::ValidationError;
+use crate::physics::BoidAggression;
+use crate::profiler::TickStage;
+use bevy::prelude::*;
+use boid_wars_shared::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+/// The trees built into the server
+pub const DEFAULT_BEHAVIORS: &str = include_str!("../../behaviors/default.ron");
+
+/// Share of a group that may die before it breaks off, for archetypes without
+/// their own `retreat_threshold`
+pub const DEFAULT_RETREAT_THRESHOLD: f32 = 0.3;
+
+/// A group behavior without its data, what `InState` checks against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BehaviorKind {
+    Patrolling,
+    Engaging,
+    Retreating,
+    Defending,
+}
+
+impl BehaviorKind {
+    pub fn of(behavior: &GroupBehavior) -> Self {
+        match behavior {
+            GroupBehavior::Patrolling { .. } => BehaviorKind::Patrolling,
+            GroupBehavior::Engaging { .. } => BehaviorKind::Engaging,
+            GroupBehavior::Retreating { .. } => BehaviorKind::Retreating,
+            GroupBehavior::Defending { .. } => BehaviorKind::Defending,
+        }
+    }
+}
+
+/// Something a tree can test about a group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    /// The group's current behavior
+    InState(BehaviorKind),
+    /// Engaging a player who is no longer in the game
+    TargetLost,
+    /// A player is within the group's detection range (Recon's `detection_range`,
+    /// otherwise `groups.group_aggression_range`) or `groups.scout_detection_range`
+    /// of one of its scouts
+    ThreatInRange,
+    /// The nearest threat has shot at boids recently
+    Provoked,
+    /// More than this share of the group has died
+    LossesAbove(f32),
+    /// More of the group has died than its archetype's `retreat_threshold`
+    HeavyLosses,
+    /// Retreating and within this distance of the rally point
+    NearRallyPoint(f32),
+    Not(Box<Condition>),
+}
+
+/// What a tree can make a group do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    /// Follow the home territory's patrol route
+    Patrol,
+    /// Attack the nearest threat; fails when there is none
+    Engage,
+    /// Fall back to the home territory's center. Recon groups flee faster by
+    /// their `flee_speed_bonus`.
+    Retreat { speed_multiplier: f32 },
+    /// Hold the rally point when retreating, otherwise the home territory's center
+    Defend { radius: f32 },
+    /// Keep doing what the group is doing
+    Hold,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BehaviorNode {
+    /// Tries children in order until one succeeds
+    Selector {
+        name: String,
+        children: Vec<BehaviorNode>,
+    },
+    /// Succeeds only if every child does, stopping at the first failure
+    Sequence {
+        name: String,
+        children: Vec<BehaviorNode>,
+    },
+    Check(Condition),
+    Act(Action),
+}
+
+/// The nearest player in detection range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Threat {
+    pub player_id: u64,
+    /// From the group's center or the nearest scout, whichever is closer
+    pub distance: f32,
+}
+
+/// Everything a tree can ask about one group this tick
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupFacts {
+    pub behavior: BehaviorKind,
+    /// Whether the engaged player still exists; true when not engaging
+    pub target_present: bool,
+    pub threat: Option<Threat>,
+    /// The threat has attacked boids within the aggression memory
+    pub provoked: bool,
+    /// Share of the group's initial size that has died
+    pub losses: f32,
+    pub retreat_threshold: f32,
+    /// Distance to the rally point while retreating
+    pub rally_distance: Option<f32>,
+}
+
+impl Condition {
+    pub fn holds(&self, facts: &GroupFacts) -> bool {
+        match self {
+            Condition::InState(kind) => facts.behavior == *kind,
+            Condition::TargetLost => {
+                facts.behavior == BehaviorKind::Engaging && !facts.target_present
+            }
+            Condition::ThreatInRange => facts.threat.is_some(),
+            Condition::Provoked => facts.threat.is_some() && facts.provoked,
+            Condition::LossesAbove(share) => facts.losses > *share,
+            Condition::HeavyLosses => facts.losses > facts.retreat_threshold,
+            Condition::NearRallyPoint(distance) => facts
+                .rally_distance
+                .is_some_and(|rally_distance| rally_distance < *distance),
+            Condition::Not(condition) => !condition.holds(facts),
+        }
+    }
+}
+
+/// The action a tree picked and the names of the nodes that led to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision<'a> {
+    pub action: &'a Action,
+    pub path: Vec<&'a str>,
+}
+
+impl Decision<'_> {
+    /// `root > branch > Action`, as shown in the debug views
+    pub fn describe(&self) -> String {
+        let mut text = self.path.join(" > ");
+        if !text.is_empty() {
+            text.push_str(" > ");
+        }
+        text.push_str(self.action.label());
+        text
+    }
+
+    /// Whether `text` is what [`describe`](Self::describe) returns, without building it
+    pub fn is_described_by(&self, text: &str) -> bool {
+        let mut rest = text;
+        for name in &self.path {
+            match rest
+                .strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix(" > "))
+            {
+                Some(remaining) => rest = remaining,
+                None => return false,
+            }
+        }
+        rest == self.action.label()
+    }
+}
+
+impl Action {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Patrol => "Patrol",
+            Action::Engage => "Engage",
+            Action::Retreat { .. } => "Retreat",
+            Action::Defend { .. } => "Defend",
+            Action::Hold => "Hold",
+        }
+    }
+
+    /// Whether the action can run at all
+    fn applicable(&self, facts: &GroupFacts) -> bool {
+        match self {
+            Action::Engage => facts.threat.is_some(),
+            _ => true,
+        }
+    }
+}
+
+/// Result of running one node
+enum Outcome<'a> {
+    Failure,
+    /// Succeeded, having reached this action if any
+    Success(Option<Decision<'a>>),
+}
+
+impl BehaviorNode {
+    /// Walk the tree for a group; `None` if no action was reached
+    pub fn decide(&self, facts: &GroupFacts) -> Option<Decision<'_>> {
+        match self.run(facts) {
+            Outcome::Success(decision) => decision,
+            Outcome::Failure => None,
+        }
+    }
+
+    fn run(&self, facts: &GroupFacts) -> Outcome<'_> {
+        match self {
+            BehaviorNode::Selector { name, children } => {
+                for child in children {
+                    if let Outcome::Success(decision) = child.run(facts) {
+                        return Outcome::Success(decision.map(|decision| decision.under(name)));
+                    }
+                }
+                Outcome::Failure
+            }
+            BehaviorNode::Sequence { name, children } => {
+                let mut first = None;
+                for child in children {
+                    match child.run(facts) {
+                        Outcome::Failure => return Outcome::Failure,
+                        Outcome::Success(decision) => first = first.or(decision),
+                    }
+                }
+                Outcome::Success(first.map(|decision| decision.under(name)))
+            }
+            BehaviorNode::Check(condition) => {
+                if condition.holds(facts) {
+                    Outcome::Success(None)
+                } else {
+                    Outcome::Failure
+                }
+            }
+            BehaviorNode::Act(action) => {
+                if action.applicable(facts) {
+                    Outcome::Success(Some(Decision {
+                        action,
+                        path: vec![],
+                    }))
+                } else {
+                    Outcome::Failure
+                }
+            }
+        }
+    }
+
+    /// Report every problem in this subtree, prefixed with `entry`
+    fn validate(&self, entry: &str, errors: &mut Vec<ValidationError>) {
+        let mut error = |entry: String, message: &str| {
+            errors.push(ValidationError {
+                entry,
+                message: message.to_string(),
+            })
+        };
+
+        match self {
+            BehaviorNode::Selector { name, children }
+            | BehaviorNode::Sequence { name, children } => {
+                if name.trim().is_empty() {
+                    error(format!("{entry}.name"), "name must not be empty");
+                }
+                if children.is_empty() {
+                    error(format!("{entry}.children"), "needs at least one child");
+                }
+                for (i, child) in children.iter().enumerate() {
+                    child.validate(&format!("{entry}.children[{i}]"), errors);
+                }
+            }
+            BehaviorNode::Check(condition) => {
+                let mut condition = condition;
+                while let Condition::Not(inner) = condition {
+                    condition = inner;
+                }
+                match *condition {
+                    Condition::LossesAbove(share) if !(0.0..=1.0).contains(&share) => {
+                        error(entry.to_string(), "LossesAbove must be between 0 and 1")
+                    }
+                    Condition::NearRallyPoint(distance) if !non_negative(distance) => {
+                        error(entry.to_string(), "NearRallyPoint must not be negative")
+                    }
+                    _ => {}
+                }
+            }
+            BehaviorNode::Act(action) => match *action {
+                Action::Retreat { speed_multiplier } if !positive(speed_multiplier) => error(
+                    entry.to_string(),
+                    "Retreat speed_multiplier must be positive",
+                ),
+                Action::Defend { radius } if !positive(radius) => {
+                    error(entry.to_string(), "Defend radius must be positive")
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+fn positive(value: f32) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+fn non_negative(value: f32) -> bool {
+    value.is_finite() && value >= 0.0
+}
+
+impl<'a> Decision<'a> {
+    fn under(mut self, name: &'a str) -> Self {
+        self.path.insert(0, name);
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum BehaviorFileError {
+    Io(std::io::Error),
+    /// Unknown extension or malformed RON/JSON
+    Parse(String),
+    Invalid(Vec<ValidationError>),
+}
+
+impl fmt::Display for BehaviorFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BehaviorFileError::Io(e) => write!(f, "{e}"),
+            BehaviorFileError::Parse(message) => write!(f, "{message}"),
+            BehaviorFileError::Invalid(errors) => {
+                write!(f, "{} problem(s)", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for BehaviorFileError {}
+
+/// One behavior tree per archetype
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BehaviorTrees {
+    pub recon: BehaviorNode,
+    pub assault: BehaviorNode,
+    pub defensive: BehaviorNode,
+}
+
+impl Default for BehaviorTrees {
+    fn default() -> Self {
+        Self::from_ron(DEFAULT_BEHAVIORS).expect("bundled behavior trees parse")
+    }
+}
+
+impl BehaviorTrees {
+    /// Parse a `.ron` or `.json` file
+    pub fn load(path: &Path) -> Result<Self, BehaviorFileError> {
+        let text = std::fs::read_to_string(path).map_err(BehaviorFileError::Io)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => Self::from_ron(&text),
+            Some("json") => Self::from_json(&text),
+            _ => Err(BehaviorFileError::Parse(format!(
+                "{}: expected a .ron or .json behavior file",
+                path.display()
+            ))),
+        }
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, BehaviorFileError> {
+        ron::from_str(text).map_err(|e| BehaviorFileError::Parse(e.to_string()))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, BehaviorFileError> {
+        serde_json::from_str(text).map_err(|e| BehaviorFileError::Parse(e.to_string()))
+    }
+
+    pub fn load_validated(path: &Path) -> Result<Self, BehaviorFileError> {
+        let trees = Self::load(path)?;
+        trees.validate().map_err(BehaviorFileError::Invalid)?;
+        Ok(trees)
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.recon.validate("recon", &mut errors);
+        self.assault.validate("assault", &mut errors);
+        self.defensive.validate("defensive", &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn tree(&self, archetype: &GroupArchetype) -> &BehaviorNode {
+        match archetype {
+            GroupArchetype::Recon { .. } => &self.recon,
+            GroupArchetype::Assault { .. } => &self.assault,
+            GroupArchetype::Defensive { .. } => &self.defensive,
+        }
+    }
+}
+
+/// The tree node that last decided a group's behavior
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActiveBehaviorNode(pub String);
+
+/// The behavior `action` switches a group to, `None` if it stays as it is
+pub fn next_behavior(
+    action: &Action,
+    group: &BoidGroup,
+    facts: &GroupFacts,
+) -> Option<GroupBehavior> {
+    let next = match action {
+        Action::Hold => return None,
+        Action::Patrol => {
+            if facts.behavior == BehaviorKind::Patrolling {
+                return None;
+            }
+            GroupBehavior::Patrolling {
+                route: group.home_territory.patrol_points.clone(),
+                current_waypoint: 0,
+            }
+        }
+        Action::Engage => {
+            let threat = facts.threat?;
+            if matches!(
+                group.behavior_state,
+                GroupBehavior::Engaging { primary_target, .. } if primary_target == threat.player_id as u32
+            ) {
+                return None;
+            }
+            GroupBehavior::Engaging {
+                primary_target: threat.player_id as u32,
+                secondary_targets: vec![],
+            }
+        }
+        Action::Retreat { speed_multiplier } => {
+            if facts.behavior == BehaviorKind::Retreating {
+                return None;
+            }
+            let bonus = match group.archetype {
+                GroupArchetype::Recon {
+                    flee_speed_bonus, ..
+                } => flee_speed_bonus,
+                _ => 1.0,
+            };
+            GroupBehavior::Retreating {
+                rally_point: group.home_territory.center,
+                speed_multiplier: speed_multiplier * bonus,
+            }
+        }
+        Action::Defend { radius } => {
+            if facts.behavior == BehaviorKind::Defending {
+                return None;
+            }
+            let position = match group.behavior_state {
+                GroupBehavior::Retreating { rally_point, .. } => rally_point,
+                _ => group.home_territory.center,
+            };
+            GroupBehavior::Defending {
+                position,
+                radius: *radius,
+            }
+        }
+    };
+    Some(next)
+}
+
+/// Run every group's behavior tree
+pub(crate) fn decide_group_behavior(
+    mut commands: Commands,
+    trees: Res<BehaviorTrees>,
+    config: Res<BoidGroupConfig>,
+    aggression: Res<BoidAggression>,
+    mut groups: Query<(
+        Entity,
+        &mut BoidGroup,
+        &Position,
+        Option<&mut ActiveBehaviorNode>,
+    )>,
+    players: Query<(Entity, &Position, &Player), Without<Boid>>,
+    members: Query<(&BoidGroupMember, &Position), With<Boid>>,
+) {
+    let mut member_counts: HashMap<Entity, usize> = HashMap::new();
+    let mut scouts: HashMap<Entity, Vec<Vec2>> = HashMap::new();
+    for (member, position) in members.iter() {
+        *member_counts.entry(member.group_entity).or_default() += 1;
+        if member.role_in_group == BoidRole::Scout {
+            scouts
+                .entry(member.group_entity)
+                .or_default()
+                .push(position.0);
+        }
+    }
+    // Players who have shot at any boid recently
+    let attackers: HashSet<u64> = aggression
+        .boid_aggression
+        .values()
+        .filter_map(|data| players.get(data.attacker).ok())
+        .map(|(_, _, player)| player.id)
+        .collect();
+
+    for (entity, mut group, group_pos, active) in groups.iter_mut() {
+        let detection_range = match group.archetype {
+            GroupArchetype::Recon {
+                detection_range, ..
+            } => detection_range,
+            _ => config.group_aggression_range,
+        };
+        let group_scouts = scouts.get(&entity).map_or(&[][..], Vec::as_slice);
+        let threat = players
+            .iter()
+            .filter_map(|(_, position, player)| {
+                let from_group = position.0.distance(group_pos.0);
+                let from_scouts = group_scouts
+                    .iter()
+                    .map(|scout| scout.distance(position.0))
+                    .fold(f32::INFINITY, f32::min);
+                let detected =
+                    from_group < detection_range || from_scouts < config.scout_detection_range;
+                detected.then_some(Threat {
+                    player_id: player.id,
+                    distance: from_group.min(from_scouts),
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance));
+
+        let member_count = member_counts.get(&entity).copied().unwrap_or(0);
+        let facts = GroupFacts {
+            behavior: BehaviorKind::of(&group.behavior_state),
+            target_present: match group.behavior_state {
+                GroupBehavior::Engaging { primary_target, .. } => players
+                    .iter()
+                    .any(|(_, _, player)| player.id as u32 == primary_target),
+                _ => true,
+            },
+            threat,
+            provoked: threat.is_some_and(|threat| attackers.contains(&threat.player_id)),
+            losses: 1.0 - member_count as f32 / group.initial_size.max(1) as f32,
+            retreat_threshold: match group.archetype {
+                GroupArchetype::Defensive {
+                    retreat_threshold, ..
+                } => retreat_threshold,
+                _ => DEFAULT_RETREAT_THRESHOLD,
+            },
+            rally_distance: match group.behavior_state {
+                GroupBehavior::Retreating { rally_point, .. } => {
+                    Some(group_pos.0.distance(rally_point))
+                }
+                _ => None,
+            },
+        };
+
+        let decision = trees.tree(&group.archetype).decide(&facts);
+        // Groups mostly stay on the same node, so only format the path when it moved
+        let describes = |text: &str| match &decision {
+            Some(decision) => decision.is_described_by(text),
+            None => text.is_empty(),
+        };
+        let describe = || {
+            decision
+                .as_ref()
+                .map(Decision::describe)
+                .unwrap_or_default()
+        };
+        match active {
+            Some(mut active) => {
+                if !describes(&active.0) {
+                    active.0 = describe();
+                }
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(ActiveBehaviorNode(describe()));
+            }
+        }
+
+        // Only touch the group when its behavior actually changes
+        if let Some(next) =
+            decision.and_then(|decision| next_behavior(decision.action, &group, &facts))
+        {
+            group.behavior_state = next;
+        }
+    }
+}
+
+/// Plugin for behavior-tree group decisions
+pub struct BehaviorTreePlugin;
+
+impl Plugin for BehaviorTreePlugin {
+    fn build(&self, app: &mut App) {
+        // A `--behaviors` file is inserted before the plugin runs
+        app.init_resource::<BehaviorTrees>();
+        app.add_systems(Update, decide_group_behavior.in_set(TickStage::GroupAi));
+    }
+}
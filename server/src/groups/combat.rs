@@ -1,5 +1,5 @@
-use crate::groups::BoidGroupConfig;
-use crate::physics::BoidAggression;
+use crate::groups::behavior_tree::decide_group_behavior;
+use crate::profiler::TickStage;
 use bevy::prelude::*;
 use boid_wars_shared::*;
 
@@ -11,10 +11,10 @@ impl Plugin for GroupCombatPlugin {
         app.add_systems(
             Update,
             (
-                group_target_selection,
-                group_combat_coordinator.after(group_target_selection),
+                group_combat_coordinator.after(decide_group_behavior),
                 rotate_active_shooters,
-            ),
+            )
+                .in_set(TickStage::GroupAi),
         );
 
         // Timer for shooter rotation
@@ -29,108 +29,6 @@ impl Plugin for GroupCombatPlugin {
 #[derive(Resource)]
 struct ShooterRotationTimer(Timer);
 
-/// Select targets for groups based on their behavior
-fn group_target_selection(
-    mut groups: Query<(&mut BoidGroup, &Position)>,
-    players: Query<(Entity, &Position, &Player), Without<Boid>>,
-    boids: Query<&BoidGroupMember, With<Boid>>,
-    aggression: Res<BoidAggression>,
-    config: Res<BoidGroupConfig>,
-) {
-    for (mut group, group_pos) in groups.iter_mut() {
-        match &mut group.behavior_state {
-            GroupBehavior::Patrolling { .. } => {
-                // Check for nearby threats
-                let detection_range = match group.archetype {
-                    GroupArchetype::Recon {
-                        detection_range, ..
-                    } => detection_range,
-                    _ => config.group_aggression_range,
-                };
-
-                // Find nearest player
-                if let Some(player) = players
-                    .iter()
-                    .map(|(e, p, pl)| (e, p.0.distance(group_pos.0), pl))
-                    .filter(|(_, dist, _)| *dist < detection_range)
-                    .min_by(|a, b| a.1.total_cmp(&b.1))
-                    .map(|(_, _, pl)| pl)
-                {
-                    // Check if any boid in the group has been attacked by this player
-                    let group_under_attack = aggression.boid_aggression.values().any(|data| {
-                        if let Ok((_, _, p)) = players.get(data.attacker) {
-                            p.id == player.id
-                        } else {
-                            false
-                        }
-                    });
-
-                    if group_under_attack {
-                        // Switch to engaging
-                        group.behavior_state = GroupBehavior::Engaging {
-                            primary_target: player.id as u32,
-                            secondary_targets: vec![],
-                        };
-                    }
-                }
-            }
-            GroupBehavior::Engaging { primary_target, .. } => {
-                // Check if target still exists and is in range
-                let target_exists = players
-                    .iter()
-                    .any(|(_, _, p)| p.id as u32 == *primary_target);
-
-                if !target_exists {
-                    // Return to patrolling
-                    group.behavior_state = GroupBehavior::Patrolling {
-                        route: group.home_territory.patrol_points.clone(),
-                        current_waypoint: 0,
-                    };
-                } else {
-                    // Check if should retreat
-                    let member_count = count_group_members(&group.id, &boids);
-                    let retreat_threshold = match group.archetype {
-                        GroupArchetype::Defensive {
-                            retreat_threshold, ..
-                        } => retreat_threshold,
-                        _ => 0.3, // Default 30% losses
-                    };
-
-                    if member_count
-                        < (group.initial_size as f32 * (1.0 - retreat_threshold)) as usize
-                    {
-                        // Retreat to home territory with archetype-specific speed
-                        let retreat_speed = match group.archetype {
-                            GroupArchetype::Recon {
-                                flee_speed_bonus, ..
-                            } => {
-                                1.5 * flee_speed_bonus // Enhanced retreat speed for Recon
-                            }
-                            _ => 1.5, // Default retreat speed
-                        };
-
-                        group.behavior_state = GroupBehavior::Retreating {
-                            rally_point: group.home_territory.center,
-                            speed_multiplier: retreat_speed,
-                        };
-                    }
-                }
-            }
-            GroupBehavior::Retreating { rally_point, .. } => {
-                // Check if reached rally point
-                if group_pos.0.distance(*rally_point) < 100.0 {
-                    // Switch to defending
-                    group.behavior_state = GroupBehavior::Defending {
-                        position: *rally_point,
-                        radius: 200.0,
-                    };
-                }
-            }
-            _ => {}
-        }
-    }
-}
-
 /// Coordinate combat for groups
 fn group_combat_coordinator(
     mut groups: Query<(&mut BoidGroup, &Position)>,
@@ -155,9 +53,10 @@ fn group_combat_coordinator(
 
                             // Faster fire rates during pursuit/engagement
                             combat_stats.fire_rate = match member.role_in_group {
-                                BoidRole::Leader => 0.5,  // 1 shot every 2 seconds
+                                BoidRole::Support => 0.5, // 1 shot every 2 seconds
+                                BoidRole::Leader => 0.4,  // 1 shot every 2.5 seconds
                                 BoidRole::Flanker => 0.4, // 1 shot every 2.5 seconds
-                                _ => 0.3,                 // 1 shot every ~3.3 seconds
+                                BoidRole::Scout => 0.3,   // 1 shot every ~3.3 seconds
                             };
                         } else {
                             // Non-shooter - disable combat by setting very low fire rate
@@ -192,18 +91,8 @@ fn update_active_shooters(
     // Sort by priority (role and distance)
     eligible_shooters.sort_by(|a, b| {
         // Prioritize by role first
-        let role_priority_a = match a.2 {
-            BoidRole::Leader => 0,
-            BoidRole::Flanker => 1,
-            BoidRole::Support => 2,
-            BoidRole::Scout => 3,
-        };
-        let role_priority_b = match b.2 {
-            BoidRole::Leader => 0,
-            BoidRole::Flanker => 1,
-            BoidRole::Support => 2,
-            BoidRole::Scout => 3,
-        };
+        let role_priority_a = shooter_priority(a.2);
+        let role_priority_b = shooter_priority(b.2);
 
         if role_priority_a != role_priority_b {
             role_priority_a.cmp(&role_priority_b)
@@ -235,6 +124,17 @@ fn update_active_shooters(
     group.active_shooters = new_shooters;
 }
 
+/// Order in which roles get a group's shooter slots: support boids hang back to shoot,
+/// scouts are busy ranging ahead
+pub fn shooter_priority(role: BoidRole) -> u8 {
+    match role {
+        BoidRole::Support => 0,
+        BoidRole::Leader => 1,
+        BoidRole::Flanker => 2,
+        BoidRole::Scout => 3,
+    }
+}
+
 /// Periodically rotate active shooters
 fn rotate_active_shooters(
     mut groups: Query<&mut BoidGroup>,
@@ -257,11 +157,3 @@ fn rotate_active_shooters(
         }
     }
 }
-
-/// Count members of a group
-fn count_group_members(group_id: &u32, boids: &Query<&BoidGroupMember, With<Boid>>) -> usize {
-    boids
-        .iter()
-        .filter(|member| member.group_id == *group_id)
-        .count()
-}
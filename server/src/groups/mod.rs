@@ -1,22 +1,31 @@
 use crate::config::PhysicsConfig;
+use crate::interest::InterestManaged;
 use crate::physics::GameCollisionGroups;
 use crate::position_sync::SyncPosition;
+use crate::profiler::TickStage;
+use crate::rng::GameRng;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use boid_wars_shared::*;
 use lightyear::prelude::server::*;
+use lightyear::prelude::NetworkRelevanceMode;
 use lightyear::shared::replication::components::ReplicationGroup;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+pub mod behavior_tree;
 pub mod combat;
 pub mod formation;
 pub mod movement;
+pub mod roles;
 pub mod territory;
 
 use formation::*;
 use territory::*;
 
-/// Configuration for the boid group system
-#[derive(Resource, Debug, Clone)]
+/// Configuration for the boid group system, the `[groups]` section of the settings file
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BoidGroupConfig {
     // Group parameters
     pub min_group_size: u32,
@@ -33,6 +42,8 @@ pub struct BoidGroupConfig {
     pub max_shooters_percentage: f32,
     pub shooter_rotation_interval: f32,
     pub group_aggression_range: f32,
+    /// Players this close to one of a group's scouts are detected by the whole group
+    pub scout_detection_range: f32,
 
     // Territory parameters
     pub territory_radius: f32,
@@ -44,6 +55,10 @@ pub struct BoidGroupConfig {
     pub lod_medium_distance: f32,
     pub lod_far_distance: f32,
 
+    // Replication parameters
+    pub replicate_distant_as_group: bool,
+    pub group_replication_interval: f32,
+
     // Performance limits
     pub max_groups: u32,
     pub max_total_boids: u32,
@@ -67,6 +82,7 @@ impl Default for BoidGroupConfig {
             max_shooters_percentage: 0.1, // Only 10% of group can shoot
             shooter_rotation_interval: 5.0, // Rotate every 5 seconds
             group_aggression_range: 300.0, // Scaled for smaller arena
+            scout_detection_range: 250.0, // Scouts range ahead, so this reaches further
 
             // Territory parameters
             territory_radius: 200.0, // Scaled for smaller arena
@@ -78,6 +94,10 @@ impl Default for BoidGroupConfig {
             lod_medium_distance: 1000.0, // Reduced update rate
             lod_far_distance: 1500.0,    // Minimal updates for distant groups
 
+            // Replication parameters
+            replicate_distant_as_group: true, // Far/Distant groups sent as one summary
+            group_replication_interval: 0.2,  // Matches the Far LOD update rate
+
             // Performance limits
             max_groups: 50,         // Support large-scale battles
             max_total_boids: 10000, // Target 10k+ entities
@@ -85,6 +105,55 @@ impl Default for BoidGroupConfig {
     }
 }
 
+impl ConfigSection for BoidGroupConfig {
+    const SECTION: &'static str = "groups";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        check.check(
+            "min_group_size",
+            self.min_group_size > 0 && self.min_group_size <= self.max_group_size,
+            format!(
+                "must be between 1 and max_group_size ({}), got {}",
+                self.max_group_size, self.min_group_size
+            ),
+        );
+        check.check(
+            "default_group_size",
+            (self.min_group_size..=self.max_group_size).contains(&self.default_group_size),
+            format!(
+                "must be between min_group_size and max_group_size, got {}",
+                self.default_group_size
+            ),
+        );
+        check.check(
+            "formation_strength",
+            (0.0..=1.0).contains(&self.formation_strength),
+            format!("must be between 0 and 1, got {}", self.formation_strength),
+        );
+        check.check(
+            "max_shooters_percentage",
+            (0.0..=1.0).contains(&self.max_shooters_percentage),
+            format!(
+                "is a fraction between 0 and 1, got {}",
+                self.max_shooters_percentage
+            ),
+        );
+        check.positive("shooter_rotation_interval", self.shooter_rotation_interval);
+        check.non_negative("scout_detection_range", self.scout_detection_range);
+        check.positive("territory_radius", self.territory_radius);
+        check.check(
+            "lod_medium_distance",
+            self.lod_near_distance < self.lod_medium_distance
+                && self.lod_medium_distance < self.lod_far_distance,
+            "LOD distances must increase: near < medium < far",
+        );
+        check.positive(
+            "group_replication_interval",
+            self.group_replication_interval,
+        );
+    }
+}
+
 /// Counter for generating unique group IDs
 #[derive(Resource, Default)]
 pub struct GroupIdCounter(pub u32);
@@ -114,7 +183,19 @@ pub enum LODLevel {
     Distant, // Static until player approaches
 }
 
+impl LODLevel {
+    /// Whether clients get this group as a `ReplicatedGroup` instead of its boids
+    pub fn replicates_as_group(&self) -> bool {
+        matches!(self, LODLevel::Far | LODLevel::Distant)
+    }
+}
+
+/// Marks a group, and its member boids, while the group is replicated as a whole
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct SwarmCollapsed;
+
 /// Spawn a boid group with the specified parameters
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_boid_group(
     commands: &mut Commands,
     archetype: GroupArchetype,
@@ -123,6 +204,33 @@ pub fn spawn_boid_group(
     group_id_counter: &mut GroupIdCounter,
     boid_id_counter: &mut BoidIdCounter,
     physics_config: &PhysicsConfig,
+    rng: &mut GameRng,
+) -> Entity {
+    spawn_boid_group_in_formation(
+        commands,
+        archetype,
+        Formation::default_for_archetype(&archetype),
+        size,
+        territory,
+        group_id_counter,
+        boid_id_counter,
+        physics_config,
+        rng,
+    )
+}
+
+/// Like [`spawn_boid_group`] with an explicit starting formation
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_boid_group_in_formation(
+    commands: &mut Commands,
+    archetype: GroupArchetype,
+    formation: Formation,
+    size: u32,
+    territory: TerritoryData,
+    group_id_counter: &mut GroupIdCounter,
+    boid_id_counter: &mut BoidIdCounter,
+    physics_config: &PhysicsConfig,
+    rng: &mut GameRng,
 ) -> Entity {
     // Generate unique group ID
     let group_id = group_id_counter.0;
@@ -138,7 +246,7 @@ pub fn spawn_boid_group(
                 id: group_id,
                 archetype,
                 home_territory: territory.clone(),
-                current_formation: Formation::default_for_archetype(&archetype),
+                current_formation: formation.clone(),
                 behavior_state: GroupBehavior::Patrolling {
                     route: territory.patrol_points.clone(),
                     current_waypoint: 0,
@@ -153,12 +261,23 @@ pub fn spawn_boid_group(
                 level: LODLevel::Near,
                 last_update: 0.0,
             },
-            // No replication - groups are server-side only
+            // Only reaches clients while the group is collapsed (see update_group_replication)
+            ReplicatedGroup {
+                id: group_id,
+                position: territory.center,
+                formation: formation.clone(),
+                member_count: size,
+                archetype,
+            },
+            Replicate {
+                relevance_mode: NetworkRelevanceMode::InterestManagement,
+                ..default()
+            },
+            InterestManaged,
         ))
         .id();
 
     // Calculate formation positions
-    let formation = Formation::default_for_archetype(&archetype);
     let formation_positions = calculate_formation_positions(&formation, size as usize);
 
     // Spawn member boids
@@ -167,12 +286,7 @@ pub fn spawn_boid_group(
         boid_id_counter.0 += 1;
 
         // Determine role based on position in formation
-        let role = match i {
-            0 => BoidRole::Leader,
-            n if n < 3 => BoidRole::Flanker,
-            n if n < size as usize / 2 => BoidRole::Support,
-            _ => BoidRole::Scout,
-        };
+        let role = roles::role_for_slot(i, size);
 
         // Create boid position
         let x = territory.center.x + offset.x;
@@ -221,8 +335,13 @@ pub fn spawn_boid_group(
             }
         }
 
+        // Support boids hang back, so they need the reach to keep shooting
+        if role == BoidRole::Support {
+            bundle.combat_stats.aggression_range *= roles::SUPPORT_RANGE_BONUS;
+        }
+
         // Random initial velocity
-        let angle = rand::random::<f32>() * std::f32::consts::TAU;
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
         let speed = 50.0;
         bundle.velocity = boid_wars_shared::Velocity::new(angle.cos() * speed, angle.sin() * speed);
 
@@ -240,26 +359,31 @@ pub fn spawn_boid_group(
             BoidSpriteGroup { group_id }, // Add sprite group for client rendering
             Replicate {
                 group: ReplicationGroup::new_id(group_id.into()),
+                relevance_mode: NetworkRelevanceMode::InterestManagement,
                 ..default()
             },
+            wire::movement_delta_compression(),
+            InterestManaged,
             // Physics components
-            RigidBody::Dynamic,
-            Collider::ball(physics_config.boid_radius * size_scale),
-            GameCollisionGroups::boid(),
-            ActiveEvents::COLLISION_EVENTS,
-            Transform::from_xyz(x, y, 0.0),
-            GlobalTransform::default(),
-            bevy_rapier2d::dynamics::Velocity {
-                linvel: Vec2::new(angle.cos() * speed, angle.sin() * speed),
-                angvel: 0.0,
-            },
-            GravityScale(0.0),
-            Damping {
-                linear_damping: 0.0,
-                angular_damping: 1.0,
-            },
-            AdditionalMassProperties::Mass(0.5),
-            SyncPosition,
+            (
+                RigidBody::Dynamic,
+                Collider::ball(physics_config.boid_radius * size_scale),
+                GameCollisionGroups::boid(),
+                ActiveEvents::COLLISION_EVENTS,
+                Transform::from_xyz(x, y, 0.0),
+                GlobalTransform::default(),
+                bevy_rapier2d::dynamics::Velocity {
+                    linvel: Vec2::new(angle.cos() * speed, angle.sin() * speed),
+                    angvel: 0.0,
+                },
+                GravityScale(0.0),
+                Damping {
+                    linear_damping: 0.0,
+                    angular_damping: 1.0,
+                },
+                AdditionalMassProperties::Mass(0.5),
+                SyncPosition,
+            ),
         ));
     }
 
@@ -278,6 +402,8 @@ impl Plugin for BoidGroupPlugin {
 
         // Add sub-plugins
         app.add_plugins((
+            behavior_tree::BehaviorTreePlugin,
+            roles::GroupRolesPlugin,
             TerritoryPlugin,
             FormationPlugin,
             movement::GroupMovementPlugin,
@@ -285,7 +411,15 @@ impl Plugin for BoidGroupPlugin {
         ));
 
         // Add systems
-        app.add_systems(Update, (update_group_lod, cleanup_empty_groups));
+        app.add_systems(
+            Update,
+            (
+                update_group_lod,
+                update_group_replication.after(update_group_lod),
+                cleanup_empty_groups,
+            )
+                .in_set(TickStage::GroupAi),
+        );
 
         info!("Boid group system initialized");
     }
@@ -313,6 +447,73 @@ fn update_group_lod(
     }
 }
 
+/// Collapse far groups into their `ReplicatedGroup` summary and expand them up close
+#[allow(clippy::type_complexity)]
+fn update_group_replication(
+    mut commands: Commands,
+    mut groups: Query<(
+        Entity,
+        &BoidGroup,
+        &Position,
+        &GroupLOD,
+        &mut ReplicatedGroup,
+        Has<SwarmCollapsed>,
+    )>,
+    members: Query<(Entity, &BoidGroupMember, Has<SwarmCollapsed>), With<Boid>>,
+    config: Res<BoidGroupConfig>,
+    time: Res<Time>,
+    mut since_update: Local<f32>,
+) {
+    *since_update += time.delta_secs();
+    let refresh = *since_update >= config.group_replication_interval;
+    if refresh {
+        *since_update = 0.0;
+    }
+
+    let mut collapsed_groups = std::collections::HashSet::new();
+    let mut member_counts: std::collections::HashMap<Entity, u32> =
+        std::collections::HashMap::new();
+    for (_, member, _) in members.iter() {
+        *member_counts.entry(member.group_entity).or_default() += 1;
+    }
+
+    for (entity, group, position, lod, mut replicated, is_collapsed) in groups.iter_mut() {
+        let collapse = config.replicate_distant_as_group && lod.level.replicates_as_group();
+        if collapse {
+            collapsed_groups.insert(entity);
+        }
+        if collapse != is_collapsed {
+            if collapse {
+                commands.entity(entity).insert(SwarmCollapsed);
+            } else {
+                commands.entity(entity).remove::<SwarmCollapsed>();
+            }
+        }
+
+        // Always refresh on collapse so clients start from the current layout
+        if collapse && (refresh || !is_collapsed) {
+            replicated.set_if_neq(ReplicatedGroup {
+                id: group.id,
+                position: position.0,
+                formation: group.current_formation.clone(),
+                member_count: member_counts.get(&entity).copied().unwrap_or(0),
+                archetype: group.archetype,
+            });
+        }
+    }
+
+    for (entity, member, is_collapsed) in members.iter() {
+        let collapse = collapsed_groups.contains(&member.group_entity);
+        if collapse != is_collapsed {
+            if collapse {
+                commands.entity(entity).insert(SwarmCollapsed);
+            } else {
+                commands.entity(entity).remove::<SwarmCollapsed>();
+            }
+        }
+    }
+}
+
 /// Clean up empty groups
 fn cleanup_empty_groups(
     mut commands: Commands,
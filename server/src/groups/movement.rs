@@ -1,4 +1,5 @@
 use crate::groups::{GroupLOD, LODLevel};
+use crate::profiler::TickStage;
 use crate::spatial_grid::SpatialGridSet;
 use bevy::prelude::*;
 use boid_wars_shared::*;
@@ -15,19 +16,20 @@ impl Plugin for GroupMovementPlugin {
                 // Group movement just updates group center positions
                 group_movement_system.before(SpatialGridSet::Read),
                 // Note: All boid movement is now handled by flocking.rs
-            ),
+            )
+                .in_set(TickStage::GroupAi),
         );
     }
 }
 
 /// Update group positions based on member positions
+#[allow(clippy::type_complexity)]
 fn group_movement_system(
-    mut groups: Query<(&mut BoidGroup, &mut Position, &GroupLOD, Entity), Without<Boid>>,
+    mut groups: Query<(&mut Position, &GroupLOD, Entity), (With<BoidGroup>, Without<Boid>)>,
     members: Query<(&BoidGroupMember, &Position), With<Boid>>,
-    players: Query<&Player>,
     time: Res<Time>,
 ) {
-    for (mut group, mut pos, lod, group_entity) in groups.iter_mut() {
+    for (mut pos, lod, group_entity) in groups.iter_mut() {
         // Skip update based on LOD
         if !should_update_group(lod, &time) {
             continue;
@@ -48,18 +50,6 @@ fn group_movement_system(
             // Update group position to center of members
             pos.0 = center / member_count as f32;
         }
-
-        // Update behavior state based on combat (keep existing logic for target detection)
-        if let GroupBehavior::Engaging { primary_target, .. } = &mut group.behavior_state {
-            // Check if target still exists
-            if !players.iter().any(|p| p.id as u32 == *primary_target) {
-                // Lost target, return to patrol
-                group.behavior_state = GroupBehavior::Patrolling {
-                    route: group.home_territory.patrol_points.clone(),
-                    current_waypoint: 0,
-                };
-            }
-        }
     }
 }
 
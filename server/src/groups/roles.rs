@@ -0,0 +1,144 @@
+//! What each member of a group does beyond flocking.
+//!
+//! Roles are handed out by formation slot when a group spawns. The leader steers the
+//! group along its route and everyone else follows it. Scouts range out ahead and spot
+//! players for the whole group. In a fight, flankers swing round to the target's side
+//! and support boids hang back and do most of the shooting. The movement itself lives in
+//! `update_flocking`. When a leader dies, [`promote_leaders`] picks a successor.
+
+use crate::groups::behavior_tree::decide_group_behavior;
+use crate::profiler::TickStage;
+use bevy::prelude::*;
+use boid_wars_shared::*;
+use std::collections::HashMap;
+
+/// How much further support boids can shoot than the rest of their group
+pub const SUPPORT_RANGE_BONUS: f32 = 1.3;
+
+/// The member currently leading a group
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupLeader(pub Entity);
+
+/// Role for the boid in formation slot `index` of a group of `size`
+pub fn role_for_slot(index: usize, size: u32) -> BoidRole {
+    match index {
+        0 => BoidRole::Leader,
+        n if n < 3 => BoidRole::Flanker,
+        n if n < size as usize / 2 => BoidRole::Support,
+        _ => BoidRole::Scout,
+    }
+}
+
+/// Distance a group likes to fight its target from
+pub fn engagement_range(archetype: &GroupArchetype) -> f32 {
+    match *archetype {
+        GroupArchetype::Assault {
+            preferred_range, ..
+        } => preferred_range,
+        GroupArchetype::Defensive {
+            protection_radius, ..
+        } => protection_radius * 0.8,
+        // Middle of the band recon groups circle in
+        GroupArchetype::Recon { .. } => 215.0,
+    }
+}
+
+/// Where a flanker heads: `range` out from the target on `side` (+1 or -1) of the
+/// line the group attacks along from `anchor`
+pub fn flank_point(anchor: Vec2, target: Vec2, side: f32, range: f32) -> Vec2 {
+    let approach = (target - anchor).normalize_or(Vec2::X);
+    target + approach.perp() * side * range
+}
+
+/// Which side of the target a flanker takes, so a group's flankers split up
+pub fn flank_side(boid: Entity) -> f32 {
+    if boid.index() % 2 == 0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+/// The member that takes over a leaderless group: flankers first, then support, then
+/// scouts, nearest the group's center within each role
+pub fn successor(
+    center: Vec2,
+    candidates: impl IntoIterator<Item = (Entity, BoidRole, Vec2)>,
+) -> Option<Entity> {
+    candidates
+        .into_iter()
+        .min_by(|a, b| {
+            succession_rank(a.1)
+                .cmp(&succession_rank(b.1))
+                .then(a.2.distance(center).total_cmp(&b.2.distance(center)))
+        })
+        .map(|(entity, _, _)| entity)
+}
+
+fn succession_rank(role: BoidRole) -> u8 {
+    match role {
+        BoidRole::Leader => 0,
+        BoidRole::Flanker => 1,
+        BoidRole::Support => 2,
+        BoidRole::Scout => 3,
+    }
+}
+
+/// Keep every group led: adopt a member already marked as leader, otherwise promote one
+pub(crate) fn promote_leaders(
+    mut commands: Commands,
+    groups: Query<(Entity, &BoidGroup, &Position, Option<&GroupLeader>)>,
+    mut members: Query<(Entity, &mut BoidGroupMember, &Position), With<Boid>>,
+) {
+    let mut by_group: HashMap<Entity, Vec<(Entity, BoidRole, Vec2)>> = HashMap::new();
+    for (entity, member, position) in members.iter() {
+        by_group.entry(member.group_entity).or_default().push((
+            entity,
+            member.role_in_group,
+            position.0,
+        ));
+    }
+
+    for (group_entity, group, center, leader) in groups.iter() {
+        let Some(candidates) = by_group.remove(&group_entity) else {
+            // Empty groups are cleaned up elsewhere
+            continue;
+        };
+        let current = leader.map(|leader| leader.0);
+        if current.is_some_and(|leader| {
+            candidates
+                .iter()
+                .any(|&(entity, role, _)| entity == leader && role == BoidRole::Leader)
+        }) {
+            continue;
+        }
+
+        let Some(next) = successor(center.0, candidates) else {
+            continue;
+        };
+        if let Ok((_, mut member, _)) = members.get_mut(next) {
+            if member.role_in_group != BoidRole::Leader {
+                debug!(
+                    "Group {} lost its leader, promoting a {:?}",
+                    group.id, member.role_in_group
+                );
+                member.role_in_group = BoidRole::Leader;
+            }
+        }
+        commands.entity(group_entity).insert(GroupLeader(next));
+    }
+}
+
+/// Plugin for group roles and leader succession
+pub struct GroupRolesPlugin;
+
+impl Plugin for GroupRolesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            promote_leaders
+                .before(decide_group_behavior)
+                .in_set(TickStage::GroupAi),
+        );
+    }
+}
@@ -1,9 +1,16 @@
+use crate::profiler::TickStage;
+use crate::rng::GameRng;
+use crate::zone::ZoneState;
 use bevy::prelude::*;
-use boid_wars_shared::{ArenaZone, TerritoryData, Vec2};
+use boid_wars_shared::{ArenaZone, BoidGroup, GroupBehavior, TerritoryData, Vec2, GAME_CONFIG};
 use rand::Rng;
 
 /// Generate territories for the entire arena
-pub fn generate_territories(arena_width: f32, arena_height: f32) -> Vec<TerritoryData> {
+pub fn generate_territories(
+    arena_width: f32,
+    arena_height: f32,
+    rng: &mut GameRng,
+) -> Vec<TerritoryData> {
     let mut territories = Vec::new();
     let mut territory_id = 0;
 
@@ -17,6 +24,7 @@ pub fn generate_territories(arena_width: f32, arena_height: f32) -> Vec<Territor
                 0.9,
                 zone,
                 &mut territory_id,
+                rng,
             ),
             ArenaZone::Middle => generate_ring_territories(
                 arena_width,
@@ -25,6 +33,7 @@ pub fn generate_territories(arena_width: f32, arena_height: f32) -> Vec<Territor
                 0.7,
                 zone,
                 &mut territory_id,
+                rng,
             ),
             ArenaZone::Inner => generate_cluster_territories(
                 arena_width,
@@ -33,6 +42,7 @@ pub fn generate_territories(arena_width: f32, arena_height: f32) -> Vec<Territor
                 0.4,
                 zone,
                 &mut territory_id,
+                rng,
             ),
             _ => vec![],
         };
@@ -53,6 +63,7 @@ fn generate_ring_territories(
     outer_radius_ratio: f32,
     zone: ArenaZone,
     territory_id: &mut u32,
+    rng: &mut GameRng,
 ) -> Vec<TerritoryData> {
     let mut territories = Vec::new();
     let center = Vec2::new(arena_width / 2.0, arena_height / 2.0);
@@ -67,8 +78,6 @@ fn generate_ring_territories(
     let circumference = 2.0 * std::f32::consts::PI * avg_radius;
     let territory_count = (circumference / 300.0).ceil() as i32; // One territory per ~300 units
 
-    let mut rng = rand::thread_rng();
-
     for i in 0..territory_count {
         // Calculate base angle with some randomness
         let base_angle = (i as f32 / territory_count as f32) * 2.0 * std::f32::consts::PI;
@@ -113,6 +122,7 @@ fn generate_cluster_territories(
     outer_radius_ratio: f32,
     zone: ArenaZone,
     territory_id: &mut u32,
+    rng: &mut GameRng,
 ) -> Vec<TerritoryData> {
     let mut territories = Vec::new();
     let center = Vec2::new(arena_width / 2.0, arena_height / 2.0);
@@ -121,8 +131,6 @@ fn generate_cluster_territories(
     let max_radius = (arena_width.min(arena_height) / 2.0) * 0.9;
     let outer_radius = max_radius * outer_radius_ratio;
 
-    let mut rng = rand::thread_rng();
-
     // Create 3-4 clusters in the inner zone
     let cluster_count = rng.gen_range(3..=4);
 
@@ -205,7 +213,63 @@ pub struct TerritoryPlugin;
 
 impl Plugin for TerritoryPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_territory_ownership);
+        app.add_systems(
+            Update,
+            (update_territory_ownership, keep_territories_in_zone).in_set(TickStage::GroupAi),
+        );
+    }
+}
+
+/// Move group territories that fall outside the upcoming safe zone back inside it
+fn keep_territories_in_zone(mut groups: Query<&mut BoidGroup>, zone: Option<Res<ZoneState>>) {
+    let Some(zone) = zone else {
+        return;
+    };
+    if !zone.is_active() {
+        return;
+    }
+
+    let game_config = &*GAME_CONFIG;
+
+    for mut group in groups.iter_mut() {
+        let territory = &group.home_territory;
+        if zone.next_zone_contains_circle(territory.center, territory.radius) {
+            continue;
+        }
+
+        // Shrink the territory if it no longer fits, then pull it inside
+        let radius = territory.radius.min(zone.next_radius * 0.5);
+        let offset = (territory.center - zone.next_center)
+            .clamp_length_max((zone.next_radius - radius).max(0.0));
+        let center = zone.next_center + offset;
+        let patrol_points = generate_patrol_points(
+            center,
+            radius,
+            game_config.game_width,
+            game_config.game_height,
+        );
+
+        group.home_territory.center = center;
+        group.home_territory.radius = radius;
+        group.home_territory.patrol_points = patrol_points.clone();
+
+        // Redirect behaviors that were heading for the old territory
+        match &mut group.behavior_state {
+            GroupBehavior::Patrolling {
+                route,
+                current_waypoint,
+            } => {
+                *route = patrol_points;
+                *current_waypoint = 0;
+            }
+            GroupBehavior::Retreating { rally_point, .. } => {
+                *rally_point = center;
+            }
+            GroupBehavior::Defending { position, .. } => {
+                *position = center;
+            }
+            GroupBehavior::Engaging { .. } => {}
+        }
     }
 }
 
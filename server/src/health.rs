@@ -1,6 +1,49 @@
+//! Health and metrics over plain HTTP, served from a background thread.
+//!
+//! - `GET /healthz` - liveness: 200 while the main loop keeps running
+//! - `GET /readyz` - readiness: 200 once the server is up and has room for another client
+//! - `GET /metrics` - Prometheus text format
+//!
+//! The Bevy side publishes into [`HealthState`]; the HTTP thread only ever reads it.
+//!
+//! Lightyear only counts bytes for the server socket as a whole, so per-client bandwidth
+//! comes from the [`ClientBandwidth`] reports each client sends about its own connection.
+
+use crate::interest::InterestManager;
+use crate::lobby::{PlayerSlots, Spectators};
+use crate::physics::{BoidProjectilePool, ProjectilePool};
+use crate::pool::PoolStatus;
+use crate::profiler::{TickProfiler, TickStage};
+use crate::spatial_grid::{SpatialGrid, SpatialGridStats};
 use bevy::prelude::*;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use boid_wars_shared::{Boid, ClientBandwidth, GAME_CONFIG};
+use lightyear::connection::id::ClientId;
+use lightyear::connection::server::ServerConnections;
+use lightyear::prelude::server::DisconnectEvent;
+use lightyear::server::message::ReceiveMessage;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// The main loop counts as hung when it hasn't run for this long
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Fixed ticks kept for the tick-duration percentiles
+const TICK_WINDOW: usize = 512;
+
+/// How often the metrics snapshot is rebuilt (seconds)
+const SNAPSHOT_INTERVAL: f32 = 1.0;
+
+/// Longest request line the HTTP endpoint reads; every path it serves is short
+const MAX_REQUEST_LINE: usize = 1024;
+
+/// Time a client gets to send the whole request line, however slowly it trickles in
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// Simple health state for tracking server health
 #[derive(Resource, Clone)]
@@ -8,6 +51,12 @@ pub struct HealthState {
     pub is_healthy: Arc<AtomicBool>,
     pub player_count: Arc<AtomicUsize>,
     pub entity_count: Arc<AtomicUsize>,
+    /// Set once the server is listening and has room for another client
+    pub is_ready: Arc<AtomicBool>,
+    /// Milliseconds after `started` that the main loop last ran
+    pub last_update_ms: Arc<AtomicU64>,
+    pub started: Instant,
+    pub metrics: Arc<Mutex<MetricsSnapshot>>,
 }
 
 impl Default for HealthState {
@@ -16,6 +65,10 @@ impl Default for HealthState {
             is_healthy: Arc::new(AtomicBool::new(true)),
             player_count: Arc::new(AtomicUsize::new(0)),
             entity_count: Arc::new(AtomicUsize::new(0)),
+            is_ready: Arc::new(AtomicBool::new(false)),
+            last_update_ms: Arc::new(AtomicU64::new(0)),
+            started: Instant::now(),
+            metrics: Arc::new(Mutex::new(MetricsSnapshot::default())),
         }
     }
 }
@@ -25,6 +78,10 @@ impl HealthState {
         self.is_healthy.store(healthy, Ordering::SeqCst);
     }
 
+    pub fn set_ready(&self, ready: bool) {
+        self.is_ready.store(ready, Ordering::SeqCst);
+    }
+
     pub fn update_player_count(&self, count: usize) {
         self.player_count.store(count, Ordering::SeqCst);
     }
@@ -33,10 +90,26 @@ impl HealthState {
         self.entity_count.store(count, Ordering::SeqCst);
     }
 
+    /// Record that the main loop just ran
+    pub fn heartbeat(&self) {
+        let elapsed = self.started.elapsed().as_millis() as u64;
+        self.last_update_ms.store(elapsed, Ordering::SeqCst);
+    }
+
     pub fn is_healthy(&self) -> bool {
         self.is_healthy.load(Ordering::SeqCst)
     }
 
+    /// Healthy and the main loop ran recently
+    pub fn is_live(&self) -> bool {
+        let last = Duration::from_millis(self.last_update_ms.load(Ordering::SeqCst));
+        self.is_healthy() && self.started.elapsed().saturating_sub(last) < LIVENESS_TIMEOUT
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.is_live() && self.is_ready.load(Ordering::SeqCst)
+    }
+
     pub fn get_player_count(&self) -> usize {
         self.player_count.load(Ordering::SeqCst)
     }
@@ -44,26 +117,550 @@ impl HealthState {
     pub fn get_entity_count(&self) -> usize {
         self.entity_count.load(Ordering::SeqCst)
     }
+
+    pub fn publish(&self, snapshot: MetricsSnapshot) {
+        if let Ok(mut metrics) = self.metrics.lock() {
+            *metrics = snapshot;
+        }
+    }
+
+    /// Prometheus text exposition of the latest snapshot
+    pub fn render_metrics(&self) -> String {
+        let metrics = self
+            .metrics
+            .lock()
+            .map(|metrics| metrics.clone())
+            .unwrap_or_default();
+        let mut out = String::new();
+
+        let mut gauge = |name: &str, help: &str, value: f64| {
+            let _ = writeln!(out, "# HELP boidwars_{name} {help}");
+            let _ = writeln!(out, "# TYPE boidwars_{name} gauge");
+            let _ = writeln!(out, "boidwars_{name} {value}");
+        };
+        gauge(
+            "up",
+            "1 while the main loop is running",
+            bool_value(self.is_live()),
+        );
+        gauge(
+            "ready",
+            "1 while accepting clients",
+            bool_value(self.is_ready()),
+        );
+        gauge(
+            "players",
+            "Connected players",
+            self.get_player_count() as f64,
+        );
+        gauge(
+            "spectators",
+            "Connected spectators",
+            metrics.spectators as f64,
+        );
+        gauge(
+            "entities",
+            "Entities in the world",
+            self.get_entity_count() as f64,
+        );
+        gauge("boids", "Live boids", metrics.boids as f64);
+        gauge(
+            "projectiles",
+            "Projectiles in flight",
+            (metrics.projectile_pool.active + metrics.boid_projectile_pool.active) as f64,
+        );
+        gauge(
+            "grid_occupied_cells",
+            "Spatial grid cells holding at least one entity",
+            metrics.grid.occupied_cells as f64,
+        );
+        gauge(
+            "grid_entities",
+            "Entities indexed in the spatial grid",
+            metrics.grid.total_entities as f64,
+        );
+        gauge(
+            "grid_max_entities_per_cell",
+            "Most entities in a single grid cell",
+            metrics.grid.max_entities_per_cell as f64,
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP boidwars_tick_duration_seconds Fixed update duration over the last {TICK_WINDOW} ticks"
+        );
+        let _ = writeln!(out, "# TYPE boidwars_tick_duration_seconds summary");
+        for (quantile, value) in [
+            ("0.5", metrics.tick.p50),
+            ("0.95", metrics.tick.p95),
+            ("0.99", metrics.tick.p99),
+        ] {
+            let _ = writeln!(
+                out,
+                "boidwars_tick_duration_seconds{{quantile=\"{}\"}} {}",
+                quantile,
+                value.as_secs_f64()
+            );
+        }
+        let _ = writeln!(
+            out,
+            "boidwars_tick_duration_seconds_count {}",
+            metrics.tick.count
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP boidwars_stage_duration_seconds Time per tick in each profiled stage"
+        );
+        let _ = writeln!(out, "# TYPE boidwars_stage_duration_seconds summary");
+        for (stage, percentiles) in &metrics.stages {
+            for (quantile, value) in [
+                ("0.5", percentiles.p50),
+                ("0.95", percentiles.p95),
+                ("0.99", percentiles.p99),
+            ] {
+                let _ = writeln!(
+                    out,
+                    "boidwars_stage_duration_seconds{{stage=\"{}\",quantile=\"{}\"}} {}",
+                    stage,
+                    quantile,
+                    value.as_secs_f64()
+                );
+            }
+        }
+        let _ = writeln!(
+            out,
+            "# HELP boidwars_tick_overruns_total Ticks that took longer than the fixed timestep"
+        );
+        let _ = writeln!(out, "# TYPE boidwars_tick_overruns_total counter");
+        let _ = writeln!(
+            out,
+            "boidwars_tick_overruns_total {}",
+            metrics.tick_overruns
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP boidwars_pool_utilization Active share of each bounded pool's max size"
+        );
+        let _ = writeln!(out, "# TYPE boidwars_pool_utilization gauge");
+        for (pool, status) in [
+            ("projectile", &metrics.projectile_pool),
+            ("boid_projectile", &metrics.boid_projectile_pool),
+        ] {
+            let _ = writeln!(
+                out,
+                "boidwars_pool_utilization{{pool=\"{}\"}} {}",
+                pool,
+                status.utilization()
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP boidwars_client_replicated_entities Entities in each client's interest set"
+        );
+        let _ = writeln!(out, "# TYPE boidwars_client_replicated_entities gauge");
+        for client in &metrics.clients {
+            let _ = writeln!(
+                out,
+                "boidwars_client_replicated_entities{{client=\"{}\",role=\"{}\"}} {}",
+                client.client_id, client.role, client.replicated_entities
+            );
+        }
+
+        write_client_bandwidth(
+            &mut out,
+            &metrics.clients,
+            "received",
+            "Bytes per second each client received from the server, as the client measured it",
+            |bandwidth| bandwidth.received_bytes_per_sec,
+        );
+        write_client_bandwidth(
+            &mut out,
+            &metrics.clients,
+            "sent",
+            "Bytes per second each client sent to the server, as the client measured it",
+            |bandwidth| bandwidth.sent_bytes_per_sec,
+        );
+
+        out
+    }
+}
+
+/// One bandwidth gauge, with a sample for each client that has reported
+fn write_client_bandwidth(
+    out: &mut String,
+    clients: &[ClientMetrics],
+    direction: &str,
+    help: &str,
+    rate: impl Fn(&ClientBandwidth) -> f32,
+) {
+    let name = format!("boidwars_client_{direction}_bytes_per_second");
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} gauge");
+    for client in clients {
+        if let Some(bandwidth) = &client.bandwidth {
+            let _ = writeln!(
+                out,
+                "{}{{client=\"{}\",role=\"{}\"}} {}",
+                name,
+                client.client_id,
+                client.role,
+                rate(bandwidth)
+            );
+        }
+    }
 }
 
-/// Plugin to manage health state
-pub struct HealthPlugin;
+fn bool_value(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Slower-changing numbers, rebuilt every `SNAPSHOT_INTERVAL`
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub spectators: usize,
+    pub boids: usize,
+    pub projectile_pool: PoolUsage,
+    pub boid_projectile_pool: PoolUsage,
+    pub tick: TickPercentiles,
+    /// Per-stage timings from the tick profiler
+    pub stages: Vec<(&'static str, TickPercentiles)>,
+    /// Ticks over the fixed-timestep budget since startup
+    pub tick_overruns: u64,
+    pub grid: SpatialGridStats,
+    pub clients: Vec<ClientMetrics>,
+}
+
+/// `BoundedPool::status` without the borrow
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoolUsage {
+    pub active: usize,
+    pub available: usize,
+    pub max_size: usize,
+}
+
+impl PoolUsage {
+    pub fn utilization(&self) -> f64 {
+        if self.max_size == 0 {
+            0.0
+        } else {
+            self.active as f64 / self.max_size as f64
+        }
+    }
+}
+
+impl From<PoolStatus> for PoolUsage {
+    fn from(status: PoolStatus) -> Self {
+        Self {
+            active: status.active,
+            available: status.available,
+            max_size: status.max_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TickPercentiles {
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub count: usize,
+}
+
+impl TickPercentiles {
+    /// Nearest-rank percentiles of the given durations
+    pub fn from_samples(samples: impl IntoIterator<Item = Duration>) -> Self {
+        let mut sorted: Vec<Duration> = samples.into_iter().collect();
+        sorted.sort_unstable();
+        let rank = |quantile: f64| -> Duration {
+            if sorted.is_empty() {
+                return Duration::ZERO;
+            }
+            let index = (quantile * sorted.len() as f64).ceil() as usize;
+            sorted[index.clamp(1, sorted.len()) - 1]
+        };
+
+        Self {
+            p50: rank(0.5),
+            p95: rank(0.95),
+            p99: rank(0.99),
+            count: sorted.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientMetrics {
+    pub client_id: u64,
+    /// "player" or "spectator"
+    pub role: &'static str,
+    pub replicated_entities: usize,
+    /// Latest report from the client, if it has sent one
+    pub bandwidth: Option<ClientBandwidth>,
+}
+
+/// Latest [`ClientBandwidth`] report from each connected client
+#[derive(Resource, Default, Debug)]
+pub struct ClientBandwidths(pub HashMap<ClientId, ClientBandwidth>);
+
+/// Durations of the most recent fixed updates
+#[derive(Resource, Default)]
+pub struct TickTimings {
+    tick_started: Option<Instant>,
+    samples: VecDeque<Duration>,
+}
+
+impl TickTimings {
+    pub fn record(&mut self, duration: Duration) {
+        if self.samples.len() == TICK_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(duration);
+    }
+
+    pub fn percentiles(&self) -> TickPercentiles {
+        TickPercentiles::from_samples(self.samples.iter().copied())
+    }
+}
+
+/// Plugin to manage health state and serve it over HTTP when `bind_addr` is set
+pub struct HealthPlugin {
+    pub bind_addr: Option<String>,
+}
 
 impl Plugin for HealthPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<HealthState>()
-            .add_systems(Update, update_health_metrics);
+            .init_resource::<TickTimings>()
+            .init_resource::<ClientBandwidths>()
+            .add_systems(FixedFirst, start_tick_timer)
+            .add_systems(FixedLast, finish_tick_timer)
+            .add_systems(
+                Update,
+                (track_client_bandwidth, update_health_metrics).chain(),
+            );
+
+        let Some(addr) = &self.bind_addr else {
+            return;
+        };
+        let state = app.world().resource::<HealthState>().clone();
+        match spawn_http_server(addr, state) {
+            Ok(addr) => info!("🩺 Health and metrics on http://{}/metrics", addr),
+            Err(e) => error!("Failed to start health endpoint on {}: {}", addr, e),
+        }
+    }
+}
+
+fn start_tick_timer(mut timings: ResMut<TickTimings>) {
+    timings.tick_started = Some(Instant::now());
+}
+
+fn finish_tick_timer(mut timings: ResMut<TickTimings>) {
+    if let Some(started) = timings.tick_started.take() {
+        timings.record(started.elapsed());
+    }
+}
+
+fn track_client_bandwidth(
+    mut reports: EventReader<ReceiveMessage<ClientBandwidth>>,
+    mut disconnections: EventReader<DisconnectEvent>,
+    mut bandwidths: ResMut<ClientBandwidths>,
+) {
+    for report in reports.read() {
+        bandwidths.0.insert(report.from, report.message);
+    }
+    for disconnection in disconnections.read() {
+        bandwidths.0.remove(&disconnection.client_id);
     }
 }
 
 /// System to update health metrics
+#[allow(clippy::too_many_arguments)]
 fn update_health_metrics(
     health_state: Res<HealthState>,
-    // You can add queries here to count players and entities
-    // For now, we'll just mark as healthy
+    time: Res<Time>,
+    mut since_snapshot: Local<f32>,
+    all_entities: Query<Entity>,
+    boids: Query<(), With<Boid>>,
+    (projectile_pool, boid_projectile_pool): (Res<ProjectilePool>, Res<BoidProjectilePool>),
+    (spatial_grid, tick_timings, profiler): (
+        Res<SpatialGrid>,
+        Res<TickTimings>,
+        Option<Res<TickProfiler>>,
+    ),
+    (player_slots, spectators): (Res<PlayerSlots>, Res<Spectators>),
+    (interest, bandwidths): (Res<InterestManager>, Res<ClientBandwidths>),
+    server_connections: Option<Res<ServerConnections>>,
 ) {
+    health_state.heartbeat();
     health_state.set_healthy(true);
-    // TODO: Add actual player and entity counting
-    // health_state.update_player_count(player_query.iter().count());
-    // health_state.update_entity_count(entity_query.iter().count());
-}
\ No newline at end of file
+    health_state.update_player_count(player_slots.len());
+    health_state.update_entity_count(all_entities.iter().len());
+
+    // Ready once listening, while a player slot or spectator place is free
+    let game_config = &*GAME_CONFIG;
+    let has_room = player_slots.len() < game_config.max_players as usize
+        || spectators.waiting_count() < game_config.max_spectators as usize;
+    health_state.set_ready(server_connections.is_some() && has_room);
+
+    *since_snapshot += time.delta_secs();
+    if *since_snapshot < SNAPSHOT_INTERVAL {
+        return;
+    }
+    *since_snapshot = 0.0;
+
+    let connected: Vec<(ClientId, &'static str)> = player_slots
+        .iter_ordered()
+        .into_iter()
+        .map(|(client_id, _)| (client_id, "player"))
+        .chain(
+            spectators
+                .clients()
+                .map(|client_id| (client_id, "spectator")),
+        )
+        .collect();
+    let clients = connected
+        .into_iter()
+        .map(|(client_id, role)| ClientMetrics {
+            client_id: client_id.to_bits(),
+            role,
+            replicated_entities: interest.relevant_count(client_id),
+            bandwidth: bandwidths.0.get(&client_id).copied(),
+        })
+        .collect();
+
+    health_state.publish(MetricsSnapshot {
+        spectators: spectators.len(),
+        boids: boids.iter().len(),
+        projectile_pool: projectile_pool.status().into(),
+        boid_projectile_pool: boid_projectile_pool.status().into(),
+        tick: tick_timings.percentiles(),
+        stages: profiler
+            .as_ref()
+            .map(|profiler| {
+                TickStage::ALL
+                    .into_iter()
+                    .map(|stage| (stage.name(), profiler.stage_percentiles(stage)))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        tick_overruns: profiler.map_or(0, |profiler| profiler.overruns),
+        grid: spatial_grid.get_stats(),
+        clients,
+    });
+}
+
+/// Status, content type and body for a request path
+pub fn respond(state: &HealthState, path: &str) -> (u16, &'static str, String) {
+    let text = "text/plain; charset=utf-8";
+    match path {
+        "/healthz" | "/health" => {
+            if state.is_live() {
+                (200, text, "ok\n".to_string())
+            } else {
+                (503, text, "main loop stalled\n".to_string())
+            }
+        }
+        "/readyz" => {
+            if state.is_ready() {
+                (200, text, "ready\n".to_string())
+            } else {
+                (503, text, "not ready\n".to_string())
+            }
+        }
+        "/metrics" => (200, "text/plain; version=0.0.4", state.render_metrics()),
+        _ => (404, text, "not found\n".to_string()),
+    }
+}
+
+/// Serve `respond` on `addr` from a background thread; returns the bound address
+pub fn spawn_http_server(addr: &str, state: HealthState) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+
+    std::thread::Builder::new()
+        .name("health-http".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(e) = handle_connection(stream, &state) {
+                            warn!("Health endpoint request failed: {}", e);
+                        }
+                    }
+                    Err(e) => warn!("Health endpoint accept failed: {}", e),
+                }
+            }
+        })?;
+
+    Ok(local_addr)
+}
+
+/// Read up to the first newline, bounded in length and in total time so a slow or
+/// oversized request can't hold up the accept loop
+fn read_request_line(stream: &mut TcpStream) -> io::Result<String> {
+    let deadline = Instant::now() + REQUEST_TIMEOUT;
+    let mut line = Vec::new();
+    let mut buf = [0; 256];
+    loop {
+        if let Some(end) = line.iter().position(|&byte| byte == b'\n') {
+            line.truncate(end);
+            break;
+        }
+        if line.len() >= MAX_REQUEST_LINE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request line too long",
+            ));
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        stream.set_read_timeout(Some(remaining))?;
+        let read = stream.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        line.extend_from_slice(&buf[..read]);
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+/// One request per connection; only the request line matters
+fn handle_connection(mut stream: TcpStream, state: &HealthState) -> io::Result<()> {
+    let request_line = read_request_line(&mut stream)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => respond(state, path.split('?').next().unwrap_or(path)),
+        _ => (
+            405,
+            "text/plain; charset=utf-8",
+            "only GET is supported\n".to_string(),
+        ),
+    };
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
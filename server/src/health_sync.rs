@@ -11,6 +11,7 @@ pub struct HealthTracker {
 }
 
 /// System to detect health changes and send events
+#[allow(clippy::type_complexity)]
 pub fn detect_health_changes(
     mut connection: ResMut<ConnectionManager>,
     mut health_tracker: ResMut<HealthTracker>,
@@ -73,6 +74,7 @@ pub fn detect_health_changes(
 }
 
 /// System to send initial health state when entities spawn
+#[allow(clippy::type_complexity)]
 pub fn send_initial_health(
     mut connection: ResMut<ConnectionManager>,
     mut health_tracker: ResMut<HealthTracker>,
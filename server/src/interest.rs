@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use boid_wars_shared::{BoidGroup, Position};
+use lightyear::connection::id::ClientId;
+use lightyear::prelude::server::RelevanceManager;
+use std::collections::{HashMap, HashSet};
+
+use crate::groups::SwarmCollapsed;
+use crate::lobby::{PlayerSlots, Spectators};
+use crate::spatial_grid::{SpatialGrid, SpatialGridSet};
+
+/// Area-of-interest replication settings
+#[derive(Resource, Debug, Clone)]
+pub struct InterestConfig {
+    /// When disabled every client sees every entity, as without interest management
+    pub enabled: bool,
+    /// Entities closer than this to a client's ship become relevant
+    pub view_radius: f32,
+    /// Extra distance before a relevant entity is dropped again (hysteresis)
+    pub margin: f32,
+    /// How often relevance is recomputed (seconds)
+    pub update_interval: f32,
+}
+
+impl Default for InterestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            view_radius: 1000.0, // Covers the 1200x900 client view with zoom-out headroom
+            margin: 200.0,
+            update_interval: 0.1,
+        }
+    }
+}
+
+impl InterestConfig {
+    /// Distance at which a relevant entity stops being relevant
+    pub fn exit_radius(&self) -> f32 {
+        self.view_radius + self.margin
+    }
+}
+
+/// Marks entities replicated only to clients whose ship is nearby.
+///
+/// Must be paired with `NetworkRelevanceMode::InterestManagement` on `Replicate`.
+/// Boids of a `SwarmCollapsed` group are never relevant; the group entity is
+/// relevant only while collapsed.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct InterestManaged;
+
+/// Clients that were sent a projectile's spawn message, so the despawn reaches the same set
+#[derive(Component, Debug, Clone, Default)]
+pub struct ProjectileAudience(pub Vec<ClientId>);
+
+/// Relevance changes for one client from a single update
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct InterestChanges {
+    pub gained: Vec<Entity>,
+    pub lost: Vec<Entity>,
+}
+
+#[derive(Debug, Default)]
+struct ClientInterest {
+    /// Ship position, `None` for clients that see everything (spectators, lobby)
+    viewer: Option<Vec2>,
+    relevant: HashSet<Entity>,
+}
+
+/// Which interest-managed entities each client currently has
+#[derive(Resource, Default)]
+pub struct InterestManager {
+    clients: HashMap<ClientId, ClientInterest>,
+}
+
+impl InterestManager {
+    /// Recompute a client's relevant set from the entities near its viewer.
+    ///
+    /// `candidates` must include every managed entity within `exit_radius` of
+    /// the viewer (or every managed entity when `viewer` is `None`). Anything
+    /// previously relevant that is missing from `candidates` is lost.
+    pub fn update_client(
+        &mut self,
+        client_id: ClientId,
+        viewer: Option<Vec2>,
+        candidates: impl IntoIterator<Item = (Entity, Vec2)>,
+        config: &InterestConfig,
+    ) -> InterestChanges {
+        let interest = self.clients.entry(client_id).or_default();
+        interest.viewer = viewer;
+
+        let enter_sq = config.view_radius * config.view_radius;
+        let exit_sq = config.exit_radius() * config.exit_radius();
+
+        let relevant: HashSet<Entity> = candidates
+            .into_iter()
+            .filter(|(entity, position)| match viewer {
+                None => true,
+                Some(viewer) => {
+                    let distance_sq = viewer.distance_squared(*position);
+                    // Already-visible entities get the margin before dropping out
+                    distance_sq <= enter_sq
+                        || (distance_sq <= exit_sq && interest.relevant.contains(entity))
+                }
+            })
+            .map(|(entity, _)| entity)
+            .collect();
+
+        let changes = InterestChanges {
+            gained: relevant.difference(&interest.relevant).copied().collect(),
+            lost: interest.relevant.difference(&relevant).copied().collect(),
+        };
+        interest.relevant = relevant;
+        changes
+    }
+
+    /// Forget clients that are no longer connected
+    pub fn retain_clients(&mut self, mut keep: impl FnMut(ClientId) -> bool) {
+        self.clients.retain(|client_id, _| keep(*client_id));
+    }
+
+    pub fn is_relevant(&self, client_id: ClientId, entity: Entity) -> bool {
+        self.clients
+            .get(&client_id)
+            .is_some_and(|interest| interest.relevant.contains(&entity))
+    }
+
+    pub fn relevant_count(&self, client_id: ClientId) -> usize {
+        self.clients
+            .get(&client_id)
+            .map_or(0, |interest| interest.relevant.len())
+    }
+
+    /// Clients that should hear about an event at `position`
+    pub fn audience(&self, position: Vec2, config: &InterestConfig) -> Vec<ClientId> {
+        let exit_sq = config.exit_radius() * config.exit_radius();
+        self.clients
+            .iter()
+            .filter(|(_, interest)| {
+                interest
+                    .viewer
+                    .is_none_or(|viewer| viewer.distance_squared(position) <= exit_sq)
+            })
+            .map(|(client_id, _)| *client_id)
+            .collect()
+    }
+}
+
+/// Grant and revoke entity relevance as ships move around the arena
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_interest(
+    mut since_update: Local<f32>,
+    time: Res<Time>,
+    config: Res<InterestConfig>,
+    player_slots: Res<PlayerSlots>,
+    spectators: Res<Spectators>,
+    spatial_grid: Res<SpatialGrid>,
+    managed: Query<(Entity, &Position, Has<BoidGroup>, Has<SwarmCollapsed>), With<InterestManaged>>,
+    ships: Query<&Position>,
+    mut interest: ResMut<InterestManager>,
+    mut relevance: ResMut<RelevanceManager>,
+) {
+    *since_update += time.delta_secs();
+    if *since_update < config.update_interval {
+        return;
+    }
+    *since_update = 0.0;
+
+    // Players see around their ship; spectators and players without a ship see everything
+    let mut viewers: Vec<(ClientId, Option<Vec2>)> = player_slots
+        .iter_ordered()
+        .into_iter()
+        .map(|(client_id, slot)| {
+            let viewer = if !config.enabled || spectators.contains(client_id) {
+                None
+            } else {
+                ships.get(slot.entity).ok().map(|position| position.0)
+            };
+            (client_id, viewer)
+        })
+        .collect();
+    for client_id in spectators.clients() {
+        if player_slots.get(client_id).is_none() {
+            viewers.push((client_id, None));
+        }
+    }
+
+    interest.retain_clients(|client_id| viewers.iter().any(|(id, _)| *id == client_id));
+
+    // Groups stand in for their boids while collapsed, and are hidden otherwise
+    let candidate = |(entity, position, is_group, collapsed): (Entity, &Position, bool, bool)| {
+        (is_group == collapsed).then_some((entity, position.0))
+    };
+
+    for (client_id, viewer) in viewers {
+        let changes = match viewer {
+            Some(position) => {
+                let candidates = spatial_grid
+                    .get_nearby_entities(position, config.exit_radius())
+                    .into_iter()
+                    .filter_map(|entity| managed.get(entity).ok())
+                    .filter_map(candidate);
+                interest.update_client(client_id, viewer, candidates, &config)
+            }
+            None => {
+                let candidates = managed.iter().filter_map(candidate);
+                interest.update_client(client_id, viewer, candidates, &config)
+            }
+        };
+
+        for entity in changes.gained {
+            relevance.gain_relevance(client_id, entity);
+        }
+        for entity in changes.lost {
+            // Despawned entities are cleaned up by replication itself
+            if managed.contains(entity) {
+                relevance.lose_relevance(client_id, entity);
+            }
+        }
+    }
+}
+
+/// Plugin limiting replication to entities near each client's ship
+pub struct InterestPlugin;
+
+impl Plugin for InterestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InterestConfig>()
+            .init_resource::<InterestManager>()
+            .add_systems(FixedUpdate, update_interest.in_set(SpatialGridSet::Read));
+    }
+}
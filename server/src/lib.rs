@@ -1,9 +1,26 @@
 // Expose modules for benchmarking and testing
+pub mod admin;
+pub mod cli;
+pub mod combat;
 pub mod config;
 pub mod despawn_utils;
 pub mod flocking;
 pub mod groups;
+pub mod health;
+pub mod health_sync;
+pub mod interest;
+pub mod lobby;
+pub mod map;
+pub mod map_file;
+pub mod navigation;
 pub mod physics;
 pub mod pool;
 pub mod position_sync;
+pub mod profiler;
+pub mod replay;
+pub mod rng;
+pub mod round;
+pub mod settings;
 pub mod spatial_grid;
+pub mod targeting;
+pub mod zone;
@@ -0,0 +1,715 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{Collider, ExternalForce, ExternalImpulse, RigidBody};
+use boid_wars_shared::*;
+use lightyear::connection::id::ClientId;
+use lightyear::prelude::server::*;
+use lightyear::prelude::{MessageSend, NetworkRelevanceMode, NetworkTarget};
+use lightyear::server::message::ReceiveMessage;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::config::PhysicsConfig;
+use crate::despawn_utils::SafeDespawnExt;
+use crate::interest::InterestManaged;
+use crate::map::{self, MapConfig};
+use crate::physics::{self, GameCollisionGroups, PlayerDeathEvent, Ship, WeaponStats};
+use crate::position_sync::SyncPosition;
+
+/// Distance kept between player spawn points and the arena walls
+const SPAWN_EDGE_MARGIN: f32 = 100.0;
+
+/// Set by the admin console to start the match without waiting for ready checks
+/// or the player minimum; consumed by the next lobby check
+#[derive(Resource, Default)]
+pub struct ForceStart(pub bool);
+
+/// Server-side session state shared by the lobby and game systems
+#[derive(Resource)]
+pub struct GameState {
+    pub phase: GamePhase,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self {
+            phase: GamePhase::WaitingForPlayers,
+        }
+    }
+}
+
+/// A connected client's lobby slot
+#[derive(Debug, Clone)]
+pub struct PlayerSlot {
+    pub number: PlayerNumber,
+    /// Spawned player entity, `Entity::PLACEHOLDER` until the match starts
+    pub entity: Entity,
+    pub ready: bool,
+}
+
+/// Slot table keyed by client, sized by `GameConfig::max_players`
+#[derive(Resource, Default)]
+pub struct PlayerSlots {
+    slots: HashMap<ClientId, PlayerSlot>,
+}
+
+impl PlayerSlots {
+    /// Assign the lowest free player number to a client.
+    ///
+    /// Returns `None` when all `max_players` slots are taken.
+    pub fn assign(&mut self, client_id: ClientId, max_players: u8) -> Option<PlayerNumber> {
+        if let Some(slot) = self.slots.get(&client_id) {
+            return Some(slot.number);
+        }
+
+        let number = (1..=max_players)
+            .map(PlayerNumber)
+            .find(|number| !self.slots.values().any(|slot| slot.number == *number))?;
+
+        self.slots.insert(
+            client_id,
+            PlayerSlot {
+                number,
+                entity: Entity::PLACEHOLDER,
+                ready: false,
+            },
+        );
+        Some(number)
+    }
+
+    pub fn remove(&mut self, client_id: ClientId) -> Option<PlayerSlot> {
+        self.slots.remove(&client_id)
+    }
+
+    pub fn get(&self, client_id: ClientId) -> Option<&PlayerSlot> {
+        self.slots.get(&client_id)
+    }
+
+    pub fn get_mut(&mut self, client_id: ClientId) -> Option<&mut PlayerSlot> {
+        self.slots.get_mut(&client_id)
+    }
+
+    /// Whether the client with these ID bits holds a slot
+    pub fn contains_player(&self, player_id: u64) -> bool {
+        self.client_id_for(player_id).is_some()
+    }
+
+    /// Client holding a slot, looked up by its ID bits
+    pub fn client_id_for(&self, player_id: u64) -> Option<ClientId> {
+        self.slots
+            .keys()
+            .find(|client_id| client_id.to_bits() == player_id)
+            .copied()
+    }
+
+    /// Clear ready flags and spawned entities so the same clients can play again.
+    ///
+    /// Returns the player entities that still need despawning.
+    pub fn reset_for_rematch(&mut self) -> Vec<Entity> {
+        self.slots
+            .values_mut()
+            .filter_map(|slot| {
+                slot.ready = false;
+                let entity = std::mem::replace(&mut slot.entity, Entity::PLACEHOLDER);
+                (entity != Entity::PLACEHOLDER).then_some(entity)
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// True when at least one player is connected and every player is ready
+    pub fn all_ready(&self) -> bool {
+        !self.slots.is_empty() && self.slots.values().all(|slot| slot.ready)
+    }
+
+    /// Slots ordered by player number
+    pub fn iter_ordered(&self) -> Vec<(ClientId, &PlayerSlot)> {
+        let mut slots: Vec<_> = self.slots.iter().map(|(id, slot)| (*id, slot)).collect();
+        slots.sort_by_key(|(_, slot)| slot.number);
+        slots
+    }
+
+    /// Per-player ready list for the lobby broadcast
+    pub fn statuses(&self) -> Vec<LobbyPlayerStatus> {
+        self.iter_ordered()
+            .into_iter()
+            .map(|(client_id, slot)| LobbyPlayerStatus {
+                player_id: client_id.to_bits(),
+                player_number: slot.number.0,
+                ready: slot.ready,
+            })
+            .collect()
+    }
+}
+
+/// Connected clients that are watching instead of playing
+#[derive(Resource, Default)]
+pub struct Spectators {
+    /// In join order, so the longest-waiting spectator gets the next free slot
+    clients: Vec<(ClientId, SpectateReason)>,
+}
+
+impl Spectators {
+    /// Start spectating, or update the reason for an existing spectator
+    pub fn add(&mut self, client_id: ClientId, reason: SpectateReason) {
+        match self.clients.iter_mut().find(|(id, _)| *id == client_id) {
+            Some(entry) => entry.1 = reason,
+            None => self.clients.push((client_id, reason)),
+        }
+    }
+
+    pub fn remove(&mut self, client_id: ClientId) -> Option<SpectateReason> {
+        let index = self.clients.iter().position(|(id, _)| *id == client_id)?;
+        Some(self.clients.remove(index).1)
+    }
+
+    pub fn reason(&self, client_id: ClientId) -> Option<SpectateReason> {
+        self.clients
+            .iter()
+            .find(|(id, _)| *id == client_id)
+            .map(|(_, reason)| *reason)
+    }
+
+    pub fn contains(&self, client_id: ClientId) -> bool {
+        self.reason(client_id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// All spectating clients in join order
+    pub fn clients(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.clients.iter().map(|(id, _)| *id)
+    }
+
+    /// Spectators without a player slot (overflow and late joiners)
+    pub fn waiting_count(&self) -> usize {
+        self.waiting().count()
+    }
+
+    /// Longest-waiting spectator without a player slot
+    pub fn next_waiting(&self) -> Option<ClientId> {
+        self.waiting().next()
+    }
+
+    /// Drop eliminated players, who play again from the next lobby
+    pub fn release_eliminated(&mut self) -> Vec<ClientId> {
+        let released = self
+            .clients
+            .iter()
+            .filter(|(_, reason)| *reason == SpectateReason::Eliminated)
+            .map(|(id, _)| *id)
+            .collect();
+        self.clients
+            .retain(|(_, reason)| *reason != SpectateReason::Eliminated);
+        released
+    }
+
+    fn waiting(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.clients
+            .iter()
+            .filter(|(_, reason)| *reason != SpectateReason::Eliminated)
+            .map(|(id, _)| *id)
+    }
+}
+
+/// Spawn points evenly spread on an ellipse inset from the arena walls.
+///
+/// The first point sits towards the top-left corner and the rest follow
+/// around the ellipse, so any two consecutive players start well apart.
+pub fn spawn_points(count: usize, arena_width: f32, arena_height: f32) -> Vec<Vec2> {
+    let center = Vec2::new(arena_width / 2.0, arena_height / 2.0);
+    let radius = Vec2::new(
+        (arena_width / 2.0 - SPAWN_EDGE_MARGIN).max(0.0),
+        (arena_height / 2.0 - SPAWN_EDGE_MARGIN).max(0.0),
+    );
+    let start_angle = -3.0 * std::f32::consts::FRAC_PI_4;
+
+    (0..count)
+        .map(|i| {
+            let angle = start_angle + std::f32::consts::TAU * i as f32 / count as f32;
+            center + Vec2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+pub struct LobbyPlugin;
+
+impl Plugin for LobbyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameState>()
+            .init_resource::<PlayerSlots>()
+            .init_resource::<Spectators>()
+            .init_resource::<ForceStart>()
+            .init_resource::<MovementRules>();
+
+        app.add_systems(
+            Update,
+            (
+                handle_connections,
+                handle_disconnections,
+                handle_player_input,
+                handle_player_ready,
+                update_spectators,
+                send_game_state_updates,
+                check_start_game,
+            ),
+        );
+    }
+}
+
+fn send_rejection(
+    connection_manager: &mut ConnectionManager,
+    client_id: ClientId,
+    current_players: u8,
+    message: String,
+) {
+    let server_full_msg = ServerFullMessage {
+        current_players,
+        max_players: GAME_CONFIG.max_players,
+        message,
+    };
+
+    if let Err(e) = connection_manager.send_message_to_target::<ReliableChannel, _>(
+        &server_full_msg,
+        NetworkTarget::Single(client_id),
+    ) {
+        warn!("Failed to send ServerFull message: {:?}", e);
+    }
+}
+
+fn send_spectator_status(
+    connection_manager: &mut ConnectionManager,
+    client_id: ClientId,
+    reason: Option<SpectateReason>,
+) {
+    if let Err(e) = connection_manager.send_message_to_target::<ReliableChannel, _>(
+        &SpectatorStatus { reason },
+        NetworkTarget::Single(client_id),
+    ) {
+        warn!("Failed to send spectator status: {:?}", e);
+    }
+}
+
+/// Move from waiting to the lobby once enough players hold slots
+fn enter_lobby_if_ready(game_state: &mut GameState, player_slots: &PlayerSlots) {
+    if game_state.phase == GamePhase::WaitingForPlayers
+        && player_slots.len() >= GAME_CONFIG.min_players as usize
+    {
+        game_state.phase = GamePhase::Lobby;
+        info!(
+            "{} players connected, entering lobby phase",
+            player_slots.len()
+        );
+    }
+}
+
+// Handle new client connections
+fn handle_connections(
+    mut connections: EventReader<ConnectEvent>,
+    mut player_slots: ResMut<PlayerSlots>,
+    mut spectators: ResMut<Spectators>,
+    mut connection_manager: ResMut<ConnectionManager>,
+    mut game_state: ResMut<GameState>,
+) {
+    let game_config = &*GAME_CONFIG;
+
+    for event in connections.read() {
+        let client_id = event.client_id;
+
+        // Late joiners would have no ship to control until the next round
+        let in_match = matches!(game_state.phase, GamePhase::InGame | GamePhase::GameOver);
+        let player_number = if in_match {
+            None
+        } else {
+            player_slots.assign(client_id, game_config.max_players)
+        };
+
+        let Some(player_number) = player_number else {
+            let reason = if in_match {
+                SpectateReason::MatchInProgress
+            } else {
+                SpectateReason::ServerFull
+            };
+
+            // Watch until a slot opens up
+            if spectators.waiting_count() < game_config.max_spectators as usize {
+                info!("Client {:?} joined as spectator ({:?})", client_id, reason);
+                spectators.add(client_id, reason);
+                send_spectator_status(&mut connection_manager, client_id, Some(reason));
+                game_state.set_changed();
+                continue;
+            }
+
+            // No spectator room either - client will disconnect themselves after receiving the message
+            info!("Server full: rejecting client {:?}", client_id);
+            send_rejection(
+                &mut connection_manager,
+                client_id,
+                player_slots.len() as u8,
+                format!(
+                    "Server is full ({}/{} players, {} spectators). Please try again later.",
+                    player_slots.len(),
+                    game_config.max_players,
+                    spectators.waiting_count()
+                ),
+            );
+            continue;
+        };
+
+        info!("Client {:?} connected as {:?}", client_id, player_number);
+
+        // Force a change detection for immediate update
+        game_state.set_changed();
+        enter_lobby_if_ready(&mut game_state, &player_slots);
+
+        // Player spawning happens in check_start_game
+        info!(
+            "Player slot assigned for client {:?}, waiting for ready signal",
+            client_id
+        );
+    }
+}
+
+// Handle client disconnections
+fn handle_disconnections(
+    mut commands: Commands,
+    mut disconnections: EventReader<DisconnectEvent>,
+    mut player_slots: ResMut<PlayerSlots>,
+    mut spectators: ResMut<Spectators>,
+    mut game_state: ResMut<GameState>,
+) {
+    let game_config = &*GAME_CONFIG;
+
+    for event in disconnections.read() {
+        let client_id = event.client_id;
+
+        if spectators.remove(client_id).is_some() {
+            info!("Spectator {:?} disconnected", client_id);
+            game_state.set_changed();
+        }
+
+        let Some(slot) = player_slots.remove(client_id) else {
+            continue;
+        };
+        info!(
+            "Player {} (client {:?}) disconnected",
+            slot.number.0, client_id
+        );
+
+        // Only despawn if entity was actually spawned (not placeholder).
+        // The ship may already be gone if the player was eliminated.
+        if slot.entity != Entity::PLACEHOLDER {
+            commands.safe_despawn(slot.entity);
+        }
+
+        // Return to waiting phase once we drop below the minimum. A running
+        // round plays out instead; the round tracker counts this as an elimination.
+        if game_state.phase == GamePhase::Lobby
+            && player_slots.len() < game_config.min_players as usize
+        {
+            game_state.phase = GamePhase::WaitingForPlayers;
+            info!("Not enough players, returning to waiting phase");
+        }
+    }
+}
+
+// Handle player input messages - update physics input properly
+fn handle_player_input(
+    mut message_events: EventReader<ReceiveMessage<boid_wars_shared::PlayerInput>>,
+    mut players: Query<(&Player, &mut physics::PlayerInput, &mut InputAck), With<physics::Player>>,
+) {
+    for event in message_events.read() {
+        let client_id = event.from;
+        let input = &event.message;
+
+        // Input validation
+        if !validate_player_input(input) {
+            warn!(
+                "Invalid input from client {:?}: movement={:?}, aim={:?}",
+                client_id, input.movement, input.aim
+            );
+            continue;
+        }
+
+        // Find the player for this client and update their physics input
+        for (player, mut physics_input, mut ack) in players.iter_mut() {
+            if player.id == client_id.to_bits() {
+                // Unreliable channel: inputs can arrive out of order
+                if input.sequence != 0 && input.sequence <= ack.sequence {
+                    continue;
+                }
+                *ack = InputAck {
+                    sequence: input.sequence,
+                    applied_secs: 0.0,
+                };
+
+                physics_input.movement = input.movement.normalize_or_zero(); // Ensure normalized
+                physics_input.aim_direction = input.aim.normalize_or_zero(); // Ensure normalized
+                physics_input.thrust = input_thrust(input.movement);
+                physics_input.shooting = input.fire;
+            }
+        }
+    }
+}
+
+/// Validate player input to prevent malicious or malformed data
+pub fn validate_player_input(input: &boid_wars_shared::PlayerInput) -> bool {
+    // Check movement vector is valid
+    if !input.movement.is_finite() || input.movement.length() > 1.1 {
+        return false;
+    }
+
+    // Check aim direction is valid
+    if !input.aim.is_finite() || input.aim.length() > 1.1 {
+        return false;
+    }
+
+    true
+}
+
+// Handle player ready messages
+fn handle_player_ready(
+    mut message_events: EventReader<ReceiveMessage<PlayerReady>>,
+    game_state: Res<GameState>,
+    mut player_slots: ResMut<PlayerSlots>,
+) {
+    for event in message_events.read() {
+        // Only process ready in lobby phase
+        if game_state.phase != GamePhase::Lobby {
+            continue;
+        }
+
+        if let Some(slot) = player_slots.get_mut(event.from) {
+            slot.ready = true;
+            info!("Player {} is ready!", slot.number.0);
+        }
+    }
+}
+
+// Eliminated players watch the rest of the round; spectators take free slots in the lobby
+fn update_spectators(
+    mut death_events: EventReader<PlayerDeathEvent>,
+    mut game_state: ResMut<GameState>,
+    mut player_slots: ResMut<PlayerSlots>,
+    mut spectators: ResMut<Spectators>,
+    mut connection_manager: ResMut<ConnectionManager>,
+) {
+    for event in death_events.read() {
+        if let Some(client_id) = player_slots.client_id_for(event.player_id) {
+            spectators.add(client_id, SpectateReason::Eliminated);
+            send_spectator_status(
+                &mut connection_manager,
+                client_id,
+                Some(SpectateReason::Eliminated),
+            );
+        }
+    }
+
+    if !matches!(
+        game_state.phase,
+        GamePhase::WaitingForPlayers | GamePhase::Lobby
+    ) || spectators.is_empty()
+    {
+        return;
+    }
+
+    // Everyone holding a slot plays again in the next round
+    for client_id in spectators.release_eliminated() {
+        send_spectator_status(&mut connection_manager, client_id, None);
+    }
+
+    while let Some(client_id) = spectators.next_waiting() {
+        let Some(player_number) = player_slots.assign(client_id, GAME_CONFIG.max_players) else {
+            break;
+        };
+        spectators.remove(client_id);
+        send_spectator_status(&mut connection_manager, client_id, None);
+        info!("Spectator {:?} promoted to {:?}", client_id, player_number);
+
+        game_state.set_changed();
+        enter_lobby_if_ready(&mut game_state, &player_slots);
+    }
+}
+
+// Send game state updates to all clients
+fn send_game_state_updates(
+    game_state: Res<GameState>,
+    player_slots: Res<PlayerSlots>,
+    spectators: Res<Spectators>,
+    movement: Res<MovementRules>,
+    mut connection_manager: ResMut<ConnectionManager>,
+) {
+    // Always send updates when game state, player slots or movement rules change
+    if !game_state.is_changed()
+        && !player_slots.is_changed()
+        && !spectators.is_changed()
+        && !movement.is_changed()
+    {
+        return;
+    }
+
+    let update = GameStateUpdate {
+        phase: game_state.phase.clone(),
+        player_count: player_slots.len() as u8,
+        max_players: GAME_CONFIG.max_players,
+        players: player_slots.statuses(),
+        spectator_count: spectators.waiting_count() as u8,
+        movement: *movement,
+    };
+
+    // Send to all connected clients
+    if let Err(e) =
+        connection_manager.send_message_to_target::<ReliableChannel, _>(&update, NetworkTarget::All)
+    {
+        warn!("Failed to send game state update: {:?}", e);
+    }
+}
+
+// Check if we should start the game
+fn check_start_game(
+    mut commands: Commands,
+    mut game_state: ResMut<GameState>,
+    mut player_slots: ResMut<PlayerSlots>,
+    physics_config: Res<PhysicsConfig>,
+    map_config: Res<MapConfig>,
+    mut force_start: ResMut<ForceStart>,
+) {
+    let game_config = &*GAME_CONFIG;
+
+    // Only check in lobby phase
+    if game_state.phase != GamePhase::Lobby {
+        return;
+    }
+
+    if std::mem::take(&mut force_start.0) {
+        info!("Admin forced start with {} players", player_slots.len());
+    } else if player_slots.len() < game_config.min_players as usize || !player_slots.all_ready() {
+        // Need enough players and every connected player ready
+        return;
+    } else {
+        info!("All {} players ready! Starting game...", player_slots.len());
+    }
+
+    let spawns = map::player_spawns(
+        &map_config,
+        player_slots.len(),
+        Vec2::new(game_config.game_width, game_config.game_height),
+    );
+    let ordered: Vec<(ClientId, PlayerNumber)> = player_slots
+        .iter_ordered()
+        .into_iter()
+        .map(|(client_id, slot)| (client_id, slot.number))
+        .collect();
+
+    for ((client_id, player_number), spawn) in ordered.into_iter().zip(spawns) {
+        let player_entity = spawn_player(
+            &mut commands,
+            &physics_config,
+            client_id,
+            spawn.x,
+            spawn.y,
+            player_number,
+        );
+        if let Some(slot) = player_slots.get_mut(client_id) {
+            slot.entity = player_entity;
+        }
+    }
+
+    // Move to in-game phase
+    game_state.phase = GamePhase::InGame;
+    info!("Game started!");
+}
+
+// Helper function to spawn a player
+fn spawn_player(
+    commands: &mut Commands,
+    physics_config: &PhysicsConfig,
+    client_id: ClientId,
+    spawn_x: f32,
+    spawn_y: f32,
+    player_number: PlayerNumber,
+) -> Entity {
+    let game_config = &*GAME_CONFIG;
+
+    let player_entity = commands
+        .spawn((
+            PlayerBundle::new(
+                client_id.to_bits(),
+                format!("Player {}", client_id.to_bits()),
+                spawn_x,
+                spawn_y,
+                player_number,
+            ),
+            // Echoes the last applied input for client reconciliation
+            InputAck::default(),
+            // Networking, only to clients near this ship
+            Replicate {
+                controlled_by: ControlledBy {
+                    target: NetworkTarget::Single(client_id),
+                    ..default()
+                },
+                relevance_mode: NetworkRelevanceMode::InterestManagement,
+                ..default()
+            },
+            wire::movement_delta_compression(),
+            InterestManaged,
+        ))
+        .id();
+
+    // Add physics components
+    commands.entity(player_entity).insert((
+        physics::Player {
+            player_id: client_id.to_bits(),
+            ..Default::default()
+        },
+        physics::PlayerInput::default(),
+        Ship::default(),
+        WeaponStats::default(),
+        Health {
+            current: game_config.default_health,
+            max: game_config.default_health,
+        },
+    ));
+
+    // Add physics body components
+    commands.entity(player_entity).insert((
+        RigidBody::Dynamic,
+        Collider::cuboid(
+            physics_config.player_collider_size,
+            physics_config.player_collider_size,
+        ),
+        GameCollisionGroups::player(),
+        physics::Velocity::zero(),
+        ExternalForce::default(),
+        ExternalImpulse::default(),
+        Transform::from_xyz(spawn_x, spawn_y, 0.0),
+        GlobalTransform::default(),
+        bevy_rapier2d::dynamics::GravityScale(0.0),
+        bevy_rapier2d::dynamics::Sleeping::disabled(),
+        bevy_rapier2d::dynamics::Damping {
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+        },
+        bevy_rapier2d::dynamics::AdditionalMassProperties::Mass(1.0),
+        SyncPosition,
+    ));
+
+    info!(
+        "Player {} spawned at ({}, {}) for client {:?}",
+        player_number.0, spawn_x, spawn_y, client_id
+    );
+
+    player_entity
+}
@@ -1,32 +1,61 @@
 use bevy::app::PluginGroupBuilder;
 use bevy::prelude::*;
 use boid_wars_shared::*;
-use lightyear::connection::id::ClientId;
 use lightyear::prelude::server::*;
-use lightyear::prelude::{MessageSend, NetworkTarget, SharedConfig};
-use lightyear::server::message::ReceiveMessage;
+use lightyear::prelude::{SharedConfig, TickConfig};
 use std::net::SocketAddr;
-use tracing::{info, warn};
+use tracing::{error, info};
 
 // Camera2dBundle should be in prelude
 
+pub mod admin;
+pub mod cli;
+pub mod combat;
 pub mod config;
 pub mod debug_ui;
 pub mod despawn_utils;
 pub mod flocking;
 pub mod groups;
+pub mod health;
 pub mod health_sync;
+pub mod interest;
+pub mod lobby;
+pub mod map;
+pub mod map_file;
+pub mod navigation;
 pub mod physics;
 pub mod pool;
 pub mod position_sync;
+pub mod profiler;
+pub mod replay;
+pub mod rng;
+pub mod round;
+pub mod settings;
 pub mod spatial_grid;
-use bevy_rapier2d::prelude::{Collider, ExternalForce, ExternalImpulse, RigidBody};
+pub mod targeting;
+pub mod zone;
+use admin::AdminConsolePlugin;
+use clap::Parser;
+use cli::{ServerArgs, Transport};
 use config::PhysicsConfig;
 use debug_ui::DebugUIPlugin;
+use groups::behavior_tree::BehaviorTrees;
+use health::HealthPlugin;
 use health_sync::HealthSyncPlugin;
-use physics::{GameCollisionGroups, PhysicsPlugin, Ship, WeaponStats};
-use position_sync::{PositionSyncPlugin, SyncPosition};
+use interest::InterestPlugin;
+use lobby::{GameState, LobbyPlugin, PlayerSlots};
+use map::{MapConfig, MapPlugin};
+use map_file::{spawn_map_groups, MapFile};
+use navigation::NavigationPlugin;
+use physics::PhysicsPlugin;
+use position_sync::PositionSyncPlugin;
+use profiler::ProfilerPlugin;
+use replay::{inputs_hash, Replay, ReplayMode, ReplayPlugin};
+use rng::GameRng;
+use round::RoundPlugin;
+use settings::{SettingsPlugin, SettingsSource};
 use spatial_grid::SpatialGridPlugin;
+use zone::ZonePlugin;
 
 /// Returns the appropriate base plugins depending on whether we're in debug or release mode
 fn get_base_plugins() -> PluginGroupBuilder {
@@ -51,8 +80,19 @@ fn get_base_plugins() -> PluginGroupBuilder {
 }
 
 fn main() {
+    // Bad arguments print usage and exit before anything starts
+    let args = ServerArgs::parse();
+
     // Initialize logging first
-    tracing_subscriber::fmt::init();
+    args.log_format.init();
+
+    // Load the recording up front so a bad file fails fast
+    let replay = args.replay.as_ref().map(|path| {
+        Replay::load(path).unwrap_or_else(|e| {
+            error!("Failed to load replay {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
 
     info!("🚀 Starting Boid Wars server...");
     info!(
@@ -64,19 +104,72 @@ fn main() {
         }
     );
 
+    // Layer defaults < settings file < environment < --set, and install the result
+    // before anything reads the global configs
+    let settings_source = SettingsSource {
+        file: args.config.clone(),
+        overrides: args.setting_overrides(),
+    };
+    let settings = settings_source
+        .load()
+        .and_then(|settings| {
+            settings.install_globals()?;
+            Ok(settings)
+        })
+        .unwrap_or_else(|errors| {
+            error!("Invalid settings:\n{}", error_report(&errors));
+            std::process::exit(2);
+        });
+    if let Some(path) = &settings_source.file {
+        info!("⚙️  Loaded settings from {}", path.display());
+    }
+
     // Load configuration
     let network_config = &*NETWORK_CONFIG;
     let game_config = &*GAME_CONFIG;
 
+    let map_file = args.map.as_ref().map(|path| {
+        MapFile::load_validated(
+            path,
+            Vec2::new(game_config.game_width, game_config.game_height),
+            game_config.max_players as usize,
+        )
+        .unwrap_or_else(|e| {
+            error!("Invalid map {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+
+    let behavior_trees = args.behaviors.as_ref().map(|path| {
+        BehaviorTrees::load_validated(path).unwrap_or_else(|e| {
+            error!("Invalid behavior trees {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+
+    // A recording only replays against the inputs it was made with
+    let replay_inputs = inputs_hash(&settings, map_file.as_ref(), behavior_trees.as_ref());
+    if let Some(replay) = &replay {
+        if let Err(e) = replay.header.check_inputs(replay_inputs) {
+            error!("Cannot play back replay: {}", e);
+            std::process::exit(1);
+        }
+    }
+
     // Configure server address
     info!(
         "📡 Parsing server bind address: {}",
         network_config.server_bind_addr
     );
-    let server_addr: SocketAddr = network_config
-        .server_bind_addr
-        .parse()
-        .expect("Failed to parse server bind address");
+    let server_addr: SocketAddr = if replay.is_some() {
+        // Playback takes no connections; stay clear of a live server
+        SocketAddr::from(([127, 0, 0, 1], 0))
+    } else {
+        network_config
+            .server_bind_addr
+            .parse()
+            .expect("Failed to parse server bind address")
+    };
 
     info!(
         "🌐 Server will listen on {} | Game area: {}x{}",
@@ -86,14 +179,51 @@ fn main() {
     info!("🎯 Protocol ID: {}", network_config.protocol_id);
 
     // Create server config
-    info!("⚙️  Creating Lightyear server configuration...");
-    let lightyear_config = create_websocket_config(server_addr, network_config);
+    info!(
+        "⚙️  Creating Lightyear server configuration ({:?})...",
+        args.transport
+    );
+    // Lightyear owns `Time<Fixed>` and sets it from the tick config; playback runs at the
+    // rate it was recorded at
+    let timestep = replay
+        .as_ref()
+        .map(|replay| replay.header.timestep)
+        .or(args.timestep());
+    if let Some(timestep) = timestep {
+        info!("⏱️  Fixed tick rate: {:?} per tick", timestep);
+    }
+    let lightyear_config =
+        create_server_config(server_addr, network_config, args.transport, timestep);
 
     info!("🎮 Building Bevy app with plugins...");
     let mut app = App::new();
 
     info!("🔌 Adding base plugins...");
-    app.add_plugins(get_base_plugins());
+    if replay.is_some() {
+        app.add_plugins(DefaultPlugins.build().disable::<WindowPlugin>());
+    } else {
+        app.add_plugins(get_base_plugins());
+    }
+
+    settings.insert_resources(&mut app);
+    app.add_plugins(SettingsPlugin {
+        source: settings_source,
+    });
+
+    if let Some(seed) = args.seed {
+        app.insert_resource(GameRng::from_seed(seed));
+    }
+    if let Some(file) = map_file {
+        info!("🗺️  Using map '{}'", file.name);
+        app.insert_resource(MapConfig {
+            file: Some(file),
+            ..default()
+        });
+    }
+    if let Some(trees) = behavior_trees {
+        info!("🧠 Using custom behavior trees");
+        app.insert_resource(trees);
+    }
 
     info!("🔌 Adding game plugins...");
     app.add_plugins(DebugUIPlugin)
@@ -105,20 +235,51 @@ fn main() {
         .add_plugins(HealthSyncPlugin) // Event-based health synchronization
         .add_plugins(flocking::FlockingPlugin) // Add flocking behavior
         .add_plugins(groups::BoidGroupPlugin)
-        .add_plugins(BoidWarsServerPlugin);
+        .add_plugins(NavigationPlugin) // Routes groups around obstacles
+        .add_plugins(MapPlugin) // Seeded obstacle layouts
+        .add_plugins(ZonePlugin) // Shrinking battle-royale zone
+        .add_plugins(InterestPlugin) // Per-client area-of-interest replication
+        .add_plugins(RoundPlugin) // Win conditions, results and rematch
+        .add_plugins(ProfilerPlugin) // Per-stage tick timings and traces
+        .add_plugins(BoidWarsServerPlugin)
+        .add_plugins(AdminConsolePlugin {
+            // Playback runs unattended
+            read_stdin: !args.no_console && replay.is_none(),
+        })
+        .add_plugins(HealthPlugin {
+            // Playback has nothing to probe
+            bind_addr: Some(SERVER_CONFIG.health_bind_addr.clone())
+                .filter(|addr| !addr.is_empty() && replay.is_none()),
+        });
+
+    // Replay recording or headless playback
+    let replay_mode = match (replay, args.record) {
+        (Some(replay), _) => Some(ReplayMode::Playback(replay)),
+        (None, Some(path)) => Some(ReplayMode::Record(path)),
+        (None, None) => None,
+    };
+    if let Some(mode) = replay_mode {
+        app.add_plugins(ReplayPlugin {
+            mode,
+            inputs: replay_inputs,
+        });
+    }
 
     info!("🚀 Starting Bevy app...");
     app.run();
 }
 
-fn create_websocket_config(
+fn create_server_config(
     server_addr: SocketAddr,
     network_config: &NetworkConfig,
+    transport: Transport,
+    timestep: Option<std::time::Duration>,
 ) -> lightyear::prelude::server::ServerConfig {
-    // Create WebSocket server config
-
-    // WebSocket transport - NO CERTIFICATES!
-    let transport = ServerTransport::WebSocketServer { server_addr };
+    let transport = match transport {
+        // WebSocket transport - NO CERTIFICATES!
+        Transport::WebSocket => ServerTransport::WebSocketServer { server_addr },
+        Transport::Udp => ServerTransport::UdpSocket(server_addr),
+    };
     let io = IoConfig::from_transport(transport);
 
     // Use Netcode auth with a shared key for dev
@@ -132,7 +293,10 @@ fn create_websocket_config(
     };
 
     lightyear::prelude::server::ServerConfig {
-        shared: SharedConfig::default(),
+        shared: SharedConfig {
+            tick: timestep.map_or_else(|| SharedConfig::default().tick, TickConfig::new),
+            ..default()
+        },
         net: vec![net_config],
         packet: Default::default(),
         replication: Default::default(),
@@ -145,23 +309,11 @@ pub struct BoidWarsServerPlugin;
 
 impl Plugin for BoidWarsServerPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<GameState>();
-
         // Add startup system to spawn server
         app.add_systems(Startup, setup_server);
 
-        // Connection handling
-        app.add_systems(
-            Update,
-            (
-                handle_connections,
-                handle_disconnections,
-                handle_player_input,
-                handle_player_ready,
-                send_game_state_updates,
-                check_start_game,
-            ),
-        );
+        // Connection handling, ready checks and player spawning
+        app.add_plugins(LobbyPlugin);
 
         // Add game systems
         app.add_systems(Update, (log_status, spawn_collision_objects_delayed));
@@ -179,41 +331,137 @@ fn setup_server(mut commands: Commands) {
     });
 
     // Create status timer
+    let interval = SERVER_CONFIG.status_log_interval;
     commands.insert_resource(StatusTimer(Timer::from_seconds(
-        5.0, // Default status log interval
+        interval,
         TimerMode::Repeating,
     )));
 
-    info!("⏰ Status timer configured (5s intervals)");
-
-    // Initialize player slots
-    commands.insert_resource(PlayerSlots::default());
+    info!("⏰ Status timer configured ({}s intervals)", interval);
 }
 
 // Spawn AI players when the game starts
+#[allow(clippy::too_many_arguments)]
 fn spawn_collision_objects_delayed(
     mut commands: Commands,
     game_state: Res<GameState>,
     mut spawned: Local<bool>,
     physics_config: Res<PhysicsConfig>,
+    map_config: Res<MapConfig>,
+    player_slots: Res<PlayerSlots>,
+    mut rng: ResMut<GameRng>,
+    mut group_id_counter: ResMut<groups::GroupIdCounter>,
+    mut boid_id_counter: ResMut<groups::BoidIdCounter>,
 ) {
+    // Arena is cleared between rounds, so respawn for every new match
+    if matches!(
+        game_state.phase,
+        boid_wars_shared::GamePhase::WaitingForPlayers | boid_wars_shared::GamePhase::Lobby
+    ) {
+        *spawned = false;
+    }
+
     // Only spawn when game is in InGame phase and we haven't spawned yet
     if game_state.phase == boid_wars_shared::GamePhase::InGame && !*spawned {
         *spawned = true;
         // Spawn peaceful boids instead of AI players
 
-        // Re-enabled to test boid synchronization
-        spawn_boid_flock(&mut commands, &physics_config);
-        spawn_static_obstacles(&mut commands);
+        // Load or generate the arena around spawn points and territories
+        let game_config = &*GAME_CONFIG;
+        let territories = flock_territories();
+        let territory_centers: Vec<Vec2> = territories.iter().map(|t| t.center).collect();
+        let map = map::build_match_map(
+            &map_config,
+            &mut rng,
+            Vec2::new(game_config.game_width, game_config.game_height),
+            player_slots.len(),
+            &territory_centers,
+        );
+        info!(
+            "Arena {:?} with {} obstacles",
+            map.source,
+            map.obstacles.len()
+        );
+        map::spawn_map_obstacles(&mut commands, &map);
+        commands.insert_resource(map);
+
+        match &map_config.file {
+            Some(file) => spawn_map_groups(
+                &mut commands,
+                file,
+                &mut group_id_counter,
+                &mut boid_id_counter,
+                &physics_config,
+                &mut rng,
+            ),
+            // Re-enabled to test boid synchronization
+            None => spawn_boid_flock(
+                &mut commands,
+                &physics_config,
+                territories,
+                &mut group_id_counter,
+                &mut boid_id_counter,
+                &mut rng,
+            ),
+        }
     }
 }
 
+/// Territories of the three boid groups spawned each match
+fn flock_territories() -> [TerritoryData; 3] {
+    [
+        // First group: Assault group in center
+        TerritoryData {
+            center: Vec2::new(800.0, 600.0), // Arena center
+            radius: 400.0,
+            zone: ArenaZone::Inner,
+            patrol_points: vec![
+                Vec2::new(600.0, 400.0),
+                Vec2::new(1000.0, 400.0),
+                Vec2::new(1000.0, 800.0),
+                Vec2::new(600.0, 800.0),
+            ],
+            neighboring_territories: vec![],
+        },
+        // Second group: Defensive group in upper area
+        TerritoryData {
+            center: Vec2::new(800.0, 300.0), // Upper center
+            radius: 300.0,
+            zone: ArenaZone::Middle,
+            patrol_points: vec![
+                Vec2::new(500.0, 200.0),
+                Vec2::new(1100.0, 200.0),
+                Vec2::new(1100.0, 400.0),
+                Vec2::new(500.0, 400.0),
+            ],
+            neighboring_territories: vec![],
+        },
+        // Third group: Recon group patrolling outer edges
+        TerritoryData {
+            center: Vec2::new(800.0, 900.0), // Lower area
+            radius: 500.0,
+            zone: ArenaZone::Outer,
+            patrol_points: vec![
+                Vec2::new(200.0, 700.0),
+                Vec2::new(1400.0, 700.0),
+                Vec2::new(1400.0, 1100.0),
+                Vec2::new(200.0, 1100.0),
+            ],
+            neighboring_territories: vec![],
+        },
+    ]
+}
+
 // Helper function to spawn peaceful boids using the group system
-fn spawn_boid_flock(commands: &mut Commands, physics_config: &PhysicsConfig) {
+fn spawn_boid_flock(
+    commands: &mut Commands,
+    physics_config: &PhysicsConfig,
+    territories: [TerritoryData; 3],
+    group_id_counter: &mut groups::GroupIdCounter,
+    boid_id_counter: &mut groups::BoidIdCounter,
+    rng: &mut GameRng,
+) {
     let _game_config = &*GAME_CONFIG;
-    // Get resources
-    let mut group_id_counter = groups::GroupIdCounter::default();
-    let mut boid_id_counter = groups::BoidIdCounter::default();
 
     // Spawn groups in different zones
     let mut spawned_groups = 0;
@@ -243,26 +491,15 @@ fn spawn_boid_flock(commands: &mut Commands, physics_config: &PhysicsConfig) {
         },
         20, // Small group for testing
         simple_territory,
-        &mut group_id_counter,
-        &mut boid_id_counter,
+        group_id_counter,
+        boid_id_counter,
         physics_config,
     );
     */
 
-    // First group: Assault group in center
-    let assault_territory = TerritoryData {
-        center: Vec2::new(800.0, 600.0), // Arena center
-        radius: 400.0,
-        zone: ArenaZone::Inner,
-        patrol_points: vec![
-            Vec2::new(600.0, 400.0),
-            Vec2::new(1000.0, 400.0),
-            Vec2::new(1000.0, 800.0),
-            Vec2::new(600.0, 800.0),
-        ],
-        neighboring_territories: vec![],
-    };
+    let [assault_territory, defensive_territory, recon_territory] = territories;
 
+    // First group: Assault group in center
     groups::spawn_boid_group(
         commands,
         GroupArchetype::Assault {
@@ -271,26 +508,14 @@ fn spawn_boid_flock(commands: &mut Commands, physics_config: &PhysicsConfig) {
         },
         15,
         assault_territory,
-        &mut group_id_counter,
-        &mut boid_id_counter,
+        group_id_counter,
+        boid_id_counter,
         physics_config,
+        rng,
     );
     spawned_groups += 1;
 
     // Second group: Defensive group in upper area
-    let defensive_territory = TerritoryData {
-        center: Vec2::new(800.0, 300.0), // Upper center
-        radius: 300.0,
-        zone: ArenaZone::Middle,
-        patrol_points: vec![
-            Vec2::new(500.0, 200.0),
-            Vec2::new(1100.0, 200.0),
-            Vec2::new(1100.0, 400.0),
-            Vec2::new(500.0, 400.0),
-        ],
-        neighboring_territories: vec![],
-    };
-
     groups::spawn_boid_group(
         commands,
         GroupArchetype::Defensive {
@@ -299,26 +524,14 @@ fn spawn_boid_flock(commands: &mut Commands, physics_config: &PhysicsConfig) {
         },
         20,
         defensive_territory,
-        &mut group_id_counter,
-        &mut boid_id_counter,
+        group_id_counter,
+        boid_id_counter,
         physics_config,
+        rng,
     );
     spawned_groups += 1;
 
     // Third group: Recon group patrolling outer edges
-    let recon_territory = TerritoryData {
-        center: Vec2::new(800.0, 900.0), // Lower area
-        radius: 500.0,
-        zone: ArenaZone::Outer,
-        patrol_points: vec![
-            Vec2::new(200.0, 700.0),
-            Vec2::new(1400.0, 700.0),
-            Vec2::new(1400.0, 1100.0),
-            Vec2::new(200.0, 1100.0),
-        ],
-        neighboring_territories: vec![],
-    };
-
     groups::spawn_boid_group(
         commands,
         GroupArchetype::Recon {
@@ -327,9 +540,10 @@ fn spawn_boid_flock(commands: &mut Commands, physics_config: &PhysicsConfig) {
         },
         12,
         recon_territory,
-        &mut group_id_counter,
-        &mut boid_id_counter,
+        group_id_counter,
+        boid_id_counter,
         physics_config,
+        rng,
     );
     spawned_groups += 1;
 
@@ -345,8 +559,8 @@ fn spawn_boid_flock(commands: &mut Commands, physics_config: &PhysicsConfig) {
             },
             30, // Smaller groups for testing
             territory.clone(),
-            &mut group_id_counter,
-            &mut boid_id_counter,
+            group_id_counter,
+            boid_id_counter,
             physics_config,
         );
         spawned_groups += 1;
@@ -354,223 +568,6 @@ fn spawn_boid_flock(commands: &mut Commands, physics_config: &PhysicsConfig) {
     */
 
     info!("Spawned {} boid groups with territories", spawned_groups);
-
-    // Update resource counters
-    commands.insert_resource(group_id_counter);
-    commands.insert_resource(boid_id_counter);
-}
-
-// Helper function to spawn static obstacles
-fn spawn_static_obstacles(commands: &mut Commands) {
-    let collision_groups = GameCollisionGroups::wall();
-
-    // Create some obstacles scattered around the arena
-    let obstacles = [
-        (200.0, 200.0, 30.0, 30.0), // Square obstacle
-        (600.0, 150.0, 40.0, 20.0), // Rectangular obstacle
-        (300.0, 400.0, 25.0, 25.0), // Small square
-        (500.0, 350.0, 35.0, 35.0), // Medium square
-        (150.0, 450.0, 50.0, 15.0), // Long rectangle
-    ];
-
-    for (i, (x, y, width, height)) in obstacles.iter().enumerate() {
-        commands.spawn((
-            RigidBody::Fixed,
-            Collider::cuboid(width / 2.0, height / 2.0), // Rapier uses half-extents
-            Transform::from_xyz(*x, *y, 0.0),
-            GlobalTransform::default(),
-            collision_groups,
-            bevy_rapier2d::prelude::ActiveEvents::COLLISION_EVENTS, // Enable collision detection
-            Name::new(format!("Obstacle {}", i + 1)),
-            // Add network components to make it visible to clients
-            boid_wars_shared::Position(Vec2::new(*x, *y)),
-            boid_wars_shared::Obstacle {
-                id: i as u32,
-                width: *width,
-                height: *height,
-            },
-            boid_wars_shared::Health::default(),
-            lightyear::prelude::server::Replicate::default(),
-            SyncPosition, // Mark for position sync
-        ));
-    }
-}
-
-// Handle new client connections
-fn handle_connections(
-    mut connections: EventReader<ConnectEvent>,
-    mut player_slots: ResMut<PlayerSlots>,
-    mut connection_manager: ResMut<ConnectionManager>,
-    mut game_state: ResMut<GameState>,
-) {
-    let game_config = &*GAME_CONFIG;
-
-    for event in connections.read() {
-        let client_id = event.client_id;
-
-        // Determine which player slot to assign
-        let player_number = if player_slots.player1.is_none() {
-            PlayerNumber::Player1
-        } else if player_slots.player2.is_none() {
-            PlayerNumber::Player2
-        } else {
-            // Server is full - send rejection message then disconnect
-            info!("Server full: rejecting client {:?}", client_id);
-            
-            let server_full_msg = boid_wars_shared::ServerFullMessage {
-                current_players: 2,
-                max_players: 2,
-                message: "Server is full (2/2 players). Please try again later.".to_string(),
-            };
-            
-            // Send message to the specific client
-            if let Err(e) = connection_manager
-                .send_message_to_target::<boid_wars_shared::ReliableChannel, _>(
-                    &server_full_msg,
-                    NetworkTarget::Single(client_id),
-                ) {
-                warn!("Failed to send ServerFull message: {:?}", e);
-            }
-            
-            // Client will disconnect themselves after receiving ServerFull message
-            continue;
-        };
-
-        info!("Client {:?} connected as {:?}", client_id, player_number);
-
-        // Store player slot assignment WITHOUT spawning yet
-        // We'll use a placeholder entity until they're ready
-        match player_number {
-            PlayerNumber::Player1 => {
-                player_slots.player1 = Some((client_id, Entity::PLACEHOLDER));
-                // Force a change detection for immediate update
-                game_state.set_changed();
-                if player_slots.player2.is_some() {
-                    // Both players connected, move to lobby phase
-                    game_state.phase = boid_wars_shared::GamePhase::Lobby;
-                    info!("Both players connected, entering lobby phase");
-                }
-            }
-            PlayerNumber::Player2 => {
-                player_slots.player2 = Some((client_id, Entity::PLACEHOLDER));
-                // Force a change detection for immediate update
-                game_state.set_changed();
-                if player_slots.player1.is_some() {
-                    // Both players connected, move to lobby phase
-                    game_state.phase = boid_wars_shared::GamePhase::Lobby;
-                    info!("Both players connected, entering lobby phase");
-                }
-            }
-        }
-
-        // Player spawning code moved to check_start_game system
-        info!(
-            "Player slot assigned for client {:?}, waiting for ready signal",
-            client_id
-        );
-    }
-}
-
-// Handle client disconnections
-fn handle_disconnections(
-    mut commands: Commands,
-    mut disconnections: EventReader<DisconnectEvent>,
-    mut player_slots: ResMut<PlayerSlots>,
-    mut game_state: ResMut<GameState>,
-) {
-    for event in disconnections.read() {
-        let client_id = event.client_id;
-
-        // Find and remove player from slots
-        if let Some((stored_id, entity)) = player_slots.player1 {
-            if stored_id == client_id {
-                info!("Player 1 (client {:?}) disconnected", client_id);
-                player_slots.player1 = None;
-                game_state.player1_ready = false;
-                
-                // Only despawn if entity was actually spawned (not placeholder)
-                if entity != Entity::PLACEHOLDER {
-                    commands.entity(entity).despawn();
-                }
-            }
-        }
-
-        if let Some((stored_id, entity)) = player_slots.player2 {
-            if stored_id == client_id {
-                info!("Player 2 (client {:?}) disconnected", client_id);
-                player_slots.player2 = None;
-                game_state.player2_ready = false;
-                
-                // Only despawn if entity was actually spawned (not placeholder)
-                if entity != Entity::PLACEHOLDER {
-                    commands.entity(entity).despawn();
-                }
-            }
-        }
-        
-        // Reset to waiting phase if any player disconnects
-        if player_slots.player1.is_none() || player_slots.player2.is_none() {
-            game_state.phase = boid_wars_shared::GamePhase::WaitingForPlayers;
-            info!("Player disconnected, returning to waiting phase");
-        }
-    }
-}
-
-// Handle player input messages - update physics input properly
-fn handle_player_input(
-    mut message_events: EventReader<ReceiveMessage<boid_wars_shared::PlayerInput>>,
-    mut players: Query<
-        (
-            &boid_wars_shared::Player,
-            &mut physics::PlayerInput,
-            &PlayerNumber,
-        ),
-        With<physics::Player>,
-    >,
-) {
-    for event in message_events.read() {
-        let client_id = event.from;
-        let input = &event.message;
-
-        // Input validation
-        if !validate_player_input(input) {
-            warn!(
-                "Invalid input from client {:?}: movement={:?}, aim={:?}",
-                client_id, input.movement, input.aim
-            );
-            continue;
-        }
-
-        // Find the player for this client and update their physics input
-        for (player, mut physics_input, _player_number) in players.iter_mut() {
-            if player.id == client_id.to_bits() {
-                // Both players now have full functionality
-                physics_input.movement = input.movement.normalize_or_zero(); // Ensure normalized
-                physics_input.aim_direction = input.aim.normalize_or_zero(); // Ensure normalized
-                physics_input.thrust = if input.movement.length() > 0.0 {
-                    1.0
-                } else {
-                    0.0
-                };
-                physics_input.shooting = input.fire;
-            }
-        }
-    }
-}
-
-/// Validate player input to prevent malicious or malformed data
-fn validate_player_input(input: &boid_wars_shared::PlayerInput) -> bool {
-    // Check movement vector is valid
-    if !input.movement.is_finite() || input.movement.length() > 1.1 {
-        return false;
-    }
-
-    // Check aim direction is valid
-    if !input.aim.is_finite() || input.aim.length() > 1.1 {
-        return false;
-    }
-
-    true
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -597,217 +594,3 @@ fn log_status(
 
 #[derive(Resource)]
 struct StatusTimer(Timer);
-
-#[derive(Resource)]
-struct GameState {
-    phase: boid_wars_shared::GamePhase,
-    player1_ready: bool,
-    player2_ready: bool,
-}
-
-impl Default for GameState {
-    fn default() -> Self {
-        Self {
-            phase: boid_wars_shared::GamePhase::WaitingForPlayers,
-            player1_ready: false,
-            player2_ready: false,
-        }
-    }
-}
-
-#[derive(Resource, Default)]
-struct PlayerSlots {
-    player1: Option<(ClientId, Entity)>,
-    player2: Option<(ClientId, Entity)>,
-}
-
-// Handle player ready messages
-fn handle_player_ready(
-    mut message_events: EventReader<ReceiveMessage<boid_wars_shared::PlayerReady>>,
-    mut game_state: ResMut<GameState>,
-    player_slots: Res<PlayerSlots>,
-) {
-    for event in message_events.read() {
-        let client_id = event.from;
-        
-        // Only process ready in lobby phase
-        if game_state.phase != boid_wars_shared::GamePhase::Lobby {
-            continue;
-        }
-        
-        // Mark the appropriate player as ready
-        if let Some((stored_id, _)) = player_slots.player1 {
-            if stored_id == client_id {
-                game_state.player1_ready = true;
-                info!("Player 1 is ready!");
-            }
-        }
-        
-        if let Some((stored_id, _)) = player_slots.player2 {
-            if stored_id == client_id {
-                game_state.player2_ready = true;
-                info!("Player 2 is ready!");
-            }
-        }
-    }
-}
-
-// Send game state updates to all clients
-fn send_game_state_updates(
-    game_state: Res<GameState>,
-    player_slots: Res<PlayerSlots>,
-    mut connection_manager: ResMut<ConnectionManager>,
-) {
-    // Always send updates when game state or player slots change
-    if !game_state.is_changed() && !player_slots.is_changed() {
-        return;
-    }
-    
-    // Count connected players
-    let player_count = match (player_slots.player1.is_some(), player_slots.player2.is_some()) {
-        (true, true) => 2,
-        (true, false) | (false, true) => 1,
-        (false, false) => 0,
-    };
-    
-    let update = boid_wars_shared::GameStateUpdate {
-        phase: game_state.phase.clone(),
-        player_count,
-        player1_ready: game_state.player1_ready,
-        player2_ready: game_state.player2_ready,
-    };
-    
-    // Send to all connected clients
-    if let Err(e) = connection_manager
-        .send_message_to_target::<boid_wars_shared::ReliableChannel, _>(
-            &update,
-            NetworkTarget::All,
-        ) {
-        warn!("Failed to send game state update: {:?}", e);
-    }
-}
-
-// Check if we should start the game
-fn check_start_game(
-    mut commands: Commands,
-    mut game_state: ResMut<GameState>,
-    mut player_slots: ResMut<PlayerSlots>,
-    physics_config: Res<PhysicsConfig>,
-) {
-    // Only check in lobby phase
-    if game_state.phase != boid_wars_shared::GamePhase::Lobby {
-        return;
-    }
-    
-    // Check if both players are ready
-    if !game_state.player1_ready || !game_state.player2_ready {
-        return;
-    }
-    
-    info!("Both players ready! Starting game...");
-    
-    // Spawn player 1
-    if let Some((client_id, ref mut entity)) = player_slots.player1.as_mut() {
-        let player_entity = spawn_player(
-            &mut commands,
-            &physics_config,
-            *client_id,
-            100.0, // spawn_x
-            100.0, // spawn_y
-            boid_wars_shared::PlayerNumber::Player1,
-        );
-        *entity = player_entity;
-    }
-    
-    // Spawn player 2
-    if let Some((client_id, ref mut entity)) = player_slots.player2.as_mut() {
-        let game_config = &*GAME_CONFIG;
-        let player_entity = spawn_player(
-            &mut commands,
-            &physics_config,
-            *client_id,
-            game_config.game_width - 100.0,  // spawn_x
-            game_config.game_height - 100.0, // spawn_y
-            boid_wars_shared::PlayerNumber::Player2,
-        );
-        *entity = player_entity;
-    }
-    
-    // Move to in-game phase
-    game_state.phase = boid_wars_shared::GamePhase::InGame;
-    info!("Game started!");
-}
-
-// Helper function to spawn a player
-fn spawn_player(
-    commands: &mut Commands,
-    physics_config: &PhysicsConfig,
-    client_id: ClientId,
-    spawn_x: f32,
-    spawn_y: f32,
-    player_number: boid_wars_shared::PlayerNumber,
-) -> Entity {
-    let game_config = &*GAME_CONFIG;
-    
-    let player_entity = commands
-        .spawn((
-            PlayerBundle::new(
-                client_id.to_bits(),
-                format!("Player {}", client_id.to_bits()),
-                spawn_x,
-                spawn_y,
-                player_number,
-            ),
-            // Networking
-            Replicate {
-                controlled_by: ControlledBy {
-                    target: NetworkTarget::Single(client_id),
-                    ..default()
-                },
-                ..default()
-            },
-        ))
-        .id();
-    
-    // Add physics components
-    commands.entity(player_entity).insert((
-        physics::Player {
-            player_id: client_id.to_bits(),
-            ..Default::default()
-        },
-        physics::PlayerInput::default(),
-        Ship::default(),
-        WeaponStats::default(),
-        boid_wars_shared::Health {
-            current: game_config.default_health,
-            max: game_config.default_health,
-        },
-    ));
-    
-    // Add physics body components
-    commands.entity(player_entity).insert((
-        RigidBody::Dynamic,
-        Collider::cuboid(
-            physics_config.player_collider_size,
-            physics_config.player_collider_size,
-        ),
-        GameCollisionGroups::player(),
-        physics::Velocity::zero(),
-        ExternalForce::default(),
-        ExternalImpulse::default(),
-        Transform::from_xyz(spawn_x, spawn_y, 0.0),
-        GlobalTransform::default(),
-        bevy_rapier2d::dynamics::GravityScale(0.0),
-        bevy_rapier2d::dynamics::Sleeping::disabled(),
-        bevy_rapier2d::dynamics::Damping {
-            linear_damping: 0.0,
-            angular_damping: 0.0,
-        },
-        bevy_rapier2d::dynamics::AdditionalMassProperties::Mass(1.0),
-        SyncPosition,
-    ));
-    
-    info!("Player spawned at ({}, {}) for client {:?}", spawn_x, spawn_y, client_id);
-    
-    player_entity
-}
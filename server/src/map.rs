@@ -0,0 +1,376 @@
+//! Seeded arena layouts.
+//!
+//! Each match gets a fresh obstacle layout generated from a seed, so the same
+//! seed always produces the same map (and replays reproduce it). Generation
+//! keeps clear areas around spawn points and territory centers, and rejects
+//! any obstacle that would cut the free space into disconnected pockets.
+//!
+//! A hand-made map file (see [`crate::map_file`]) replaces generation.
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{ActiveEvents, Collider, RigidBody};
+use boid_wars_shared::{Health, Obstacle, Position};
+use lightyear::prelude::server::Replicate;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+
+use crate::lobby::spawn_points;
+use crate::map_file::MapFile;
+use crate::physics::GameCollisionGroups;
+use crate::position_sync::SyncPosition;
+use crate::rng::GameRng;
+
+/// Style of obstacle layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapLayout {
+    /// Clusters of small rocks
+    AsteroidField,
+    /// Long thin walls forming lanes
+    Corridors,
+    /// Mixed obstacles mirrored through the arena center, for fair spawns
+    Symmetric,
+}
+
+impl MapLayout {
+    pub const ALL: [MapLayout; 3] = [
+        MapLayout::AsteroidField,
+        MapLayout::Corridors,
+        MapLayout::Symmetric,
+    ];
+}
+
+/// Map generation settings
+#[derive(Resource, Debug, Clone)]
+pub struct MapConfig {
+    /// Fixed layout, or `None` to pick one from the seed
+    pub layout: Option<MapLayout>,
+    /// Fixed seed, or `None` for a new seed from `GameRng` every match
+    pub seed: Option<u64>,
+    /// Hand-made map; replaces generation and the built-in group spawns
+    pub file: Option<MapFile>,
+    pub obstacle_count: usize,
+    /// Radius kept free of obstacles around each spawn point
+    pub spawn_clearance: f32,
+    /// Radius kept free of obstacles around each territory center
+    pub territory_clearance: f32,
+    /// Distance kept between obstacles and the arena walls
+    pub edge_margin: f32,
+    /// Narrowest gap a ship must be able to fly through
+    pub passage_width: f32,
+    /// Resolution of the connectivity check
+    pub cell_size: f32,
+    /// Candidate obstacles tried before giving up on `obstacle_count`
+    pub max_attempts: usize,
+}
+
+impl Default for MapConfig {
+    fn default() -> Self {
+        Self {
+            layout: None,
+            seed: None,
+            file: None,
+            obstacle_count: 18,
+            spawn_clearance: 150.0,
+            territory_clearance: 120.0,
+            edge_margin: 80.0,
+            passage_width: 80.0, // Ships are ~62px across
+            cell_size: 20.0,
+            max_attempts: 400,
+        }
+    }
+}
+
+/// Axis-aligned obstacle rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObstacleRect {
+    pub center: Vec2,
+    pub size: Vec2,
+}
+
+impl ObstacleRect {
+    pub fn new(center: Vec2, size: Vec2) -> Self {
+        Self { center, size }
+    }
+
+    /// Distance from the rectangle's edge to `point` (0 inside)
+    pub fn distance_to(&self, point: Vec2) -> f32 {
+        let outside = ((point - self.center).abs() - self.size / 2.0).max(Vec2::ZERO);
+        outside.length()
+    }
+
+    /// Gap between two rectangles (0 if they touch or overlap)
+    pub fn gap_to(&self, other: &ObstacleRect) -> f32 {
+        let outside =
+            ((self.center - other.center).abs() - (self.size + other.size) / 2.0).max(Vec2::ZERO);
+        outside.length()
+    }
+
+    /// The same rectangle rotated 180° around the arena center
+    fn mirrored(&self, arena_size: Vec2) -> Self {
+        Self::new(arena_size - self.center, self.size)
+    }
+}
+
+/// Where the current arena came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapSource {
+    Generated {
+        seed: u64,
+        layout: MapLayout,
+    },
+    /// Name of a hand-made map file
+    File(String),
+}
+
+/// Obstacle layout of the current match
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ArenaMap {
+    pub source: MapSource,
+    pub arena_size: Vec2,
+    pub obstacles: Vec<ObstacleRect>,
+}
+
+/// Generate a layout; the result depends only on the arguments.
+///
+/// `clear_areas` are `(center, radius)` circles no obstacle may touch.
+pub fn generate_map(
+    seed: u64,
+    layout: MapLayout,
+    arena_size: Vec2,
+    clear_areas: &[(Vec2, f32)],
+    config: &MapConfig,
+) -> ArenaMap {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut obstacles: Vec<ObstacleRect> = Vec::new();
+
+    for _ in 0..config.max_attempts {
+        if obstacles.len() >= config.obstacle_count {
+            break;
+        }
+
+        let candidates = match layout {
+            MapLayout::AsteroidField => asteroid_cluster(&mut rng, arena_size),
+            MapLayout::Corridors => vec![corridor_wall(&mut rng, arena_size)],
+            MapLayout::Symmetric => {
+                let rect = if rng.gen_bool(0.5) {
+                    corridor_wall(&mut rng, arena_size)
+                } else {
+                    random_block(&mut rng, arena_size)
+                };
+                vec![rect, rect.mirrored(arena_size)]
+            }
+        };
+
+        if fits(&candidates, &obstacles, arena_size, clear_areas, config) {
+            let before = obstacles.len();
+            obstacles.extend(candidates);
+            if !is_connected(&obstacles, arena_size, config) {
+                obstacles.truncate(before);
+            }
+        }
+    }
+
+    ArenaMap {
+        source: MapSource::Generated { seed, layout },
+        arena_size,
+        obstacles,
+    }
+}
+
+fn random_point(rng: &mut StdRng, arena_size: Vec2) -> Vec2 {
+    Vec2::new(
+        rng.gen_range(0.0..arena_size.x),
+        rng.gen_range(0.0..arena_size.y),
+    )
+}
+
+/// 2-4 rocks around a random point
+fn asteroid_cluster(rng: &mut StdRng, arena_size: Vec2) -> Vec<ObstacleRect> {
+    let center = random_point(rng, arena_size);
+    let count = rng.gen_range(2..=4);
+
+    (0..count)
+        .map(|_| {
+            let offset = Vec2::new(rng.gen_range(-70.0..70.0), rng.gen_range(-70.0..70.0));
+            let side = rng.gen_range(20.0..50.0);
+            let size = Vec2::new(side, side * rng.gen_range(0.7..1.3));
+            ObstacleRect::new(center + offset, size)
+        })
+        .collect()
+}
+
+fn corridor_wall(rng: &mut StdRng, arena_size: Vec2) -> ObstacleRect {
+    let length = rng.gen_range(200.0..450.0);
+    let thickness = rng.gen_range(18.0..28.0);
+    let size = if rng.gen_bool(0.5) {
+        Vec2::new(length, thickness)
+    } else {
+        Vec2::new(thickness, length)
+    };
+    ObstacleRect::new(random_point(rng, arena_size), size)
+}
+
+fn random_block(rng: &mut StdRng, arena_size: Vec2) -> ObstacleRect {
+    let size = Vec2::new(rng.gen_range(30.0..80.0), rng.gen_range(30.0..80.0));
+    ObstacleRect::new(random_point(rng, arena_size), size)
+}
+
+/// Inside the arena margin, clear of keep-out areas and not overlapping anything
+fn fits(
+    candidates: &[ObstacleRect],
+    existing: &[ObstacleRect],
+    arena_size: Vec2,
+    clear_areas: &[(Vec2, f32)],
+    config: &MapConfig,
+) -> bool {
+    let min = Vec2::splat(config.edge_margin);
+    let max = arena_size - config.edge_margin;
+
+    candidates.iter().enumerate().all(|(i, rect)| {
+        let half = rect.size / 2.0;
+        let inside = (rect.center - half).cmpge(min).all() && (rect.center + half).cmple(max).all();
+
+        inside
+            && clear_areas
+                .iter()
+                .all(|(center, radius)| rect.distance_to(*center) > *radius)
+            && existing
+                .iter()
+                .chain(&candidates[..i])
+                .all(|other| rect.gap_to(other) > 0.0)
+    })
+}
+
+/// Whether all free space forms one region a ship can fly through.
+///
+/// Obstacles are grown by half the passage width, so any gap narrower than
+/// `passage_width` counts as blocked.
+pub fn is_connected(obstacles: &[ObstacleRect], arena_size: Vec2, config: &MapConfig) -> bool {
+    let cell = config.cell_size.max(1.0);
+    let columns = (arena_size.x / cell).ceil() as usize;
+    let rows = (arena_size.y / cell).ceil() as usize;
+    let inflate = config.passage_width / 2.0;
+
+    let blocked: Vec<bool> = (0..rows * columns)
+        .map(|index| {
+            let point = Vec2::new(
+                (index % columns) as f32 + 0.5,
+                (index / columns) as f32 + 0.5,
+            ) * cell;
+            obstacles
+                .iter()
+                .any(|rect| rect.distance_to(point) < inflate)
+        })
+        .collect();
+
+    let Some(start) = blocked.iter().position(|blocked| !blocked) else {
+        return false;
+    };
+
+    // Flood fill from the first free cell
+    let mut visited = vec![false; blocked.len()];
+    let mut queue = VecDeque::from([start]);
+    visited[start] = true;
+    let mut reached = 1;
+
+    while let Some(index) = queue.pop_front() {
+        let (x, y) = (index % columns, index / columns);
+        let neighbors = [
+            (x > 0).then(|| index - 1),
+            (x + 1 < columns).then(|| index + 1),
+            (y > 0).then(|| index - columns),
+            (y + 1 < rows).then(|| index + columns),
+        ];
+        for next in neighbors.into_iter().flatten() {
+            if !blocked[next] && !visited[next] {
+                visited[next] = true;
+                reached += 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    reached == blocked.iter().filter(|blocked| !**blocked).count()
+}
+
+/// Player spawn points: the map file's, or spread around the arena
+pub fn player_spawns(config: &MapConfig, count: usize, arena_size: Vec2) -> Vec<Vec2> {
+    match &config.file {
+        Some(file) if !file.player_spawns.is_empty() => {
+            file.player_spawns.iter().copied().take(count).collect()
+        }
+        _ => spawn_points(count, arena_size.x, arena_size.y),
+    }
+}
+
+/// This match's arena: the map file's obstacles if one is loaded, otherwise a
+/// generated layout around the spawn points and territory centers
+pub fn build_match_map(
+    config: &MapConfig,
+    rng: &mut GameRng,
+    arena_size: Vec2,
+    player_count: usize,
+    territory_centers: &[Vec2],
+) -> ArenaMap {
+    if let Some(file) = &config.file {
+        return ArenaMap {
+            source: MapSource::File(file.name.clone()),
+            arena_size,
+            obstacles: file.obstacle_rects(),
+        };
+    }
+
+    let seed = config.seed.unwrap_or_else(|| rng.gen());
+    let layout = config
+        .layout
+        .unwrap_or(MapLayout::ALL[(seed % MapLayout::ALL.len() as u64) as usize]);
+
+    let clear_areas: Vec<(Vec2, f32)> = player_spawns(config, player_count, arena_size)
+        .into_iter()
+        .map(|point| (point, config.spawn_clearance))
+        .chain(
+            territory_centers
+                .iter()
+                .map(|center| (*center, config.territory_clearance)),
+        )
+        .collect();
+
+    generate_map(seed, layout, arena_size, &clear_areas, config)
+}
+
+/// Spawn the map's obstacles as static colliders replicated to every client
+pub fn spawn_map_obstacles(commands: &mut Commands, map: &ArenaMap) {
+    let collision_groups = GameCollisionGroups::wall();
+
+    for (i, rect) in map.obstacles.iter().enumerate() {
+        commands.spawn((
+            RigidBody::Fixed,
+            Collider::cuboid(rect.size.x / 2.0, rect.size.y / 2.0), // Rapier uses half-extents
+            Transform::from_translation(rect.center.extend(0.0)),
+            GlobalTransform::default(),
+            collision_groups,
+            ActiveEvents::COLLISION_EVENTS,
+            Name::new(format!("Obstacle {}", i + 1)),
+            Position(rect.center),
+            Obstacle {
+                id: i as u32,
+                width: rect.size.x,
+                height: rect.size.y,
+            },
+            Health::default(),
+            // Sent to every client: a long wall can be on screen while its center is
+            // far outside the view radius, and obstacles are few and never move
+            Replicate::default(),
+            SyncPosition,
+        ));
+    }
+}
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapConfig>();
+    }
+}
@@ -0,0 +1,404 @@
+//! Hand-made arena maps loaded from RON or JSON.
+//!
+//! A map file describes everything about an arena that used to be hard-coded:
+//! interior walls and obstacles, territories with their zones and patrol
+//! routes, the boid groups spawned in them and the player spawn points.
+//!
+//! ```ron
+//! (
+//!     name: "twin lanes",
+//!     arena: (width: 1600.0, height: 1200.0),
+//!     walls: [(from: (400.0, 300.0), to: (1200.0, 300.0), thickness: 20.0)],
+//!     obstacles: [(center: (800.0, 600.0), size: (60.0, 60.0))],
+//!     territories: [
+//!         (name: "north", center: (800.0, 180.0), radius: 200.0, zone: Middle,
+//!          patrol: [(600.0, 150.0), (1000.0, 150.0)]),
+//!     ],
+//!     groups: [
+//!         (territory: "north", size: 12,
+//!          archetype: Recon(detection_range: 400.0, flee_speed_bonus: 1.3)),
+//!     ],
+//!     player_spawns: [(200.0, 200.0), (1400.0, 1000.0)],
+//! )
+//! ```
+//!
+//! Files are checked with [`MapFile::validate`] before use; every problem is
+//! reported with the path of the entry that caused it.
+
+use bevy::prelude::*;
+use boid_wars_shared::{ArenaZone, Formation, GroupArchetype, TerritoryData};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::config::PhysicsConfig;
+use crate::groups::{self, BoidIdCounter, GroupIdCounter};
+use crate::map::ObstacleRect;
+use crate::rng::GameRng;
+
+/// Largest group a map may spawn
+pub const MAX_GROUP_SIZE: u32 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArenaSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Axis-aligned wall segment
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WallDef {
+    pub from: Vec2,
+    pub to: Vec2,
+    pub thickness: f32,
+}
+
+impl WallDef {
+    pub fn rect(&self) -> ObstacleRect {
+        let span = (self.to - self.from).abs();
+        ObstacleRect::new(
+            (self.from + self.to) / 2.0,
+            Vec2::new(span.x.max(self.thickness), span.y.max(self.thickness)),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ObstacleDef {
+    pub center: Vec2,
+    pub size: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TerritoryDef {
+    /// Referenced by group spawns
+    pub name: String,
+    pub center: Vec2,
+    pub radius: f32,
+    pub zone: ArenaZone,
+    pub patrol: Vec<Vec2>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupSpawnDef {
+    /// Name of the territory the group spawns in and patrols
+    pub territory: String,
+    pub archetype: GroupArchetype,
+    pub size: u32,
+    /// Starting formation; the archetype's default if omitted
+    #[serde(default)]
+    pub formation: Option<Formation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MapFile {
+    pub name: String,
+    pub arena: ArenaSize,
+    #[serde(default)]
+    pub walls: Vec<WallDef>,
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleDef>,
+    #[serde(default)]
+    pub territories: Vec<TerritoryDef>,
+    #[serde(default)]
+    pub groups: Vec<GroupSpawnDef>,
+    /// Empty to use the default spawn ring
+    #[serde(default)]
+    pub player_spawns: Vec<Vec2>,
+}
+
+/// One problem in a map file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Path of the offending entry, e.g. `groups[2].territory`
+    pub entry: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.entry, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum MapFileError {
+    Io(std::io::Error),
+    /// Unknown extension or malformed RON/JSON
+    Parse(String),
+    Invalid(Vec<ValidationError>),
+}
+
+impl fmt::Display for MapFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapFileError::Io(e) => write!(f, "{e}"),
+            MapFileError::Parse(message) => write!(f, "{message}"),
+            MapFileError::Invalid(errors) => {
+                write!(f, "{} problem(s)", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapFileError {}
+
+impl MapFile {
+    /// Parse a `.ron` or `.json` file
+    pub fn load(path: &Path) -> Result<Self, MapFileError> {
+        let text = std::fs::read_to_string(path).map_err(MapFileError::Io)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => Self::from_ron(&text),
+            Some("json") => Self::from_json(&text),
+            _ => Err(MapFileError::Parse(format!(
+                "{}: expected a .ron or .json map",
+                path.display()
+            ))),
+        }
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, MapFileError> {
+        ron::from_str(text).map_err(|e| MapFileError::Parse(e.to_string()))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, MapFileError> {
+        serde_json::from_str(text).map_err(|e| MapFileError::Parse(e.to_string()))
+    }
+
+    /// Load and validate against the server's arena and player limit
+    pub fn load_validated(
+        path: &Path,
+        arena_size: Vec2,
+        max_players: usize,
+    ) -> Result<Self, MapFileError> {
+        let map = Self::load(path)?;
+        map.validate(arena_size, max_players)
+            .map_err(MapFileError::Invalid)?;
+        Ok(map)
+    }
+
+    /// Walls and obstacles as rectangles
+    pub fn obstacle_rects(&self) -> Vec<ObstacleRect> {
+        self.walls
+            .iter()
+            .map(WallDef::rect)
+            .chain(
+                self.obstacles
+                    .iter()
+                    .map(|obstacle| ObstacleRect::new(obstacle.center, obstacle.size)),
+            )
+            .collect()
+    }
+
+    pub fn territory(&self, name: &str) -> Option<&TerritoryDef> {
+        self.territories
+            .iter()
+            .find(|territory| territory.name == name)
+    }
+
+    /// Check the map against the server's arena and player limit
+    pub fn validate(
+        &self,
+        arena_size: Vec2,
+        max_players: usize,
+    ) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let mut error =
+            |entry: String, message: String| errors.push(ValidationError { entry, message });
+
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+        let inside = |point: Vec2| {
+            point.is_finite()
+                && point.x >= 0.0
+                && point.y >= 0.0
+                && point.x <= arena_size.x
+                && point.y <= arena_size.y
+        };
+
+        if self.arena.width != arena_size.x || self.arena.height != arena_size.y {
+            error(
+                "arena".to_string(),
+                format!(
+                    "map is {}x{} but the server arena is {}x{} (BOID_WARS_GAME_WIDTH/HEIGHT)",
+                    self.arena.width, self.arena.height, arena_size.x, arena_size.y
+                ),
+            );
+        }
+
+        for (i, wall) in self.walls.iter().enumerate() {
+            let entry = format!("walls[{i}]");
+            if !positive(wall.thickness) {
+                error(entry.clone(), "thickness must be positive".to_string());
+            }
+            if wall.from == wall.to {
+                error(entry.clone(), "wall has zero length".to_string());
+            } else if wall.from.x != wall.to.x && wall.from.y != wall.to.y {
+                error(
+                    entry.clone(),
+                    "walls must be horizontal or vertical".to_string(),
+                );
+            }
+            if !inside(wall.from) || !inside(wall.to) {
+                error(entry, "wall leaves the arena".to_string());
+            }
+        }
+
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            let entry = format!("obstacles[{i}]");
+            if !positive(obstacle.size.x) || !positive(obstacle.size.y) {
+                error(entry.clone(), "size must be positive".to_string());
+            }
+            let half = obstacle.size / 2.0;
+            if !inside(obstacle.center - half) || !inside(obstacle.center + half) {
+                error(entry, "obstacle leaves the arena".to_string());
+            }
+        }
+
+        let mut names = HashSet::new();
+        for (i, territory) in self.territories.iter().enumerate() {
+            let entry = format!("territories[{}] '{}'", i, territory.name);
+            if !names.insert(territory.name.as_str()) {
+                error(entry.clone(), "duplicate territory name".to_string());
+            }
+            if !positive(territory.radius) {
+                error(entry.clone(), "radius must be positive".to_string());
+            }
+            if !inside(territory.center) {
+                error(entry.clone(), "center is outside the arena".to_string());
+            }
+            if territory.patrol.is_empty() {
+                error(entry.clone(), "patrol route is empty".to_string());
+            }
+            for (j, point) in territory.patrol.iter().enumerate() {
+                if !inside(*point) {
+                    error(
+                        format!("{entry}.patrol[{j}]"),
+                        "patrol point is outside the arena".to_string(),
+                    );
+                }
+            }
+        }
+
+        for (i, group) in self.groups.iter().enumerate() {
+            let entry = format!("groups[{i}]");
+            if self.territory(&group.territory).is_none() {
+                error(
+                    format!("{entry}.territory"),
+                    format!("no territory named '{}'", group.territory),
+                );
+            }
+            if group.size == 0 || group.size > MAX_GROUP_SIZE {
+                error(
+                    format!("{entry}.size"),
+                    format!("size must be between 1 and {MAX_GROUP_SIZE}"),
+                );
+            }
+            if let GroupArchetype::Defensive {
+                retreat_threshold, ..
+            } = group.archetype
+            {
+                if !(0.0..=1.0).contains(&retreat_threshold) {
+                    error(
+                        format!("{entry}.archetype"),
+                        "retreat_threshold must be between 0 and 1".to_string(),
+                    );
+                }
+            }
+        }
+
+        if !self.player_spawns.is_empty() && self.player_spawns.len() < max_players {
+            error(
+                "player_spawns".to_string(),
+                format!(
+                    "{} spawn points but up to {} players can join",
+                    self.player_spawns.len(),
+                    max_players
+                ),
+            );
+        }
+        let rects = self.obstacle_rects();
+        for (i, spawn) in self.player_spawns.iter().enumerate() {
+            let entry = format!("player_spawns[{i}]");
+            if !inside(*spawn) {
+                error(entry, "spawn point is outside the arena".to_string());
+                continue;
+            }
+            if let Some(j) = rects
+                .iter()
+                .position(|rect| rect.distance_to(*spawn) == 0.0)
+            {
+                let blocker = if j < self.walls.len() {
+                    format!("walls[{j}]")
+                } else {
+                    format!("obstacles[{}]", j - self.walls.len())
+                };
+                error(entry, format!("spawn point is inside {blocker}"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn territory_data(territory: &TerritoryDef) -> TerritoryData {
+        TerritoryData {
+            center: territory.center,
+            radius: territory.radius,
+            zone: territory.zone,
+            patrol_points: territory.patrol.clone(),
+            neighboring_territories: vec![],
+        }
+    }
+}
+
+/// Spawn the map's boid groups
+pub fn spawn_map_groups(
+    commands: &mut Commands,
+    map: &MapFile,
+    group_id_counter: &mut GroupIdCounter,
+    boid_id_counter: &mut BoidIdCounter,
+    physics_config: &PhysicsConfig,
+    rng: &mut GameRng,
+) {
+    for group in &map.groups {
+        // Validation guarantees the territory exists
+        let Some(territory) = map.territory(&group.territory) else {
+            continue;
+        };
+
+        groups::spawn_boid_group_in_formation(
+            commands,
+            group.archetype,
+            group
+                .formation
+                .clone()
+                .unwrap_or_else(|| Formation::default_for_archetype(&group.archetype)),
+            group.size,
+            MapFile::territory_data(territory),
+            group_id_counter,
+            boid_id_counter,
+            physics_config,
+            rng,
+        );
+    }
+
+    info!(
+        "Spawned {} boid groups from map '{}'",
+        map.groups.len(),
+        map.name
+    );
+}
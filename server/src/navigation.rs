@@ -0,0 +1,577 @@
+//! Obstacle-aware navigation for boid groups.
+//!
+//! The arena is rasterised into a [`NavGrid`] of walkable cells, with every obstacle
+//! inflated by a clearance so routes keep boids off the walls. Each group gets a goal
+//! from its behavior (next patrol waypoint, rally point, defended position or engaged
+//! player) and a [`FlowField`] toward it, shared between groups heading for the same
+//! cell. Flocking asks [`Navigation::heading`] which way to go: straight at the goal
+//! when nothing is in the way, otherwise down the flow field.
+//!
+//! The grid is rebuilt whenever obstacles are spawned or despawned, so new maps and
+//! round resets are picked up automatically.
+
+use crate::profiler::TickStage;
+use crate::spatial_grid::SpatialGridSet;
+use bevy::prelude::*;
+use boid_wars_shared::{
+    Boid, BoidGroup, ConfigSection, GroupBehavior, Obstacle, Player, Position, Validator,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Cost of a straight step between cells; diagonals cost 14 (≈ 10·√2)
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+/// Navigation grid settings, the `[navigation]` section of the settings file
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavigationConfig {
+    /// Side of one grid cell in world units
+    pub cell_size: f32,
+    /// Extra margin kept around every obstacle and the arena edge
+    pub clearance: f32,
+    /// Distance from a patrol waypoint at which the group moves on to the next one
+    pub waypoint_reach: f32,
+    /// Distance from a rally point or defended position at which routing stops
+    pub arrival_radius: f32,
+}
+
+impl Default for NavigationConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 40.0,
+            clearance: 20.0, // Roughly a boid's width
+            waypoint_reach: 120.0,
+            arrival_radius: 60.0,
+        }
+    }
+}
+
+impl ConfigSection for NavigationConfig {
+    const SECTION: &'static str = "navigation";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        check.positive("cell_size", self.cell_size);
+        check.non_negative("clearance", self.clearance);
+        check.non_negative("waypoint_reach", self.waypoint_reach);
+        check.non_negative("arrival_radius", self.arrival_radius);
+    }
+}
+
+/// Grid cell coordinates (column, row)
+pub type Cell = (usize, usize);
+
+/// Walkable and blocked cells covering the arena
+#[derive(Debug, Clone, Default)]
+pub struct NavGrid {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    blocked: Vec<bool>,
+}
+
+impl NavGrid {
+    /// An open grid over an arena of `arena` size
+    pub fn new(arena: Vec2, cell_size: f32) -> Self {
+        let columns = (arena.x / cell_size).ceil().max(1.0) as usize;
+        let rows = (arena.y / cell_size).ceil().max(1.0) as usize;
+        Self {
+            cell_size,
+            columns,
+            rows,
+            blocked: vec![false; columns * rows],
+        }
+    }
+
+    /// Grid for an arena with rectangular obstacles given as (center, size),
+    /// each grown by `clearance` on every side
+    pub fn build(
+        arena: Vec2,
+        cell_size: f32,
+        clearance: f32,
+        obstacles: impl IntoIterator<Item = (Vec2, Vec2)>,
+    ) -> Self {
+        let mut grid = Self::new(arena, cell_size);
+        for (center, size) in obstacles {
+            grid.block_rect(center, size / 2.0 + Vec2::splat(clearance));
+        }
+        grid.block_border(clearance);
+        grid
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    /// Block every cell whose center lies inside the rectangle
+    pub fn block_rect(&mut self, center: Vec2, half_extents: Vec2) {
+        let min = center - half_extents;
+        let max = center + half_extents;
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let point = self.center_of((column, row));
+                if point.cmpge(min).all() && point.cmple(max).all() {
+                    self.blocked[row * self.columns + column] = true;
+                }
+            }
+        }
+    }
+
+    /// Block cells whose centers are within `margin` of the arena edge
+    fn block_border(&mut self, margin: f32) {
+        let arena = Vec2::new(self.columns as f32, self.rows as f32) * self.cell_size;
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let point = self.center_of((column, row));
+                if point.cmplt(Vec2::splat(margin)).any()
+                    || point.cmpgt(arena - Vec2::splat(margin)).any()
+                {
+                    self.blocked[row * self.columns + column] = true;
+                }
+            }
+        }
+    }
+
+    /// Cell containing `position`, clamped to the grid
+    pub fn cell_of(&self, position: Vec2) -> Cell {
+        let column = (position.x / self.cell_size).floor().max(0.0) as usize;
+        let row = (position.y / self.cell_size).floor().max(0.0) as usize;
+        (
+            column.min(self.columns.saturating_sub(1)),
+            row.min(self.rows.saturating_sub(1)),
+        )
+    }
+
+    pub fn center_of(&self, (column, row): Cell) -> Vec2 {
+        (Vec2::new(column as f32, row as f32) + 0.5) * self.cell_size
+    }
+
+    /// Whether a cell is blocked; anything outside the grid counts as blocked
+    pub fn is_blocked(&self, (column, row): Cell) -> bool {
+        column >= self.columns || row >= self.rows || self.blocked[row * self.columns + column]
+    }
+
+    /// Whether a straight line between two points only crosses walkable cells
+    pub fn line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        // Walk every cell the segment touches (Amanatides & Woo)
+        let size = Vec2::new(self.columns as f32, self.rows as f32) * self.cell_size;
+        let from = from.clamp(Vec2::ZERO, size - 0.001);
+        let to = to.clamp(Vec2::ZERO, size - 0.001);
+        let direction = to - from;
+        let (mut column, mut row) = self.cell_of(from);
+        let end = self.cell_of(to);
+
+        // Distance along the segment, as a fraction of it, to the next column/row boundary
+        let boundary = |position: f32, cell: usize, direction: f32| {
+            if direction > 0.0 {
+                ((cell + 1) as f32 * self.cell_size - position) / direction
+            } else if direction < 0.0 {
+                (cell as f32 * self.cell_size - position) / direction
+            } else {
+                f32::INFINITY
+            }
+        };
+        let mut next_x = boundary(from.x, column, direction.x);
+        let mut next_y = boundary(from.y, row, direction.y);
+        let delta_x = self.cell_size / direction.x.abs();
+        let delta_y = self.cell_size / direction.y.abs();
+
+        loop {
+            if self.is_blocked((column, row)) {
+                return false;
+            }
+            if (column, row) == end || next_x.min(next_y) > 1.0 {
+                return true;
+            }
+            // Both endpoints are inside the grid, so stepping never leaves it
+            if next_x < next_y {
+                column = if direction.x > 0.0 {
+                    column + 1
+                } else {
+                    column.saturating_sub(1)
+                };
+                next_x += delta_x;
+            } else {
+                row = if direction.y > 0.0 {
+                    row + 1
+                } else {
+                    row.saturating_sub(1)
+                };
+                next_y += delta_y;
+            }
+        }
+    }
+
+    /// Walkable cell nearest to `cell`, searching outward ring by ring
+    pub fn nearest_walkable(&self, cell: Cell) -> Option<Cell> {
+        if !self.is_blocked(cell) {
+            return Some(cell);
+        }
+        let (column, row) = (cell.0 as isize, cell.1 as isize);
+        for radius in 1..self.columns.max(self.rows) as isize {
+            let mut best: Option<(isize, Cell)> = None;
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    if dx.abs() != radius && dy.abs() != radius {
+                        continue;
+                    }
+                    let Some(candidate) = self.offset((column, row), dx, dy) else {
+                        continue;
+                    };
+                    let distance = dx * dx + dy * dy;
+                    if !self.is_blocked(candidate) && best.is_none_or(|(d, _)| distance < d) {
+                        best = Some((distance, candidate));
+                    }
+                }
+            }
+            if let Some((_, found)) = best {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    fn offset(&self, (column, row): (isize, isize), dx: isize, dy: isize) -> Option<Cell> {
+        let (column, row) = (column + dx, row + dy);
+        (column >= 0 && row >= 0 && (column as usize) < self.columns && (row as usize) < self.rows)
+            .then_some((column as usize, row as usize))
+    }
+
+    /// Walkable neighbours of a cell and the cost of stepping to them.
+    /// Diagonals are only allowed when both adjacent sides are open, so routes
+    /// never cut an obstacle's corner.
+    fn neighbors(&self, cell: Cell) -> impl Iterator<Item = (Cell, u32)> + '_ {
+        let origin = (cell.0 as isize, cell.1 as isize);
+        [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ]
+        .into_iter()
+        .filter_map(move |(dx, dy)| {
+            let next = self.offset(origin, dx, dy)?;
+            if self.is_blocked(next) {
+                return None;
+            }
+            if dx != 0 && dy != 0 {
+                let side_x = self.offset(origin, dx, 0)?;
+                let side_y = self.offset(origin, 0, dy)?;
+                if self.is_blocked(side_x) || self.is_blocked(side_y) {
+                    return None;
+                }
+                return Some((next, DIAGONAL_COST));
+            }
+            Some((next, STRAIGHT_COST))
+        })
+    }
+
+    /// Flow field toward `goal`, or `None` if the goal has no walkable cell near it
+    pub fn flow_field(&self, goal: Vec2) -> Option<FlowField> {
+        let goal_cell = self.nearest_walkable(self.cell_of(goal))?;
+        let index = |(column, row): Cell| row * self.columns + column;
+
+        // Dijkstra outward from the goal
+        let mut cost = vec![u32::MAX; self.blocked.len()];
+        let mut open = BinaryHeap::new();
+        cost[index(goal_cell)] = 0;
+        open.push(Reverse((0, goal_cell)));
+        while let Some(Reverse((current_cost, cell))) = open.pop() {
+            if current_cost > cost[index(cell)] {
+                continue;
+            }
+            for (next, step) in self.neighbors(cell) {
+                let next_cost = current_cost + step;
+                if next_cost < cost[index(next)] {
+                    cost[index(next)] = next_cost;
+                    open.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+
+        Some(FlowField { goal_cell, cost })
+    }
+
+    /// Waypoints from `from` to `to` along the flow field, one per cell,
+    /// or `None` if `to` cannot be reached
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        let field = self.flow_field(to)?;
+        let mut cell = self.nearest_walkable(self.cell_of(from))?;
+        field.cost_at(self, cell)?;
+
+        let mut path = vec![self.center_of(cell)];
+        while cell != field.goal_cell {
+            cell = field.next_cell(self, cell)?;
+            path.push(self.center_of(cell));
+        }
+        Some(path)
+    }
+}
+
+/// Step costs from every cell to one goal cell
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    goal_cell: Cell,
+    cost: Vec<u32>,
+}
+
+impl FlowField {
+    pub fn goal_cell(&self) -> Cell {
+        self.goal_cell
+    }
+
+    /// Cost to reach the goal from `cell`, `None` if it can't
+    pub fn cost_at(&self, grid: &NavGrid, (column, row): Cell) -> Option<u32> {
+        self.cost
+            .get(row * grid.columns + column)
+            .copied()
+            .filter(|&cost| cost != u32::MAX)
+    }
+
+    /// Cheapest neighbour of `cell`, one step closer to the goal
+    fn next_cell(&self, grid: &NavGrid, cell: Cell) -> Option<Cell> {
+        let here = self.cost_at(grid, cell).unwrap_or(u32::MAX);
+        grid.neighbors(cell)
+            .filter_map(|(next, _)| Some((self.cost_at(grid, next)?, next)))
+            .filter(|&(cost, _)| cost < here)
+            .min_by_key(|&(cost, _)| cost)
+            .map(|(_, next)| next)
+    }
+
+    /// Unit direction to move in from `position`, `None` once in the goal cell or
+    /// when the goal can't be reached from here
+    pub fn direction(&self, grid: &NavGrid, position: Vec2) -> Option<Vec2> {
+        let mut cell = grid.cell_of(position);
+        if grid.is_blocked(cell) {
+            // Pushed inside an obstacle's clearance: head for the nearest open cell first
+            cell = grid.nearest_walkable(cell)?;
+            if self.cost_at(grid, cell).is_some() {
+                return Some((grid.center_of(cell) - position).normalize_or_zero());
+            }
+            return None;
+        }
+        if cell == self.goal_cell {
+            return None;
+        }
+        let next = self.next_cell(grid, cell)?;
+        Some((grid.center_of(next) - position).normalize_or_zero())
+    }
+}
+
+/// Where a group is trying to get to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavGoal {
+    pub point: Vec2,
+    /// Routing stops within this distance of `point`
+    pub arrival_radius: f32,
+}
+
+/// The arena's nav grid plus every group's goal and the flow fields toward them
+#[derive(Resource, Debug, Default)]
+pub struct Navigation {
+    pub grid: NavGrid,
+    goals: HashMap<Entity, NavGoal>,
+    fields: HashMap<Cell, FlowField>,
+}
+
+impl Navigation {
+    pub fn new(grid: NavGrid) -> Self {
+        Self {
+            grid,
+            goals: HashMap::new(),
+            fields: HashMap::new(),
+        }
+    }
+
+    /// Swap in a rebuilt grid; every cached flow field is stale after this
+    pub fn set_grid(&mut self, grid: NavGrid) {
+        self.grid = grid;
+        self.fields.clear();
+    }
+
+    pub fn goal(&self, group: Entity) -> Option<NavGoal> {
+        self.goals.get(&group).copied()
+    }
+
+    /// Give `group` a new goal, or none to stop routing it
+    pub fn set_goal(&mut self, group: Entity, goal: Option<NavGoal>) {
+        match goal {
+            Some(goal) => {
+                let cell = self.grid.cell_of(goal.point);
+                if !self.fields.contains_key(&cell) {
+                    if let Some(field) = self.grid.flow_field(goal.point) {
+                        self.fields.insert(cell, field);
+                    }
+                }
+                self.goals.insert(group, goal);
+            }
+            None => {
+                self.goals.remove(&group);
+            }
+        }
+    }
+
+    /// Forget goals for groups that no longer exist and fields nobody is following
+    pub fn retain_groups(&mut self, mut alive: impl FnMut(Entity) -> bool) {
+        self.goals.retain(|&group, _| alive(group));
+        let grid = &self.grid;
+        let in_use: Vec<Cell> = self
+            .goals
+            .values()
+            .map(|goal| grid.cell_of(goal.point))
+            .collect();
+        self.fields.retain(|cell, _| in_use.contains(cell));
+    }
+
+    /// Which way a member of `group` at `position` should head to reach the group's
+    /// goal: straight when the way is clear, around obstacles otherwise. `None` when
+    /// the group has no goal, has arrived, or can't get there.
+    pub fn heading(&self, group: Entity, position: Vec2) -> Option<Vec2> {
+        let goal = self.goals.get(&group)?;
+        if position.distance(goal.point) <= goal.arrival_radius {
+            return None;
+        }
+        if self.grid.line_of_sight(position, goal.point) {
+            return Some((goal.point - position).normalize_or_zero());
+        }
+        self.fields
+            .get(&self.grid.cell_of(goal.point))?
+            .direction(&self.grid, position)
+    }
+}
+
+/// Rebuild the nav grid when obstacles appear or disappear
+#[allow(clippy::type_complexity)]
+fn rebuild_nav_grid(
+    mut navigation: ResMut<Navigation>,
+    config: Res<NavigationConfig>,
+    added: Query<(), Added<Obstacle>>,
+    mut removed: RemovedComponents<Obstacle>,
+    obstacles: Query<(&Position, &Obstacle), Without<Boid>>,
+    mut built: Local<bool>,
+) {
+    let removed_any = removed.read().count() > 0;
+    if *built && added.is_empty() && !removed_any && !config.is_changed() {
+        return;
+    }
+    *built = true;
+
+    let game_config = &*boid_wars_shared::GAME_CONFIG;
+    let grid = NavGrid::build(
+        Vec2::new(game_config.game_width, game_config.game_height),
+        config.cell_size,
+        config.clearance,
+        obstacles
+            .iter()
+            .map(|(position, obstacle)| (position.0, Vec2::new(obstacle.width, obstacle.height))),
+    );
+    let (columns, rows) = grid.dimensions();
+    debug!(
+        "Rebuilt nav grid: {}x{} cells from {} obstacles",
+        columns,
+        rows,
+        obstacles.iter().count()
+    );
+    navigation.set_grid(grid);
+
+    // Recompute fields for the goals groups already have
+    let goals: Vec<(Entity, NavGoal)> = navigation.goals.drain().collect();
+    for (group, goal) in goals {
+        navigation.set_goal(group, Some(goal));
+    }
+}
+
+/// Advance patrol waypoints and point every group's route at its current goal
+fn group_navigation_system(
+    mut navigation: ResMut<Navigation>,
+    config: Res<NavigationConfig>,
+    mut groups: Query<(Entity, &mut BoidGroup, &Position), Without<Boid>>,
+    players: Query<(&Player, &Position), Without<Boid>>,
+) {
+    for (group_entity, mut group, center) in groups.iter_mut() {
+        // Move on to the next waypoint once the group has reached this one
+        if let GroupBehavior::Patrolling {
+            route,
+            current_waypoint,
+        } = &group.behavior_state
+        {
+            if !route.is_empty() {
+                let current = *current_waypoint % route.len();
+                let next = if center.0.distance(route[current]) <= config.waypoint_reach {
+                    (current + 1) % route.len()
+                } else {
+                    current
+                };
+                // Only write when it moves, so the group isn't flagged changed every tick
+                if next != *current_waypoint {
+                    if let GroupBehavior::Patrolling {
+                        current_waypoint, ..
+                    } = &mut group.behavior_state
+                    {
+                        *current_waypoint = next;
+                    }
+                }
+            }
+        }
+
+        let goal = match &group.behavior_state {
+            GroupBehavior::Patrolling {
+                route,
+                current_waypoint,
+            } => route.get(*current_waypoint).map(|&point| NavGoal {
+                point,
+                arrival_radius: 0.0,
+            }),
+            GroupBehavior::Retreating { rally_point, .. } => Some(NavGoal {
+                point: *rally_point,
+                arrival_radius: config.arrival_radius,
+            }),
+            GroupBehavior::Defending { position, radius } => Some(NavGoal {
+                point: *position,
+                arrival_radius: radius.max(config.arrival_radius),
+            }),
+            GroupBehavior::Engaging { primary_target, .. } => players
+                .iter()
+                .find(|(player, _)| player.id as u32 == *primary_target)
+                .map(|(_, position)| NavGoal {
+                    point: position.0,
+                    // Archetype pursuit takes over once there is a clear line
+                    arrival_radius: 0.0,
+                }),
+        };
+
+        // Flow fields are cached per goal cell, so a target moving within a cell is free
+        navigation.set_goal(group_entity, goal);
+    }
+
+    navigation.retain_groups(|group| groups.contains(group));
+}
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavigationConfig>()
+            .init_resource::<Navigation>();
+
+        app.add_systems(
+            FixedUpdate,
+            (rebuild_nav_grid, group_navigation_system)
+                .chain()
+                .in_set(TickStage::GroupAi)
+                .before(SpatialGridSet::Read),
+        );
+
+        info!("Navigation plugin initialized");
+    }
+}
@@ -1,15 +1,21 @@
+use crate::combat::{CombatConfig, Faction, RamCooldowns};
 use crate::config::{MonitoringConfig, PhysicsConfig};
+use crate::interest::{InterestConfig, InterestManaged, InterestManager, ProjectileAudience};
 use crate::pool::{BoundedPool, PooledEntity};
 use crate::position_sync::SyncPosition;
-use crate::spatial_grid::SpatialGridSet;
+use crate::rng::GameRng;
+use crate::spatial_grid::{LayerMask, SpatialGridSet};
+use crate::targeting::{AccuracyProfile, TargetingConfig};
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use boid_wars_shared;
+use boid_wars_shared::MovementRules;
 use lightyear::prelude::server::*;
-use lightyear::prelude::{MessageSend, NetworkTarget};
+use lightyear::prelude::{MessageSend, NetworkRelevanceMode, NetworkTarget};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 // Static names to avoid runtime allocations
 const POOLED_PROJECTILE_NAME: &str = "Pooled Projectile";
@@ -32,6 +38,8 @@ pub struct PhysicsBuffers {
     pub player_collision_buffer: Vec<(Entity, Entity, f32, Option<Entity>)>,
     /// Buffer for boid collision data
     pub boid_collision_buffer: Vec<(Entity, Entity, f32, Option<Entity>)>,
+    /// Buffer for ship-boid contacts as (player, boid)
+    pub ram_buffer: Vec<(Entity, Entity)>,
 }
 
 impl Default for PhysicsBuffers {
@@ -41,6 +49,7 @@ impl Default for PhysicsBuffers {
             alert_buffer: Vec::with_capacity(128),
             player_collision_buffer: Vec::with_capacity(64),
             boid_collision_buffer: Vec::with_capacity(256),
+            ram_buffer: Vec::with_capacity(64),
         }
     }
 }
@@ -49,6 +58,23 @@ impl Default for PhysicsBuffers {
 #[derive(Component)]
 pub struct Despawning;
 
+/// Sent when a player's projectile damages a player or boid
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageDealtEvent {
+    pub attacker_id: u64,
+    pub target: Entity,
+    pub amount: f32,
+}
+
+/// Sent when a player is eliminated
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerDeathEvent {
+    pub entity: Entity,
+    pub player_id: u64,
+    /// Player credited with the kill, `None` for environmental deaths
+    pub killer_id: Option<u64>,
+}
+
 /// Component to track pooled projectiles
 #[derive(Component)]
 pub struct PooledProjectile(PooledEntity);
@@ -56,12 +82,14 @@ pub struct PooledProjectile(PooledEntity);
 /// Resource to track player aggression for boid AI
 #[derive(Resource)]
 pub struct PlayerAggression {
-    /// Maps player entity to the time they last attacked
-    pub aggressive_players: HashMap<Entity, Instant>,
+    /// Maps player entity to the game time they last attacked
+    pub aggressive_players: HashMap<Entity, Duration>,
     /// How long a player remains "aggressive" after attacking
     pub aggression_duration: Duration,
+    /// Game time as of the latest cleanup pass, so replays expire entries at the same ticks
+    now: Duration,
     /// Last time cleanup was performed
-    last_cleanup: Instant,
+    last_cleanup: Duration,
     /// How often to cleanup expired entries (in seconds)
     cleanup_interval: f32,
 }
@@ -71,7 +99,8 @@ impl Default for PlayerAggression {
         Self {
             aggressive_players: HashMap::new(),
             aggression_duration: Duration::from_secs(5), // Players stay aggressive for 5 seconds
-            last_cleanup: Instant::now(),
+            now: Duration::ZERO,
+            last_cleanup: Duration::ZERO,
             cleanup_interval: 1.0,
         }
     }
@@ -98,30 +127,30 @@ impl PlayerAggression {
             }
         }
 
-        self.aggressive_players.insert(player, Instant::now());
+        self.aggressive_players.insert(player, self.now);
     }
 
     /// Check if a player is currently aggressive
     pub fn is_aggressive(&self, player: Entity) -> bool {
         if let Some(&last_attack) = self.aggressive_players.get(&player) {
-            last_attack.elapsed() < self.aggression_duration
+            self.now.saturating_sub(last_attack) < self.aggression_duration
         } else {
             false
         }
     }
 
-    /// Clean up expired aggression entries (only runs periodically)
-    pub fn cleanup_expired(&mut self) {
-        let now = Instant::now();
+    /// Advance to game time `now` and clean up expired entries (only runs periodically)
+    pub fn cleanup_expired(&mut self, now: Duration) {
+        self.now = now;
 
         // Only cleanup if enough time has passed
-        if now.duration_since(self.last_cleanup).as_secs_f32() < self.cleanup_interval {
+        if now.saturating_sub(self.last_cleanup).as_secs_f32() < self.cleanup_interval {
             return;
         }
 
         self.last_cleanup = now;
         self.aggressive_players.retain(|_, &mut last_attack| {
-            now.duration_since(last_attack) < self.aggression_duration
+            now.saturating_sub(last_attack) < self.aggression_duration
         });
     }
 }
@@ -135,8 +164,10 @@ pub struct BoidAggression {
     pub aggression_duration: Duration,
     /// Radius within which boids alert their neighbors
     pub alert_radius: f32,
+    /// Game time as of the latest cleanup pass
+    now: Duration,
     /// Last time cleanup was performed
-    last_cleanup: Instant,
+    last_cleanup: Duration,
     /// How often to cleanup expired entries (in seconds)
     cleanup_interval: f32,
 }
@@ -146,8 +177,8 @@ pub struct BoidAggression {
 pub struct BoidAggressionData {
     /// The player entity that attacked this boid
     pub attacker: Entity,
-    /// When the attack occurred
-    pub attack_time: Instant,
+    /// Game time of the attack
+    pub attack_time: Duration,
     /// Whether this boid has alerted its neighbors
     pub alert_sent: bool,
 }
@@ -159,7 +190,8 @@ impl FromWorld for BoidAggression {
             boid_aggression: HashMap::new(),
             aggression_duration: config.boid_aggression_memory_duration,
             alert_radius: config.boid_aggression_alert_radius,
-            last_cleanup: Instant::now(),
+            now: Duration::ZERO,
+            last_cleanup: Duration::ZERO,
             cleanup_interval: 1.0, // Cleanup every second instead of every frame
         }
     }
@@ -190,7 +222,7 @@ impl BoidAggression {
             boid,
             BoidAggressionData {
                 attacker,
-                attack_time: Instant::now(),
+                attack_time: self.now,
                 alert_sent: false,
             },
         );
@@ -204,7 +236,7 @@ impl BoidAggression {
     /// Check if a boid is currently aggressive (has recent attacker)
     pub fn is_aggressive(&self, boid: Entity) -> bool {
         if let Some(data) = self.boid_aggression.get(&boid) {
-            data.attack_time.elapsed() < self.aggression_duration
+            self.now.saturating_sub(data.attack_time) < self.aggression_duration
         } else {
             false
         }
@@ -226,18 +258,18 @@ impl BoidAggression {
         }
     }
 
-    /// Clean up expired aggression entries (only runs periodically)
-    pub fn cleanup_expired(&mut self) {
-        let now = Instant::now();
+    /// Advance to game time `now` and clean up expired entries (only runs periodically)
+    pub fn cleanup_expired(&mut self, now: Duration) {
+        self.now = now;
 
         // Only cleanup if enough time has passed
-        if now.duration_since(self.last_cleanup).as_secs_f32() < self.cleanup_interval {
+        if now.saturating_sub(self.last_cleanup).as_secs_f32() < self.cleanup_interval {
             return;
         }
 
         self.last_cleanup = now;
         self.boid_aggression
-            .retain(|_, data| now.duration_since(data.attack_time) < self.aggression_duration);
+            .retain(|_, data| now.saturating_sub(data.attack_time) < self.aggression_duration);
     }
 }
 
@@ -285,8 +317,11 @@ impl Default for PhysicsPlugin {
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        // Initialize configuration
-        let physics_config = PhysicsConfig::default();
+        // Initialize configuration, keeping any layered settings inserted before the plugin
+        if !app.world().contains_resource::<PhysicsConfig>() {
+            app.insert_resource(PhysicsConfig::default());
+        }
+        let physics_config = app.world().resource::<PhysicsConfig>();
         let pool_size = physics_config.projectile_pool_size;
         let collider_radius = physics_config.projectile_collider_radius;
 
@@ -302,13 +337,22 @@ impl Plugin for PhysicsPlugin {
 
         app
             // Add configuration resources
-            .insert_resource(physics_config)
             .init_resource::<MonitoringConfig>()
             .init_resource::<ArenaConfig>()
             .init_resource::<PlayerAggression>()
             .init_resource::<BoidAggression>()
             .init_resource::<PhysicsBuffers>()
+            .init_resource::<CombatConfig>()
+            .init_resource::<RamCooldowns>()
+            .init_resource::<TargetingConfig>()
             .init_resource::<ProjectileIdGenerator>()
+            .init_resource::<GameRng>()
+            .init_resource::<MovementRules>()
+            // Shooting sends projectile events to the clients near the muzzle
+            .init_resource::<InterestManager>()
+            .init_resource::<InterestConfig>()
+            .add_event::<DamageDealtEvent>()
+            .add_event::<PlayerDeathEvent>()
             .insert_resource(ProjectilePool::new(
                 ProjectileTemplate { collider_radius },
                 pool_size,
@@ -347,6 +391,7 @@ impl Plugin for PhysicsPlugin {
             .add_systems(
                 FixedUpdate,
                 (
+                    update_movement_rules.before(PhysicsSet::Input),
                     player_input_system.in_set(PhysicsSet::Input),
                     ai_player_system.in_set(PhysicsSet::AI),
                     swarm_communication_system
@@ -397,11 +442,13 @@ impl FromWorld for ArenaConfig {
 /// Collision groups for different entity types
 ///
 /// ## Group Allocation:
-/// - `GROUP_1`: Players - can be hit by all projectiles, collide with walls and other players
+/// - `GROUP_1`: Players - can be hit by all projectiles, collide with walls, other players and boids
 /// - `GROUP_2`: Player projectiles - hit players, boids, and walls
 /// - `GROUP_3`: Walls - block all entities and projectiles
-/// - `GROUP_4`: Boids - can be hit by player projectiles, collide with walls and other boids
-/// - `GROUP_5`: Boid projectiles - only hit players and walls (not other boids)
+/// - `GROUP_4`: Boids - can be hit by all projectiles, collide with walls, players and other boids
+/// - `GROUP_5`: Boid projectiles - hit players, boids, and walls
+///
+/// Projectiles report every overlap; whether a hit does damage is up to [`CombatConfig`].
 /// - `GROUP_6-32`: Reserved for future use (power-ups, obstacles, etc.)
 pub struct GameCollisionGroups {
     pub players: Group,
@@ -428,7 +475,11 @@ impl GameCollisionGroups {
         let groups = Self::default();
         bevy_rapier2d::geometry::CollisionGroups::new(
             groups.players,
-            groups.players | groups.projectiles | groups.walls | groups.boid_projectiles, // Players collide with both types of projectiles
+            groups.players
+                | groups.projectiles
+                | groups.walls
+                | groups.boids
+                | groups.boid_projectiles, // Players collide with both types of projectiles and ram boids
         )
     }
 
@@ -452,7 +503,11 @@ impl GameCollisionGroups {
         let groups = Self::default();
         bevy_rapier2d::geometry::CollisionGroups::new(
             groups.boids,
-            groups.projectiles | groups.walls | groups.boids, // Boids only collide with player projectiles, not boid projectiles
+            groups.players
+                | groups.projectiles
+                | groups.walls
+                | groups.boids
+                | groups.boid_projectiles, // Boid projectiles pass through unless friendly fire is on
         )
     }
 
@@ -460,7 +515,7 @@ impl GameCollisionGroups {
         let groups = Self::default();
         bevy_rapier2d::geometry::CollisionGroups::new(
             groups.boid_projectiles,
-            groups.players | groups.walls, // Boid projectiles only hit players and walls, not other boids
+            groups.players | groups.walls | groups.boids, // Hits on boids are filtered by CombatConfig
         )
     }
 }
@@ -548,11 +603,22 @@ impl Default for Ship {
     }
 }
 
+/// Admin-granted invulnerability: projectiles and the zone deal this ship no damage
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct GodMode;
+
+/// The four arena walls, spawned once at startup and kept across rematches
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ArenaWall;
+
 /// Projectile component
 #[derive(Component, Clone, Debug)]
 pub struct Projectile {
     pub damage: f32,
     pub owner: Option<Entity>,
+    /// Side the shooter fought for when firing; shots keep it after their shooter dies.
+    /// `None` only on pooled projectiles that haven't been fired.
+    pub owner_faction: Option<Faction>,
     pub projectile_type: ProjectileType,
     pub lifetime: Timer,
     pub speed: f32,
@@ -569,6 +635,7 @@ pub struct ProjectileIdGenerator {
 }
 
 impl ProjectileIdGenerator {
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> u32 {
         let id = self.next_id;
         self.next_id = self.next_id.wrapping_add(1);
@@ -653,6 +720,7 @@ fn setup_projectile_pool(
                 Projectile {
                     damage: 0.0,
                     owner: None, // No owner for pooled projectiles
+                    owner_faction: None,
                     projectile_type: ProjectileType::Basic,
                     lifetime: timer,
                     speed: 0.0,
@@ -699,6 +767,7 @@ fn setup_boid_projectile_pool(
                 Projectile {
                     damage: 5.0, // Boid projectiles deal 5 damage
                     owner: None, // No owner for pooled projectiles
+                    owner_faction: None,
                     projectile_type: ProjectileType::Basic,
                     lifetime: timer,
                     speed: 400.0, // Slower than player projectiles
@@ -745,6 +814,7 @@ fn setup_arena(mut commands: Commands, arena_config: Res<ArenaConfig>) {
         },
         lightyear::prelude::server::Replicate::default(),
         SyncPosition,
+        ArenaWall,
     ));
     wall_id += 1;
 
@@ -772,6 +842,7 @@ fn setup_arena(mut commands: Commands, arena_config: Res<ArenaConfig>) {
         },
         lightyear::prelude::server::Replicate::default(),
         SyncPosition,
+        ArenaWall,
     ));
     wall_id += 1;
 
@@ -799,6 +870,7 @@ fn setup_arena(mut commands: Commands, arena_config: Res<ArenaConfig>) {
         },
         lightyear::prelude::server::Replicate::default(),
         SyncPosition,
+        ArenaWall,
     ));
     wall_id += 1;
 
@@ -826,6 +898,7 @@ fn setup_arena(mut commands: Commands, arena_config: Res<ArenaConfig>) {
         },
         lightyear::prelude::server::Replicate::default(),
         SyncPosition,
+        ArenaWall,
     ));
 }
 
@@ -836,6 +909,7 @@ fn ai_player_system(
     time: Res<Time>,
     arena_config: Res<ArenaConfig>,
     physics_config: Res<PhysicsConfig>,
+    mut rng: ResMut<GameRng>,
 ) {
     for (mut input, mut ai, transform) in ai_players.iter_mut() {
         ai.behavior_timer += time.delta_secs();
@@ -869,9 +943,8 @@ fn ai_player_system(
                 if ai.behavior_timer > 3.0 {
                     ai.behavior_timer = 0.0;
                     ai.target_position = Vec2::new(
-                        rand::random::<f32>() * arena_config.width * 0.8 + arena_config.width * 0.1,
-                        rand::random::<f32>() * arena_config.height * 0.8
-                            + arena_config.height * 0.1,
+                        rng.gen::<f32>() * arena_config.width * 0.8 + arena_config.width * 0.1,
+                        rng.gen::<f32>() * arena_config.height * 0.8 + arena_config.height * 0.1,
                     );
                 }
 
@@ -881,7 +954,7 @@ fn ai_player_system(
                 input.thrust = 1.0;
 
                 // Shoot occasionally
-                input.shooting = rand::random::<f32>() < 0.05;
+                input.shooting = rng.gen::<f32>() < 0.05;
             }
 
             AIType::Shooter => {
@@ -935,49 +1008,56 @@ fn ai_player_system(
     }
 }
 
+/// Rebuild the movement rules clients predict with when the physics tuning changes
+fn update_movement_rules(config: Res<PhysicsConfig>, mut rules: ResMut<MovementRules>) {
+    if !config.is_changed() {
+        return;
+    }
+    rules.set_if_neq(MovementRules::new(
+        &boid_wars_shared::GAME_CONFIG,
+        config.player_damping_factor,
+        config.player_collider_size,
+    ));
+}
+
 /// System to process player input and set velocity directly
+#[allow(clippy::type_complexity)]
 fn player_input_system(
     mut player_query: Query<(
         &mut PlayerInput,
         &Player,
         &mut bevy_rapier2d::dynamics::Velocity,
         &Transform,
+        Option<&mut boid_wars_shared::InputAck>,
     )>,
+    rules: Res<MovementRules>,
     time: Res<Time>,
     mut debug_timer: Local<f32>,
 ) {
     *debug_timer += time.delta_secs();
 
-    for (input, player, mut velocity, transform) in player_query.iter_mut() {
+    for (input, player, mut velocity, transform, ack) in player_query.iter_mut() {
         // Store old velocity for comparison
         let _old_velocity = velocity.linvel;
 
-        // Set velocity directly like the old network system did
-        if input.thrust > 0.0 {
-            let movement_direction = input.movement.normalize_or_zero();
-            let target_speed = 200.0; // pixels/second - similar to old system
+        // Set velocity directly; the client predicts with the same shared rules
+        velocity.linvel = rules.input_velocity(input.movement, input.thrust);
 
-            // Set velocity directly
-            velocity.linvel = movement_direction * target_speed;
-        } else {
-            // Stop when no input
-            velocity.linvel = Vec2::ZERO;
+        // Tell the client how much of its current input is already simulated.
+        // Only moving inputs matter for replay, so idle ships don't re-replicate.
+        if let Some(mut ack) = ack {
+            if input.thrust > 0.0 {
+                ack.applied_secs += time.delta_secs();
+            }
         }
 
         // Handle rotation
         if input.aim_direction.length() > 0.1 {
-            let target_angle = input.aim_direction.y.atan2(input.aim_direction.x) - std::f32::consts::FRAC_PI_2;
+            let target_angle = boid_wars_shared::aim_angle(input.aim_direction);
             let current_angle = transform.rotation.to_euler(EulerRot::ZYX).0;
             
             // Calculate shortest angular distance (fixes spinning bug)
-            let mut angle_diff = target_angle - current_angle;
-            // Normalize to [-π, π] using the shortest path
-            while angle_diff > std::f32::consts::PI {
-                angle_diff -= 2.0 * std::f32::consts::PI;
-            }
-            while angle_diff < -std::f32::consts::PI {
-                angle_diff += 2.0 * std::f32::consts::PI;
-            }
+            let angle_diff = boid_wars_shared::shortest_angle_delta(current_angle, target_angle);
 
             velocity.angvel = angle_diff * player.turn_rate;
             
@@ -998,12 +1078,12 @@ fn player_input_system(
 fn player_movement_system(
     mut player_query: Query<(&Player, &Ship, &mut Velocity, &Transform), With<Player>>,
     _time: Res<Time>,
-    config: Res<PhysicsConfig>,
+    rules: Res<MovementRules>,
 ) {
     for (_player, ship, mut velocity, _transform) in player_query.iter_mut() {
-        // Apply damping for momentum feel
-        velocity.linvel *= config.player_damping_factor;
-        velocity.angvel *= config.player_damping_factor;
+        // Apply damping for momentum feel, the same factor clients predict with
+        velocity.linvel *= rules.damping_factor;
+        velocity.angvel *= rules.damping_factor;
 
         // Clamp max speed
         if velocity.linvel.length() > ship.max_speed {
@@ -1013,6 +1093,7 @@ fn player_movement_system(
 }
 
 /// System to handle shooting
+#[allow(clippy::too_many_arguments)]
 fn shooting_system(
     mut commands: Commands,
     mut player_query: Query<(Entity, &PlayerInput, &mut Player, &WeaponStats, &Transform)>,
@@ -1020,6 +1101,8 @@ fn shooting_system(
     mut player_aggression: ResMut<PlayerAggression>,
     mut id_generator: ResMut<ProjectileIdGenerator>,
     mut connection_manager: ResMut<ConnectionManager>,
+    interest: Res<InterestManager>,
+    interest_config: Res<InterestConfig>,
     time: Res<Time>,
     config: Res<PhysicsConfig>,
 ) {
@@ -1044,7 +1127,7 @@ fn shooting_system(
             let network_id = id_generator.next();
 
             // Try to get a projectile from the pool
-            let _projectile_entity = if let Some(pooled_handle) = pool.acquire() {
+            let projectile_entity = if let Some(pooled_handle) = pool.acquire() {
                 let _status = pool.status();
 
                 // Update existing projectile components
@@ -1053,6 +1136,7 @@ fn shooting_system(
                     Projectile {
                         damage: weapon.damage,
                         owner: Some(entity), // Use actual player entity
+                        owner_faction: Some(Faction::Player(player.player_id)),
                         projectile_type: ProjectileType::Basic,
                         lifetime: {
                             let mut timer = Timer::new(weapon.projectile_lifetime, TimerMode::Once);
@@ -1085,6 +1169,7 @@ fn shooting_system(
                         Projectile {
                             damage: weapon.damage,
                             owner: Some(entity), // Use actual player entity
+                            owner_faction: Some(Faction::Player(player.player_id)),
                             projectile_type: ProjectileType::Basic,
                             lifetime: Timer::new(weapon.projectile_lifetime, TimerMode::Once),
                             speed: weapon.projectile_speed,
@@ -1107,7 +1192,7 @@ fn shooting_system(
                     .id()
             };
 
-            // Send spawn event to all clients
+            // Send spawn event to clients near the muzzle
             let spawn_event = boid_wars_shared::ProjectileSpawnEvent {
                 id: network_id,
                 position: projectile_spawn_pos,
@@ -1117,12 +1202,18 @@ fn shooting_system(
                 is_boid_projectile: false,
             };
 
+            let audience = interest.audience(projectile_spawn_pos, &interest_config);
+            commands
+                .entity(projectile_entity)
+                .insert(ProjectileAudience(audience.clone()));
+
             connection_manager
                 .send_message_to_target::<boid_wars_shared::ReliableChannel, _>(
                     &spawn_event,
-                    NetworkTarget::All,
+                    NetworkTarget::Only(audience),
                 )
                 .unwrap_or_else(|e| {
+                    warn!("Failed to send projectile spawn event: {:?}", e);
                 });
         }
     }
@@ -1139,22 +1230,33 @@ fn boid_shooting_system(
             &boid_wars_shared::BoidCombatStats,
             &mut boid_wars_shared::BoidCombatState,
             &boid_wars_shared::Position,
+            Option<&boid_wars_shared::BoidGroupMember>,
         ),
         With<boid_wars_shared::Boid>,
     >,
-    player_query: Query<(Entity, &boid_wars_shared::Position), With<boid_wars_shared::Player>>,
+    player_query: Query<
+        (
+            Entity,
+            &boid_wars_shared::Position,
+            &boid_wars_shared::Velocity,
+        ),
+        With<boid_wars_shared::Player>,
+    >,
+    group_query: Query<&boid_wars_shared::BoidGroup>,
+    targeting: Res<TargetingConfig>,
     boid_aggression: Res<BoidAggression>,
     spatial_grid: Res<crate::spatial_grid::SpatialGrid>,
     mut boid_pool: ResMut<BoidProjectilePool>,
     mut id_generator: ResMut<ProjectileIdGenerator>,
     mut connection_manager: ResMut<ConnectionManager>,
+    interest: Res<InterestManager>,
+    interest_config: Res<InterestConfig>,
     time: Res<Time>,
     config: Res<PhysicsConfig>,
+    mut rng: ResMut<GameRng>,
 ) {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-
-    for (boid_entity, transform, combat_stats, mut combat_state, boid_pos) in boid_query.iter_mut()
+    for (boid_entity, transform, combat_stats, mut combat_state, boid_pos, member) in
+        boid_query.iter_mut()
     {
         // Update shooting timer
         combat_state.last_shot_time += time.delta_secs();
@@ -1166,7 +1268,7 @@ fn boid_shooting_system(
         }
 
         // Find target player
-        let target_pos = find_boid_target(
+        let target = find_boid_target(
             boid_entity,
             boid_pos,
             &player_query,
@@ -1175,13 +1277,31 @@ fn boid_shooting_system(
             combat_stats.aggression_range,
         );
 
-        if let Some(target_position) = target_pos {
+        if let Some((_, target_position, target_velocity)) = target {
             // Reset shooting timer
             combat_state.last_shot_time = 0.0;
 
+            // Aim the way the boid's group fights
+            let profile = member
+                .and_then(|member| group_query.get(member.group_entity).ok())
+                .map_or(AccuracyProfile::UNGROUPED, |group| {
+                    targeting.profile(&group.archetype)
+                });
+            let aim_point = profile.aim_point(
+                boid_pos.0,
+                target_position.0,
+                target_velocity.0,
+                combat_stats.projectile_speed,
+            );
+
             // Calculate aim direction with spread
-            let base_direction = (target_position - boid_pos.0).normalize();
-            let spread_angle = rng.gen_range(-combat_stats.spread_angle..combat_stats.spread_angle);
+            let base_direction = (aim_point - boid_pos.0).normalize_or(Vec2::X);
+            let spread = combat_stats.spread_angle * profile.spread_multiplier;
+            let spread_angle = if spread > 0.0 {
+                rng.gen_range(-spread..spread)
+            } else {
+                0.0
+            };
             let rotation = std::f32::consts::PI * spread_angle;
             let cos_rot = rotation.cos();
             let sin_rot = rotation.sin();
@@ -1200,14 +1320,16 @@ fn boid_shooting_system(
 
             // Generate unique network ID for this projectile
             let network_id = id_generator.next();
+            let faction = Faction::Swarm(member.map(|member| member.group_id));
 
             // Try to get a projectile from the boid pool
-            let _projectile_entity = if let Some(pooled_handle) = boid_pool.acquire() {
+            let projectile_entity = if let Some(pooled_handle) = boid_pool.acquire() {
                 // Update existing projectile components
                 commands.entity(pooled_handle.entity).insert((
                     Projectile {
                         damage: combat_stats.damage,
                         owner: Some(boid_entity),
+                        owner_faction: Some(faction),
                         projectile_type: ProjectileType::Basic,
                         lifetime: {
                             let mut timer = Timer::new(Duration::from_secs(2), TimerMode::Once);
@@ -1235,6 +1357,7 @@ fn boid_shooting_system(
                         Projectile {
                             damage: combat_stats.damage,
                             owner: Some(boid_entity),
+                            owner_faction: Some(faction),
                             projectile_type: ProjectileType::Basic,
                             lifetime: Timer::new(Duration::from_secs(2), TimerMode::Once),
                             speed: combat_stats.projectile_speed,
@@ -1256,7 +1379,7 @@ fn boid_shooting_system(
                     .id()
             };
 
-            // Send spawn event to all clients
+            // Send spawn event to clients near the muzzle
             let spawn_event = boid_wars_shared::ProjectileSpawnEvent {
                 id: network_id,
                 position: projectile_spawn_pos,
@@ -1266,53 +1389,59 @@ fn boid_shooting_system(
                 is_boid_projectile: true,
             };
 
+            let audience = interest.audience(projectile_spawn_pos, &interest_config);
+            commands
+                .entity(projectile_entity)
+                .insert(ProjectileAudience(audience.clone()));
+
             connection_manager
                 .send_message_to_target::<boid_wars_shared::ReliableChannel, _>(
                     &spawn_event,
-                    NetworkTarget::All,
+                    NetworkTarget::Only(audience),
                 )
                 .unwrap_or_else(|e| {
+                    warn!("Failed to send boid projectile spawn event: {:?}", e);
                 });
         }
     }
 }
 
 /// Find target for boid shooting
-fn find_boid_target(
+#[allow(clippy::type_complexity)]
+fn find_boid_target<'a>(
     boid_entity: Entity,
     boid_pos: &boid_wars_shared::Position,
-    players: &Query<(Entity, &boid_wars_shared::Position), With<boid_wars_shared::Player>>,
+    players: &'a Query<
+        (
+            Entity,
+            &boid_wars_shared::Position,
+            &boid_wars_shared::Velocity,
+        ),
+        With<boid_wars_shared::Player>,
+    >,
     aggression: &BoidAggression,
     spatial_grid: &crate::spatial_grid::SpatialGrid,
     range: f32,
-) -> Option<Vec2> {
+) -> Option<(
+    Entity,
+    &'a boid_wars_shared::Position,
+    &'a boid_wars_shared::Velocity,
+)> {
     // 1. Check if boid has a remembered attacker
     if let Some(attacker_entity) = aggression.get_attacker(boid_entity) {
-        if let Ok((_, player_pos)) = players.get(attacker_entity) {
+        if let Ok((entity, player_pos, player_vel)) = players.get(attacker_entity) {
             let distance = boid_pos.0.distance(player_pos.0);
             if distance <= range {
-                return Some(player_pos.0);
-            }
-        }
-    }
-
-    // 2. Use spatial grid to find nearby players
-    let nearby_entities = spatial_grid.get_nearby_entities(boid_pos.0, range);
-
-    let mut closest_player = None;
-    let mut closest_distance = range;
-
-    for entity in nearby_entities {
-        if let Ok((_, player_pos)) = players.get(entity) {
-            let distance = boid_pos.0.distance(player_pos.0);
-            if distance < closest_distance {
-                closest_distance = distance;
-                closest_player = Some(player_pos.0);
+                return Some((entity, player_pos, player_vel));
             }
         }
     }
 
-    closest_player
+    // 2. Otherwise the closest player in range
+    spatial_grid
+        .k_nearest(boid_pos.0, 1, range, LayerMask::PLAYER)
+        .first()
+        .and_then(|entry| players.get(entry.entity).ok())
 }
 
 /// System for swarm communication - alerts nearby boids when one is attacked
@@ -1336,11 +1465,13 @@ fn swarm_communication_system(
     for &(alerting_boid, alerting_pos) in &buffers.alert_buffer {
         if let Some(attacker) = boid_aggression.get_attacker(alerting_boid) {
             // Find nearby boids within alert radius
-            let nearby_entities =
-                spatial_grid.get_nearby_entities(alerting_pos, boid_aggression.alert_radius);
+            let nearby_boids: Vec<Entity> = spatial_grid
+                .within_radius(alerting_pos, boid_aggression.alert_radius, LayerMask::BOID)
+                .map(|entry| entry.entity)
+                .collect();
 
             // Alert all nearby boids about the threat
-            for entity in nearby_entities {
+            for entity in nearby_boids {
                 if let Ok((nearby_boid, _)) = boid_query.get(entity) {
                     // Don't alert the boid to itself
                     if nearby_boid != alerting_boid {
@@ -1410,63 +1541,72 @@ fn collision_system(
         Query<&mut boid_wars_shared::Health, With<boid_wars_shared::Boid>>,
     )>,
     projectile_query: Query<&Projectile>,
-    boid_entity_query: Query<Entity, With<boid_wars_shared::Boid>>,
-    _obstacle_query: Query<Entity, With<boid_wars_shared::Obstacle>>,
+    boid_query: Query<Option<&boid_wars_shared::BoidGroupMember>, With<boid_wars_shared::Boid>>,
+    ship_velocities: Query<&Velocity, With<Player>>,
+    obstacle_query: Query<Entity, With<boid_wars_shared::Obstacle>>,
+    combat: Res<CombatConfig>,
+    time: Res<Time>,
+    mut ram_cooldowns: ResMut<RamCooldowns>,
     mut boid_aggression: ResMut<BoidAggression>,
+    mut damage_events: EventWriter<DamageDealtEvent>,
+    mut death_events: EventWriter<PlayerDeathEvent>,
+    god_mode: Query<(), With<GodMode>>,
 ) {
     // Clear and reuse pre-allocated buffers
     buffers.player_collision_buffer.clear();
     buffers.boid_collision_buffer.clear();
+    buffers.ram_buffer.clear();
 
     // Process collision events directly without intermediate collection
     for collision_event in collision_events.read() {
-        if let CollisionEvent::Started(entity1, entity2, _) = collision_event {
-            // Check if entity1 is a projectile
-            if let Ok(projectile) = projectile_query.get(*entity1) {
-                if health_queries.p0().get(*entity2).is_ok() {
-                    // Projectile hit player
-                    buffers.player_collision_buffer.push((
-                        *entity1,
-                        *entity2,
-                        projectile.damage,
-                        projectile.owner,
-                    ));
-                } else if boid_entity_query.get(*entity2).is_ok() {
-                    // Projectile hit boid
-                    buffers.boid_collision_buffer.push((
-                        *entity1,
-                        *entity2,
-                        projectile.damage,
-                        projectile.owner,
-                    ));
-                } else if _obstacle_query.get(*entity2).is_ok() {
-                    // Projectile hit obstacle - despawn projectile
-                    commands.entity(*entity1).insert(Despawning);
-                }
+        let CollisionEvent::Started(entity1, entity2, _) = collision_event else {
+            continue;
+        };
+
+        for (hitter, target) in [(*entity1, *entity2), (*entity2, *entity1)] {
+            // Ship touching a boid: a ram if the ship is fast enough
+            if ship_velocities.contains(hitter) && boid_query.contains(target) {
+                buffers.ram_buffer.push((hitter, target));
+                continue;
             }
 
-            // Check if entity2 is a projectile
-            if let Ok(projectile) = projectile_query.get(*entity2) {
-                if health_queries.p0().get(*entity1).is_ok() {
-                    // Projectile hit player
+            let Ok(projectile) = projectile_query.get(hitter) else {
+                continue;
+            };
+            // Parked pool projectiles haven't been fired and hit nothing
+            let Some(attacker) = projectile.owner_faction else {
+                continue;
+            };
+
+            let target_player = health_queries
+                .p0()
+                .get(target)
+                .ok()
+                .map(|(player, _)| player.player_id);
+            if let Some(player_id) = target_player {
+                // Projectile hit player; disallowed shots fly through
+                if combat.can_damage(attacker, Faction::Player(player_id)) {
                     buffers.player_collision_buffer.push((
-                        *entity2,
-                        *entity1,
+                        hitter,
+                        target,
                         projectile.damage,
                         projectile.owner,
                     ));
-                } else if boid_entity_query.get(*entity1).is_ok() {
-                    // Projectile hit boid
+                }
+            } else if let Ok(member) = boid_query.get(target) {
+                // Projectile hit boid; a boid never shoots itself
+                let faction = Faction::Swarm(member.map(|m| m.group_id));
+                if projectile.owner != Some(target) && combat.can_damage(attacker, faction) {
                     buffers.boid_collision_buffer.push((
-                        *entity2,
-                        *entity1,
+                        hitter,
+                        target,
                         projectile.damage,
                         projectile.owner,
                     ));
-                } else if _obstacle_query.get(*entity1).is_ok() {
-                    // Projectile hit obstacle - despawn projectile
-                    commands.entity(*entity2).insert(Despawning);
                 }
+            } else if obstacle_query.get(target).is_ok() {
+                // Projectile hit obstacle - despawn projectile
+                commands.entity(hitter).insert(Despawning);
             }
         }
     }
@@ -1480,13 +1620,42 @@ fn collision_system(
             }
         }
 
-        if let Ok((_player, mut health)) = health_queries.p0().get_mut(player_entity) {
+        // Invulnerable ships still absorb the shot
+        if god_mode.contains(player_entity) {
+            commands.entity(projectile_entity).insert(Despawning);
+            continue;
+        }
+
+        let attacker_id = owner.and_then(|owner_entity| {
+            health_queries
+                .p0()
+                .get(owner_entity)
+                .ok()
+                .map(|(player, _)| player.player_id)
+        });
+
+        if let Ok((player, mut health)) = health_queries.p0().get_mut(player_entity) {
             // Hit a player - apply damage to Health component
             let old_health = health.current;
             health.current = (health.current - damage).max(0.0);
 
-            if health.current <= 0.0 {
-                handle_player_death(&mut commands, player_entity);
+            if let Some(attacker_id) = attacker_id {
+                damage_events.write(DamageDealtEvent {
+                    attacker_id,
+                    target: player_entity,
+                    amount: old_health - health.current,
+                });
+            }
+
+            // Only the hit that takes the player to zero counts as the kill
+            if old_health > 0.0 && health.current <= 0.0 {
+                handle_player_death(
+                    &mut commands,
+                    &mut death_events,
+                    player_entity,
+                    player.player_id,
+                    attacker_id,
+                );
             }
 
             // Mark projectile for despawn
@@ -1497,16 +1666,28 @@ fn collision_system(
     // Process boid collisions
     for &(projectile_entity, boid_entity, damage, owner) in &buffers.boid_collision_buffer {
         // Check if owner is a player first (before borrowing health)
-        let owner_is_player = if let Some(owner_entity) = owner {
-            health_queries.p0().get(owner_entity).is_ok()
-        } else {
-            false
-        };
+        let attacker_id = owner.and_then(|owner_entity| {
+            health_queries
+                .p0()
+                .get(owner_entity)
+                .ok()
+                .map(|(player, _)| player.player_id)
+        });
+        let owner_is_player = attacker_id.is_some();
 
         if let Ok(mut health) = health_queries.p1().get_mut(boid_entity) {
             // Hit a boid - apply damage
+            let old_health = health.current;
             health.current = (health.current - damage).max(0.0);
 
+            if let Some(attacker_id) = attacker_id {
+                damage_events.write(DamageDealtEvent {
+                    attacker_id,
+                    target: boid_entity,
+                    amount: old_health - health.current,
+                });
+            }
+
             // Track aggression if projectile came from a player
             if owner_is_player {
                 if let Some(owner_entity) = owner {
@@ -1523,18 +1704,96 @@ fn collision_system(
             commands.entity(projectile_entity).insert(Despawning);
         }
     }
+
+    // Process ship-boid rams
+    let now = time.elapsed();
+    ram_cooldowns.expire(now, combat.ram_cooldown);
+    for &(player_entity, boid_entity) in &buffers.ram_buffer {
+        let Ok(velocity) = ship_velocities.get(player_entity) else {
+            continue;
+        };
+        let Some((to_boid, to_player)) = combat.ram_damage(velocity.linvel.length()) else {
+            continue;
+        };
+        let Ok(player_id) = health_queries
+            .p0()
+            .get(player_entity)
+            .map(|(player, _)| player.player_id)
+        else {
+            continue;
+        };
+        let boid_faction = Faction::Swarm(
+            boid_query
+                .get(boid_entity)
+                .ok()
+                .flatten()
+                .map(|m| m.group_id),
+        );
+        if !ram_cooldowns.try_ram(player_entity, boid_entity, now, combat.ram_cooldown) {
+            continue;
+        }
+
+        if combat.can_damage(Faction::Player(player_id), boid_faction) {
+            if let Ok(mut health) = health_queries.p1().get_mut(boid_entity) {
+                let old_health = health.current;
+                health.current = (health.current - to_boid).max(0.0);
+
+                damage_events.write(DamageDealtEvent {
+                    attacker_id: player_id,
+                    target: boid_entity,
+                    amount: old_health - health.current,
+                });
+                boid_aggression.record_attack(boid_entity, player_entity);
+
+                if health.current <= 0.0 {
+                    commands.entity(boid_entity).insert(Despawning);
+                }
+            }
+        }
+
+        // The ship takes a knock too, unless it is invulnerable
+        if god_mode.contains(player_entity)
+            || !combat.can_damage(boid_faction, Faction::Player(player_id))
+        {
+            continue;
+        }
+        if let Ok((_, mut health)) = health_queries.p0().get_mut(player_entity) {
+            let old_health = health.current;
+            health.current = (health.current - to_player).max(0.0);
+
+            if old_health > 0.0 && health.current <= 0.0 {
+                handle_player_death(
+                    &mut commands,
+                    &mut death_events,
+                    player_entity,
+                    player_id,
+                    None,
+                );
+            }
+        }
+    }
 }
 
 /// Handle player death
-fn handle_player_death(commands: &mut Commands, player_entity: Entity) {
+pub fn handle_player_death(
+    commands: &mut Commands,
+    death_events: &mut EventWriter<PlayerDeathEvent>,
+    player_entity: Entity,
+    player_id: u64,
+    killer_id: Option<u64>,
+) {
     // In battle royale mode, death is permanent
     // Mark the player entity for cleanup
     commands.entity(player_entity).insert(Despawning);
 
-    // TODO: Emit death event for UI/spectator mode
-    // TODO: Update game state for battle royale (track remaining players)
-    // TODO: Trigger death visual/audio effects
+    // Round tracking picks this up for eliminations and kill credit
+    death_events.write(PlayerDeathEvent {
+        entity: player_entity,
+        player_id,
+        killer_id,
+    });
 
+    // TODO: Trigger death visual/audio effects
 }
 
 /// System to return projectiles to pool instead of despawning
@@ -1555,6 +1814,7 @@ fn return_projectiles_to_pool(
             Option<&Despawning>,
             Option<&ProjectileTemplate>,
             Option<&BoidProjectileTemplate>,
+            Option<&ProjectileAudience>,
         ),
         With<Projectile>,
     >,
@@ -1570,6 +1830,7 @@ fn return_projectiles_to_pool(
         despawning,
         player_template,
         boid_template,
+        audience,
     ) in projectiles.iter_mut()
     {
         // Check if this projectile should be returned to pool
@@ -1579,13 +1840,18 @@ fn return_projectiles_to_pool(
             // Send despawn event if this projectile has a network ID
             if let Some(ProjectileNetworkId(id)) = network_id {
                 let despawn_event = boid_wars_shared::ProjectileDespawnEvent { id: *id };
+                // Same clients that were told about the spawn
+                let target = audience
+                    .map(|audience| NetworkTarget::Only(audience.0.clone()))
+                    .unwrap_or(NetworkTarget::All);
 
                 connection_manager
                     .send_message_to_target::<boid_wars_shared::ReliableChannel, _>(
                         &despawn_event,
-                        NetworkTarget::All,
+                        target,
                     )
                     .unwrap_or_else(|e| {
+                        warn!("Failed to send projectile despawn event: {:?}", e);
                     });
             }
             // Only process pooled projectiles
@@ -1621,6 +1887,7 @@ fn return_projectiles_to_pool(
                 if let Ok(mut entity_commands) = commands.get_entity(entity) {
                     // Remove the network ID component
                     entity_commands.remove::<ProjectileNetworkId>();
+                    entity_commands.remove::<ProjectileAudience>();
 
                     // Remove old network components (if any still exist)
                     entity_commands.remove::<boid_wars_shared::Projectile>();
@@ -1645,7 +1912,9 @@ fn return_projectiles_to_pool(
                         false
                     };
 
-                    // Silently ignore pool return failures
+                    if !released {
+                        warn!("Projectile {:?} was not returned to its pool", entity);
+                    }
                 }
             } else if despawning.is_some() {
                 // Non-pooled projectile marked for despawn
@@ -1685,7 +1954,10 @@ fn monitor_pool_health(
     if *debug_timer > config.pool_health_check_interval {
         let status = pool.status();
         let utilization = (status.active as f32 / status.total.max(1) as f32) * 100.0;
-
+        debug!(
+            "Projectile pool: {}/{} active ({:.1}%)",
+            status.active, status.total, utilization
+        );
 
         *debug_timer = 0.0;
     }
@@ -1704,7 +1976,10 @@ fn monitor_boid_pool_health(
     if *debug_timer > config.pool_health_check_interval {
         let status = pool.status();
         let utilization = (status.active as f32 / status.total.max(1) as f32) * 100.0;
-
+        debug!(
+            "Boid projectile pool: {}/{} active ({:.1}%)",
+            status.active, status.total, utilization
+        );
 
         *debug_timer = 0.0;
     }
@@ -1825,7 +2100,12 @@ pub fn spawn_ai_player(
         },
         bevy_rapier2d::dynamics::AdditionalMassProperties::Mass(10.0),
         bevy_rapier2d::dynamics::GravityScale(0.0),
-        lightyear::prelude::server::Replicate::default(),
+        lightyear::prelude::server::Replicate {
+            relevance_mode: NetworkRelevanceMode::InterestManagement,
+            ..default()
+        },
+        boid_wars_shared::wire::movement_delta_compression(),
+        InterestManaged,
         Name::new(format!("AI Player {player_id} ({ai_type:?})")),
     ));
 
@@ -1833,11 +2113,11 @@ pub fn spawn_ai_player(
 }
 
 /// System to clean up expired player aggression entries
-fn cleanup_player_aggression(mut player_aggression: ResMut<PlayerAggression>) {
-    player_aggression.cleanup_expired();
+fn cleanup_player_aggression(mut player_aggression: ResMut<PlayerAggression>, time: Res<Time>) {
+    player_aggression.cleanup_expired(time.elapsed());
 }
 
 /// System to clean up expired boid aggression entries
-fn cleanup_boid_aggression(mut boid_aggression: ResMut<BoidAggression>) {
-    boid_aggression.cleanup_expired();
+fn cleanup_boid_aggression(mut boid_aggression: ResMut<BoidAggression>, time: Res<Time>) {
+    boid_aggression.cleanup_expired(time.elapsed());
 }
@@ -1,6 +1,5 @@
 use bevy::prelude::*;
 use std::collections::VecDeque;
-use tracing::warn;
 
 /// Generation-based entity handle to prevent use-after-free
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
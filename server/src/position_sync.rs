@@ -1,7 +1,6 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use boid_wars_shared::{Boid, Player, Position, Velocity as NetworkVelocity};
-use std::time::Duration;
 
 // Constants
 const ROTATION_SYNC_THRESHOLD: f32 = 0.01; // ~0.5 degrees in radians
@@ -105,6 +104,7 @@ pub struct DriftMetrics {
 pub struct SyncPerformanceMetrics {
     pub positions_synced: usize,
     pub velocities_synced: usize,
+    /// Time in `TickStage::Sync` last tick, set by the profiler
     pub sync_time_ms: f32,
     pub last_frame_syncs: usize,
 }
@@ -149,7 +149,6 @@ pub fn sync_player_physics_to_network(
         return;
     }
 
-    let start = std::time::Instant::now();
     let mut sync_count = 0;
 
     for (transform, mut position, mut rotation, _entity) in query.iter_mut() {
@@ -181,7 +180,6 @@ pub fn sync_player_physics_to_network(
     // Update metrics
     metrics.positions_synced += sync_count;
     metrics.last_frame_syncs = sync_count;
-    metrics.sync_time_ms = start.elapsed().as_secs_f32() * 1000.0;
 }
 
 /// Sync boid physics Transform to network Position (15Hz)
@@ -345,7 +343,7 @@ pub fn log_sync_performance(
     time: Res<Time>,
 ) {
     // Log every 5 seconds
-    if timer.duration() == Duration::ZERO {
+    if timer.duration() == std::time::Duration::ZERO {
         *timer = Timer::from_seconds(5.0, TimerMode::Repeating);
     }
 
@@ -0,0 +1,400 @@
+//! Tick-budget profiler.
+//!
+//! Each [`TickStage`] is bracketed by a begin and an end system in every schedule it
+//! runs in, so a stage's time is the wall-clock span from just before its first system
+//! to just after its last one, summed over the frame. A frame runs from `First` to
+//! `Last` and is flagged when it takes longer than the fixed timestep.
+//!
+//! `trace <ticks> [file]` on the admin console captures the next ticks as a Chrome
+//! trace (open it in `chrome://tracing` or Perfetto).
+
+use crate::health::TickPercentiles;
+use crate::position_sync::{SyncPerformanceMetrics, SyncSet};
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use boid_wars_shared::SERVER_CONFIG;
+use lightyear::prelude::MainSet;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Ticks kept for the rolling percentiles
+const PROFILE_WINDOW: usize = 600;
+
+/// Minimum time between two over-budget warnings
+const OVERRUN_LOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where `trace` writes when no file is given
+pub const DEFAULT_TRACE_PATH: &str = "boid-wars-trace.json";
+
+/// The parts of a server tick that get timed
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TickStage {
+    /// Boid steering
+    Flocking,
+    /// Group LOD, targeting, formations, territories and group movement
+    GroupAi,
+    /// Rapier simulation step
+    PhysicsStep,
+    /// Physics to network component sync
+    Sync,
+    /// Lightyear packet send, including replication
+    ReplicationSend,
+}
+
+impl TickStage {
+    pub const ALL: [TickStage; 5] = [
+        TickStage::Flocking,
+        TickStage::GroupAi,
+        TickStage::PhysicsStep,
+        TickStage::Sync,
+        TickStage::ReplicationSend,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TickStage::Flocking => "flocking",
+            TickStage::GroupAi => "group_ai",
+            TickStage::PhysicsStep => "physics_step",
+            TickStage::Sync => "sync",
+            TickStage::ReplicationSend => "replication_send",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// One complete event in the Chrome trace format
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceEvent {
+    pub name: &'static str,
+    pub cat: &'static str,
+    pub ph: &'static str,
+    /// Microseconds since the profiler started
+    pub ts: u64,
+    pub dur: u64,
+    pub pid: u32,
+    pub tid: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChromeTrace<'a> {
+    trace_events: &'a [TraceEvent],
+    display_time_unit: &'static str,
+}
+
+/// Render events as a Chrome trace JSON document
+pub fn chrome_trace_json(events: &[TraceEvent]) -> String {
+    serde_json::to_string(&ChromeTrace {
+        trace_events: events,
+        display_time_unit: "ms",
+    })
+    .expect("trace events always serialize")
+}
+
+struct TraceCapture {
+    path: PathBuf,
+    remaining_ticks: u32,
+    events: Vec<TraceEvent>,
+}
+
+/// A tick that went over budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickOverrun {
+    pub duration: Duration,
+    pub budget: Duration,
+    /// Stage with the most time this tick
+    pub slowest: (TickStage, Duration),
+}
+
+/// Per-stage timings for the current tick plus rolling history
+#[derive(Resource)]
+pub struct TickProfiler {
+    /// Time allowed per tick; the fixed timestep unless set otherwise
+    pub budget: Duration,
+    /// Ticks recorded since startup
+    pub ticks: u64,
+    /// Ticks that took longer than `budget`
+    pub overruns: u64,
+    epoch: Instant,
+    tick_started: Option<Instant>,
+    open: [Option<Instant>; TickStage::ALL.len()],
+    current: [Duration; TickStage::ALL.len()],
+    stage_history: [VecDeque<Duration>; TickStage::ALL.len()],
+    tick_history: VecDeque<Duration>,
+    capture: Option<TraceCapture>,
+}
+
+impl TickProfiler {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            ticks: 0,
+            overruns: 0,
+            epoch: Instant::now(),
+            tick_started: None,
+            open: Default::default(),
+            current: Default::default(),
+            stage_history: Default::default(),
+            tick_history: VecDeque::new(),
+            capture: None,
+        }
+    }
+
+    pub fn begin_tick(&mut self, now: Instant) {
+        self.tick_started = Some(now);
+        self.current = Default::default();
+    }
+
+    /// Close the tick; returns the overrun if it blew the budget
+    pub fn end_tick(&mut self, now: Instant) -> Option<TickOverrun> {
+        let started = self.tick_started.take()?;
+        let duration = now.saturating_duration_since(started);
+
+        push_bounded(&mut self.tick_history, duration);
+        for stage in TickStage::ALL {
+            push_bounded(
+                &mut self.stage_history[stage.index()],
+                self.current[stage.index()],
+            );
+        }
+        self.ticks += 1;
+        self.trace(started, now, "tick", 0);
+
+        if let Some(capture) = &mut self.capture {
+            capture.remaining_ticks = capture.remaining_ticks.saturating_sub(1);
+        }
+
+        if duration <= self.budget {
+            return None;
+        }
+        self.overruns += 1;
+        let slowest = TickStage::ALL
+            .into_iter()
+            .map(|stage| (stage, self.current[stage.index()]))
+            .max_by_key(|(_, time)| *time)
+            .expect("there is always a stage");
+        Some(TickOverrun {
+            duration,
+            budget: self.budget,
+            slowest,
+        })
+    }
+
+    pub fn begin(&mut self, stage: TickStage, now: Instant) {
+        self.open[stage.index()] = Some(now);
+    }
+
+    pub fn end(&mut self, stage: TickStage, now: Instant) {
+        let Some(started) = self.open[stage.index()].take() else {
+            return;
+        };
+        self.current[stage.index()] += now.saturating_duration_since(started);
+        self.trace(started, now, stage.name(), stage.index() as u32 + 1);
+    }
+
+    /// Time spent in a stage so far this tick
+    pub fn current(&self, stage: TickStage) -> Duration {
+        self.current[stage.index()]
+    }
+
+    pub fn stage_percentiles(&self, stage: TickStage) -> TickPercentiles {
+        TickPercentiles::from_samples(self.stage_history[stage.index()].iter().copied())
+    }
+
+    pub fn tick_percentiles(&self) -> TickPercentiles {
+        TickPercentiles::from_samples(self.tick_history.iter().copied())
+    }
+
+    /// Start capturing the next `ticks` ticks for a trace written to `path`
+    pub fn start_capture(&mut self, ticks: u32, path: PathBuf) {
+        self.capture = Some(TraceCapture {
+            path,
+            remaining_ticks: ticks,
+            events: Vec::new(),
+        });
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    /// The finished capture's path and events, once it has seen all its ticks
+    pub fn take_finished_capture(&mut self) -> Option<(PathBuf, Vec<TraceEvent>)> {
+        if self.capture.as_ref()?.remaining_ticks > 0 {
+            return None;
+        }
+        self.capture
+            .take()
+            .map(|capture| (capture.path, capture.events))
+    }
+
+    /// One-line p50/p95/p99 summary in milliseconds
+    pub fn summary(&self) -> String {
+        let mut line = String::new();
+        let mut push = |name: &str, p: TickPercentiles| {
+            let _ = write!(
+                line,
+                "{} {:.2}/{:.2}/{:.2} | ",
+                name,
+                as_ms(p.p50),
+                as_ms(p.p95),
+                as_ms(p.p99)
+            );
+        };
+        for stage in TickStage::ALL {
+            push(stage.name(), self.stage_percentiles(stage));
+        }
+        push("tick", self.tick_percentiles());
+        let _ = write!(
+            line,
+            "{} of {} ticks over {:.2}ms budget",
+            self.overruns,
+            self.ticks,
+            as_ms(self.budget)
+        );
+        line
+    }
+
+    fn trace(&mut self, start: Instant, end: Instant, name: &'static str, tid: u32) {
+        let Some(capture) = &mut self.capture else {
+            return;
+        };
+        capture.events.push(TraceEvent {
+            name,
+            cat: "tick",
+            ph: "X",
+            ts: start.saturating_duration_since(self.epoch).as_micros() as u64,
+            dur: end.saturating_duration_since(start).as_micros() as u64,
+            pid: 1,
+            tid,
+        });
+    }
+}
+
+impl Default for TickProfiler {
+    fn default() -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / 64.0))
+    }
+}
+
+fn push_bounded(history: &mut VecDeque<Duration>, value: Duration) {
+    if history.len() == PROFILE_WINDOW {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Times the tick stages and reports slow ticks
+pub struct ProfilerPlugin;
+
+impl Plugin for ProfilerPlugin {
+    fn build(&self, app: &mut App) {
+        let budget = app
+            .world()
+            .get_resource::<Time<Fixed>>()
+            .map(|time| time.timestep())
+            .unwrap_or_else(|| TickProfiler::default().budget);
+        app.insert_resource(TickProfiler::new(budget));
+
+        // Stages made of other plugins' sets
+        app.configure_sets(
+            PostUpdate,
+            (
+                bevy_rapier2d::plugin::PhysicsSet::StepSimulation.in_set(TickStage::PhysicsStep),
+                SyncSet::PhysicsToNetwork.in_set(TickStage::Sync),
+                MainSet::Send.in_set(TickStage::ReplicationSend),
+            ),
+        );
+
+        bracket_stage(app, FixedUpdate, TickStage::Flocking);
+        bracket_stage(app, Update, TickStage::GroupAi);
+        bracket_stage(app, FixedUpdate, TickStage::GroupAi);
+        bracket_stage(app, PostUpdate, TickStage::PhysicsStep);
+        bracket_stage(app, PostUpdate, TickStage::Sync);
+        bracket_stage(app, PostUpdate, TickStage::ReplicationSend);
+
+        app.add_systems(First, begin_tick).add_systems(
+            Last,
+            (end_tick, write_finished_trace, log_profile_summary).chain(),
+        );
+    }
+}
+
+fn bracket_stage(app: &mut App, schedule: impl ScheduleLabel + Clone, stage: TickStage) {
+    app.add_systems(
+        schedule.clone(),
+        (move |mut profiler: ResMut<TickProfiler>| profiler.begin(stage, Instant::now()))
+            .before(stage),
+    )
+    .add_systems(
+        schedule,
+        (move |mut profiler: ResMut<TickProfiler>| profiler.end(stage, Instant::now()))
+            .after(stage),
+    );
+}
+
+fn begin_tick(mut profiler: ResMut<TickProfiler>) {
+    profiler.begin_tick(Instant::now());
+}
+
+fn end_tick(
+    mut profiler: ResMut<TickProfiler>,
+    sync_metrics: Option<ResMut<SyncPerformanceMetrics>>,
+    mut last_warning: Local<Option<Instant>>,
+) {
+    if let Some(mut sync_metrics) = sync_metrics {
+        sync_metrics.sync_time_ms = as_ms(profiler.current(TickStage::Sync)) as f32;
+    }
+
+    let now = Instant::now();
+    let Some(overrun) = profiler.end_tick(now) else {
+        return;
+    };
+    if last_warning.is_some_and(|last| now.duration_since(last) < OVERRUN_LOG_INTERVAL) {
+        return;
+    }
+    *last_warning = Some(now);
+    let (stage, time) = overrun.slowest;
+    warn!(
+        "⏱️  Tick took {:.2}ms of a {:.2}ms budget; slowest stage {} at {:.2}ms ({} overruns so far)",
+        as_ms(overrun.duration),
+        as_ms(overrun.budget),
+        stage.name(),
+        as_ms(time),
+        profiler.overruns
+    );
+}
+
+fn write_finished_trace(mut profiler: ResMut<TickProfiler>) {
+    let Some((path, events)) = profiler.take_finished_capture() else {
+        return;
+    };
+    match std::fs::write(&path, chrome_trace_json(&events)) {
+        Ok(()) => info!(
+            "📈 Wrote {} trace events to {}",
+            events.len(),
+            path.display()
+        ),
+        Err(e) => error!("Failed to write trace {}: {}", path.display(), e),
+    }
+}
+
+fn log_profile_summary(profiler: Res<TickProfiler>, time: Res<Time>, mut since: Local<f32>) {
+    *since += time.delta_secs();
+    if *since < SERVER_CONFIG.status_log_interval {
+        return;
+    }
+    *since = 0.0;
+    debug!("[Profile ms p50/p95/p99] {}", profiler.summary());
+}
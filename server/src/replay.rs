@@ -0,0 +1,853 @@
+//! Match recording and deterministic playback.
+//!
+//! A recording holds everything from outside the simulation that a match
+//! depends on: the RNG seed, the fixed timestep, a hash of the settings, map
+//! and behavior trees, the length of every frame, and every connection, ready,
+//! input and admin command stamped with the frame it arrived in. It also holds
+//! periodic checkpoints of the simulation state.
+//!
+//! Frame lengths are recorded because only part of the simulation runs on the
+//! fixed schedule: rapier steps, shooting and most group AI advance by the
+//! frame's delta in `Update` and `PostUpdate`. Rapier is built with
+//! `enhanced-determinism` so contacts resolve in the same order on every run.
+//!
+//! Playback re-runs the server headless, as fast as the machine allows, with
+//! each frame advancing the clock by its recorded length. It feeds the recorded
+//! events back in the same frames and compares each checkpoint against the
+//! recording to find the first divergent frame. It refuses to start if the
+//! settings, map or behavior trees differ from the recording's, since every
+//! checkpoint would diverge anyway.
+//!
+//! File layout (little endian):
+//!
+//! - header: magic `BWRP`, version `u16`, seed `u64`, timestep nanos `u64`,
+//!   inputs hash `u64`
+//! - records until EOF: kind `u8`, frame `u64`, then a kind-specific payload
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::time::{TimeSystem, TimeUpdateStrategy};
+use boid_wars_shared::{Boid, GroupArchetype, Health, Player, PlayerInput, PlayerReady, Position};
+use lightyear::connection::id::ClientId;
+use lightyear::prelude::server::*;
+use lightyear::server::message::ReceiveMessage;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::admin::{AdminCommand, AdminSet};
+use crate::groups::behavior_tree::BehaviorTrees;
+use crate::map_file::MapFile;
+use crate::rng::GameRng;
+use crate::settings::Settings;
+
+pub const REPLAY_MAGIC: [u8; 4] = *b"BWRP";
+pub const REPLAY_VERSION: u16 = 3;
+
+const KIND_CONNECT: u8 = 0;
+const KIND_DISCONNECT: u8 = 1;
+const KIND_READY: u8 = 2;
+const KIND_INPUT: u8 = 3;
+const KIND_CHECKPOINT: u8 = 4;
+const KIND_FRAME: u8 = 5;
+const KIND_ADMIN: u8 = 6;
+
+const ADMIN_START: u8 = 0;
+const ADMIN_SPAWN: u8 = 1;
+const ADMIN_GOD: u8 = 2;
+
+const ARCHETYPE_ASSAULT: u8 = 0;
+const ARCHETYPE_DEFENSIVE: u8 = 1;
+const ARCHETYPE_RECON: u8 = 2;
+
+/// Replay settings
+#[derive(Resource, Debug, Clone)]
+pub struct ReplayConfig {
+    /// Frames between state checkpoints
+    pub checkpoint_interval: u64,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            checkpoint_interval: 60, // About once a second at 60 fps
+        }
+    }
+}
+
+/// Frames run since startup, counting the current one
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct SimulationFrame(pub u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayHeader {
+    pub version: u16,
+    pub seed: u64,
+    pub timestep: Duration,
+    /// [`inputs_hash`] of the settings, map and behavior trees
+    pub inputs: u64,
+}
+
+/// Hash the layered settings, the map file (if any) and the behavior trees (the
+/// defaults if none were given), so playback can tell it was started differently
+pub fn inputs_hash(
+    settings: &Settings,
+    map: Option<&MapFile>,
+    trees: Option<&BehaviorTrees>,
+) -> u64 {
+    let trees = trees.cloned().unwrap_or_default();
+    let mut hash = Fnv64::default();
+    for bytes in [
+        serde_json::to_vec(settings),
+        serde_json::to_vec(&map),
+        serde_json::to_vec(&trees),
+    ] {
+        hash.write_bytes(&bytes.expect("inputs serialize to JSON"));
+    }
+    hash.0
+}
+
+/// Summary of the simulation state at the end of one frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub players: u32,
+    pub boids: u32,
+    pub hash: u64,
+}
+
+impl Checkpoint {
+    /// Hash players `(id, position, health)` and boids `(id, position)`.
+    ///
+    /// Order does not matter; values are quantized to 1/100 so the hash only
+    /// changes when the state does.
+    pub fn capture(
+        players: impl IntoIterator<Item = (u64, Vec2, f32)>,
+        boids: impl IntoIterator<Item = (u32, Vec2)>,
+    ) -> Self {
+        let quantize = |value: f32| (value * 100.0).round() as i64 as u64;
+
+        let mut players: Vec<_> = players.into_iter().collect();
+        players.sort_by_key(|(id, _, _)| *id);
+        let mut boids: Vec<_> = boids.into_iter().collect();
+        boids.sort_by_key(|(id, _)| *id);
+
+        let mut hash = Fnv64::default();
+        for (id, position, health) in &players {
+            hash.write(*id);
+            hash.write(quantize(position.x));
+            hash.write(quantize(position.y));
+            hash.write(quantize(*health));
+        }
+        for (id, position) in &boids {
+            hash.write(*id as u64);
+            hash.write(quantize(position.x));
+            hash.write(quantize(position.y));
+        }
+
+        Self {
+            players: players.len() as u32,
+            boids: boids.len() as u32,
+            hash: hash.0,
+        }
+    }
+}
+
+/// FNV-1a, stable across builds unlike `DefaultHasher`
+struct Fnv64(u64);
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv64 {
+    fn write(&mut self, value: u64) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write(bytes.len() as u64);
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// A recorded event. `Frame` is the time a frame advanced the clock by, and `Admin`
+/// holds console commands that [affect the simulation](AdminCommand::affects_simulation).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayEvent {
+    Connect { client: u64 },
+    Disconnect { client: u64 },
+    Ready { client: u64 },
+    Input { client: u64, input: PlayerInput },
+    Checkpoint(Checkpoint),
+    Frame { delta: Duration },
+    Admin(AdminCommand),
+}
+
+/// An event that arrived during frame `frame`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayRecord {
+    pub frame: u64,
+    pub event: ReplayEvent,
+}
+
+fn write_u8(writer: &mut impl Write, value: u8) -> io::Result<()> {
+    writer.write_all(&[value])
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_u64(writer: &mut impl Write, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_vec2(writer: &mut impl Write, value: Vec2) -> io::Result<()> {
+    writer.write_all(&value.x.to_le_bytes())?;
+    writer.write_all(&value.y.to_le_bytes())
+}
+
+/// Archetype tag and its two tuning values
+fn write_archetype(writer: &mut impl Write, archetype: &GroupArchetype) -> io::Result<()> {
+    let (tag, tuning) = match *archetype {
+        GroupArchetype::Assault {
+            aggression_multiplier,
+            preferred_range,
+        } => (
+            ARCHETYPE_ASSAULT,
+            Vec2::new(aggression_multiplier, preferred_range),
+        ),
+        GroupArchetype::Defensive {
+            protection_radius,
+            retreat_threshold,
+        } => (
+            ARCHETYPE_DEFENSIVE,
+            Vec2::new(protection_radius, retreat_threshold),
+        ),
+        GroupArchetype::Recon {
+            detection_range,
+            flee_speed_bonus,
+        } => (
+            ARCHETYPE_RECON,
+            Vec2::new(detection_range, flee_speed_bonus),
+        ),
+    };
+    write_u8(writer, tag)?;
+    write_vec2(writer, tuning)
+}
+
+fn write_admin(writer: &mut impl Write, command: &AdminCommand) -> io::Result<()> {
+    match command {
+        AdminCommand::Start => write_u8(writer, ADMIN_START),
+        AdminCommand::Spawn {
+            archetype,
+            position,
+            size,
+        } => {
+            write_u8(writer, ADMIN_SPAWN)?;
+            write_archetype(writer, archetype)?;
+            write_vec2(writer, *position)?;
+            write_u32(writer, *size)
+        }
+        AdminCommand::God(client) => {
+            write_u8(writer, ADMIN_GOD)?;
+            write_u64(writer, *client)
+        }
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{other:?} does not change the simulation and is not recorded"),
+        )),
+    }
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_vec2(reader: &mut impl Read) -> io::Result<Vec2> {
+    let x = f32::from_le_bytes(read_bytes(reader)?);
+    let y = f32::from_le_bytes(read_bytes(reader)?);
+    Ok(Vec2::new(x, y))
+}
+
+fn read_archetype(reader: &mut impl Read) -> io::Result<GroupArchetype> {
+    let tag = read_bytes::<1>(reader)?[0];
+    let tuning = read_vec2(reader)?;
+    Ok(match tag {
+        ARCHETYPE_ASSAULT => GroupArchetype::Assault {
+            aggression_multiplier: tuning.x,
+            preferred_range: tuning.y,
+        },
+        ARCHETYPE_DEFENSIVE => GroupArchetype::Defensive {
+            protection_radius: tuning.x,
+            retreat_threshold: tuning.y,
+        },
+        ARCHETYPE_RECON => GroupArchetype::Recon {
+            detection_range: tuning.x,
+            flee_speed_bonus: tuning.y,
+        },
+        other => return Err(invalid_data(format!("unknown archetype {other}"))),
+    })
+}
+
+fn read_admin(reader: &mut impl Read) -> io::Result<AdminCommand> {
+    Ok(match read_bytes::<1>(reader)?[0] {
+        ADMIN_START => AdminCommand::Start,
+        ADMIN_SPAWN => AdminCommand::Spawn {
+            archetype: read_archetype(reader)?,
+            position: read_vec2(reader)?,
+            size: read_u32(reader)?,
+        },
+        ADMIN_GOD => AdminCommand::God(read_u64(reader)?),
+        other => return Err(invalid_data(format!("unknown admin command {other}"))),
+    })
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl ReplayHeader {
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&REPLAY_MAGIC)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        write_u64(writer, self.seed)?;
+        write_u64(writer, self.timestep.as_nanos() as u64)?;
+        write_u64(writer, self.inputs)
+    }
+
+    /// Read a header, rejecting other files and unsupported versions
+    pub fn read(reader: &mut impl Read) -> io::Result<Self> {
+        if read_bytes::<4>(reader)? != REPLAY_MAGIC {
+            return Err(invalid_data("not a replay file".to_string()));
+        }
+        let version = u16::from_le_bytes(read_bytes(reader)?);
+        if version != REPLAY_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported replay version {version} (expected {REPLAY_VERSION})"
+            )));
+        }
+
+        Ok(Self {
+            version,
+            seed: read_u64(reader)?,
+            timestep: Duration::from_nanos(read_u64(reader)?),
+            inputs: read_u64(reader)?,
+        })
+    }
+
+    /// Refuse to play back against different settings, map or behavior trees
+    pub fn check_inputs(&self, inputs: u64) -> Result<(), String> {
+        if self.inputs == inputs {
+            return Ok(());
+        }
+        Err(
+            "the settings, map or behavior trees differ from the ones the match was \
+             recorded with; pass the same --config, --set, --map and --behaviors"
+                .to_string(),
+        )
+    }
+}
+
+impl ReplayRecord {
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let kind = match self.event {
+            ReplayEvent::Connect { .. } => KIND_CONNECT,
+            ReplayEvent::Disconnect { .. } => KIND_DISCONNECT,
+            ReplayEvent::Ready { .. } => KIND_READY,
+            ReplayEvent::Input { .. } => KIND_INPUT,
+            ReplayEvent::Checkpoint(_) => KIND_CHECKPOINT,
+            ReplayEvent::Frame { .. } => KIND_FRAME,
+            ReplayEvent::Admin(_) => KIND_ADMIN,
+        };
+        write_u8(writer, kind)?;
+        write_u64(writer, self.frame)?;
+
+        match &self.event {
+            ReplayEvent::Connect { client }
+            | ReplayEvent::Disconnect { client }
+            | ReplayEvent::Ready { client } => write_u64(writer, *client),
+            ReplayEvent::Input { client, input } => {
+                write_u64(writer, *client)?;
+                write_vec2(writer, input.movement)?;
+                write_vec2(writer, input.aim)?;
+                write_u8(writer, input.fire as u8)?;
+                write_u32(writer, input.sequence)
+            }
+            ReplayEvent::Checkpoint(checkpoint) => {
+                write_u32(writer, checkpoint.players)?;
+                write_u32(writer, checkpoint.boids)?;
+                write_u64(writer, checkpoint.hash)
+            }
+            ReplayEvent::Frame { delta } => write_u64(writer, delta.as_nanos() as u64),
+            ReplayEvent::Admin(command) => write_admin(writer, command),
+        }
+    }
+
+    /// Read the next record; `None` at the end of the file
+    pub fn read(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let mut kind = [0u8];
+        if reader.read(&mut kind)? == 0 {
+            return Ok(None);
+        }
+        let frame = read_u64(reader)?;
+
+        let event = match kind[0] {
+            KIND_CONNECT => ReplayEvent::Connect {
+                client: read_u64(reader)?,
+            },
+            KIND_DISCONNECT => ReplayEvent::Disconnect {
+                client: read_u64(reader)?,
+            },
+            KIND_READY => ReplayEvent::Ready {
+                client: read_u64(reader)?,
+            },
+            KIND_INPUT => {
+                let client = read_u64(reader)?;
+                let movement = read_vec2(reader)?;
+                let aim = read_vec2(reader)?;
+                let fire = read_bytes::<1>(reader)?[0] != 0;
+                let sequence = read_u32(reader)?;
+                ReplayEvent::Input {
+                    client,
+                    input: PlayerInput::new(movement, aim, fire).with_sequence(sequence),
+                }
+            }
+            KIND_CHECKPOINT => ReplayEvent::Checkpoint(Checkpoint {
+                players: read_u32(reader)?,
+                boids: read_u32(reader)?,
+                hash: read_u64(reader)?,
+            }),
+            KIND_FRAME => ReplayEvent::Frame {
+                delta: Duration::from_nanos(read_u64(reader)?),
+            },
+            KIND_ADMIN => ReplayEvent::Admin(read_admin(reader)?),
+            other => return Err(invalid_data(format!("unknown record kind {other}"))),
+        };
+
+        Ok(Some(Self { frame, event }))
+    }
+}
+
+/// A whole recording in memory
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    pub records: Vec<ReplayRecord>,
+}
+
+impl Replay {
+    pub fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let header = ReplayHeader::read(reader)?;
+        let mut records = Vec::new();
+        while let Some(record) = ReplayRecord::read(reader)? {
+            records.push(record);
+        }
+        Ok(Self { header, records })
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+/// Writes the running match to a replay file
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    writer: BufWriter<File>,
+    inputs: u64,
+    header_written: bool,
+}
+
+impl ReplayRecorder {
+    pub fn create(path: &Path, inputs: u64) -> io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            inputs,
+            header_written: false,
+        })
+    }
+
+    fn record(&mut self, frame: u64, event: ReplayEvent) {
+        if let Err(e) = (ReplayRecord { frame, event }).write(&mut self.writer) {
+            error!("Failed to write replay record: {}", e);
+        }
+    }
+}
+
+/// Recorded frame lengths and events left to feed back, and checkpoints left to compare
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    frames: VecDeque<(u64, Duration)>,
+    events: VecDeque<ReplayRecord>,
+    checkpoints: VecDeque<(u64, Checkpoint)>,
+    matched: usize,
+    pub divergence: Option<Divergence>,
+}
+
+/// First checkpoint that did not match the recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub frame: u64,
+    pub expected: Checkpoint,
+    pub actual: Checkpoint,
+}
+
+impl ReplayPlayback {
+    pub fn new(records: Vec<ReplayRecord>) -> Self {
+        let mut frames = VecDeque::new();
+        let mut events = VecDeque::new();
+        let mut checkpoints = VecDeque::new();
+        for record in records {
+            match record.event {
+                ReplayEvent::Checkpoint(checkpoint) => {
+                    checkpoints.push_back((record.frame, checkpoint))
+                }
+                ReplayEvent::Frame { delta } => frames.push_back((record.frame, delta)),
+                _ => events.push_back(record),
+            }
+        }
+
+        Self {
+            frames,
+            events,
+            checkpoints,
+            matched: 0,
+            divergence: None,
+        }
+    }
+
+    /// Take the recorded length of `frame`, dropping earlier ones; `None` if it was not recorded
+    pub fn frame_delta(&mut self, frame: u64) -> Option<Duration> {
+        while let Some(&(at, delta)) = self.frames.front() {
+            if at > frame {
+                break;
+            }
+            self.frames.pop_front();
+            if at == frame {
+                return Some(delta);
+            }
+        }
+        None
+    }
+
+    /// Compare the state at the end of `frame` with the recorded checkpoint, if there is one
+    pub fn verify(&mut self, frame: u64, actual: Checkpoint) -> Option<Divergence> {
+        while self.checkpoints.front().is_some_and(|(at, _)| *at < frame) {
+            self.checkpoints.pop_front();
+        }
+        let (at, expected) = *self.checkpoints.front()?;
+        if at != frame {
+            return None;
+        }
+        self.checkpoints.pop_front();
+
+        if expected == actual {
+            self.matched += 1;
+            return None;
+        }
+        let divergence = Divergence {
+            frame,
+            expected,
+            actual,
+        };
+        self.divergence.get_or_insert(divergence);
+        Some(divergence)
+    }
+
+    /// Checkpoints that matched so far
+    pub fn matched(&self) -> usize {
+        self.matched
+    }
+
+    /// Nothing left to replay or compare
+    pub fn is_finished(&self) -> bool {
+        self.frames.is_empty() && self.events.is_empty() && self.checkpoints.is_empty()
+    }
+}
+
+/// Whether this server run records or plays back a match
+#[derive(Debug, Clone)]
+pub enum ReplayMode {
+    Record(PathBuf),
+    Playback(Replay),
+}
+
+pub struct ReplayPlugin {
+    pub mode: ReplayMode,
+    /// [`inputs_hash`] of this run, written into recordings
+    pub inputs: u64,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayConfig>()
+            .init_resource::<SimulationFrame>()
+            .add_systems(First, advance_simulation_frame.before(TimeSystem));
+
+        match &self.mode {
+            ReplayMode::Record(path) => {
+                let recorder = match ReplayRecorder::create(path, self.inputs) {
+                    Ok(recorder) => recorder,
+                    Err(e) => {
+                        error!("Failed to create replay file {}: {}", path.display(), e);
+                        return;
+                    }
+                };
+
+                app.init_resource::<GameRng>()
+                    .insert_resource(recorder)
+                    .add_systems(Startup, write_replay_header)
+                    // After the console has turned this frame's lines into commands
+                    .add_systems(Update, record_events.after(AdminSet))
+                    .add_systems(Last, (record_frame, record_checkpoints).chain());
+            }
+            ReplayMode::Playback(replay) => {
+                let timestep = replay.header.timestep;
+                info!(
+                    "Replaying {} records (seed {}, timestep {:?})",
+                    replay.records.len(),
+                    replay.header.seed,
+                    timestep
+                );
+
+                // Each frame advances the clock by its recorded length instead of the wall clock
+                app.insert_resource(GameRng::from_seed(replay.header.seed))
+                    .insert_resource(Time::<Fixed>::from_duration(timestep))
+                    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO))
+                    .insert_resource(ReplayPlayback::new(replay.records.clone()))
+                    .add_systems(
+                        First,
+                        apply_frame_delta
+                            .after(advance_simulation_frame)
+                            .before(TimeSystem),
+                    )
+                    .add_systems(
+                        RunFixedMainLoop,
+                        inject_replay_events.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                    )
+                    .add_systems(Last, verify_checkpoints);
+            }
+        }
+    }
+}
+
+fn advance_simulation_frame(mut frame: ResMut<SimulationFrame>) {
+    frame.0 += 1;
+}
+
+fn capture_checkpoint(
+    players: &Query<(&Player, &Position, Option<&Health>)>,
+    boids: &Query<(&Boid, &Position)>,
+) -> Checkpoint {
+    Checkpoint::capture(
+        players.iter().map(|(player, position, health)| {
+            (player.id, position.0, health.map_or(0.0, |h| h.current))
+        }),
+        boids.iter().map(|(boid, position)| (boid.id, position.0)),
+    )
+}
+
+fn write_replay_header(
+    mut recorder: ResMut<ReplayRecorder>,
+    rng: Res<GameRng>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let header = ReplayHeader {
+        version: REPLAY_VERSION,
+        seed: rng.seed(),
+        timestep: fixed_time.timestep(),
+        inputs: recorder.inputs,
+    };
+    match header.write(&mut recorder.writer) {
+        Ok(()) => {
+            recorder.header_written = true;
+            info!("Recording match (seed {})", header.seed);
+        }
+        Err(e) => error!("Failed to write replay header, recording disabled: {}", e),
+    }
+}
+
+/// Record every event the lobby and admin systems will handle this frame
+fn record_events(
+    mut connections: EventReader<ConnectEvent>,
+    mut disconnections: EventReader<DisconnectEvent>,
+    mut readies: EventReader<ReceiveMessage<PlayerReady>>,
+    mut inputs: EventReader<ReceiveMessage<PlayerInput>>,
+    mut admin_commands: EventReader<AdminCommand>,
+    mut recorder: ResMut<ReplayRecorder>,
+    frame: Res<SimulationFrame>,
+) {
+    if !recorder.header_written {
+        return;
+    }
+    let frame = frame.0;
+
+    for event in connections.read() {
+        let client = event.client_id.to_bits();
+        recorder.record(frame, ReplayEvent::Connect { client });
+    }
+    for event in readies.read() {
+        let client = event.from.to_bits();
+        recorder.record(frame, ReplayEvent::Ready { client });
+    }
+    for event in inputs.read() {
+        let client = event.from.to_bits();
+        let input = event.message.clone();
+        recorder.record(frame, ReplayEvent::Input { client, input });
+    }
+    for command in admin_commands.read() {
+        if command.affects_simulation() {
+            recorder.record(frame, ReplayEvent::Admin(command.clone()));
+        }
+    }
+    for event in disconnections.read() {
+        let client = event.client_id.to_bits();
+        recorder.record(frame, ReplayEvent::Disconnect { client });
+    }
+}
+
+fn record_frame(
+    mut recorder: ResMut<ReplayRecorder>,
+    real_time: Res<Time<Real>>,
+    frame: Res<SimulationFrame>,
+) {
+    if recorder.header_written {
+        let delta = real_time.delta();
+        recorder.record(frame.0, ReplayEvent::Frame { delta });
+    }
+}
+
+fn record_checkpoints(
+    players: Query<(&Player, &Position, Option<&Health>)>,
+    boids: Query<(&Boid, &Position)>,
+    mut recorder: ResMut<ReplayRecorder>,
+    config: Res<ReplayConfig>,
+    frame: Res<SimulationFrame>,
+) {
+    if !recorder.header_written || frame.0 % config.checkpoint_interval.max(1) != 0 {
+        return;
+    }
+
+    let checkpoint = capture_checkpoint(&players, &boids);
+    recorder.record(frame.0, ReplayEvent::Checkpoint(checkpoint));
+
+    // Keep the file usable if the server is killed
+    if let Err(e) = recorder.writer.flush() {
+        warn!("Failed to flush replay file: {}", e);
+    }
+}
+
+/// Advance the clock this frame by the recorded frame length
+fn apply_frame_delta(
+    mut playback: ResMut<ReplayPlayback>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    frame: Res<SimulationFrame>,
+) {
+    if let Some(delta) = playback.frame_delta(frame.0) {
+        *strategy = TimeUpdateStrategy::ManualDuration(delta);
+    }
+}
+
+/// Feed recorded events back in the frame they arrived in, before anything handles them
+#[allow(clippy::too_many_arguments)]
+fn inject_replay_events(
+    mut playback: ResMut<ReplayPlayback>,
+    mut connections: EventWriter<ConnectEvent>,
+    mut disconnections: EventWriter<DisconnectEvent>,
+    mut readies: EventWriter<ReceiveMessage<PlayerReady>>,
+    mut inputs: EventWriter<ReceiveMessage<PlayerInput>>,
+    mut admin_commands: EventWriter<AdminCommand>,
+    frame: Res<SimulationFrame>,
+) {
+    while playback
+        .events
+        .front()
+        .is_some_and(|record| record.frame <= frame.0)
+    {
+        let Some(record) = playback.events.pop_front() else {
+            break;
+        };
+
+        // Every client connects over netcode. The lobby never looks at the connection
+        // entity, and there is none during playback.
+        match record.event {
+            ReplayEvent::Connect { client } => {
+                connections.write(ConnectEvent {
+                    client_id: ClientId::Netcode(client),
+                    entity: Entity::PLACEHOLDER,
+                });
+            }
+            ReplayEvent::Disconnect { client } => {
+                disconnections.write(DisconnectEvent {
+                    client_id: ClientId::Netcode(client),
+                    entity: Entity::PLACEHOLDER,
+                });
+            }
+            ReplayEvent::Ready { client } => {
+                readies.write(ReceiveMessage::new(PlayerReady, ClientId::Netcode(client)));
+            }
+            ReplayEvent::Input { client, input } => {
+                inputs.write(ReceiveMessage::new(input, ClientId::Netcode(client)));
+            }
+            ReplayEvent::Admin(command) => {
+                admin_commands.write(command);
+            }
+            ReplayEvent::Checkpoint(_) | ReplayEvent::Frame { .. } => {}
+        }
+    }
+}
+
+/// Compare checkpoints and stop once the recording is exhausted
+fn verify_checkpoints(
+    players: Query<(&Player, &Position, Option<&Health>)>,
+    boids: Query<(&Boid, &Position)>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut exit: EventWriter<AppExit>,
+    frame: Res<SimulationFrame>,
+) {
+    let checkpoint = capture_checkpoint(&players, &boids);
+    if let Some(divergence) = playback.verify(frame.0, checkpoint) {
+        error!(
+            "Replay diverged at frame {}: expected {} players, {} boids, hash {:016x}; got {} players, {} boids, hash {:016x}",
+            divergence.frame,
+            divergence.expected.players,
+            divergence.expected.boids,
+            divergence.expected.hash,
+            divergence.actual.players,
+            divergence.actual.boids,
+            divergence.actual.hash
+        );
+        exit.write(AppExit::from_code(1));
+        return;
+    }
+
+    if playback.is_finished() {
+        info!(
+            "Replay finished at frame {}: all {} checkpoints matched",
+            frame.0, playback.matched
+        );
+        exit.write(AppExit::Success);
+    }
+}
@@ -0,0 +1,382 @@
+use bevy::prelude::*;
+use boid_wars_shared::*;
+use lightyear::prelude::server::*;
+use lightyear::prelude::{MessageSend, NetworkTarget};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::despawn_utils::SafeDespawnExt;
+use crate::lobby::{GameState, PlayerSlots};
+use crate::physics::{ArenaWall, DamageDealtEvent, PlayerDeathEvent};
+
+/// Timing for the match lifecycle
+#[derive(Resource, Debug, Clone)]
+pub struct RoundConfig {
+    /// Round ends after this many seconds even with several survivors (0 disables)
+    pub time_limit_secs: f32,
+    /// Pause between the win condition and the results broadcast
+    pub game_over_secs: f32,
+    /// How long results are shown before returning to the lobby
+    pub results_secs: f32,
+}
+
+impl Default for RoundConfig {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: 600.0, // Well past the last zone shrink
+            game_over_secs: 3.0,
+            results_secs: 10.0,
+        }
+    }
+}
+
+/// Stats for one player during the current round
+#[derive(Debug, Clone, Default)]
+pub struct PlayerRoundStats {
+    pub player_number: u8,
+    pub kills: u32,
+    pub damage_dealt: f32,
+    /// Round time (seconds since start) at which the player was eliminated
+    pub eliminated_at: Option<f32>,
+}
+
+impl PlayerRoundStats {
+    pub fn is_alive(&self) -> bool {
+        self.eliminated_at.is_none()
+    }
+}
+
+/// Per-round bookkeeping: who is alive, kills, damage and timing
+#[derive(Resource, Debug, Default)]
+pub struct MatchTracker {
+    running: bool,
+    /// Seconds since the round started
+    pub elapsed: f32,
+    /// Set once a win condition is met
+    pub end_reason: Option<MatchEndReason>,
+    /// Seconds spent in the current post-round phase
+    pub phase_timer: f32,
+    players: HashMap<u64, PlayerRoundStats>,
+}
+
+impl MatchTracker {
+    /// Start tracking a round with the given `(player_id, player_number)` pairs
+    pub fn begin(&mut self, players: impl IntoIterator<Item = (u64, u8)>) {
+        *self = Self {
+            running: true,
+            players: players
+                .into_iter()
+                .map(|(player_id, player_number)| {
+                    (
+                        player_id,
+                        PlayerRoundStats {
+                            player_number,
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        };
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        if self.running && self.end_reason.is_none() {
+            self.elapsed += dt;
+        }
+    }
+
+    pub fn get(&self, player_id: u64) -> Option<&PlayerRoundStats> {
+        self.players.get(&player_id)
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn alive_count(&self) -> usize {
+        self.players
+            .values()
+            .filter(|stats| stats.is_alive())
+            .count()
+    }
+
+    /// Alive player IDs
+    pub fn alive(&self) -> impl Iterator<Item = u64> + '_ {
+        self.players
+            .iter()
+            .filter(|(_, stats)| stats.is_alive())
+            .map(|(player_id, _)| *player_id)
+    }
+
+    pub fn record_damage(&mut self, attacker_id: u64, amount: f32) {
+        if self.end_reason.is_some() {
+            return;
+        }
+        if let Some(stats) = self.players.get_mut(&attacker_id) {
+            stats.damage_dealt += amount;
+        }
+    }
+
+    /// Mark a player eliminated and credit the kill.
+    ///
+    /// Repeated deaths for the same player are ignored.
+    pub fn record_death(&mut self, player_id: u64, killer_id: Option<u64>) {
+        if self.end_reason.is_some() {
+            return;
+        }
+        let Some(stats) = self.players.get_mut(&player_id) else {
+            return;
+        };
+        if !stats.is_alive() {
+            return;
+        }
+        stats.eliminated_at = Some(self.elapsed);
+
+        if let Some(killer) = killer_id.filter(|killer| *killer != player_id) {
+            if let Some(killer_stats) = self.players.get_mut(&killer) {
+                killer_stats.kills += 1;
+            }
+        }
+    }
+
+    /// Check the win conditions for the running round
+    pub fn check_end(&self, config: &RoundConfig) -> Option<MatchEndReason> {
+        if !self.running || self.end_reason.is_some() {
+            return None;
+        }
+
+        // A solo round (min_players = 1) only ends when that player dies
+        let survivors_to_win = if self.players.len() > 1 { 1 } else { 0 };
+        if self.alive_count() <= survivors_to_win {
+            return Some(MatchEndReason::LastPlayerStanding);
+        }
+
+        if config.time_limit_secs > 0.0 && self.elapsed >= config.time_limit_secs {
+            return Some(MatchEndReason::TimeLimit);
+        }
+
+        None
+    }
+
+    pub fn end(&mut self, reason: MatchEndReason) {
+        self.end_reason = Some(reason);
+        self.phase_timer = 0.0;
+    }
+
+    /// Build the results broadcast.
+    ///
+    /// Survivors rank first (by kills, then damage), followed by eliminated
+    /// players in reverse elimination order.
+    pub fn results(&self, next_round_in: f32) -> MatchResults {
+        let mut ranked: Vec<(&u64, &PlayerRoundStats)> = self.players.iter().collect();
+        ranked.sort_by(|(a_id, a), (b_id, b)| {
+            let a_time = a.eliminated_at.unwrap_or(f32::INFINITY);
+            let b_time = b.eliminated_at.unwrap_or(f32::INFINITY);
+            b_time
+                .total_cmp(&a_time)
+                .then(b.kills.cmp(&a.kills))
+                .then(b.damage_dealt.total_cmp(&a.damage_dealt))
+                .then(a_id.cmp(b_id))
+        });
+
+        let players: Vec<PlayerMatchStats> = ranked
+            .into_iter()
+            .enumerate()
+            .map(|(index, (player_id, stats))| PlayerMatchStats {
+                player_id: *player_id,
+                player_number: stats.player_number,
+                placement: (index + 1).min(u8::MAX as usize) as u8,
+                kills: stats.kills,
+                damage_dealt: stats.damage_dealt,
+                survival_secs: stats.eliminated_at.unwrap_or(self.elapsed),
+            })
+            .collect();
+
+        let winner = players
+            .first()
+            .filter(|first| self.get(first.player_id).is_some_and(|s| s.is_alive()))
+            .map(|first| first.player_id);
+
+        MatchResults {
+            winner,
+            reason: self
+                .end_reason
+                .unwrap_or(MatchEndReason::LastPlayerStanding),
+            duration_secs: self.elapsed,
+            players,
+            next_round_in,
+        }
+    }
+}
+
+/// Plugin driving InGame -> GameOver -> Results -> Lobby
+pub struct RoundPlugin;
+
+impl Plugin for RoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundConfig>()
+            .init_resource::<MatchTracker>()
+            .add_systems(
+                Update,
+                (
+                    begin_round,
+                    track_round_events,
+                    check_round_end,
+                    advance_post_round,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Start tracking once the lobby moves into the match
+fn begin_round(
+    game_state: Res<GameState>,
+    player_slots: Res<PlayerSlots>,
+    mut tracker: ResMut<MatchTracker>,
+) {
+    if game_state.phase != GamePhase::InGame || tracker.is_running() {
+        return;
+    }
+
+    tracker.begin(
+        player_slots
+            .iter_ordered()
+            .into_iter()
+            .map(|(client_id, slot)| (client_id.to_bits(), slot.number.0)),
+    );
+    info!("Round started with {} players", tracker.player_count());
+}
+
+/// Fold combat events and disconnects into the round stats
+fn track_round_events(
+    mut damage_events: EventReader<DamageDealtEvent>,
+    mut death_events: EventReader<PlayerDeathEvent>,
+    player_slots: Res<PlayerSlots>,
+    mut tracker: ResMut<MatchTracker>,
+    time: Res<Time>,
+) {
+    if !tracker.is_running() {
+        damage_events.clear();
+        death_events.clear();
+        return;
+    }
+
+    tracker.tick(time.delta_secs());
+
+    for event in damage_events.read() {
+        tracker.record_damage(event.attacker_id, event.amount);
+    }
+
+    for event in death_events.read() {
+        tracker.record_death(event.player_id, event.killer_id);
+        info!(
+            "Player {} eliminated ({} remaining)",
+            event.player_id,
+            tracker.alive_count()
+        );
+    }
+
+    // Leaving mid-round counts as an elimination
+    let departed: Vec<u64> = tracker
+        .alive()
+        .filter(|player_id| !player_slots.contains_player(*player_id))
+        .collect();
+    for player_id in departed {
+        tracker.record_death(player_id, None);
+        info!("Player {} left the round", player_id);
+    }
+}
+
+fn check_round_end(
+    config: Res<RoundConfig>,
+    mut game_state: ResMut<GameState>,
+    mut tracker: ResMut<MatchTracker>,
+) {
+    if game_state.phase != GamePhase::InGame {
+        return;
+    }
+
+    if let Some(reason) = tracker.check_end(&config) {
+        tracker.end(reason);
+        game_state.phase = GamePhase::GameOver;
+        info!(
+            "Round over after {:.0}s: {:?} ({} alive)",
+            tracker.elapsed,
+            reason,
+            tracker.alive_count()
+        );
+    }
+}
+
+/// Broadcast results after the game-over pause, then reset for a rematch
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn advance_post_round(
+    mut commands: Commands,
+    config: Res<RoundConfig>,
+    mut game_state: ResMut<GameState>,
+    mut player_slots: ResMut<PlayerSlots>,
+    mut tracker: ResMut<MatchTracker>,
+    mut connection_manager: ResMut<ConnectionManager>,
+    arena_entities: Query<
+        Entity,
+        (
+            Or<(With<Boid>, With<BoidGroup>, With<Obstacle>)>,
+            Without<ArenaWall>,
+        ),
+    >,
+    time: Res<Time>,
+) {
+    match game_state.phase {
+        GamePhase::GameOver => {
+            tracker.phase_timer += time.delta_secs();
+            if tracker.phase_timer < config.game_over_secs {
+                return;
+            }
+
+            let results = tracker.results(config.results_secs);
+            info!("Round results: winner {:?}", results.winner);
+            if let Err(e) = connection_manager
+                .send_message_to_target::<ReliableChannel, _>(&results, NetworkTarget::All)
+            {
+                warn!("Failed to send match results: {:?}", e);
+            }
+
+            tracker.phase_timer = 0.0;
+            game_state.phase = GamePhase::Results;
+        }
+        GamePhase::Results => {
+            tracker.phase_timer += time.delta_secs();
+            if tracker.phase_timer < config.results_secs {
+                return;
+            }
+
+            // Clear the arena; boids and obstacles respawn when the next round starts, the
+            // walls only exist once
+            for entity in arena_entities.iter() {
+                commands.safe_despawn(entity);
+            }
+            for entity in player_slots.reset_for_rematch() {
+                commands.safe_despawn(entity);
+            }
+            tracker.reset();
+
+            game_state.phase = if player_slots.len() >= GAME_CONFIG.min_players as usize {
+                GamePhase::Lobby
+            } else {
+                GamePhase::WaitingForPlayers
+            };
+            info!("Arena reset, returning to {:?}", game_state.phase);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,304 @@
+//! One TOML file for every server setting, layered defaults < file < env < command line.
+//!
+//! ```toml
+//! [game]
+//! max_players = 8
+//!
+//! [flocking]
+//! max_speed = 450.0
+//! ```
+//!
+//! Environment variables override single keys as `BOID_WARS_<SECTION>__<FIELD>`
+//! (e.g. `BOID_WARS_FLOCKING__MAX_SPEED=450`), and `--set flocking.max_speed=450`
+//! overrides them again. While the server runs, edits to the file's `[physics]`,
+//! `[combat]`, `[targeting]`, `[flocking]` and `[navigation]` sections are pushed into
+//! the live resources; everything else is read once at startup.
+
+use crate::combat::CombatConfig;
+use crate::config::{MonitoringConfig, PhysicsConfig};
+use crate::flocking::FlockingConfig;
+use crate::groups::BoidGroupConfig;
+use crate::navigation::NavigationConfig;
+use crate::targeting::TargetingConfig;
+use bevy::prelude::*;
+use boid_wars_shared::{
+    error_report, install_shared, ClientConfig, ConfigError, GameConfig, Layers, NetworkConfig,
+    ServerConfig,
+};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+use tracing::{error, info, warn};
+
+/// Every section of the settings file
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Settings {
+    pub network: NetworkConfig,
+    pub game: GameConfig,
+    pub server: ServerConfig,
+    pub physics: PhysicsConfig,
+    pub combat: CombatConfig,
+    pub targeting: TargetingConfig,
+    pub flocking: FlockingConfig,
+    pub navigation: NavigationConfig,
+    pub groups: BoidGroupConfig,
+    pub monitoring: MonitoringConfig,
+}
+
+impl Settings {
+    /// Layer the file at `path` (if any), the environment and `overrides` over the defaults
+    pub fn load(
+        path: Option<&Path>,
+        env: impl IntoIterator<Item = (String, String)>,
+        overrides: &[(String, String)],
+    ) -> Result<Self, Vec<ConfigError>> {
+        let file = match path {
+            Some(path) => {
+                let text = std::fs::read_to_string(path).map_err(|e| {
+                    vec![ConfigError {
+                        key: path.display().to_string(),
+                        message: e.to_string(),
+                    }]
+                })?;
+                Some((path.display().to_string(), text))
+            }
+            None => None,
+        };
+        let file = file
+            .as_ref()
+            .map(|(origin, text)| (origin.as_str(), text.as_str()));
+        Self::layered(file, env, overrides)
+    }
+
+    /// Layer an already-read file given as `(origin, text)`
+    pub fn layered(
+        file: Option<(&str, &str)>,
+        env: impl IntoIterator<Item = (String, String)>,
+        overrides: &[(String, String)],
+    ) -> Result<Self, Vec<ConfigError>> {
+        let mut layers = Layers::new()
+            .with_defaults::<NetworkConfig>()
+            .with_defaults::<GameConfig>()
+            .with_defaults::<ServerConfig>()
+            .with_defaults::<PhysicsConfig>()
+            .with_defaults::<CombatConfig>()
+            .with_defaults::<TargetingConfig>()
+            .with_defaults::<FlockingConfig>()
+            .with_defaults::<NavigationConfig>()
+            .with_defaults::<BoidGroupConfig>()
+            .with_defaults::<MonitoringConfig>();
+
+        if let Some((origin, text)) = file {
+            layers.merge_file(origin, text);
+        }
+        layers.merge_env(env);
+        for (key, value) in overrides {
+            layers.set(key, value);
+        }
+
+        let settings = Self {
+            network: layers.section(),
+            game: layers.section(),
+            server: layers.section(),
+            physics: layers.section(),
+            combat: layers.section(),
+            targeting: layers.section(),
+            flocking: layers.section(),
+            navigation: layers.section(),
+            groups: layers.section(),
+            monitoring: layers.section(),
+        };
+        layers.finish(settings)
+    }
+
+    /// Make the shared sections the values behind `GAME_CONFIG` and friends.
+    /// Must run before anything reads those globals.
+    pub fn install_globals(&self) -> Result<(), Vec<ConfigError>> {
+        // The server has no client section, and never reads it
+        install_shared(
+            self.network.clone(),
+            self.game.clone(),
+            self.server.clone(),
+            ClientConfig::default(),
+        )
+    }
+
+    /// Insert the server sections as resources, before the plugins that would default them
+    pub fn insert_resources(self, app: &mut App) {
+        app.insert_resource(self.physics)
+            .insert_resource(self.combat)
+            .insert_resource(self.targeting)
+            .insert_resource(self.flocking)
+            .insert_resource(self.navigation)
+            .insert_resource(self.groups)
+            .insert_resource(self.monitoring);
+    }
+}
+
+/// Split a `section.field=value` command-line override
+pub fn parse_override(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if key.contains('.') => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("expected section.field=value, got '{arg}'")),
+    }
+}
+
+/// Where the running settings came from, so a reload can layer them the same way
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SettingsSource {
+    pub file: Option<PathBuf>,
+    pub overrides: Vec<(String, String)>,
+}
+
+impl SettingsSource {
+    pub fn load(&self) -> Result<Settings, Vec<ConfigError>> {
+        Settings::load(self.file.as_deref(), std::env::vars(), &self.overrides)
+    }
+}
+
+/// Watches the settings file and hot-reloads its gameplay tuning sections
+pub struct SettingsPlugin {
+    pub source: SettingsSource,
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.source.clone());
+
+        let Some(path) = &self.source.file else {
+            return;
+        };
+        match SettingsWatcher::new(path) {
+            Ok(watcher) => {
+                info!("👀 Watching {} for settings changes", path.display());
+                app.insert_resource(watcher)
+                    .add_systems(Update, reload_settings);
+            }
+            Err(e) => warn!(
+                "Settings hot reload disabled, cannot watch {}: {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+}
+
+type WatchEvents = Receiver<notify::Result<notify::Event>>;
+
+/// File-system watcher on the settings file's directory.
+/// Editors often save by replacing the file, so the directory is watched rather than the file.
+#[derive(Resource)]
+struct SettingsWatcher {
+    watch: Mutex<(RecommendedWatcher, WatchEvents)>,
+    file_name: std::ffi::OsString,
+}
+
+impl SettingsWatcher {
+    fn new(path: &Path) -> notify::Result<Self> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            watch: Mutex::new((watcher, events)),
+            file_name: path.file_name().unwrap_or_default().to_owned(),
+        })
+    }
+
+    /// Whether the file changed since the last call; a burst of writes counts once
+    fn changed(&self) -> bool {
+        let Ok(watch) = self.watch.lock() else {
+            return false;
+        };
+        watch.1.try_iter().any(|event| {
+            event.is_ok_and(|event| {
+                matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event
+                        .paths
+                        .iter()
+                        .any(|path| path.file_name() == Some(self.file_name.as_os_str()))
+            })
+        })
+    }
+}
+
+/// Re-layer the settings after the file changes and push the gameplay sections into the
+/// live resources. A file with errors is reported and the running values are kept.
+#[allow(clippy::too_many_arguments)]
+fn reload_settings(
+    watcher: Res<SettingsWatcher>,
+    source: Res<SettingsSource>,
+    mut physics: ResMut<PhysicsConfig>,
+    mut combat: ResMut<CombatConfig>,
+    mut targeting: ResMut<TargetingConfig>,
+    mut flocking: ResMut<FlockingConfig>,
+    mut navigation: ResMut<NavigationConfig>,
+    groups: Res<BoidGroupConfig>,
+    monitoring: Res<MonitoringConfig>,
+) {
+    if !watcher.changed() {
+        return;
+    }
+
+    let settings = match source.load() {
+        Ok(settings) => settings,
+        Err(errors) => {
+            error!(
+                "Settings not reloaded, keeping the running values:\n{}",
+                error_report(&errors)
+            );
+            return;
+        }
+    };
+
+    if settings.physics != *physics {
+        *physics = settings.physics;
+        info!("🔄 Applied [physics] settings");
+    }
+    if settings.combat != *combat {
+        *combat = settings.combat;
+        info!("🔄 Applied [combat] settings");
+    }
+    if settings.targeting != *targeting {
+        *targeting = settings.targeting;
+        info!("🔄 Applied [targeting] settings");
+    }
+    if settings.flocking != *flocking {
+        *flocking = settings.flocking;
+        info!("🔄 Applied [flocking] settings");
+    }
+    if settings.navigation != *navigation {
+        *navigation = settings.navigation;
+        info!("🔄 Applied [navigation] settings");
+    }
+
+    let restart_only = [
+        (
+            "network",
+            settings.network != *boid_wars_shared::NETWORK_CONFIG,
+        ),
+        ("game", settings.game != *boid_wars_shared::GAME_CONFIG),
+        (
+            "server",
+            settings.server != *boid_wars_shared::SERVER_CONFIG,
+        ),
+        ("groups", settings.groups != *groups),
+        ("monitoring", settings.monitoring != *monitoring),
+    ];
+    for (section, changed) in restart_only {
+        if changed {
+            warn!(
+                "[{}] changed but only takes effect after a restart",
+                section
+            );
+        }
+    }
+}
@@ -1,4 +1,8 @@
+use crate::physics::Projectile;
 use bevy::prelude::*;
+use boid_wars_shared::{Boid, Obstacle, Player, Position};
+use std::collections::HashMap;
+use std::ops::BitOr;
 
 /// System sets for spatial grid operations
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
@@ -9,12 +13,63 @@ pub enum SpatialGridSet {
     Read,
 }
 
+/// Kinds of entity stored in the grid, combined with `|` for queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LayerMask(pub u8);
+
+impl LayerMask {
+    pub const NONE: LayerMask = LayerMask(0);
+    pub const BOID: LayerMask = LayerMask(1 << 0);
+    pub const PLAYER: LayerMask = LayerMask(1 << 1);
+    pub const OBSTACLE: LayerMask = LayerMask(1 << 2);
+    pub const PROJECTILE: LayerMask = LayerMask(1 << 3);
+    /// Positioned entities that are none of the above
+    pub const OTHER: LayerMask = LayerMask(1 << 4);
+    pub const ALL: LayerMask = LayerMask(u8::MAX);
+
+    /// True when every layer in `other` is set
+    pub fn contains(self, other: LayerMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// True when any layer in `other` is set
+    pub fn intersects(self, other: LayerMask) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for LayerMask {
+    type Output = LayerMask;
+
+    fn bitor(self, rhs: LayerMask) -> LayerMask {
+        LayerMask(self.0 | rhs.0)
+    }
+}
+
+/// One entity in a grid cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridEntry {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub layers: LayerMask,
+}
+
+/// Closest entry along a segment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entry: GridEntry,
+    /// Distance from the segment start to where it first touches the entry
+    pub distance: f32,
+}
+
 /// Spatial grid for efficient neighbor queries
 #[derive(Resource)]
 pub struct SpatialGrid {
     cell_size: f32,
     // Flat array indexed by row * cells_per_row + col for better cache locality
-    cells: Vec<Vec<Entity>>,
+    cells: Vec<Vec<GridEntry>>,
+    // Cell and slot of every stored entity, for in-place updates and removal
+    locations: HashMap<Entity, (usize, usize)>,
     // Dimensions for index calculation
     cells_per_row: usize,
     cells_per_col: usize,
@@ -39,6 +94,7 @@ impl SpatialGrid {
         Self {
             cell_size,
             cells,
+            locations: HashMap::new(),
             cells_per_row,
             cells_per_col,
             width,
@@ -51,12 +107,65 @@ impl SpatialGrid {
         for cell in self.cells.iter_mut() {
             cell.clear();
         }
+        self.locations.clear();
+    }
+
+    /// Entities currently in the grid
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.locations.contains_key(&entity)
+    }
+
+    /// Add or move an entity; positions outside the grid remove it
+    ///
+    /// An entity that stays in its cell is updated in place.
+    pub fn insert(&mut self, entity: Entity, position: Vec2, layers: LayerMask) {
+        let entry = GridEntry {
+            entity,
+            position,
+            layers,
+        };
+        let Some(idx) = self.get_cell_index(position) else {
+            self.remove(entity);
+            return;
+        };
+
+        match self.locations.get(&entity) {
+            Some(&(cell, slot)) if cell == idx => self.cells[cell][slot] = entry,
+            Some(_) => {
+                self.remove(entity);
+                self.push(idx, entry);
+            }
+            None => self.push(idx, entry),
+        }
+    }
+
+    fn push(&mut self, idx: usize, entry: GridEntry) {
+        self.locations
+            .insert(entry.entity, (idx, self.cells[idx].len()));
+        self.cells[idx].push(entry);
     }
 
-    pub fn insert(&mut self, entity: Entity, position: Vec2) {
-        if let Some(idx) = self.get_cell_index(position) {
-            self.cells[idx].push(entity);
+    pub fn remove(&mut self, entity: Entity) -> Option<GridEntry> {
+        let (cell, slot) = self.locations.remove(&entity)?;
+        let removed = self.cells[cell].swap_remove(slot);
+        // The last entry took the removed one's slot
+        if let Some(moved) = self.cells[cell].get(slot) {
+            self.locations.insert(moved.entity, (cell, slot));
         }
+        Some(removed)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&GridEntry> {
+        let &(cell, slot) = self.locations.get(&entity)?;
+        self.cells[cell].get(slot)
     }
 
     pub fn get_nearby_entities(&self, position: Vec2, radius: f32) -> Vec<Entity> {
@@ -70,59 +179,207 @@ impl SpatialGrid {
         radius: f32,
         filter: Option<fn(Entity) -> bool>,
     ) -> Vec<Entity> {
-        // Use thread-local storage for the result to avoid allocations
-        thread_local! {
-            static RESULT_BUFFER: std::cell::RefCell<Vec<Entity>> =
-                std::cell::RefCell::new(Vec::with_capacity(1024)); // Larger buffer for 10k+ entities
+        self.nearby(position, radius, LayerMask::ALL)
+            .map(|entry| entry.entity)
+            .filter(|&entity| filter.is_none_or(|filter_fn| filter_fn(entity)))
+            .collect()
+    }
+
+    /// Entries on `layers` in the cells the circle touches, without a distance check
+    pub fn nearby(
+        &self,
+        position: Vec2,
+        radius: f32,
+        layers: LayerMask,
+    ) -> impl Iterator<Item = &GridEntry> + '_ {
+        self.cells_in_radius(position, radius)
+            .flat_map(move |idx| self.cells[idx].iter())
+            .filter(move |entry| entry.layers.intersects(layers))
+    }
+
+    /// Entries on `layers` no further than `radius` from `position`
+    pub fn within_radius(
+        &self,
+        position: Vec2,
+        radius: f32,
+        layers: LayerMask,
+    ) -> impl Iterator<Item = &GridEntry> + '_ {
+        let radius_squared = radius * radius;
+        self.nearby(position, radius, layers)
+            .filter(move |entry| entry.position.distance_squared(position) <= radius_squared)
+    }
+
+    /// Up to `k` entries on `layers` within `max_distance`, closest first
+    ///
+    /// Searches outward ring by ring and stops once no unvisited cell can hold
+    /// anything closer than the k-th candidate.
+    pub fn k_nearest(
+        &self,
+        position: Vec2,
+        k: usize,
+        max_distance: f32,
+        layers: LayerMask,
+    ) -> Vec<GridEntry> {
+        let mut found: Vec<(f32, GridEntry)> = Vec::new();
+        if k == 0 {
+            return Vec::new();
         }
 
-        RESULT_BUFFER.with(|buffer| {
-            let mut result = buffer.borrow_mut();
-            result.clear();
-
-            let _radius_squared = radius * radius;
-            let cell_radius = (radius / self.cell_size).ceil() as i32;
-            let center_cell = self.get_cell(position);
-
-            // Early bounds checking to avoid unnecessary iterations
-            let min_x = (center_cell.0 - cell_radius).max(0);
-            let max_x = (center_cell.0 + cell_radius).min(self.cells_per_row as i32 - 1);
-            let min_y = (center_cell.1 - cell_radius).max(0);
-            let max_y = (center_cell.1 + cell_radius).min(self.cells_per_col as i32 - 1);
-
-            // Check all cells within radius
-            for x in min_x..=max_x {
-                for y in min_y..=max_y {
-                    // Skip cells that are definitely outside the circle
-                    let cell_center_x = (x as f32 + 0.5) * self.cell_size;
-                    let cell_center_y = (y as f32 + 0.5) * self.cell_size;
-                    let cell_dist_sq =
-                        (cell_center_x - position.x).powi(2) + (cell_center_y - position.y).powi(2);
-
-                    // Conservative check - if cell center is too far, skip
-                    if cell_dist_sq
-                        > (radius + self.cell_size * std::f32::consts::FRAC_1_SQRT_2).powi(2)
-                    {
-                        continue;
+        let max_distance_squared = max_distance * max_distance;
+        let (cx, cy) = self.get_cell(position);
+        // Clamped before the cast so huge or infinite ranges stop at the grid's edge
+        let grid_rings = self.cells_per_row.max(self.cells_per_col) as f32 + 1.0;
+        let max_ring = ((max_distance / self.cell_size).ceil() + 1.0).min(grid_rings) as i32;
+
+        for ring in 0..=max_ring {
+            for (x, y) in ring_cells(cx, cy, ring) {
+                let Some(idx) = self.get_cell_index_from_coords(x, y) else {
+                    continue;
+                };
+                for entry in &self.cells[idx] {
+                    let distance_squared = entry.position.distance_squared(position);
+                    if entry.layers.intersects(layers) && distance_squared <= max_distance_squared {
+                        found.push((distance_squared, *entry));
                     }
+                }
+            }
+
+            // Anything in the next ring is at least `ring` cells away
+            if found.len() >= k {
+                found.sort_by(|a, b| a.0.total_cmp(&b.0));
+                found.truncate(k);
+                let reach = ring as f32 * self.cell_size;
+                if found[k - 1].0 <= reach * reach {
+                    break;
+                }
+            }
+        }
 
-                    // Use flat array index for better cache locality
-                    if let Some(idx) = self.get_cell_index_from_coords(x, y) {
-                        for &entity in &self.cells[idx] {
-                            if let Some(filter_fn) = filter {
-                                if !filter_fn(entity) {
-                                    continue;
-                                }
-                            }
-                            result.push(entity);
+        found.sort_by(|a, b| a.0.total_cmp(&b.0));
+        found.truncate(k);
+        found.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// Every entry on `layers` that a circle of `radius` swept from `start` to `end`
+    /// touches, nearest first
+    pub fn segment_query(
+        &self,
+        start: Vec2,
+        end: Vec2,
+        radius: f32,
+        layers: LayerMask,
+    ) -> Vec<RayHit> {
+        let mut hits = Vec::new();
+        self.for_each_segment_cell(start, end, radius, |cell| {
+            for entry in cell {
+                if !entry.layers.intersects(layers) {
+                    continue;
+                }
+                if let Some(t) = segment_circle_entry(start, end, entry.position, radius) {
+                    hits.push(RayHit {
+                        entry: *entry,
+                        distance: t * start.distance(end),
+                    });
+                }
+            }
+        });
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// First entry on `layers` hit by a ray, treating entries as circles of `radius`
+    pub fn raycast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        radius: f32,
+        layers: LayerMask,
+    ) -> Option<RayHit> {
+        let end = origin + direction.normalize_or_zero() * max_distance;
+        self.segment_query(origin, end, radius, layers)
+            .into_iter()
+            .next()
+    }
+
+    /// Visit each cell within `radius` of the segment once, walking cells in order
+    fn for_each_segment_cell(
+        &self,
+        start: Vec2,
+        end: Vec2,
+        radius: f32,
+        mut visit: impl FnMut(&[GridEntry]),
+    ) {
+        let spread = (radius / self.cell_size).ceil() as i32;
+        let mut visited = vec![false; self.cells.len()];
+        let mut visit_around = |x: i32, y: i32| {
+            for nx in x - spread..=x + spread {
+                for ny in y - spread..=y + spread {
+                    if let Some(idx) = self.get_cell_index_from_coords(nx, ny) {
+                        if !visited[idx] {
+                            visited[idx] = true;
+                            visit(&self.cells[idx]);
                         }
                     }
                 }
             }
+        };
+
+        // Amanatides-Woo traversal of the cells under the segment
+        let (mut x, mut y) = self.get_cell(start);
+        let (end_x, end_y) = self.get_cell(end);
+        let delta = end - start;
+        let step_x = if delta.x >= 0.0 { 1 } else { -1 };
+        let step_y = if delta.y >= 0.0 { 1 } else { -1 };
+        let t_delta_x = (self.cell_size / delta.x).abs();
+        let t_delta_y = (self.cell_size / delta.y).abs();
+        let next_boundary =
+            |cell: i32, step: i32| (cell + (step > 0) as i32) as f32 * self.cell_size;
+        let mut t_max_x = if delta.x != 0.0 {
+            (next_boundary(x, step_x) - start.x) / delta.x
+        } else {
+            f32::INFINITY
+        };
+        let mut t_max_y = if delta.y != 0.0 {
+            (next_boundary(y, step_y) - start.y) / delta.y
+        } else {
+            f32::INFINITY
+        };
+
+        let max_steps = (end_x - x).abs() + (end_y - y).abs();
+        visit_around(x, y);
+        for _ in 0..max_steps {
+            if t_max_x < t_max_y {
+                x += step_x;
+                t_max_x += t_delta_x;
+            } else {
+                y += step_y;
+                t_max_y += t_delta_y;
+            }
+            visit_around(x, y);
+        }
+    }
 
-            // Return a clone to avoid holding the RefCell borrow
-            result.clone()
-        })
+    /// Flat indices of in-bounds cells that may overlap the circle
+    fn cells_in_radius(&self, position: Vec2, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let cell_radius = (radius / self.cell_size).ceil() as i32;
+        let center_cell = self.get_cell(position);
+
+        // Early bounds checking to avoid unnecessary iterations
+        let min_x = (center_cell.0 - cell_radius).max(0);
+        let max_x = (center_cell.0 + cell_radius).min(self.cells_per_row as i32 - 1);
+        let min_y = (center_cell.1 - cell_radius).max(0);
+        let max_y = (center_cell.1 + cell_radius).min(self.cells_per_col as i32 - 1);
+        let reach_squared = (radius + self.cell_size * std::f32::consts::FRAC_1_SQRT_2).powi(2);
+
+        (min_x..=max_x)
+            .flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+            .filter(move |&(x, y)| {
+                // Conservative check - if cell center is too far, skip
+                let cell_center = (Vec2::new(x as f32, y as f32) + 0.5) * self.cell_size;
+                cell_center.distance_squared(position) <= reach_squared
+            })
+            .filter_map(move |(x, y)| self.get_cell_index_from_coords(x, y))
     }
 
     /// Get nearby entities with actual distance checking
@@ -160,11 +417,7 @@ impl SpatialGrid {
     /// Get flat array index from position, returns None if out of bounds
     fn get_cell_index(&self, position: Vec2) -> Option<usize> {
         let (x, y) = self.get_cell(position);
-        if x >= 0 && y >= 0 && x < self.cells_per_row as i32 && y < self.cells_per_col as i32 {
-            Some(y as usize * self.cells_per_row + x as usize)
-        } else {
-            None
-        }
+        self.get_cell_index_from_coords(x, y)
     }
 
     /// Get flat array index from cell coordinates, returns None if out of bounds
@@ -176,8 +429,7 @@ impl SpatialGrid {
         }
     }
 
-    /// Get statistics about grid usage (useful for debugging)
-    #[allow(dead_code)]
+    /// Get statistics about grid usage (useful for debugging and metrics)
     pub fn get_stats(&self) -> SpatialGridStats {
         let total_cells = self.cells.len();
         let occupied_cells = self.cells.iter().filter(|cell| !cell.is_empty()).count();
@@ -199,8 +451,42 @@ impl SpatialGrid {
     }
 }
 
+/// Cells on the square ring `ring` cells out from (cx, cy)
+fn ring_cells(cx: i32, cy: i32, ring: i32) -> impl Iterator<Item = (i32, i32)> {
+    let side = (-ring..=ring).flat_map(move |d| {
+        [(cx + d, cy - ring), (cx + d, cy + ring)]
+            .into_iter()
+            .take(if ring == 0 { 1 } else { 2 })
+    });
+    let middle = (-ring + 1..ring).flat_map(move |d| [(cx - ring, cy + d), (cx + ring, cy + d)]);
+    side.chain(middle)
+}
+
+/// Fraction along start..end where it first comes within `radius` of `center`
+fn segment_circle_entry(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let d = end - start;
+    let f = start - center;
+    let c = f.length_squared() - radius * radius;
+    if c <= 0.0 {
+        // Starts inside
+        return Some(0.0);
+    }
+
+    let a = d.length_squared();
+    if a == 0.0 {
+        return None;
+    }
+    let b = 2.0 * f.dot(d);
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    (0.0..=1.0).contains(&t).then_some(t)
+}
+
 /// Statistics about spatial grid usage
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SpatialGridStats {
     pub total_cells: usize,
     pub occupied_cells: usize,
@@ -217,15 +503,62 @@ impl Default for SpatialGrid {
     }
 }
 
+/// Layer for an entity from its marker components
+pub fn layer_of(boid: bool, player: bool, obstacle: bool, projectile: bool) -> LayerMask {
+    match (boid, player, obstacle, projectile) {
+        (true, _, _, _) => LayerMask::BOID,
+        (_, true, _, _) => LayerMask::PLAYER,
+        (_, _, true, _) => LayerMask::OBSTACLE,
+        (_, _, _, true) => LayerMask::PROJECTILE,
+        _ => LayerMask::OTHER,
+    }
+}
+
+type LayeredPosition = (
+    Entity,
+    &'static Position,
+    Has<Boid>,
+    Has<Player>,
+    Has<Obstacle>,
+    Has<Projectile>,
+);
+
+/// Moved, or given one of the markers `layer_of` reads
+type GridChanged = Or<(
+    Changed<Position>,
+    Added<Boid>,
+    Added<Player>,
+    Added<Obstacle>,
+    Added<Projectile>,
+)>;
+
 /// Update the spatial grid with all entity positions
+///
+/// Only entities that moved, changed layer or lost their position are touched.
 pub fn update_spatial_grid(
     mut spatial_grid: ResMut<SpatialGrid>,
-    entities: Query<(Entity, &boid_wars_shared::Position)>,
+    changed: Query<LayeredPosition, GridChanged>,
+    removed: RemovedComponents<Position>,
 ) {
-    spatial_grid.clear();
+    forget_removed(&mut spatial_grid, removed);
 
-    for (entity, pos) in entities.iter() {
-        spatial_grid.insert(entity, pos.0);
+    for (entity, pos, boid, player, obstacle, projectile) in changed.iter() {
+        spatial_grid.insert(entity, pos.0, layer_of(boid, player, obstacle, projectile));
+    }
+}
+
+/// Drop despawned entities every frame; removal events only last two frames, so a
+/// reader that only runs on fixed ticks could miss some
+fn forget_removed_positions(
+    mut spatial_grid: ResMut<SpatialGrid>,
+    removed: RemovedComponents<Position>,
+) {
+    forget_removed(&mut spatial_grid, removed);
+}
+
+fn forget_removed(spatial_grid: &mut SpatialGrid, mut removed: RemovedComponents<Position>) {
+    for entity in removed.read() {
+        spatial_grid.remove(entity);
     }
 }
 
@@ -249,7 +582,8 @@ impl Plugin for SpatialGridPlugin {
             .add_systems(
                 FixedUpdate,
                 update_spatial_grid.in_set(SpatialGridSet::Update),
-            );
+            )
+            .add_systems(Last, forget_removed_positions);
 
         info!("Spatial grid plugin initialized");
     }
@@ -0,0 +1,171 @@
+//! Where boids aim: intercept courses, per-archetype accuracy and suppression fire.
+//!
+//! `boid_shooting_system` picks a target and asks the boid's [`AccuracyProfile`] for an
+//! aim point from the target's position and replicated velocity, then scatters the shot
+//! by the profile's spread.
+
+use bevy::prelude::*;
+use boid_wars_shared::{ConfigSection, GroupArchetype, Validator};
+use serde::{Deserialize, Serialize};
+
+/// How a boid turns a target's position and velocity into an aim point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AimMode {
+    /// At the target's current position
+    Direct,
+    /// At the point where shot and target meet, scaled by `lead` (1.0 leads perfectly)
+    Intercept { lead: f32 },
+    /// At where the target will be after `lead_time` seconds, to cut off its path
+    Suppress { lead_time: f32 },
+}
+
+/// How one boid aims and how much its shots scatter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccuracyProfile {
+    pub mode: AimMode,
+    /// Multiplier on the boid's `spread_angle`
+    pub spread_multiplier: f32,
+}
+
+impl AccuracyProfile {
+    /// Boids outside any group shoot straight with their own spread
+    pub const UNGROUPED: Self = Self {
+        mode: AimMode::Direct,
+        spread_multiplier: 1.0,
+    };
+
+    /// Aim point for a target at `target` moving with `target_velocity`,
+    /// shot from `shooter` at `projectile_speed`
+    pub fn aim_point(
+        &self,
+        shooter: Vec2,
+        target: Vec2,
+        target_velocity: Vec2,
+        projectile_speed: f32,
+    ) -> Vec2 {
+        match self.mode {
+            AimMode::Direct => target,
+            AimMode::Intercept { lead } => {
+                match intercept_time(shooter, target, target_velocity, projectile_speed) {
+                    Some(time) => target + target_velocity * time * lead,
+                    // Too fast to catch: shoot where it is and hope it turns
+                    None => target,
+                }
+            }
+            AimMode::Suppress { lead_time } => target + target_velocity * lead_time,
+        }
+    }
+}
+
+/// Seconds until a shot fired now from `shooter` at `projectile_speed` meets a target at
+/// `target` moving with constant `target_velocity`, or `None` if it never can
+pub fn intercept_time(
+    shooter: Vec2,
+    target: Vec2,
+    target_velocity: Vec2,
+    projectile_speed: f32,
+) -> Option<f32> {
+    // |offset + velocity * t| = speed * t, as a*t² + b*t + c = 0
+    let offset = target - shooter;
+    let a = target_velocity.length_squared() - projectile_speed * projectile_speed;
+    let b = 2.0 * offset.dot(target_velocity);
+    let c = offset.length_squared();
+
+    if a.abs() < f32::EPSILON {
+        // Shot and target equally fast: only a target coming closer can be met
+        return (b < 0.0).then(|| -c / b);
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let (t1, t2) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+    [t1.min(t2), t1.max(t2)]
+        .into_iter()
+        .find(|&time| time >= 0.0)
+}
+
+/// Per-archetype aiming, the `[targeting]` section of the settings file
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TargetingConfig {
+    // Recon: picks shots off with a full lead
+    pub recon_lead: f32,
+    pub recon_spread_multiplier: f32,
+
+    // Assault: half a lead and a wide spray
+    pub assault_lead: f32,
+    pub assault_spread_multiplier: f32,
+
+    // Defensive: suppressing fire ahead of the target
+    pub defensive_suppression_lead_time: f32,
+    pub defensive_spread_multiplier: f32,
+}
+
+impl Default for TargetingConfig {
+    fn default() -> Self {
+        Self {
+            // Recon: picks shots off with a full lead
+            recon_lead: 1.0,
+            recon_spread_multiplier: 0.0,
+
+            // Assault: half a lead and a wide spray
+            assault_lead: 0.5,
+            assault_spread_multiplier: 4.0,
+
+            // Defensive: suppressing fire ahead of the target
+            defensive_suppression_lead_time: 0.8,
+            defensive_spread_multiplier: 1.5,
+        }
+    }
+}
+
+impl ConfigSection for TargetingConfig {
+    const SECTION: &'static str = "targeting";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        for (field, value) in [
+            ("recon_lead", self.recon_lead),
+            ("recon_spread_multiplier", self.recon_spread_multiplier),
+            ("assault_lead", self.assault_lead),
+            ("assault_spread_multiplier", self.assault_spread_multiplier),
+            (
+                "defensive_suppression_lead_time",
+                self.defensive_suppression_lead_time,
+            ),
+            (
+                "defensive_spread_multiplier",
+                self.defensive_spread_multiplier,
+            ),
+        ] {
+            check.non_negative(field, value);
+        }
+    }
+}
+
+impl TargetingConfig {
+    pub fn profile(&self, archetype: &GroupArchetype) -> AccuracyProfile {
+        match archetype {
+            GroupArchetype::Recon { .. } => AccuracyProfile {
+                mode: AimMode::Intercept {
+                    lead: self.recon_lead,
+                },
+                spread_multiplier: self.recon_spread_multiplier,
+            },
+            GroupArchetype::Assault { .. } => AccuracyProfile {
+                mode: AimMode::Intercept {
+                    lead: self.assault_lead,
+                },
+                spread_multiplier: self.assault_spread_multiplier,
+            },
+            GroupArchetype::Defensive { .. } => AccuracyProfile {
+                mode: AimMode::Suppress {
+                    lead_time: self.defensive_suppression_lead_time,
+                },
+                spread_multiplier: self.defensive_spread_multiplier,
+            },
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use bevy::prelude::*;
+use boid_wars_shared::{GamePhase, SafeZone, GAME_CONFIG};
+use lightyear::prelude::server::Replicate;
+use rand::Rng;
+use tracing::info;
+
+use crate::despawn_utils::SafeDespawnExt;
+use crate::lobby::GameState;
+use crate::physics::{self, Despawning, PhysicsSet};
+use crate::rng::GameRng;
+
+/// One step of the zone timeline: wait, then shrink to `radius_fraction`
+#[derive(Debug, Clone)]
+pub struct ZonePhase {
+    /// Seconds the zone holds still before shrinking
+    pub wait_secs: f32,
+    /// Seconds the shrink takes
+    pub shrink_secs: f32,
+    /// Target radius as a fraction of the starting radius
+    pub radius_fraction: f32,
+    /// Damage dealt per second to players outside the zone during this phase
+    pub damage_per_second: f32,
+}
+
+/// Configuration for the battle-royale safe zone
+#[derive(Resource, Debug, Clone)]
+pub struct ZoneConfig {
+    pub enabled: bool,
+    pub phases: Vec<ZonePhase>,
+    /// How often the replicated `SafeZone` is refreshed (seconds)
+    pub replication_interval: f32,
+}
+
+impl Default for ZoneConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            phases: vec![
+                ZonePhase {
+                    wait_secs: 60.0,
+                    shrink_secs: 45.0,
+                    radius_fraction: 0.65,
+                    damage_per_second: 5.0,
+                },
+                ZonePhase {
+                    wait_secs: 45.0,
+                    shrink_secs: 30.0,
+                    radius_fraction: 0.4,
+                    damage_per_second: 10.0,
+                },
+                ZonePhase {
+                    wait_secs: 30.0,
+                    shrink_secs: 30.0,
+                    radius_fraction: 0.2,
+                    damage_per_second: 20.0,
+                },
+                ZonePhase {
+                    wait_secs: 20.0,
+                    shrink_secs: 20.0,
+                    radius_fraction: 0.05,
+                    damage_per_second: 40.0,
+                },
+            ],
+            replication_interval: 0.1, // 10Hz is plenty for a slow-moving circle
+        }
+    }
+}
+
+/// Where the zone is in its timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZoneStage {
+    /// No match running
+    #[default]
+    Inactive,
+    /// Holding still before the next shrink
+    Waiting,
+    /// Moving towards the next zone
+    Shrinking,
+    /// All phases done, the zone stays at its final size
+    Final,
+}
+
+/// Server-side zone progression
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ZoneState {
+    pub stage: ZoneStage,
+    pub phase_index: usize,
+    /// Seconds spent in the current stage
+    pub elapsed: f32,
+    pub initial_radius: f32,
+    pub center: Vec2,
+    pub radius: f32,
+    start_center: Vec2,
+    start_radius: f32,
+    pub next_center: Vec2,
+    pub next_radius: f32,
+    /// Replicated zone entity, if spawned
+    pub entity: Option<Entity>,
+}
+
+impl ZoneState {
+    /// Begin the timeline with a zone that covers the whole arena
+    pub fn start(&mut self, config: &ZoneConfig, arena_size: Vec2, rng: &mut impl Rng) {
+        let center = arena_size / 2.0;
+        let radius = center.length();
+
+        self.stage = ZoneStage::Waiting;
+        self.phase_index = 0;
+        self.elapsed = 0.0;
+        self.initial_radius = radius;
+        self.center = center;
+        self.radius = radius;
+        self.pick_next_zone(config, rng);
+    }
+
+    /// Stop the timeline (match over)
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.stage != ZoneStage::Inactive
+    }
+
+    /// Advance the timeline by `dt` seconds
+    pub fn tick(&mut self, config: &ZoneConfig, dt: f32, rng: &mut impl Rng) {
+        let Some(phase) = config.phases.get(self.phase_index) else {
+            if self.is_active() {
+                self.stage = ZoneStage::Final;
+            }
+            return;
+        };
+
+        self.elapsed += dt;
+
+        match self.stage {
+            ZoneStage::Waiting => {
+                if self.elapsed >= phase.wait_secs {
+                    self.elapsed -= phase.wait_secs;
+                    self.stage = ZoneStage::Shrinking;
+                    self.start_center = self.center;
+                    self.start_radius = self.radius;
+                }
+            }
+            ZoneStage::Shrinking => {
+                let t = if phase.shrink_secs > 0.0 {
+                    (self.elapsed / phase.shrink_secs).min(1.0)
+                } else {
+                    1.0
+                };
+                self.center = self.start_center.lerp(self.next_center, t);
+                self.radius = self.start_radius + (self.next_radius - self.start_radius) * t;
+
+                if t >= 1.0 {
+                    self.elapsed = 0.0;
+                    self.phase_index += 1;
+                    if self.phase_index < config.phases.len() {
+                        self.stage = ZoneStage::Waiting;
+                        self.pick_next_zone(config, rng);
+                    } else {
+                        self.stage = ZoneStage::Final;
+                    }
+                }
+            }
+            ZoneStage::Inactive | ZoneStage::Final => {}
+        }
+    }
+
+    /// Damage per second currently applied outside the zone
+    pub fn damage_per_second(&self, config: &ZoneConfig) -> f32 {
+        if !self.is_active() {
+            return 0.0;
+        }
+        let index = self.phase_index.min(config.phases.len().saturating_sub(1));
+        config
+            .phases
+            .get(index)
+            .map(|phase| phase.damage_per_second)
+            .unwrap_or(0.0)
+    }
+
+    /// Seconds until the current stage ends
+    pub fn seconds_remaining(&self, config: &ZoneConfig) -> f32 {
+        let Some(phase) = config.phases.get(self.phase_index) else {
+            return 0.0;
+        };
+        match self.stage {
+            ZoneStage::Waiting => (phase.wait_secs - self.elapsed).max(0.0),
+            ZoneStage::Shrinking => (phase.shrink_secs - self.elapsed).max(0.0),
+            ZoneStage::Inactive | ZoneStage::Final => 0.0,
+        }
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        !self.is_active() || position.distance_squared(self.center) <= self.radius * self.radius
+    }
+
+    /// Whether a circle lies entirely inside the upcoming zone
+    pub fn next_zone_contains_circle(&self, center: Vec2, radius: f32) -> bool {
+        !self.is_active() || center.distance(self.next_center) + radius <= self.next_radius
+    }
+
+    /// Snapshot for replication
+    pub fn to_replicated(&self, config: &ZoneConfig) -> SafeZone {
+        SafeZone {
+            center: self.center,
+            radius: self.radius,
+            next_center: self.next_center,
+            next_radius: self.next_radius,
+            shrinking: self.stage == ZoneStage::Shrinking,
+            seconds_remaining: self.seconds_remaining(config),
+            damage_per_second: self.damage_per_second(config),
+        }
+    }
+
+    /// Choose the next circle so it lies entirely inside the current one
+    fn pick_next_zone(&mut self, config: &ZoneConfig, rng: &mut impl Rng) {
+        let Some(phase) = config.phases.get(self.phase_index) else {
+            self.next_center = self.center;
+            self.next_radius = self.radius;
+            return;
+        };
+
+        self.next_radius = (self.initial_radius * phase.radius_fraction).min(self.radius);
+        let max_offset = (self.radius - self.next_radius).max(0.0);
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let offset = rng.gen_range(0.0..=1.0f32).sqrt() * max_offset;
+        let mut next_center = self.center + Vec2::new(angle.cos(), angle.sin()) * offset;
+
+        // Keep the final circles over playable space
+        let game_config = &*GAME_CONFIG;
+        next_center.x = next_center.x.clamp(
+            self.next_radius.min(game_config.game_width / 2.0),
+            (game_config.game_width - self.next_radius).max(game_config.game_width / 2.0),
+        );
+        next_center.y = next_center.y.clamp(
+            self.next_radius.min(game_config.game_height / 2.0),
+            (game_config.game_height - self.next_radius).max(game_config.game_height / 2.0),
+        );
+        self.next_center = next_center;
+    }
+}
+
+/// Plugin for the shrinking battle-royale zone
+pub struct ZonePlugin;
+
+impl Plugin for ZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ZoneConfig>()
+            .init_resource::<ZoneState>()
+            .init_resource::<GameRng>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_zone_lifecycle,
+                    advance_zone,
+                    zone_damage_system.in_set(PhysicsSet::Collision),
+                )
+                    .chain(),
+            )
+            .add_systems(Update, replicate_zone);
+    }
+}
+
+/// Start the zone when a match begins and tear it down when it ends
+fn update_zone_lifecycle(
+    mut commands: Commands,
+    game_state: Res<GameState>,
+    config: Res<ZoneConfig>,
+    mut zone: ResMut<ZoneState>,
+    mut rng: ResMut<GameRng>,
+) {
+    let in_game = game_state.phase == GamePhase::InGame;
+
+    if in_game && !zone.is_active() && config.enabled && !config.phases.is_empty() {
+        let game_config = &*GAME_CONFIG;
+        zone.start(
+            &config,
+            Vec2::new(game_config.game_width, game_config.game_height),
+            &mut *rng,
+        );
+
+        let entity = commands
+            .spawn((
+                zone.to_replicated(&config),
+                Replicate::default(),
+                Name::new("Safe Zone"),
+            ))
+            .id();
+        zone.entity = Some(entity);
+
+        info!(
+            "Safe zone started at ({:.0}, {:.0}) r={:.0}",
+            zone.center.x, zone.center.y, zone.radius
+        );
+    } else if !in_game && zone.is_active() {
+        if let Some(entity) = zone.entity.take() {
+            commands.safe_despawn(entity);
+        }
+        zone.reset();
+        info!("Safe zone cleared");
+    }
+}
+
+fn advance_zone(
+    config: Res<ZoneConfig>,
+    mut zone: ResMut<ZoneState>,
+    mut rng: ResMut<GameRng>,
+    time: Res<Time>,
+) {
+    if !zone.is_active() {
+        return;
+    }
+
+    let stage = zone.stage;
+    let phase_index = zone.phase_index;
+    zone.tick(&config, time.delta_secs(), &mut *rng);
+
+    if zone.stage != stage || zone.phase_index != phase_index {
+        info!(
+            "Safe zone phase {} {:?}: r={:.0} -> {:.0}",
+            zone.phase_index + 1,
+            zone.stage,
+            zone.radius,
+            zone.next_radius
+        );
+    }
+}
+
+/// Damage players standing outside the safe zone
+#[allow(clippy::type_complexity)]
+fn zone_damage_system(
+    mut commands: Commands,
+    config: Res<ZoneConfig>,
+    zone: Res<ZoneState>,
+    mut players: Query<
+        (
+            Entity,
+            &physics::Player,
+            &Transform,
+            &mut boid_wars_shared::Health,
+        ),
+        (Without<Despawning>, Without<physics::GodMode>),
+    >,
+    mut death_events: EventWriter<physics::PlayerDeathEvent>,
+    time: Res<Time>,
+) {
+    if !zone.is_active() {
+        return;
+    }
+
+    let damage = zone.damage_per_second(&config) * time.delta_secs();
+    if damage <= 0.0 {
+        return;
+    }
+
+    for (entity, player, transform, mut health) in players.iter_mut() {
+        if health.current <= 0.0 || zone.contains(transform.translation.truncate()) {
+            continue;
+        }
+
+        health.current = (health.current - damage).max(0.0);
+        if health.current <= 0.0 {
+            physics::handle_player_death(
+                &mut commands,
+                &mut death_events,
+                entity,
+                player.player_id,
+                None,
+            );
+        }
+    }
+}
+
+/// Push the zone state to the replicated entity at a fixed rate
+fn replicate_zone(
+    config: Res<ZoneConfig>,
+    zone: Res<ZoneState>,
+    mut zones: Query<&mut SafeZone>,
+    time: Res<Time>,
+    mut timer: Local<f32>,
+) {
+    *timer += time.delta_secs();
+    if *timer < config.replication_interval {
+        return;
+    }
+    *timer = 0.0;
+
+    let Some(entity) = zone.entity else {
+        return;
+    };
+    if let Ok(mut safe_zone) = zones.get_mut(entity) {
+        *safe_zone = zone.to_replicated(&config);
+    }
+}
@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+use boid_wars_server::admin::{AdminCommand, AdminConsolePlugin};
+use boid_wars_server::cli::{LogFormat, ServerArgs, Transport};
+use boid_wars_server::config::PhysicsConfig;
+use boid_wars_server::groups::{BoidGroupConfig, BoidIdCounter, GroupIdCounter};
+use boid_wars_server::lobby::{ForceStart, GameState, PlayerSlots, Spectators};
+use boid_wars_server::physics::{self, GodMode, ProjectilePool, ProjectileTemplate};
+use boid_wars_shared::{Boid, BoidGroup, GamePhase, GroupArchetype};
+use clap::Parser;
+use lightyear::connection::id::ClientId;
+
+fn admin_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AdminConsolePlugin { read_stdin: false })
+        .init_resource::<GameState>()
+        .init_resource::<PlayerSlots>()
+        .init_resource::<Spectators>()
+        .init_resource::<ForceStart>()
+        .init_resource::<BoidGroupConfig>()
+        .init_resource::<PhysicsConfig>()
+        .init_resource::<GroupIdCounter>()
+        .init_resource::<BoidIdCounter>()
+        .insert_resource(ProjectilePool::new(
+            ProjectileTemplate {
+                collider_radius: 9.0,
+            },
+            10,
+        ));
+    app
+}
+
+fn run(app: &mut App, command: &str) {
+    let command = AdminCommand::parse(command).unwrap().unwrap();
+    app.world_mut().send_event(command);
+    app.update();
+}
+
+#[test]
+fn test_parse_commands() {
+    assert_eq!(AdminCommand::parse("   "), Ok(None));
+    assert_eq!(
+        AdminCommand::parse("kick 42"),
+        Ok(Some(AdminCommand::Kick(42)))
+    );
+    assert_eq!(
+        AdminCommand::parse("spawn recon 100 200.5"),
+        Ok(Some(AdminCommand::Spawn {
+            archetype: GroupArchetype::Recon {
+                detection_range: 400.0,
+                flee_speed_bonus: 1.3,
+            },
+            position: Vec2::new(100.0, 200.5),
+            size: 10,
+        }))
+    );
+    assert!(matches!(
+        AdminCommand::parse("spawn assault 1 2 30"),
+        Ok(Some(AdminCommand::Spawn { size: 30, .. }))
+    ));
+
+    assert_eq!(
+        AdminCommand::parse("groups"),
+        Ok(Some(AdminCommand::Groups))
+    );
+
+    let wrong = AdminCommand::parse("groups foo").unwrap_err();
+    assert!(
+        wrong.starts_with("wrong arguments for 'groups'"),
+        "{}",
+        wrong
+    );
+
+    for bad in [
+        "kick",
+        "kick bob",
+        "spawn dragons 1 2",
+        "spawn recon 1",
+        "spawn assault 0 0 0",
+        "spawn assault 0 0 4000000000",
+        "fly",
+    ] {
+        assert!(AdminCommand::parse(bad).is_err(), "accepted '{bad}'");
+    }
+}
+
+#[test]
+fn test_spawn_uses_shared_id_counters() {
+    let mut app = admin_app();
+    app.world_mut().resource_mut::<GroupIdCounter>().0 = 7;
+
+    run(&mut app, "spawn defensive 400 300 5");
+
+    let world = app.world_mut();
+    let groups: Vec<u32> = world
+        .query::<&BoidGroup>()
+        .iter(world)
+        .map(|group| group.id)
+        .collect();
+    assert_eq!(groups, vec![7]);
+    assert_eq!(world.query::<&Boid>().iter(world).count(), 5);
+    assert_eq!(world.resource::<GroupIdCounter>().0, 8);
+}
+
+#[test]
+fn test_god_mode_toggles() {
+    let mut app = admin_app();
+    let ship = app
+        .world_mut()
+        .spawn(physics::Player {
+            player_id: 5,
+            ..default()
+        })
+        .id();
+
+    run(&mut app, "god 5");
+    assert!(app.world().entity(ship).contains::<GodMode>());
+
+    run(&mut app, "god 5");
+    assert!(!app.world().entity(ship).contains::<GodMode>());
+}
+
+#[test]
+fn test_force_start_needs_players_and_no_running_match() {
+    let mut app = admin_app();
+
+    run(&mut app, "start");
+    assert!(!app.world().resource::<ForceStart>().0);
+
+    app.world_mut()
+        .resource_mut::<PlayerSlots>()
+        .assign(ClientId::Netcode(1), 4);
+
+    // Results are still up and the round resets the arena when they end
+    app.world_mut().resource_mut::<GameState>().phase = GamePhase::Results;
+    run(&mut app, "start");
+    assert!(!app.world().resource::<ForceStart>().0);
+    assert_eq!(
+        app.world().resource::<GameState>().phase,
+        GamePhase::Results
+    );
+
+    app.world_mut().resource_mut::<GameState>().phase = GamePhase::WaitingForPlayers;
+    run(&mut app, "start");
+    assert!(app.world().resource::<ForceStart>().0);
+    assert_eq!(app.world().resource::<GameState>().phase, GamePhase::Lobby);
+}
+
+#[test]
+fn test_cli_flags_become_setting_overrides() {
+    let args = ServerArgs::try_parse_from([
+        "boid-wars-server",
+        "--bind",
+        "127.0.0.1:9000",
+        "--max-players",
+        "8",
+        "--set",
+        "flocking.max_speed=450",
+        "--transport",
+        "udp",
+        "--tick-rate",
+        "30",
+        "--log-format",
+        "json",
+    ])
+    .unwrap();
+
+    assert_eq!(args.transport, Transport::Udp);
+    assert_eq!(args.log_format, LogFormat::Json);
+    assert!((args.timestep().unwrap().as_secs_f64() - 1.0 / 30.0).abs() < 1e-9);
+    assert_eq!(
+        args.setting_overrides(),
+        vec![
+            ("flocking.max_speed".to_string(), "450".to_string()),
+            (
+                "network.server_bind_addr".to_string(),
+                "127.0.0.1:9000".to_string()
+            ),
+            ("game.max_players".to_string(), "8".to_string()),
+        ]
+    );
+
+    for bad in [
+        vec!["boid-wars-server", "--tick-rate", "0"],
+        vec!["boid-wars-server", "--set", "max_speed"],
+        vec!["boid-wars-server", "--record", "a", "--replay", "b"],
+    ] {
+        assert!(ServerArgs::try_parse_from(bad).is_err());
+    }
+}
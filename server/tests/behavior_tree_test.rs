@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+use boid_wars_server::config::PhysicsConfig;
+use boid_wars_server::groups::behavior_tree::{
+    next_behavior, Action, ActiveBehaviorNode, BehaviorKind, BehaviorTreePlugin, BehaviorTrees,
+    GroupFacts, Threat,
+};
+use boid_wars_server::groups::BoidGroupConfig;
+use boid_wars_server::physics::BoidAggression;
+use boid_wars_shared::{
+    ArenaZone, BoidGroup, Formation, GroupArchetype, GroupBehavior, Position, TerritoryData,
+};
+use std::collections::HashSet;
+
+const HOME: Vec2 = Vec2::new(800.0, 600.0);
+
+fn recon() -> GroupArchetype {
+    GroupArchetype::Recon {
+        detection_range: 400.0,
+        flee_speed_bonus: 1.3,
+    }
+}
+
+fn assault() -> GroupArchetype {
+    GroupArchetype::Assault {
+        aggression_multiplier: 1.0,
+        preferred_range: 150.0,
+    }
+}
+
+fn defensive() -> GroupArchetype {
+    GroupArchetype::Defensive {
+        protection_radius: 400.0,
+        retreat_threshold: 0.4,
+    }
+}
+
+fn group(archetype: GroupArchetype, behavior_state: GroupBehavior) -> BoidGroup {
+    BoidGroup {
+        id: 1,
+        archetype,
+        home_territory: TerritoryData {
+            center: HOME,
+            radius: 200.0,
+            zone: ArenaZone::Middle,
+            patrol_points: vec![HOME - Vec2::X * 100.0, HOME + Vec2::X * 100.0],
+            neighboring_territories: vec![],
+        },
+        current_formation: Formation::default_for_archetype(&archetype),
+        behavior_state,
+        active_shooters: HashSet::new(),
+        max_shooters: 1,
+        initial_size: 10,
+    }
+}
+
+fn facts(behavior: BehaviorKind) -> GroupFacts {
+    GroupFacts {
+        behavior,
+        target_present: true,
+        threat: None,
+        provoked: false,
+        losses: 0.0,
+        retreat_threshold: 0.3,
+        rally_distance: None,
+    }
+}
+
+fn decide(trees: &BehaviorTrees, archetype: GroupArchetype, facts: &GroupFacts) -> String {
+    trees
+        .tree(&archetype)
+        .decide(facts)
+        .map(|decision| decision.describe())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_bundled_trees_are_valid() {
+    let trees = BehaviorTrees::default();
+    assert_eq!(trees.validate(), Ok(()));
+}
+
+#[test]
+fn test_patrols_engage_only_when_provoked() {
+    let trees = BehaviorTrees::default();
+    let mut patrolling = facts(BehaviorKind::Patrolling);
+    patrolling.threat = Some(Threat {
+        player_id: 7,
+        distance: 200.0,
+    });
+
+    assert_eq!(decide(&trees, assault(), &patrolling), "assault > Hold");
+
+    patrolling.provoked = true;
+    assert_eq!(
+        decide(&trees, assault(), &patrolling),
+        "assault > answer fire > Engage"
+    );
+}
+
+#[test]
+fn test_engaged_groups_drop_lost_targets_and_break_off() {
+    let trees = BehaviorTrees::default();
+
+    let mut engaging = facts(BehaviorKind::Engaging);
+    engaging.target_present = false;
+    assert_eq!(
+        decide(&trees, recon(), &engaging),
+        "recon > drop lost target > Patrol"
+    );
+
+    // 35% losses: past the usual 30%, short of Defensive's own 40%
+    let mut engaging = facts(BehaviorKind::Engaging);
+    engaging.losses = 0.35;
+    assert_eq!(
+        decide(&trees, assault(), &engaging),
+        "assault > break off > Retreat"
+    );
+    engaging.retreat_threshold = 0.4;
+    assert_eq!(decide(&trees, defensive(), &engaging), "defensive > Hold");
+
+    let mut retreating = facts(BehaviorKind::Retreating);
+    retreating.rally_distance = Some(50.0);
+    assert_eq!(
+        decide(&trees, defensive(), &retreating),
+        "defensive > regroup > Defend"
+    );
+}
+
+#[test]
+fn test_actions_switch_behavior() {
+    let retreating = group(
+        recon(),
+        GroupBehavior::Retreating {
+            rally_point: HOME + Vec2::Y * 50.0,
+            speed_multiplier: 1.0,
+        },
+    );
+
+    // Recon flees faster
+    let patrolling = group(
+        recon(),
+        GroupBehavior::Patrolling {
+            route: vec![],
+            current_waypoint: 0,
+        },
+    );
+    assert_eq!(
+        next_behavior(
+            &Action::Retreat {
+                speed_multiplier: 1.5
+            },
+            &patrolling,
+            &facts(BehaviorKind::Patrolling)
+        ),
+        Some(GroupBehavior::Retreating {
+            rally_point: HOME,
+            speed_multiplier: 1.5 * 1.3,
+        })
+    );
+
+    // Defending holds where the retreat ended
+    assert_eq!(
+        next_behavior(
+            &Action::Defend { radius: 200.0 },
+            &retreating,
+            &facts(BehaviorKind::Retreating)
+        ),
+        Some(GroupBehavior::Defending {
+            position: HOME + Vec2::Y * 50.0,
+            radius: 200.0,
+        })
+    );
+
+    // Already on it: nothing to change
+    let mut engaging_facts = facts(BehaviorKind::Engaging);
+    engaging_facts.threat = Some(Threat {
+        player_id: 7,
+        distance: 100.0,
+    });
+    let engaging = group(
+        assault(),
+        GroupBehavior::Engaging {
+            primary_target: 7,
+            secondary_targets: vec![],
+        },
+    );
+    assert_eq!(
+        next_behavior(&Action::Engage, &engaging, &engaging_facts),
+        None
+    );
+    assert_eq!(
+        next_behavior(&Action::Hold, &engaging, &engaging_facts),
+        None
+    );
+}
+
+#[test]
+fn test_decisions_match_their_description() {
+    let trees = BehaviorTrees::default();
+    let mut engaging = facts(BehaviorKind::Engaging);
+    engaging.target_present = false;
+    let decision = trees.tree(&recon()).decide(&engaging).unwrap();
+
+    assert!(decision.is_described_by(&decision.describe()));
+    assert!(decision.is_described_by("recon > drop lost target > Patrol"));
+    assert!(!decision.is_described_by("recon > Patrol"));
+    assert!(!decision.is_described_by("recon > drop lost target > Patrol > Hold"));
+    assert!(!decision.is_described_by(""));
+}
+
+#[test]
+fn test_custom_trees_are_checked() {
+    let trees = BehaviorTrees::from_ron(
+        r#"(
+            recon: Act(Hold),
+            assault: Sequence(name: "", children: [Act(Defend(radius: -1.0))]),
+            defensive: Selector(name: "empty", children: []),
+        )"#,
+    )
+    .unwrap();
+    let entries: Vec<String> = trees
+        .validate()
+        .unwrap_err()
+        .into_iter()
+        .map(|error| error.entry)
+        .collect();
+    assert_eq!(
+        entries,
+        vec!["assault.name", "assault.children[0]", "defensive.children"]
+    );
+
+    assert!(BehaviorTrees::from_ron("(recon: Act(Fly))").is_err());
+}
+
+#[test]
+fn test_groups_record_the_deciding_node() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<PhysicsConfig>()
+        .init_resource::<BoidGroupConfig>()
+        .init_resource::<BoidAggression>()
+        .add_plugins(BehaviorTreePlugin);
+
+    // Chasing a player who has left
+    let entity = app
+        .world_mut()
+        .spawn((
+            group(
+                assault(),
+                GroupBehavior::Engaging {
+                    primary_target: 99,
+                    secondary_targets: vec![],
+                },
+            ),
+            Position(HOME),
+        ))
+        .id();
+
+    app.update();
+    let world = app.world();
+    assert!(matches!(
+        world.get::<BoidGroup>(entity).unwrap().behavior_state,
+        GroupBehavior::Patrolling { .. }
+    ));
+    assert_eq!(
+        world.get::<ActiveBehaviorNode>(entity).unwrap().0,
+        "assault > drop lost target > Patrol"
+    );
+
+    app.update();
+    assert_eq!(
+        app.world().get::<ActiveBehaviorNode>(entity).unwrap().0,
+        "assault > Hold"
+    );
+}
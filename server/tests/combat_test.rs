@@ -0,0 +1,187 @@
+mod harness;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use boid_wars_server::combat::{CombatConfig, Faction, RamCooldowns};
+use boid_wars_server::physics::{GameCollisionGroups, Projectile, ProjectileType};
+use boid_wars_server::position_sync::SyncPosition;
+use boid_wars_server::settings::Settings;
+use boid_wars_shared::{Boid, Health, Position, GAME_CONFIG};
+use harness::Harness;
+use std::time::Duration;
+
+#[test]
+fn test_default_rules_keep_boids_off_each_other() {
+    let rules = CombatConfig::default();
+    let (alice, bob) = (Faction::Player(1), Faction::Player(2));
+    let (red, blue) = (Faction::Swarm(Some(1)), Faction::Swarm(Some(2)));
+
+    assert!(rules.can_damage(alice, bob));
+    assert!(rules.can_damage(alice, red));
+    assert!(rules.can_damage(red, alice));
+    assert!(!rules.can_damage(red, red));
+    assert!(!rules.can_damage(red, blue));
+    // A shot whose boid has died is still a swarm shot
+    assert!(!rules.can_damage(Faction::Swarm(None), blue));
+}
+
+#[test]
+fn test_friendly_fire_is_separate_from_swarm_infighting() {
+    let rules = CombatConfig {
+        player_vs_player: false,
+        swarm_friendly_fire: true,
+        ..Default::default()
+    };
+    let (red, blue) = (Faction::Swarm(Some(1)), Faction::Swarm(Some(2)));
+
+    assert!(!rules.can_damage(Faction::Player(1), Faction::Player(2)));
+    assert!(rules.can_damage(red, red));
+    assert!(!rules.can_damage(red, blue));
+
+    let infighting = CombatConfig {
+        swarm_vs_swarm: true,
+        ..Default::default()
+    };
+    assert!(infighting.can_damage(red, blue));
+    assert!(!infighting.can_damage(red, red));
+}
+
+#[test]
+fn test_ramming_needs_speed_and_respects_cooldown() {
+    let rules = CombatConfig::default();
+    assert_eq!(rules.ram_damage(rules.ram_min_speed - 1.0), None);
+    assert_eq!(
+        rules.ram_damage(rules.ram_min_speed),
+        Some((rules.ram_damage_to_boid, rules.ram_damage_to_player))
+    );
+
+    let (ship, boid, other) = (
+        Entity::from_raw(1),
+        Entity::from_raw(2),
+        Entity::from_raw(3),
+    );
+    let cooldown = rules.ram_cooldown;
+    let mut rams = RamCooldowns::default();
+
+    assert!(rams.try_ram(ship, boid, Duration::ZERO, cooldown));
+    assert!(!rams.try_ram(ship, boid, cooldown / 2, cooldown));
+    assert!(rams.try_ram(ship, other, cooldown / 2, cooldown));
+    assert!(rams.try_ram(ship, boid, cooldown, cooldown));
+
+    rams.expire(cooldown * 2, cooldown);
+    assert!(rams.is_empty());
+}
+
+#[test]
+fn test_ships_and_boids_collide() {
+    let player = GameCollisionGroups::player();
+    let boid = GameCollisionGroups::boid();
+    let boid_projectile = GameCollisionGroups::boid_projectile();
+
+    assert!(player.filters.contains(boid.memberships));
+    assert!(boid.filters.contains(player.memberships));
+    assert!(boid.filters.contains(boid.memberships));
+    // Boid shots reach boids; CombatConfig decides whether they hurt
+    assert!(boid_projectile.filters.contains(boid.memberships));
+    assert!(boid.filters.contains(boid_projectile.memberships));
+}
+
+#[test]
+fn test_combat_section_is_layered() {
+    let file = r#"
+        [combat]
+        swarm_friendly_fire = true
+        ram_cooldown = 0.25
+    "#;
+    let settings = Settings::layered(Some(("test.toml", file)), vec![], &[]).unwrap();
+    assert!(settings.combat.swarm_friendly_fire);
+    assert_eq!(settings.combat.ram_cooldown, Duration::from_millis(250));
+
+    let negative = [("combat.ram_damage_to_boid".to_string(), "-5".to_string())];
+    assert!(Settings::layered(None, vec![], &negative).is_err());
+}
+
+#[test]
+fn test_full_speed_ship_rams_a_boid() {
+    let mut harness = Harness::new(2);
+    harness.start_match();
+
+    // Park a boid just ahead of a ship, towards the middle of the arena
+    let ship = harness.server_ship(0).unwrap();
+    let start = harness.server.world().get::<Position>(ship).unwrap().0;
+    let center = Vec2::new(GAME_CONFIG.game_width, GAME_CONFIG.game_height) / 2.0;
+    let heading = Vec2::new((center.x - start.x).signum(), 0.0);
+    let boid_position = start + heading * 80.0;
+    let boid = harness
+        .server
+        .world_mut()
+        .spawn((
+            Boid { id: 9_999 },
+            Health::default(),
+            RigidBody::Dynamic,
+            Collider::ball(8.0),
+            GameCollisionGroups::boid(),
+            ActiveEvents::COLLISION_EVENTS,
+            Transform::from_translation(boid_position.extend(0.0)),
+            Position(boid_position),
+            GravityScale(0.0),
+            SyncPosition,
+        ))
+        .id();
+
+    // Fly flat out into it without firing
+    let rammed = harness.step_until(Duration::from_secs(2), |h| {
+        h.client(0).send_input(heading, heading, false);
+        h.server
+            .world()
+            .get::<Health>(boid)
+            .is_none_or(|health| health.current < health.max)
+    });
+    assert!(rammed, "a full-speed ram should hurt the boid");
+}
+
+/// A player shot parked on the ship, fired by `shooter` who has since left the arena
+fn spawn_orphaned_shot(harness: &mut Harness, shooter: u64, at: Vec2) {
+    harness.server.world_mut().spawn((
+        Projectile {
+            damage: 10.0,
+            owner: None,
+            owner_faction: Some(Faction::Player(shooter)),
+            projectile_type: ProjectileType::Basic,
+            lifetime: Timer::from_seconds(1.0, TimerMode::Once),
+            speed: 0.0,
+        },
+        RigidBody::Dynamic,
+        Collider::ball(4.0),
+        Sensor,
+        GameCollisionGroups::projectile(),
+        ActiveEvents::COLLISION_EVENTS,
+        Velocity::zero(),
+        Transform::from_translation(at.extend(0.0)),
+        GravityScale(0.0),
+    ));
+}
+
+#[test]
+fn test_shots_keep_their_faction_after_the_shooter_dies() {
+    let mut harness = Harness::new(2);
+    harness.start_match();
+    let shooter = harness.client(1).player_id();
+    let ship = harness.server_ship(0).unwrap();
+    let at = harness.server.world().get::<Position>(ship).unwrap().0;
+    let health = |harness: &Harness| harness.server.world().get::<Health>(ship).unwrap().current;
+    let full = health(&harness);
+
+    harness.server.insert_resource(CombatConfig {
+        player_vs_player: false,
+        ..default()
+    });
+    spawn_orphaned_shot(&mut harness, shooter, at);
+    harness.step_for(Duration::from_millis(200));
+    assert_eq!(health(&harness), full, "player shots are off");
+
+    harness.server.insert_resource(CombatConfig::default());
+    spawn_orphaned_shot(&mut harness, shooter, at);
+    let hit = harness.step_until(Duration::from_millis(500), |h| health(h) < full);
+    assert!(hit, "the same shot lands once player shots are on");
+}
@@ -0,0 +1,28 @@
+use boid_wars_shared::{
+    install_shared, ClientConfig, GameConfig, NetworkConfig, ServerConfig, GAME_CONFIG,
+};
+
+// Each test binary is its own process, so this one owns the global config slots
+
+#[test]
+fn test_install_is_all_or_nothing() {
+    // Something read the game config before the layered one was installed
+    let _ = GAME_CONFIG.game_width;
+
+    let install = || {
+        install_shared(
+            NetworkConfig::default(),
+            GameConfig::default(),
+            ServerConfig::default(),
+            ClientConfig::default(),
+        )
+    };
+    let errors = install().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].key, "game");
+
+    // The failed install left the other sections alone
+    let errors = install().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].key, "game");
+}
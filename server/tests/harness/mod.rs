@@ -0,0 +1,377 @@
+//! In-process server and clients for end-to-end tests.
+//!
+//! The server `App` and every client `App` run in the test's thread, connected through
+//! lightyear's crossbeam channel transport instead of sockets. All apps share one
+//! manual clock, so [`Harness::step`] advances the whole session by exactly one frame.
+
+#![allow(dead_code)]
+
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use boid_wars_server::health_sync::HealthSyncPlugin;
+use boid_wars_server::interest::InterestPlugin;
+use boid_wars_server::lobby::{GameState, LobbyPlugin};
+use boid_wars_server::map::MapPlugin;
+use boid_wars_server::physics::PhysicsPlugin;
+use boid_wars_server::position_sync::PositionSyncPlugin;
+use boid_wars_server::settings::Settings;
+use boid_wars_server::spatial_grid::SpatialGridPlugin;
+use boid_wars_shared::*;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use lightyear::connection::id::ClientId;
+use lightyear::prelude::{client, server, Channel, Message, SharedConfig};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Once;
+use std::time::Duration;
+
+/// Length of one harness frame
+pub const FRAME: Duration = Duration::from_micros(16_667);
+
+/// Player slots in every harness session; a third client is turned away
+pub const MAX_PLAYERS: u8 = 2;
+
+/// Harness sessions share the process-wide game config, so install it once:
+/// two players start a match and nobody may spectate.
+fn install_settings() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let overrides = [
+            ("game.max_players", MAX_PLAYERS.to_string()),
+            ("game.min_players", MAX_PLAYERS.to_string()),
+            ("game.max_spectators", "0".to_string()),
+        ]
+        .map(|(key, value)| (key.to_string(), value));
+        let settings = Settings::layered(None, vec![], &overrides).expect("harness settings");
+        settings
+            .install_globals()
+            .expect("harness settings installed before any config was read");
+    });
+}
+
+/// Every message of one type a client has received
+#[derive(Resource)]
+pub struct Inbox<M: Send + Sync + 'static>(pub Vec<M>);
+
+impl<M: Send + Sync + 'static> Default for Inbox<M> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+fn record<M: Message + Clone>(
+    mut events: EventReader<lightyear::client::message::ReceiveMessage<M>>,
+    mut inbox: ResMut<Inbox<M>>,
+) {
+    inbox
+        .0
+        .extend(events.read().map(|event| event.message.clone()));
+}
+
+/// Keep every message of type `M` a client receives in its [`Inbox<M>`]
+fn add_inbox<M: Message + Clone>(app: &mut App) {
+    app.init_resource::<Inbox<M>>()
+        .add_systems(Update, record::<M>);
+}
+
+/// One connected client app
+pub struct TestClient {
+    pub app: App,
+    /// Netcode client id this client authenticated with
+    pub client_id: u64,
+    sequence: u32,
+}
+
+impl TestClient {
+    /// Player id the server gives this client's ship
+    pub fn player_id(&self) -> u64 {
+        ClientId::Netcode(self.client_id).to_bits()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.app
+            .world()
+            .get_resource::<State<client::NetworkingState>>()
+            .is_some_and(|state| *state.get() == client::NetworkingState::Connected)
+    }
+
+    pub fn inbox<M: Send + Sync + 'static>(&self) -> &[M] {
+        &self.app.world().resource::<Inbox<M>>().0
+    }
+
+    /// Latest phase the server announced to this client
+    pub fn phase(&self) -> Option<GamePhase> {
+        self.inbox::<GameStateUpdate>()
+            .last()
+            .map(|update| update.phase.clone())
+    }
+
+    /// Replicated position of this client's own ship, if it has one
+    pub fn own_ship(&mut self) -> Option<Vec2> {
+        let player_id = self.player_id();
+        let world = self.app.world_mut();
+        world
+            .query::<(&Player, &Position)>()
+            .iter(world)
+            .find(|(player, _)| player.id == player_id)
+            .map(|(_, position)| position.0)
+    }
+
+    /// Replicated ships this client can see, by player id
+    pub fn ships(&mut self) -> Vec<u64> {
+        let world = self.app.world_mut();
+        world
+            .query::<&Player>()
+            .iter(world)
+            .map(|player| player.id)
+            .collect()
+    }
+
+    pub fn send<C: Channel, M: Message>(&mut self, message: &M) {
+        self.app
+            .world_mut()
+            .resource_mut::<client::ConnectionManager>()
+            .send_message::<C, M>(message)
+            .expect("client message sent");
+    }
+
+    /// Send the next input in sequence, like the browser client does every frame
+    pub fn send_input(&mut self, movement: Vec2, aim: Vec2, fire: bool) {
+        self.sequence += 1;
+        let input = PlayerInput::new(movement, aim, fire).with_sequence(self.sequence);
+        self.send::<UnreliableChannel, _>(&input);
+    }
+}
+
+/// A server and its clients, stepped together
+pub struct Harness {
+    pub server: App,
+    pub clients: Vec<TestClient>,
+    now: Instant,
+}
+
+impl Harness {
+    /// Start a server and `client_count` clients that begin connecting straight away
+    pub fn new(client_count: usize) -> Self {
+        Self::with_server(client_count, |_| {})
+    }
+
+    /// Like [`Harness::new`], with `configure` adding plugins to the server first
+    pub fn with_server(client_count: usize, configure: impl FnOnce(&mut App)) -> Self {
+        install_settings();
+
+        let mut server_channels = Vec::with_capacity(client_count);
+        let mut client_transports = Vec::with_capacity(client_count);
+        for i in 0..client_count {
+            // The server tells clients apart by address
+            let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 10_000 + i as u16);
+            let (to_client, from_server): (Sender<Vec<u8>>, Receiver<Vec<u8>>) = unbounded();
+            let (to_server, from_client) = unbounded();
+            server_channels.push((addr, from_client, to_client));
+            client_transports.push(client::ClientTransport::LocalChannel {
+                recv: from_server,
+                send: to_server,
+            });
+        }
+
+        let now = Instant::now();
+        let mut server = server_app(server_channels, configure);
+        server.insert_resource(TimeUpdateStrategy::ManualInstant(now));
+        server.update();
+
+        let clients = client_transports
+            .into_iter()
+            .enumerate()
+            .map(|(i, transport)| {
+                let client_id = 1_000 + i as u64;
+                let mut app = client_app(client_id, transport);
+                app.insert_resource(TimeUpdateStrategy::ManualInstant(now));
+                TestClient {
+                    app,
+                    client_id,
+                    sequence: 0,
+                }
+            })
+            .collect();
+
+        Self {
+            server,
+            clients,
+            now,
+        }
+    }
+
+    /// Advance every app by one frame: clients send first, then the server answers
+    pub fn step(&mut self) {
+        self.now += FRAME;
+        for client in &mut self.clients {
+            client
+                .app
+                .insert_resource(TimeUpdateStrategy::ManualInstant(self.now));
+            client.app.update();
+        }
+        self.server
+            .insert_resource(TimeUpdateStrategy::ManualInstant(self.now));
+        self.server.update();
+    }
+
+    pub fn step_for(&mut self, duration: Duration) {
+        let frames = duration.as_micros().div_ceil(FRAME.as_micros());
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Step until `done` holds, giving up after `timeout` of simulated time
+    pub fn step_until(
+        &mut self,
+        timeout: Duration,
+        mut done: impl FnMut(&mut Self) -> bool,
+    ) -> bool {
+        let frames = timeout.as_micros().div_ceil(FRAME.as_micros());
+        for _ in 0..frames {
+            if done(self) {
+                return true;
+            }
+            self.step();
+        }
+        done(self)
+    }
+
+    pub fn client(&mut self, index: usize) -> &mut TestClient {
+        &mut self.clients[index]
+    }
+
+    /// Server-side match phase
+    pub fn phase(&self) -> GamePhase {
+        self.server.world().resource::<GameState>().phase.clone()
+    }
+
+    /// Wait for every client to finish the netcode handshake
+    pub fn connect(&mut self) {
+        let connected = self.step_until(Duration::from_secs(5), |harness| {
+            harness.clients.iter().all(TestClient::is_connected)
+        });
+        assert!(connected, "clients did not connect");
+    }
+
+    /// Connect, ready everyone up in the lobby and wait for the match to start
+    pub fn start_match(&mut self) {
+        self.connect();
+        let in_lobby = self.step_until(Duration::from_secs(2), |harness| {
+            harness.phase() == GamePhase::Lobby
+        });
+        assert!(in_lobby, "server never entered the lobby");
+
+        for client in &mut self.clients {
+            client.send::<ReliableChannel, _>(&PlayerReady);
+        }
+        let started = self.step_until(Duration::from_secs(2), |harness| {
+            harness.phase() == GamePhase::InGame
+        });
+        assert!(started, "match did not start");
+    }
+
+    /// Server entity of a client's ship
+    pub fn server_ship(&mut self, index: usize) -> Option<Entity> {
+        let player_id = self.clients[index].player_id();
+        let world = self.server.world_mut();
+        world
+            .query::<(Entity, &Player)>()
+            .iter(world)
+            .find(|(_, player)| player.id == player_id)
+            .map(|(entity, _)| entity)
+    }
+}
+
+/// The harness server with no clients, for runs driven without a network
+pub fn headless_server(configure: impl FnOnce(&mut App)) -> App {
+    install_settings();
+    server_app(vec![], configure)
+}
+
+#[allow(clippy::type_complexity)]
+fn server_app(
+    channels: Vec<(SocketAddr, Receiver<Vec<u8>>, Sender<Vec<u8>>)>,
+    configure: impl FnOnce(&mut App),
+) -> App {
+    use server::*;
+
+    let network_config = &*NETWORK_CONFIG;
+    let netcode = NetcodeConfig::default()
+        .with_protocol_id(network_config.protocol_id)
+        .with_key(network_config.dev_key);
+    let io = IoConfig::from_transport(ServerTransport::Channels { channels });
+    let config = server::ServerConfig {
+        shared: SharedConfig::default(),
+        net: vec![NetConfig::Netcode {
+            config: netcode,
+            io,
+        }],
+        packet: Default::default(),
+        replication: Default::default(),
+        ping: Default::default(),
+    };
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, TransformPlugin))
+        .add_plugins(ServerPlugins::new(config))
+        .add_plugins(ProtocolPlugin)
+        .add_plugins(SpatialGridPlugin)
+        .add_plugins(PhysicsPlugin {
+            enable_debug_render: false,
+        })
+        .add_plugins(PositionSyncPlugin)
+        .add_plugins(HealthSyncPlugin)
+        .add_plugins(MapPlugin)
+        .add_plugins(InterestPlugin)
+        .add_plugins(LobbyPlugin)
+        .add_systems(Startup, |mut commands: Commands| {
+            commands.queue(|world: &mut World| world.start_server());
+        });
+    configure(&mut app);
+    app.finish();
+    app.cleanup();
+    app
+}
+
+fn client_app(client_id: u64, transport: client::ClientTransport) -> App {
+    use client::*;
+
+    let network_config = &*NETWORK_CONFIG;
+    let config = client::ClientConfig {
+        shared: SharedConfig::default(),
+        net: NetConfig::Netcode {
+            config: NetcodeConfig::default(),
+            io: IoConfig::from_transport(transport),
+            auth: Authentication::Manual {
+                server_addr: lightyear::transport::LOCAL_SOCKET,
+                client_id,
+                private_key: network_config.dev_key,
+                protocol_id: network_config.protocol_id,
+            },
+        },
+        replication: Default::default(),
+        packet: Default::default(),
+        ping: Default::default(),
+        interpolation: Default::default(),
+        prediction: Default::default(),
+        sync: Default::default(),
+    };
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin))
+        .add_plugins(ClientPlugins::new(config))
+        .add_plugins(ProtocolPlugin);
+    add_inbox::<GameStateUpdate>(&mut app);
+    add_inbox::<ServerFullMessage>(&mut app);
+    add_inbox::<SpectatorStatus>(&mut app);
+    add_inbox::<HealthChangeEvent>(&mut app);
+    app.add_systems(Startup, |mut commands: Commands| {
+        commands.queue(|world: &mut World| world.connect_client());
+    });
+    app.finish();
+    app.cleanup();
+    app
+}
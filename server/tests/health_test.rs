@@ -0,0 +1,182 @@
+mod harness;
+
+use boid_wars_server::health::{
+    respond, spawn_http_server, ClientMetrics, HealthPlugin, HealthState, MetricsSnapshot,
+    PoolUsage, TickPercentiles,
+};
+use boid_wars_shared::{ClientBandwidth, UnreliableChannel};
+use harness::Harness;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+#[test]
+fn test_liveness_needs_a_recent_heartbeat() {
+    let state = HealthState::default();
+    state.heartbeat();
+    assert_eq!(respond(&state, "/healthz").0, 200);
+
+    // Last ran long before the liveness timeout
+    state.last_update_ms.store(0, Ordering::SeqCst);
+    let stale = HealthState {
+        started: state.started - Duration::from_secs(60),
+        ..state.clone()
+    };
+    assert_eq!(respond(&stale, "/healthz").0, 503);
+
+    state.heartbeat();
+    state.set_healthy(false);
+    assert_eq!(respond(&state, "/healthz").0, 503);
+}
+
+#[test]
+fn test_readiness_follows_ready_flag() {
+    let state = HealthState::default();
+    state.heartbeat();
+    assert_eq!(respond(&state, "/readyz").0, 503);
+
+    state.set_ready(true);
+    assert_eq!(respond(&state, "/readyz").0, 200);
+    assert_eq!(respond(&state, "/nope").0, 404);
+}
+
+#[test]
+fn test_tick_percentiles_use_nearest_rank() {
+    let samples = (1..=100).map(Duration::from_millis);
+    let tick = TickPercentiles::from_samples(samples);
+    assert_eq!(tick.p50, Duration::from_millis(50));
+    assert_eq!(tick.p95, Duration::from_millis(95));
+    assert_eq!(tick.p99, Duration::from_millis(99));
+    assert_eq!(tick.count, 100);
+
+    assert_eq!(
+        TickPercentiles::from_samples([]),
+        TickPercentiles::default()
+    );
+}
+
+#[test]
+fn test_metrics_render_as_prometheus_text() {
+    let state = HealthState::default();
+    state.heartbeat();
+    state.update_player_count(2);
+    state.publish(MetricsSnapshot {
+        boids: 40,
+        projectile_pool: PoolUsage {
+            active: 25,
+            available: 75,
+            max_size: 100,
+        },
+        clients: vec![
+            ClientMetrics {
+                client_id: 7,
+                role: "player",
+                replicated_entities: 12,
+                bandwidth: Some(ClientBandwidth {
+                    received_bytes_per_sec: 5000.0,
+                    sent_bytes_per_sec: 800.0,
+                }),
+            },
+            ClientMetrics {
+                client_id: 8,
+                role: "spectator",
+                replicated_entities: 3,
+                bandwidth: None,
+            },
+        ],
+        ..Default::default()
+    });
+
+    let (status, content_type, body) = respond(&state, "/metrics");
+    assert_eq!(status, 200);
+    assert!(content_type.starts_with("text/plain"));
+    for line in [
+        "boidwars_up 1",
+        "boidwars_players 2",
+        "boidwars_boids 40",
+        "boidwars_projectiles 25",
+        "boidwars_pool_utilization{pool=\"projectile\"} 0.25",
+        "boidwars_client_replicated_entities{client=\"7\",role=\"player\"} 12",
+        "boidwars_client_received_bytes_per_second{client=\"7\",role=\"player\"} 5000",
+        "boidwars_client_sent_bytes_per_second{client=\"7\",role=\"player\"} 800",
+    ] {
+        assert!(
+            body.lines().any(|l| l == line),
+            "missing '{line}' in\n{body}"
+        );
+    }
+    // No report yet, so no bandwidth sample rather than a zero
+    assert!(!body.contains("bytes_per_second{client=\"8\""));
+}
+
+#[test]
+fn test_http_roundtrip() {
+    let state = HealthState::default();
+    state.heartbeat();
+    let addr = spawn_http_server("127.0.0.1:0", state).unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+    assert!(response.ends_with("\r\n\r\nok\n"), "{}", response);
+}
+
+#[test]
+fn test_oversized_request_line_is_dropped() {
+    let state = HealthState::default();
+    state.heartbeat();
+    let addr = spawn_http_server("127.0.0.1:0", state).unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut request = b"GET /".to_vec();
+    request.extend(std::iter::repeat_n(b'a', 4096));
+    // The server may hang up before taking all of it
+    let _ = stream.write_all(&request);
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    assert!(response.is_empty(), "{}", response);
+
+    // The accept loop is free for the next request
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"GET /healthz HTTP/1.1\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+}
+
+#[test]
+fn test_client_bandwidth_reports_reach_metrics() {
+    let mut harness = Harness::with_server(2, |app| {
+        app.add_plugins(HealthPlugin { bind_addr: None });
+    });
+    harness.connect();
+
+    let bandwidth = ClientBandwidth {
+        received_bytes_per_sec: 4000.0,
+        sent_bytes_per_sec: 600.0,
+    };
+    harness.client(0).send::<UnreliableChannel, _>(&bandwidth);
+    let client_id = harness.client(0).player_id();
+    let silent_id = harness.client(1).player_id();
+
+    let line = format!(
+        "boidwars_client_received_bytes_per_second{{client=\"{client_id}\",role=\"player\"}} 4000"
+    );
+    let reported = harness.step_until(Duration::from_secs(3), |harness| {
+        let state = harness.server.world().resource::<HealthState>();
+        state.render_metrics().lines().any(|l| l == line)
+    });
+    let body = harness
+        .server
+        .world()
+        .resource::<HealthState>()
+        .render_metrics();
+    assert!(reported, "missing '{line}' in\n{body}");
+    assert!(!body.contains(&format!("bytes_per_second{{client=\"{silent_id}\"")));
+}
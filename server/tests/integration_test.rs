@@ -1,8 +1,172 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn server_starts() {
-        // Basic test to ensure the server module compiles
-        assert_eq!(2 + 2, 4);
+mod harness;
+
+use bevy::prelude::*;
+use boid_wars_shared::{
+    GamePhase, GameStateUpdate, HealthChangeEvent, MovementRules, Player, Position,
+    ServerFullMessage, SpectateReason, SpectatorStatus,
+};
+use harness::{Harness, MAX_PLAYERS};
+use lightyear::prelude::Tick;
+use lightyear::shared::replication::delta::DeltaComponentHistory;
+use std::time::Duration;
+
+#[test]
+fn test_clients_ready_up_into_a_match() {
+    let mut harness = Harness::new(2);
+    harness.connect();
+
+    let in_lobby = harness.step_until(Duration::from_secs(2), |h| {
+        h.clients
+            .iter()
+            .all(|client| client.phase() == Some(GamePhase::Lobby))
+    });
+    assert!(in_lobby, "clients were never told about the lobby");
+    assert_eq!(harness.phase(), GamePhase::Lobby);
+
+    harness.start_match();
+
+    // Every client hears the match start and sees its own ship
+    let spawned = harness.step_until(Duration::from_secs(2), |h| {
+        h.clients
+            .iter_mut()
+            .all(|client| client.phase() == Some(GamePhase::InGame) && client.own_ship().is_some())
+    });
+    assert!(spawned, "ships were not replicated to their clients");
+
+    let update: &GameStateUpdate = harness.clients[0].inbox().last().unwrap();
+    assert_eq!(update.player_count, MAX_PLAYERS);
+    assert!(update.players.iter().all(|status| status.ready));
+    // Clients predict with the server's movement rules, not their own config
+    assert_eq!(
+        update.movement,
+        *harness.server.world().resource::<MovementRules>()
+    );
+}
+
+#[test]
+fn test_input_moves_the_replicated_ship() {
+    let mut harness = Harness::new(2);
+    harness.start_match();
+    let replicated =
+        harness.step_until(Duration::from_secs(2), |h| h.client(0).own_ship().is_some());
+    assert!(replicated);
+    let start = harness.client(0).own_ship().unwrap();
+
+    for _ in 0..60 {
+        harness.client(0).send_input(Vec2::X, Vec2::X, false);
+        harness.step();
     }
+    let moved = harness.step_until(Duration::from_secs(1), |h| {
+        h.client(0)
+            .own_ship()
+            .is_some_and(|position| position.distance(start) > 50.0)
+    });
+    assert!(moved, "ship stayed near {start:?}");
+
+    // The other player did not touch their controls
+    let idle = harness.server_ship(1).unwrap();
+    let idle_start = harness.server.world().get::<Position>(idle).unwrap().0;
+    harness.step_for(Duration::from_millis(200));
+    let idle_now = harness.server.world().get::<Position>(idle).unwrap().0;
+    assert!(idle_now.distance(idle_start) < 1.0);
+}
+
+/// Latest tick of the own ship's position the client rebuilt from a delta message
+fn latest_position_delta(harness: &mut Harness) -> Option<Tick> {
+    let player_id = harness.client(0).player_id();
+    let world = harness.client(0).app.world_mut();
+    world
+        .query::<(&Player, &DeltaComponentHistory<Position>)>()
+        .iter(world)
+        .find(|(player, _)| player.id == player_id)
+        .and_then(|(_, history)| history.buffer.keys().next_back().copied())
+}
+
+#[test]
+fn test_ship_movement_replicates_as_deltas() {
+    let mut harness = Harness::new(2);
+    harness.start_match();
+    let replicated = harness.step_until(Duration::from_secs(2), |h| {
+        latest_position_delta(h).is_some()
+    });
+    assert!(replicated, "ship position never arrived as a delta");
+    let first = latest_position_delta(&mut harness).unwrap();
+
+    // Updates keep going through the delta path, not just the initial insert
+    let updated = harness.step_until(Duration::from_secs(1), |h| {
+        h.client(0).send_input(Vec2::X, Vec2::X, false);
+        latest_position_delta(h).is_some_and(|tick| tick > first)
+    });
+    assert!(updated, "position updates were sent in full");
+}
+
+#[test]
+fn test_projectile_kill_reaches_the_victim() {
+    let mut harness = Harness::new(2);
+    harness.start_match();
+    harness.step_for(Duration::from_millis(500));
+
+    let shooter = harness.server_ship(0).unwrap();
+    let victim = harness.server_ship(1).unwrap();
+    let victim_id = harness.clients[1].player_id();
+
+    // One shot is enough
+    harness
+        .server
+        .world_mut()
+        .get_mut::<boid_wars_shared::Health>(victim)
+        .unwrap()
+        .current = 1.0;
+
+    let world = harness.server.world();
+    let aim = (world.get::<Position>(victim).unwrap().0
+        - world.get::<Position>(shooter).unwrap().0)
+        .normalize();
+
+    let killed = harness.step_until(Duration::from_secs(5), |h| {
+        h.client(0).send_input(Vec2::ZERO, aim, true);
+        h.server.world().get_entity(victim).is_err()
+    });
+    assert!(killed, "victim survived");
+
+    // The victim's client saw the health drop, loses the ship and spectates.
+    // The ship is gone in the tick it dies, so the last health sent is the low one.
+    let told = harness.step_until(Duration::from_secs(2), |h| {
+        let victim_client = h.client(1);
+        let hurt = victim_client
+            .inbox::<HealthChangeEvent>()
+            .iter()
+            .any(|event| event.entity_id == victim_id && event.new_health <= 1.0);
+        hurt && !victim_client.ships().contains(&victim_id)
+    });
+    assert!(told, "victim's client never saw the kill");
+    assert_eq!(
+        harness.clients[1].inbox::<SpectatorStatus>().last(),
+        Some(&SpectatorStatus {
+            reason: Some(SpectateReason::Eliminated)
+        })
+    );
+}
+
+#[test]
+fn test_full_server_turns_extra_client_away() {
+    let mut harness = Harness::new(MAX_PLAYERS as usize + 1);
+    harness.connect();
+
+    let rejected = harness.step_until(Duration::from_secs(2), |h| {
+        h.clients
+            .iter()
+            .any(|client| !client.inbox::<ServerFullMessage>().is_empty())
+    });
+    assert!(rejected, "nobody was turned away");
+
+    let turned_away: Vec<&ServerFullMessage> = harness
+        .clients
+        .iter()
+        .flat_map(|client| client.inbox::<ServerFullMessage>())
+        .collect();
+    assert_eq!(turned_away.len(), 1);
+    assert_eq!(turned_away[0].max_players, MAX_PLAYERS);
+    assert_eq!(turned_away[0].current_players, MAX_PLAYERS);
+    assert_eq!(harness.phase(), GamePhase::Lobby);
 }
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use boid_wars_server::interest::{InterestConfig, InterestManager};
+use lightyear::connection::id::ClientId;
+
+fn config() -> InterestConfig {
+    InterestConfig {
+        enabled: true,
+        view_radius: 100.0,
+        margin: 20.0,
+        update_interval: 0.1,
+    }
+}
+
+#[test]
+fn test_entities_enter_within_view_radius() {
+    let config = config();
+    let mut interest = InterestManager::default();
+    let client = ClientId::Netcode(1);
+    let near = Entity::from_raw(1);
+    let edge = Entity::from_raw(2);
+
+    let changes = interest.update_client(
+        client,
+        Some(Vec2::ZERO),
+        [(near, Vec2::new(50.0, 0.0)), (edge, Vec2::new(110.0, 0.0))],
+        &config,
+    );
+
+    // Inside the margin but never visible: not relevant yet
+    assert_eq!(changes.gained, vec![near]);
+    assert!(changes.lost.is_empty());
+    assert!(interest.is_relevant(client, near));
+    assert!(!interest.is_relevant(client, edge));
+}
+
+#[test]
+fn test_hysteresis_keeps_entities_inside_margin() {
+    let config = config();
+    let mut interest = InterestManager::default();
+    let client = ClientId::Netcode(1);
+    let boid = Entity::from_raw(1);
+
+    interest.update_client(
+        client,
+        Some(Vec2::ZERO),
+        [(boid, Vec2::new(90.0, 0.0))],
+        &config,
+    );
+
+    // Drifting just past the view radius keeps it
+    let changes = interest.update_client(
+        client,
+        Some(Vec2::ZERO),
+        [(boid, Vec2::new(115.0, 0.0))],
+        &config,
+    );
+    assert!(changes.gained.is_empty() && changes.lost.is_empty());
+    assert!(interest.is_relevant(client, boid));
+
+    // Past the margin it drops out
+    let changes = interest.update_client(
+        client,
+        Some(Vec2::ZERO),
+        [(boid, Vec2::new(125.0, 0.0))],
+        &config,
+    );
+    assert_eq!(changes.lost, vec![boid]);
+
+    // And needs to come back inside the view radius to return
+    let changes = interest.update_client(
+        client,
+        Some(Vec2::ZERO),
+        [(boid, Vec2::new(110.0, 0.0))],
+        &config,
+    );
+    assert!(changes.gained.is_empty());
+    assert!(!interest.is_relevant(client, boid));
+}
+
+#[test]
+fn test_missing_candidates_are_lost() {
+    let config = config();
+    let mut interest = InterestManager::default();
+    let client = ClientId::Netcode(1);
+    let boid = Entity::from_raw(1);
+
+    interest.update_client(client, Some(Vec2::ZERO), [(boid, Vec2::ZERO)], &config);
+    let changes = interest.update_client(client, Some(Vec2::ZERO), [], &config);
+
+    assert_eq!(changes.lost, vec![boid]);
+    assert_eq!(interest.relevant_count(client), 0);
+}
+
+#[test]
+fn test_viewer_without_ship_sees_everything() {
+    let config = config();
+    let mut interest = InterestManager::default();
+    let spectator = ClientId::Netcode(2);
+    let far = Entity::from_raw(1);
+
+    let changes =
+        interest.update_client(spectator, None, [(far, Vec2::new(5000.0, 5000.0))], &config);
+
+    assert_eq!(changes.gained, vec![far]);
+}
+
+#[test]
+fn test_projectile_audience_uses_exit_radius() {
+    let config = config();
+    let mut interest = InterestManager::default();
+    let near = ClientId::Netcode(1);
+    let far = ClientId::Netcode(2);
+    let spectator = ClientId::Netcode(3);
+
+    interest.update_client(near, Some(Vec2::ZERO), [], &config);
+    interest.update_client(far, Some(Vec2::new(1000.0, 0.0)), [], &config);
+    interest.update_client(spectator, None, [], &config);
+
+    let mut audience = interest.audience(Vec2::new(115.0, 0.0), &config);
+    audience.sort_by_key(|client_id| client_id.to_bits());
+    assert_eq!(audience, vec![near, spectator]);
+
+    // Disconnected clients stop receiving anything
+    interest.retain_clients(|client_id| client_id != spectator);
+    assert_eq!(
+        interest.audience(Vec2::new(115.0, 0.0), &config),
+        vec![near]
+    );
+}
@@ -0,0 +1,139 @@
+use boid_wars_server::lobby::{spawn_points, PlayerSlots, Spectators};
+use boid_wars_shared::{PlayerNumber, SpectateReason};
+use lightyear::connection::id::ClientId;
+
+#[test]
+fn test_slots_assign_lowest_free_number() {
+    let mut slots = PlayerSlots::default();
+
+    assert_eq!(
+        slots.assign(ClientId::Netcode(10), 4),
+        Some(PlayerNumber(1))
+    );
+    assert_eq!(
+        slots.assign(ClientId::Netcode(11), 4),
+        Some(PlayerNumber(2))
+    );
+    assert_eq!(
+        slots.assign(ClientId::Netcode(12), 4),
+        Some(PlayerNumber(3))
+    );
+
+    // Freed slot is reused before higher numbers
+    slots.remove(ClientId::Netcode(11));
+    assert_eq!(
+        slots.assign(ClientId::Netcode(13), 4),
+        Some(PlayerNumber(2))
+    );
+
+    // Reassigning a connected client keeps its slot
+    assert_eq!(
+        slots.assign(ClientId::Netcode(10), 4),
+        Some(PlayerNumber(1))
+    );
+    assert_eq!(slots.len(), 3);
+}
+
+#[test]
+fn test_slots_reject_when_full() {
+    let mut slots = PlayerSlots::default();
+
+    assert!(slots.assign(ClientId::Netcode(1), 2).is_some());
+    assert!(slots.assign(ClientId::Netcode(2), 2).is_some());
+    assert_eq!(slots.assign(ClientId::Netcode(3), 2), None);
+    assert_eq!(slots.len(), 2);
+}
+
+#[test]
+fn test_ready_list_ordered_by_player_number() {
+    let mut slots = PlayerSlots::default();
+    for id in [5, 6, 7] {
+        slots.assign(ClientId::Netcode(id), 8);
+    }
+    assert!(!slots.all_ready());
+
+    slots.get_mut(ClientId::Netcode(6)).unwrap().ready = true;
+    let statuses = slots.statuses();
+    let numbers: Vec<u8> = statuses.iter().map(|s| s.player_number).collect();
+    assert_eq!(numbers, vec![1, 2, 3]);
+    assert!(statuses[1].ready);
+    assert!(!statuses[0].ready && !statuses[2].ready);
+
+    for id in [5, 7] {
+        slots.get_mut(ClientId::Netcode(id)).unwrap().ready = true;
+    }
+    assert!(slots.all_ready());
+}
+
+#[test]
+fn test_spawn_points_inside_arena_and_spread() {
+    let (width, height) = (1600.0, 1200.0);
+
+    for count in [2, 8, 16, 64] {
+        let points = spawn_points(count, width, height);
+        assert_eq!(points.len(), count);
+
+        for point in &points {
+            assert!(
+                point.x >= 0.0 && point.x <= width,
+                "x out of arena: {point:?}"
+            );
+            assert!(
+                point.y >= 0.0 && point.y <= height,
+                "y out of arena: {point:?}"
+            );
+        }
+
+        // Every pair of spawns should be separated
+        for (i, a) in points.iter().enumerate() {
+            for b in points.iter().skip(i + 1) {
+                assert!(
+                    a.distance(*b) > 40.0,
+                    "spawns too close for {count} players"
+                );
+            }
+        }
+    }
+
+    // Two players start on opposite sides of the arena
+    let points = spawn_points(2, width, height);
+    assert!(points[0].distance(points[1]) > width / 2.0);
+}
+
+#[test]
+fn test_spectators_promoted_in_join_order() {
+    let mut spectators = Spectators::default();
+
+    spectators.add(ClientId::Netcode(20), SpectateReason::ServerFull);
+    spectators.add(ClientId::Netcode(21), SpectateReason::Eliminated);
+    spectators.add(ClientId::Netcode(22), SpectateReason::MatchInProgress);
+
+    assert_eq!(spectators.len(), 3);
+    assert_eq!(spectators.waiting_count(), 2);
+    assert_eq!(spectators.next_waiting(), Some(ClientId::Netcode(20)));
+
+    // Re-adding updates the reason instead of duplicating
+    spectators.add(ClientId::Netcode(20), SpectateReason::MatchInProgress);
+    assert_eq!(spectators.len(), 3);
+    assert_eq!(
+        spectators.reason(ClientId::Netcode(20)),
+        Some(SpectateReason::MatchInProgress)
+    );
+
+    assert_eq!(spectators.release_eliminated(), vec![ClientId::Netcode(21)]);
+    assert!(!spectators.contains(ClientId::Netcode(21)));
+
+    spectators.remove(ClientId::Netcode(20));
+    assert_eq!(spectators.next_waiting(), Some(ClientId::Netcode(22)));
+}
+
+#[test]
+fn test_slot_lookup_by_player_id() {
+    let mut slots = PlayerSlots::default();
+    slots.assign(ClientId::Netcode(10), 4);
+
+    let bits = ClientId::Netcode(10).to_bits();
+    assert_eq!(slots.client_id_for(bits), Some(ClientId::Netcode(10)));
+    assert!(slots.contains_player(bits));
+    assert_eq!(slots.client_id_for(ClientId::Netcode(11).to_bits()), None);
+}
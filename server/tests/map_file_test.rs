@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use boid_wars_server::map_file::{MapFile, MapFileError};
+use std::path::Path;
+
+const ARENA: Vec2 = Vec2::new(1600.0, 1200.0);
+
+fn entries(map: &MapFile, max_players: usize) -> Vec<String> {
+    match map.validate(ARENA, max_players) {
+        Ok(()) => vec![],
+        Err(errors) => errors.into_iter().map(|error| error.entry).collect(),
+    }
+}
+
+#[test]
+fn test_bundled_map_is_valid() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("maps/classic.ron");
+    let map = MapFile::load_validated(&path, ARENA, 8).unwrap();
+
+    assert_eq!(map.groups.len(), 3);
+    assert_eq!(map.obstacle_rects().len(), 4);
+    assert!(map.territory("upper").is_some());
+}
+
+#[test]
+fn test_json_maps_parse() {
+    let map = MapFile::from_json(
+        r#"{
+            "name": "json",
+            "arena": { "width": 1600.0, "height": 1200.0 },
+            "territories": [
+                { "name": "a", "center": [400.0, 400.0], "radius": 100.0,
+                  "zone": "Outer", "patrol": [[350.0, 400.0]] }
+            ],
+            "groups": [
+                { "territory": "a", "size": 5,
+                  "archetype": { "Recon": { "detection_range": 300.0, "flee_speed_bonus": 1.2 } } }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(entries(&map, 8), Vec::<String>::new());
+    assert!(map.player_spawns.is_empty());
+}
+
+#[test]
+fn test_unknown_fields_are_rejected() {
+    let result = MapFile::from_ron(
+        r#"(name: "typo", arena: (width: 1600.0, height: 1200.0), obstacels: [])"#,
+    );
+    assert!(matches!(result, Err(MapFileError::Parse(_))));
+}
+
+#[test]
+fn test_validation_names_offending_entries() {
+    let map = MapFile::from_ron(
+        r#"(
+            name: "broken",
+            arena: (width: 1600.0, height: 1200.0),
+            walls: [(from: (100.0, 100.0), to: (300.0, 300.0), thickness: 20.0)],
+            obstacles: [(center: (1590.0, 600.0), size: (40.0, 40.0))],
+            territories: [
+                (name: "a", center: (400.0, 400.0), radius: 100.0, zone: Outer,
+                 patrol: [(400.0, 2000.0)]),
+            ],
+            groups: [
+                (territory: "b", size: 10,
+                 archetype: Defensive(protection_radius: 300.0, retreat_threshold: 0.5)),
+                (territory: "a", size: 0,
+                 archetype: Defensive(protection_radius: 300.0, retreat_threshold: 1.5)),
+            ],
+            player_spawns: [(200.0, 200.0), (1590.0, 600.0)],
+        )"#,
+    )
+    .unwrap();
+
+    let entries = entries(&map, 2);
+    for expected in [
+        "walls[0]",
+        "obstacles[0]",
+        "territories[0] 'a'.patrol[0]",
+        "groups[0].territory",
+        "groups[1].size",
+        "groups[1].archetype",
+        "player_spawns[1]",
+    ] {
+        assert!(
+            entries.iter().any(|entry| entry == expected),
+            "missing {expected} in {entries:?}"
+        );
+    }
+}
+
+#[test]
+fn test_map_must_match_server_arena_and_players() {
+    let map = MapFile::from_ron(
+        r#"(name: "small", arena: (width: 800.0, height: 600.0),
+            player_spawns: [(100.0, 100.0), (500.0, 400.0)])"#,
+    )
+    .unwrap();
+
+    assert_eq!(entries(&map, 4), vec!["arena", "player_spawns"]);
+}
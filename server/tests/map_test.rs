@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use boid_wars_server::map::{generate_map, is_connected, MapConfig, MapLayout, ObstacleRect};
+
+const ARENA: Vec2 = Vec2::new(1600.0, 1200.0);
+
+fn clear_areas() -> Vec<(Vec2, f32)> {
+    vec![
+        (Vec2::new(200.0, 200.0), 150.0),
+        (Vec2::new(1400.0, 1000.0), 150.0),
+        (Vec2::new(800.0, 600.0), 120.0),
+    ]
+}
+
+#[test]
+fn test_same_seed_same_map() {
+    let config = MapConfig::default();
+    for layout in MapLayout::ALL {
+        let a = generate_map(7, layout, ARENA, &clear_areas(), &config);
+        let b = generate_map(7, layout, ARENA, &clear_areas(), &config);
+        assert_eq!(a, b);
+        assert!(!a.obstacles.is_empty(), "{layout:?} placed no obstacles");
+
+        let other = generate_map(8, layout, ARENA, &clear_areas(), &config);
+        assert_ne!(a.obstacles, other.obstacles);
+    }
+}
+
+#[test]
+fn test_maps_keep_clear_areas_and_connectivity() {
+    let config = MapConfig::default();
+    for seed in 0..10 {
+        for layout in MapLayout::ALL {
+            let map = generate_map(seed, layout, ARENA, &clear_areas(), &config);
+
+            for rect in &map.obstacles {
+                for (center, radius) in clear_areas() {
+                    assert!(rect.distance_to(center) > radius);
+                }
+            }
+            assert!(is_connected(&map.obstacles, ARENA, &config));
+        }
+    }
+}
+
+#[test]
+fn test_symmetric_layout_mirrors_every_obstacle() {
+    let map = generate_map(
+        3,
+        MapLayout::Symmetric,
+        ARENA,
+        &clear_areas(),
+        &MapConfig::default(),
+    );
+
+    for rect in &map.obstacles {
+        let mirrored = ARENA - rect.center;
+        assert!(map
+            .obstacles
+            .iter()
+            .any(|other| other.center.distance(mirrored) < 0.01 && other.size == rect.size));
+    }
+}
+
+#[test]
+fn test_sealed_pocket_is_not_connected() {
+    let config = MapConfig::default();
+    // Four walls boxing in a 200x200 area
+    let walls = [
+        ObstacleRect::new(Vec2::new(500.0, 400.0), Vec2::new(240.0, 20.0)),
+        ObstacleRect::new(Vec2::new(500.0, 600.0), Vec2::new(240.0, 20.0)),
+        ObstacleRect::new(Vec2::new(400.0, 500.0), Vec2::new(20.0, 240.0)),
+        ObstacleRect::new(Vec2::new(600.0, 500.0), Vec2::new(20.0, 240.0)),
+    ];
+
+    assert!(is_connected(&walls[..3], ARENA, &config));
+    assert!(!is_connected(&walls, ARENA, &config));
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use boid_wars_shared::{
+    input_thrust, shortest_angle_delta, MovementRules, GAME_CONFIG, PLAYER_DAMPING_FACTOR,
+    PLAYER_MOVE_SPEED, PLAYER_WALL_MARGIN,
+};
+
+#[test]
+fn test_input_velocity_matches_thrust() {
+    let rules = MovementRules::default();
+    assert_eq!(input_thrust(Vec2::ZERO), 0.0);
+    assert_eq!(input_thrust(Vec2::X), 1.0);
+
+    assert_eq!(rules.input_velocity(Vec2::X, 0.0), Vec2::ZERO);
+    assert_eq!(
+        rules.input_velocity(Vec2::new(3.0, 0.0), 1.0),
+        Vec2::X * PLAYER_MOVE_SPEED
+    );
+
+    // Prediction travels at the damped speed the server ends up with
+    let velocity = rules.player_velocity(Vec2::new(1.0, 1.0));
+    assert!((velocity.length() - PLAYER_MOVE_SPEED * PLAYER_DAMPING_FACTOR).abs() < 0.001);
+}
+
+#[test]
+fn test_step_is_split_invariant() {
+    let rules = MovementRules::default();
+    let start = Vec2::new(400.0, 300.0);
+    let movement = Vec2::new(1.0, -1.0).normalize();
+
+    // Replaying one long input must land where many frame-sized steps do
+    let whole = rules.step_player_position(start, movement, 0.5);
+    let stepped = (0..30).fold(start, |position, _| {
+        rules.step_player_position(position, movement, 0.5 / 30.0)
+    });
+    assert!(whole.distance(stepped) < 0.01);
+}
+
+#[test]
+fn test_step_stays_in_arena() {
+    let game_config = &*GAME_CONFIG;
+    let rules = MovementRules::default();
+    let corner = rules.step_player_position(Vec2::new(40.0, 40.0), Vec2::new(-1.0, -1.0), 10.0);
+    assert_eq!(corner, Vec2::splat(PLAYER_WALL_MARGIN));
+
+    let far = rules.step_player_position(
+        Vec2::new(game_config.game_width - 40.0, 40.0),
+        Vec2::X,
+        10.0,
+    );
+    assert_eq!(far.x, game_config.game_width - PLAYER_WALL_MARGIN);
+}
+
+#[test]
+fn test_shortest_angle_delta_wraps() {
+    use std::f32::consts::PI;
+
+    assert!((shortest_angle_delta(0.0, 0.5) - 0.5).abs() < 0.0001);
+    assert!((shortest_angle_delta(PI - 0.1, -PI + 0.1) - 0.2).abs() < 0.0001);
+    assert!((shortest_angle_delta(-PI + 0.1, PI - 0.1) + 0.2).abs() < 0.0001);
+}
+
+#[test]
+fn test_rules_follow_the_server_tuning() {
+    // A server running with heavier damping and a bigger ship
+    let rules = MovementRules::new(&GAME_CONFIG, 0.5, 50.0);
+
+    let velocity = rules.player_velocity(Vec2::X);
+    assert!((velocity.x - GAME_CONFIG.player_speed * 0.5).abs() < 0.001);
+
+    let corner = rules.step_player_position(Vec2::new(60.0, 60.0), Vec2::new(-1.0, -1.0), 10.0);
+    assert_eq!(corner, Vec2::splat(50.0));
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use boid_wars_server::navigation::{NavGoal, NavGrid, Navigation};
+use boid_wars_server::settings::Settings;
+
+const ARENA: Vec2 = Vec2::new(400.0, 400.0);
+const CELL: f32 = 20.0;
+
+/// A tall wall down the middle of the arena with gaps at the top and bottom
+fn walled_grid() -> NavGrid {
+    NavGrid::build(
+        ARENA,
+        CELL,
+        10.0,
+        [(Vec2::new(200.0, 200.0), Vec2::new(20.0, 300.0))],
+    )
+}
+
+#[test]
+fn test_path_goes_around_the_wall() {
+    let grid = walled_grid();
+    let (from, to) = (Vec2::new(100.0, 200.0), Vec2::new(300.0, 200.0));
+    assert!(!grid.line_of_sight(from, to));
+
+    let path = grid.find_path(from, to).expect("a way around");
+    assert!(path
+        .iter()
+        .all(|&point| !grid.is_blocked(grid.cell_of(point))));
+    assert_eq!(grid.cell_of(*path.last().unwrap()), grid.cell_of(to));
+
+    // Has to leave the straight line to get past the wall
+    let furthest = path
+        .iter()
+        .map(|point| (point.y - 200.0).abs())
+        .fold(0.0, f32::max);
+    assert!(furthest > 150.0, "path hugged the wall: {path:?}");
+}
+
+#[test]
+fn test_following_headings_reaches_the_goal() {
+    let mut navigation = Navigation::new(walled_grid());
+    let group = Entity::from_raw(1);
+    let goal = Vec2::new(300.0, 200.0);
+    navigation.set_goal(
+        group,
+        Some(NavGoal {
+            point: goal,
+            arrival_radius: 15.0,
+        }),
+    );
+
+    let mut position = Vec2::new(100.0, 200.0);
+    for _ in 0..1000 {
+        let Some(heading) = navigation.heading(group, position) else {
+            break;
+        };
+        position += heading * 5.0;
+        assert!(
+            !navigation
+                .grid
+                .is_blocked(navigation.grid.cell_of(position)),
+            "walked into the wall at {position:?}"
+        );
+    }
+    assert!(position.distance(goal) <= 15.0, "stuck at {position:?}");
+}
+
+#[test]
+fn test_clear_line_heads_straight_at_the_goal() {
+    let mut navigation = Navigation::new(walled_grid());
+    let group = Entity::from_raw(1);
+    navigation.set_goal(
+        group,
+        Some(NavGoal {
+            point: Vec2::new(100.0, 300.0),
+            arrival_radius: 50.0,
+        }),
+    );
+
+    let heading = navigation.heading(group, Vec2::new(100.0, 100.0)).unwrap();
+    assert!(heading.distance(Vec2::Y) < 0.0001);
+
+    // Close enough: leave the rest to flocking
+    assert_eq!(navigation.heading(group, Vec2::new(100.0, 260.0)), None);
+
+    navigation.set_goal(group, None);
+    assert_eq!(navigation.heading(group, Vec2::new(100.0, 100.0)), None);
+}
+
+#[test]
+fn test_walled_in_goal_is_unreachable() {
+    // A closed box around (300, 300)
+    let walls = [
+        (Vec2::new(300.0, 250.0), Vec2::new(120.0, 20.0)),
+        (Vec2::new(300.0, 350.0), Vec2::new(120.0, 20.0)),
+        (Vec2::new(250.0, 300.0), Vec2::new(20.0, 120.0)),
+        (Vec2::new(350.0, 300.0), Vec2::new(20.0, 120.0)),
+    ];
+    let grid = NavGrid::build(ARENA, CELL, 0.0, walls);
+    assert!(!grid.is_blocked(grid.cell_of(Vec2::new(300.0, 300.0))));
+
+    assert_eq!(
+        grid.find_path(Vec2::new(50.0, 50.0), Vec2::new(300.0, 300.0)),
+        None
+    );
+    assert!(grid
+        .find_path(Vec2::new(50.0, 50.0), Vec2::new(150.0, 350.0))
+        .is_some());
+}
+
+#[test]
+fn test_navigation_section_is_layered() {
+    let file = r#"
+        [navigation]
+        cell_size = 25.0
+
+        [flocking]
+        path_following_weight = 3.0
+    "#;
+    let settings = Settings::layered(Some(("test.toml", file)), vec![], &[]).unwrap();
+    assert_eq!(settings.navigation.cell_size, 25.0);
+    assert_eq!(settings.flocking.path_following_weight, 3.0);
+
+    let zero = [("navigation.cell_size".to_string(), "0".to_string())];
+    assert!(Settings::layered(None, vec![], &zero).is_err());
+}
@@ -1,9 +1,13 @@
 use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
 use bevy_rapier2d::prelude::*;
 use boid_wars_server::physics::{Player as PhysicsPlayer, PlayerInput as PhysicsInput};
 use boid_wars_server::position_sync::{PositionSyncPlugin, SyncPosition};
 use boid_wars_server::spatial_grid::SpatialGridPlugin;
-use boid_wars_shared::{Player, Position, Velocity};
+use boid_wars_shared::{Player, Position, ProtocolPlugin, Rotation, Velocity, NETWORK_CONFIG};
+use lightyear::prelude::server::*;
+use lightyear::prelude::SharedConfig;
+use std::time::Duration;
 
 #[test]
 fn test_physics_to_network_sync() {
@@ -96,6 +100,7 @@ fn test_player_movement_physics() {
             },
             Transform::from_xyz(400.0, 300.0, 0.0),
             Position(Vec2::new(400.0, 300.0)),
+            Rotation { angle: 0.0 },
             RigidBody::Dynamic,
             Collider::cuboid(5.0, 5.0),
             bevy_rapier2d::dynamics::Velocity::zero(),
@@ -119,6 +124,11 @@ fn test_player_movement_physics() {
         .translation
         .truncate();
 
+    // Frames a little longer than the 30Hz player sync interval, so every frame syncs
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        34,
+    )));
+
     // Run several physics updates
     for _ in 0..10 {
         app.update();
@@ -302,6 +312,10 @@ fn create_test_app() -> App {
     app.add_plugins(MinimalPlugins);
     app.add_plugins(TransformPlugin);
 
+    // Shooting systems send through the server's connection manager
+    app.add_plugins(ServerPlugins::new(test_server_config()));
+    app.add_plugins(ProtocolPlugin);
+
     // Add our plugins in the correct order
     app.add_plugins(SpatialGridPlugin); // Must be before PhysicsPlugin
     app.add_plugins(boid_wars_server::physics::PhysicsPlugin {
@@ -312,5 +326,27 @@ fn create_test_app() -> App {
     // Initialize time resource
     app.init_resource::<Time>();
 
+    // Lightyear builds its connection manager when the app finishes
+    app.finish();
+    app.cleanup();
+
     app
 }
+
+/// Server config with no clients; nothing is ever sent
+fn test_server_config() -> ServerConfig {
+    let network_config = &*NETWORK_CONFIG;
+    let netcode = NetcodeConfig::default()
+        .with_protocol_id(network_config.protocol_id)
+        .with_key(network_config.dev_key);
+    ServerConfig {
+        shared: SharedConfig::default(),
+        net: vec![NetConfig::Netcode {
+            config: netcode,
+            io: IoConfig::from_transport(ServerTransport::Channels { channels: vec![] }),
+        }],
+        packet: Default::default(),
+        replication: Default::default(),
+        ping: Default::default(),
+    }
+}
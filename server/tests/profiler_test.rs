@@ -0,0 +1,119 @@
+use boid_wars_server::admin::AdminCommand;
+use boid_wars_server::profiler::{chrome_trace_json, TickProfiler, TickStage, DEFAULT_TRACE_PATH};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+/// One tick starting at `start` with the given stage spans, back to back
+fn run_tick(profiler: &mut TickProfiler, start: Instant, stages: &[(TickStage, u64)]) -> Instant {
+    profiler.begin_tick(start);
+    let mut now = start;
+    for (stage, time) in stages {
+        profiler.begin(*stage, now);
+        now += ms(*time);
+        profiler.end(*stage, now);
+    }
+    now
+}
+
+#[test]
+fn test_stage_spans_accumulate_within_a_tick() {
+    let mut profiler = TickProfiler::new(ms(16));
+    let start = Instant::now();
+
+    // Fixed update ran twice this frame
+    let end = run_tick(
+        &mut profiler,
+        start,
+        &[
+            (TickStage::Flocking, 3),
+            (TickStage::Flocking, 2),
+            (TickStage::Sync, 1),
+        ],
+    );
+    assert_eq!(profiler.current(TickStage::Flocking), ms(5));
+    assert_eq!(profiler.end_tick(end), None);
+
+    assert_eq!(profiler.ticks, 1);
+    assert_eq!(profiler.stage_percentiles(TickStage::Flocking).p50, ms(5));
+    assert_eq!(profiler.stage_percentiles(TickStage::GroupAi).p50, ms(0));
+    assert_eq!(profiler.tick_percentiles().p99, ms(6));
+}
+
+#[test]
+fn test_over_budget_ticks_name_the_slowest_stage() {
+    let mut profiler = TickProfiler::new(ms(16));
+    let start = Instant::now();
+
+    let end = run_tick(
+        &mut profiler,
+        start,
+        &[(TickStage::PhysicsStep, 4), (TickStage::GroupAi, 14)],
+    );
+    let overrun = profiler.end_tick(end).unwrap();
+
+    assert_eq!(overrun.duration, ms(18));
+    assert_eq!(overrun.slowest, (TickStage::GroupAi, ms(14)));
+    assert_eq!(profiler.overruns, 1);
+}
+
+#[test]
+fn test_trace_capture_covers_requested_ticks() {
+    let mut profiler = TickProfiler::new(ms(16));
+    let mut now = Instant::now();
+
+    // Not capturing yet, so this tick isn't traced
+    now = run_tick(&mut profiler, now, &[(TickStage::Sync, 1)]);
+    profiler.end_tick(now);
+
+    profiler.start_capture(2, PathBuf::from("trace.json"));
+    for _ in 0..2 {
+        assert!(profiler.take_finished_capture().is_none());
+        now = run_tick(
+            &mut profiler,
+            now,
+            &[(TickStage::Flocking, 2), (TickStage::ReplicationSend, 1)],
+        );
+        profiler.end_tick(now);
+    }
+
+    let (path, events) = profiler.take_finished_capture().unwrap();
+    assert_eq!(path, PathBuf::from("trace.json"));
+    assert!(!profiler.is_capturing());
+    // Two stage spans and the tick itself, per tick
+    assert_eq!(events.len(), 6);
+
+    let trace: serde_json::Value = serde_json::from_str(&chrome_trace_json(&events)).unwrap();
+    let first = &trace["traceEvents"][0];
+    assert_eq!(first["name"], "flocking");
+    assert_eq!(first["ph"], "X");
+    assert_eq!(first["dur"], 2000);
+    assert_eq!(trace["traceEvents"][2]["name"], "tick");
+}
+
+#[test]
+fn test_console_profile_commands() {
+    assert_eq!(
+        AdminCommand::parse("profile"),
+        Ok(Some(AdminCommand::Profile))
+    );
+    assert_eq!(
+        AdminCommand::parse("trace 120"),
+        Ok(Some(AdminCommand::Trace {
+            ticks: 120,
+            path: PathBuf::from(DEFAULT_TRACE_PATH),
+        }))
+    );
+    assert_eq!(
+        AdminCommand::parse("trace 5 /tmp/t.json"),
+        Ok(Some(AdminCommand::Trace {
+            ticks: 5,
+            path: PathBuf::from("/tmp/t.json"),
+        }))
+    );
+    assert!(AdminCommand::parse("trace").is_err());
+    assert!(AdminCommand::parse("trace many").is_err());
+}
@@ -0,0 +1,291 @@
+mod harness;
+
+use bevy::ecs::world::CommandQueue;
+use bevy::prelude::*;
+use boid_wars_server::admin::{AdminCommand, AdminConsolePlugin};
+use boid_wars_server::config::PhysicsConfig;
+use boid_wars_server::flocking::FlockingPlugin;
+use boid_wars_server::groups::behavior_tree::BehaviorTrees;
+use boid_wars_server::groups::territory::generate_territories;
+use boid_wars_server::groups::{spawn_boid_group, BoidGroupPlugin, BoidIdCounter, GroupIdCounter};
+use boid_wars_server::replay::{
+    inputs_hash, Checkpoint, Replay, ReplayEvent, ReplayHeader, ReplayMode, ReplayPlayback,
+    ReplayPlugin, ReplayRecord, REPLAY_VERSION,
+};
+use boid_wars_server::rng::GameRng;
+use boid_wars_server::settings::Settings;
+use boid_wars_shared::{Boid, GroupArchetype, PlayerInput, Position, Velocity};
+use harness::Harness;
+use rand::Rng;
+use std::time::Duration;
+
+fn header() -> ReplayHeader {
+    ReplayHeader {
+        version: REPLAY_VERSION,
+        seed: 1234,
+        timestep: Duration::from_micros(15625),
+        inputs: inputs_hash(&Settings::default(), None, None),
+    }
+}
+
+fn checkpoint(hash: u64) -> Checkpoint {
+    Checkpoint {
+        players: 2,
+        boids: 10,
+        hash,
+    }
+}
+
+#[test]
+fn test_replay_file_round_trip() {
+    let records = vec![
+        ReplayRecord {
+            frame: 3,
+            event: ReplayEvent::Connect { client: 42 },
+        },
+        ReplayRecord {
+            frame: 10,
+            event: ReplayEvent::Ready { client: 42 },
+        },
+        ReplayRecord {
+            frame: 12,
+            event: ReplayEvent::Input {
+                client: 42,
+                input: PlayerInput::new(Vec2::X, Vec2::new(0.6, -0.8), true).with_sequence(7),
+            },
+        },
+        ReplayRecord {
+            frame: 12,
+            event: ReplayEvent::Admin(AdminCommand::Spawn {
+                archetype: GroupArchetype::Defensive {
+                    protection_radius: 400.0,
+                    retreat_threshold: 0.4,
+                },
+                position: Vec2::new(300.0, -25.5),
+                size: 6,
+            }),
+        },
+        ReplayRecord {
+            frame: 12,
+            event: ReplayEvent::Frame {
+                delta: Duration::from_micros(16_667),
+            },
+        },
+        ReplayRecord {
+            frame: 64,
+            event: ReplayEvent::Checkpoint(checkpoint(0xdead_beef)),
+        },
+        ReplayRecord {
+            frame: 70,
+            event: ReplayEvent::Disconnect { client: 42 },
+        },
+    ];
+
+    let mut buffer = Vec::new();
+    header().write(&mut buffer).unwrap();
+    for record in &records {
+        record.write(&mut buffer).unwrap();
+    }
+
+    let replay = Replay::read(&mut buffer.as_slice()).unwrap();
+    assert_eq!(replay.header, header());
+    assert_eq!(replay.records, records);
+}
+
+#[test]
+fn test_replay_rejects_other_versions_and_files() {
+    let mut buffer = Vec::new();
+    ReplayHeader {
+        version: REPLAY_VERSION + 1,
+        ..header()
+    }
+    .write(&mut buffer)
+    .unwrap();
+    assert!(Replay::read(&mut buffer.as_slice()).is_err());
+
+    assert!(Replay::read(&mut b"not a replay".as_slice()).is_err());
+}
+
+#[test]
+fn test_replay_refuses_different_inputs() {
+    let settings = Settings::default();
+    let recorded = header();
+    assert!(recorded
+        .check_inputs(inputs_hash(&settings, None, None))
+        .is_ok());
+
+    // Passing the default trees explicitly is the same run
+    let trees = BehaviorTrees::default();
+    assert!(recorded
+        .check_inputs(inputs_hash(&settings, None, Some(&trees)))
+        .is_ok());
+
+    let mut tuned = settings.clone();
+    tuned.flocking.max_speed += 10.0;
+    assert!(recorded
+        .check_inputs(inputs_hash(&tuned, None, None))
+        .is_err());
+}
+
+#[test]
+fn test_checkpoint_ignores_order_but_not_state() {
+    let players = [(1, Vec2::new(10.0, 20.0), 100.0), (2, Vec2::ZERO, 50.0)];
+    let boids = [(5, Vec2::new(1.0, 1.0)), (6, Vec2::new(2.0, 2.0))];
+
+    let a = Checkpoint::capture(players, boids);
+    let b = Checkpoint::capture(players.into_iter().rev(), boids.into_iter().rev());
+    assert_eq!(a, b);
+    assert_eq!((a.players, a.boids), (2, 2));
+
+    let moved = Checkpoint::capture(players, [(5, Vec2::new(1.5, 1.0)), boids[1]]);
+    assert_ne!(a.hash, moved.hash);
+}
+
+#[test]
+fn test_playback_reports_first_divergent_checkpoint() {
+    let records = [(60, 1), (120, 2), (180, 3)]
+        .into_iter()
+        .map(|(frame, hash)| ReplayRecord {
+            frame,
+            event: ReplayEvent::Checkpoint(checkpoint(hash)),
+        })
+        .collect();
+    let mut playback = ReplayPlayback::new(records);
+
+    // Frames without a checkpoint are not compared
+    assert_eq!(playback.verify(59, checkpoint(99)), None);
+    assert_eq!(playback.verify(60, checkpoint(1)), None);
+
+    let divergence = playback.verify(120, checkpoint(9)).unwrap();
+    assert_eq!(divergence.frame, 120);
+    assert_eq!(divergence.expected.hash, 2);
+
+    playback.verify(180, checkpoint(8));
+    assert_eq!(playback.divergence.map(|d| d.frame), Some(120));
+    assert!(playback.is_finished());
+}
+
+#[test]
+fn test_game_rng_is_reproducible_from_seed() {
+    let mut a = GameRng::from_seed(7);
+    let mut b = GameRng::from_seed(a.seed());
+
+    let a_values: Vec<f32> = (0..16).map(|_| a.gen()).collect();
+    let b_values: Vec<f32> = (0..16).map(|_| b.gen()).collect();
+    assert_eq!(a_values, b_values);
+}
+
+/// Spawn a group into seeded territories and move it one step along its velocities
+fn seeded_group_checkpoint(seed: u64) -> Checkpoint {
+    let mut rng = GameRng::from_seed(seed);
+    let mut world = World::new();
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &world);
+
+    let territory = generate_territories(1600.0, 1200.0, &mut rng).remove(0);
+    spawn_boid_group(
+        &mut commands,
+        GroupArchetype::Recon {
+            detection_range: 400.0,
+            flee_speed_bonus: 1.3,
+        },
+        12,
+        territory,
+        &mut GroupIdCounter::default(),
+        &mut BoidIdCounter::default(),
+        &PhysicsConfig::default(),
+        &mut rng,
+    );
+    queue.apply(&mut world);
+
+    let mut boids = world.query::<(&Boid, &Position, &Velocity)>();
+    Checkpoint::capture(
+        [],
+        boids
+            .iter(&world)
+            .map(|(boid, position, velocity)| (boid.id, position.0 + velocity.0 * 0.1)),
+    )
+}
+
+#[test]
+fn test_seeded_group_spawns_are_reproducible() {
+    let first = seeded_group_checkpoint(99);
+    assert_eq!(first.boids, 12);
+    assert_eq!(first, seeded_group_checkpoint(99));
+    assert_ne!(first, seeded_group_checkpoint(100));
+}
+
+/// Boid AI and the admin console on top of the harness server, so the match draws on
+/// the RNG and the frame clock outside the fixed schedule
+/// Boid AI and the admin console on top of the harness server, so the match draws on
+/// the RNG and the frame clock outside the fixed schedule
+fn add_gameplay(app: &mut App) {
+    app.add_plugins(FlockingPlugin)
+        .add_plugins(BoidGroupPlugin)
+        .add_plugins(AdminConsolePlugin { read_stdin: false });
+}
+
+#[test]
+fn test_recorded_match_plays_back_without_diverging() {
+    let path = std::env::temp_dir().join(format!("replay_test_{}.bwrp", std::process::id()));
+
+    let mut harness = Harness::with_server(2, |app| {
+        add_gameplay(app);
+        app.add_plugins(ReplayPlugin {
+            mode: ReplayMode::Record(path.clone()),
+            inputs: 0,
+        });
+    });
+    harness.start_match();
+    harness.server.world_mut().send_event(AdminCommand::Spawn {
+        archetype: GroupArchetype::Assault {
+            aggression_multiplier: 1.0,
+            preferred_range: 150.0,
+        },
+        position: Vec2::new(600.0, 400.0),
+        size: 8,
+    });
+
+    // Both ships circle and fire in bursts
+    for frame in 0..300 {
+        let angle = frame as f32 * 0.05;
+        for (i, client) in harness.clients.iter_mut().enumerate() {
+            let heading = Vec2::from_angle(angle + i as f32 * 2.0);
+            client.send_input(heading, heading.perp(), frame % 30 < 15);
+        }
+        harness.step();
+    }
+    // Dropping the server flushes the recording
+    drop(harness);
+
+    let replay = Replay::load(&path).expect("recording readable");
+    std::fs::remove_file(&path).ok();
+    let frames = replay
+        .records
+        .iter()
+        .filter(|record| matches!(record.event, ReplayEvent::Frame { .. }))
+        .count();
+    assert!(replay
+        .records
+        .iter()
+        .any(|record| matches!(record.event, ReplayEvent::Admin(_))));
+
+    let mut playback = harness::headless_server(|app| {
+        add_gameplay(app);
+        app.add_plugins(ReplayPlugin {
+            mode: ReplayMode::Playback(replay),
+            inputs: 0,
+        });
+    });
+    for _ in 0..frames {
+        if playback.should_exit().is_some() {
+            break;
+        }
+        playback.update();
+    }
+
+    let playback = playback.world().resource::<ReplayPlayback>();
+    assert_eq!(playback.divergence, None);
+    assert!(playback.is_finished());
+    assert!(playback.matched() >= 5);
+}
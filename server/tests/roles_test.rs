@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use boid_wars_server::config::PhysicsConfig;
+use boid_wars_server::groups::behavior_tree::{BehaviorTreePlugin, BehaviorTrees};
+use boid_wars_server::groups::combat::shooter_priority;
+use boid_wars_server::groups::roles::{
+    flank_point, flank_side, role_for_slot, successor, GroupLeader, GroupRolesPlugin,
+};
+use boid_wars_server::groups::BoidGroupConfig;
+use boid_wars_server::physics::BoidAggression;
+use boid_wars_shared::{
+    ArenaZone, Boid, BoidGroup, BoidGroupMember, BoidRole, Formation, GroupArchetype,
+    GroupBehavior, Player, Position, TerritoryData,
+};
+use std::collections::HashSet;
+
+const HOME: Vec2 = Vec2::new(800.0, 600.0);
+
+fn group() -> BoidGroup {
+    let archetype = GroupArchetype::Assault {
+        aggression_multiplier: 1.0,
+        preferred_range: 150.0,
+    };
+    BoidGroup {
+        id: 1,
+        archetype,
+        home_territory: TerritoryData {
+            center: HOME,
+            radius: 200.0,
+            zone: ArenaZone::Middle,
+            patrol_points: vec![HOME - Vec2::X * 100.0, HOME + Vec2::X * 100.0],
+            neighboring_territories: vec![],
+        },
+        current_formation: Formation::default_for_archetype(&archetype),
+        behavior_state: GroupBehavior::Patrolling {
+            route: vec![],
+            current_waypoint: 0,
+        },
+        active_shooters: HashSet::new(),
+        max_shooters: 1,
+        initial_size: 10,
+    }
+}
+
+fn spawn_member(world: &mut World, group: Entity, role: BoidRole, position: Vec2) -> Entity {
+    world
+        .spawn((
+            Boid { id: 1000 },
+            BoidGroupMember {
+                group_entity: group,
+                group_id: 1,
+                formation_slot: None,
+                role_in_group: role,
+            },
+            Position(position),
+        ))
+        .id()
+}
+
+#[test]
+fn test_roles_by_formation_slot() {
+    let roles: Vec<BoidRole> = (0..10).map(|i| role_for_slot(i, 10)).collect();
+    assert_eq!(
+        roles,
+        vec![
+            BoidRole::Leader,
+            BoidRole::Flanker,
+            BoidRole::Flanker,
+            BoidRole::Support,
+            BoidRole::Support,
+            BoidRole::Scout,
+            BoidRole::Scout,
+            BoidRole::Scout,
+            BoidRole::Scout,
+            BoidRole::Scout,
+        ]
+    );
+}
+
+#[test]
+fn test_flankers_split_to_either_side_of_the_target() {
+    let (anchor, target) = (Vec2::ZERO, Vec2::new(100.0, 0.0));
+    assert_eq!(
+        flank_point(anchor, target, 1.0, 50.0),
+        Vec2::new(100.0, 50.0)
+    );
+    assert_eq!(
+        flank_point(anchor, target, -1.0, 50.0),
+        Vec2::new(100.0, -50.0)
+    );
+
+    let sides: HashSet<i32> = (1..=2)
+        .map(|i| flank_side(Entity::from_raw(i)) as i32)
+        .collect();
+    assert_eq!(sides, HashSet::from([1, -1]));
+}
+
+#[test]
+fn test_support_boids_shoot_first() {
+    let mut roles = [
+        BoidRole::Scout,
+        BoidRole::Leader,
+        BoidRole::Flanker,
+        BoidRole::Support,
+    ];
+    roles.sort_by_key(|&role| shooter_priority(role));
+    assert_eq!(roles[0], BoidRole::Support);
+    assert_eq!(roles[3], BoidRole::Scout);
+}
+
+#[test]
+fn test_flankers_succeed_before_nearer_boids() {
+    let (flanker, support) = (Entity::from_raw(1), Entity::from_raw(2));
+    let candidates = [
+        (support, BoidRole::Support, HOME),
+        (flanker, BoidRole::Flanker, HOME + Vec2::X * 200.0),
+    ];
+    assert_eq!(successor(HOME, candidates), Some(flanker));
+    assert_eq!(successor(HOME, []), None);
+}
+
+#[test]
+fn test_dead_leader_is_replaced() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(GroupRolesPlugin);
+
+    let world = app.world_mut();
+    let group = world.spawn((group(), Position(HOME))).id();
+    let leader = spawn_member(world, group, BoidRole::Leader, HOME);
+    let far_flanker = spawn_member(world, group, BoidRole::Flanker, HOME + Vec2::X * 300.0);
+    let near_flanker = spawn_member(world, group, BoidRole::Flanker, HOME + Vec2::Y * 40.0);
+    spawn_member(world, group, BoidRole::Support, HOME + Vec2::X * 10.0);
+
+    // The spawned leader is adopted as is
+    app.update();
+    assert_eq!(
+        app.world().get::<GroupLeader>(group),
+        Some(&GroupLeader(leader))
+    );
+
+    app.world_mut().despawn(leader);
+    app.update();
+    let world = app.world();
+    assert_eq!(
+        world.get::<GroupLeader>(group),
+        Some(&GroupLeader(near_flanker))
+    );
+    assert_eq!(
+        world
+            .get::<BoidGroupMember>(near_flanker)
+            .unwrap()
+            .role_in_group,
+        BoidRole::Leader
+    );
+    assert_eq!(
+        world
+            .get::<BoidGroupMember>(far_flanker)
+            .unwrap()
+            .role_in_group,
+        BoidRole::Flanker
+    );
+}
+
+#[test]
+fn test_scouts_spot_players_for_their_group() {
+    let spot = "Sequence(name: \"spot\", children: [Check(ThreatInRange), Act(Engage)])";
+    let trees = BehaviorTrees::from_ron(&format!(
+        "(recon: {spot}, assault: {spot}, defensive: {spot})"
+    ))
+    .unwrap();
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<PhysicsConfig>()
+        .init_resource::<BoidGroupConfig>()
+        .init_resource::<BoidAggression>()
+        .insert_resource(trees)
+        .add_plugins(BehaviorTreePlugin);
+
+    // Well past the group's own detection range
+    let player = HOME + Vec2::X * 600.0;
+    let world = app.world_mut();
+    world.spawn((
+        Player {
+            id: 7,
+            name: "pilot".to_string(),
+        },
+        Position(player),
+    ));
+    let group = world.spawn((group(), Position(HOME))).id();
+    spawn_member(world, group, BoidRole::Support, HOME);
+
+    app.update();
+    assert!(matches!(
+        app.world().get::<BoidGroup>(group).unwrap().behavior_state,
+        GroupBehavior::Patrolling { .. }
+    ));
+
+    spawn_member(
+        app.world_mut(),
+        group,
+        BoidRole::Scout,
+        player - Vec2::X * 150.0,
+    );
+    app.update();
+    assert!(matches!(
+        app.world().get::<BoidGroup>(group).unwrap().behavior_state,
+        GroupBehavior::Engaging {
+            primary_target: 7,
+            ..
+        }
+    ));
+}
@@ -0,0 +1,156 @@
+mod harness;
+
+use bevy::prelude::*;
+use boid_wars_server::lobby::PlayerSlots;
+use boid_wars_server::physics::ArenaWall;
+use boid_wars_server::round::{MatchTracker, RoundConfig, RoundPlugin};
+use boid_wars_shared::{GamePhase, MatchEndReason};
+use harness::Harness;
+use lightyear::connection::id::ClientId;
+use std::time::Duration;
+
+fn three_player_round() -> MatchTracker {
+    let mut tracker = MatchTracker::default();
+    tracker.begin([(10, 1), (11, 2), (12, 3)]);
+    tracker
+}
+
+#[test]
+fn test_last_player_standing_ends_round() {
+    let config = RoundConfig::default();
+    let mut tracker = three_player_round();
+
+    assert!(tracker.is_running());
+    assert_eq!(tracker.check_end(&config), None);
+
+    tracker.tick(5.0);
+    tracker.record_damage(10, 30.0);
+    tracker.record_death(11, Some(10));
+    assert_eq!(tracker.alive_count(), 2);
+    assert_eq!(tracker.check_end(&config), None);
+
+    tracker.tick(5.0);
+    tracker.record_damage(12, 25.0);
+    tracker.record_death(10, Some(12));
+    assert_eq!(
+        tracker.check_end(&config),
+        Some(MatchEndReason::LastPlayerStanding)
+    );
+    tracker.end(MatchEndReason::LastPlayerStanding);
+
+    // Stats freeze once the round is over
+    tracker.tick(5.0);
+    tracker.record_death(12, None);
+    assert_eq!(tracker.alive_count(), 1);
+
+    let results = tracker.results(10.0);
+    assert_eq!(results.winner, Some(12));
+    assert_eq!(results.duration_secs, 10.0);
+
+    let order: Vec<u64> = results.players.iter().map(|p| p.player_id).collect();
+    assert_eq!(order, vec![12, 10, 11]);
+
+    let winner = &results.players[0];
+    assert_eq!(winner.placement, 1);
+    assert_eq!(winner.kills, 1);
+    assert_eq!(winner.damage_dealt, 25.0);
+    assert_eq!(winner.survival_secs, 10.0);
+
+    let first_out = &results.players[2];
+    assert_eq!(first_out.placement, 3);
+    assert_eq!(first_out.survival_secs, 5.0);
+}
+
+#[test]
+fn test_deaths_count_once_and_self_kills_score_nothing() {
+    let mut tracker = three_player_round();
+
+    tracker.record_death(11, Some(10));
+    tracker.record_death(11, Some(10));
+    tracker.record_death(12, Some(12));
+
+    assert_eq!(tracker.get(10).unwrap().kills, 1);
+    assert_eq!(tracker.get(12).unwrap().kills, 0);
+}
+
+#[test]
+fn test_time_limit_ends_round() {
+    let config = RoundConfig {
+        time_limit_secs: 30.0,
+        ..Default::default()
+    };
+    let mut tracker = three_player_round();
+
+    tracker.tick(29.0);
+    assert_eq!(tracker.check_end(&config), None);
+    tracker.tick(1.0);
+    assert_eq!(tracker.check_end(&config), Some(MatchEndReason::TimeLimit));
+}
+
+#[test]
+fn test_solo_round_runs_until_death() {
+    let config = RoundConfig::default();
+    let mut tracker = MatchTracker::default();
+    tracker.begin([(10, 1)]);
+
+    assert_eq!(tracker.check_end(&config), None);
+    tracker.record_death(10, None);
+    assert_eq!(
+        tracker.check_end(&config),
+        Some(MatchEndReason::LastPlayerStanding)
+    );
+    assert_eq!(tracker.results(10.0).winner, None);
+}
+
+#[test]
+fn test_slots_reset_for_rematch() {
+    let mut slots = PlayerSlots::default();
+    let mut world = World::new();
+
+    for bits in [10, 11] {
+        let client_id = ClientId::Netcode(bits);
+        slots.assign(client_id, 4);
+        let slot = slots.get_mut(client_id).unwrap();
+        slot.ready = true;
+        slot.entity = world.spawn_empty().id();
+    }
+
+    let stale = slots.reset_for_rematch();
+    assert_eq!(stale.len(), 2);
+    assert!(!slots.all_ready());
+    assert!(slots.contains_player(ClientId::Netcode(10).to_bits()));
+    assert!(slots.reset_for_rematch().is_empty());
+}
+
+fn arena_walls(harness: &mut Harness) -> usize {
+    let world = harness.server.world_mut();
+    world
+        .query_filtered::<(), With<ArenaWall>>()
+        .iter(world)
+        .count()
+}
+
+#[test]
+fn test_rematch_reset_keeps_arena_walls() {
+    let mut harness = Harness::with_server(2, |app| {
+        app.insert_resource(RoundConfig {
+            time_limit_secs: 1.0,
+            game_over_secs: 0.1,
+            results_secs: 0.1,
+        })
+        .add_plugins(RoundPlugin);
+    });
+    harness.start_match();
+    assert_eq!(arena_walls(&mut harness), 4);
+
+    let in_results = harness.step_until(Duration::from_secs(3), |harness| {
+        harness.phase() == GamePhase::Results
+    });
+    assert!(in_results, "round never reached the results screen");
+    let reset = harness.step_until(Duration::from_secs(1), |harness| {
+        harness.phase() != GamePhase::Results
+    });
+    assert!(reset, "round never left the results screen");
+
+    assert_eq!(arena_walls(&mut harness), 4);
+}
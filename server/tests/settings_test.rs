@@ -0,0 +1,126 @@
+use boid_wars_server::settings::{parse_override, Settings};
+use boid_wars_shared::ConfigError;
+use std::path::Path;
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn error_keys(errors: Vec<ConfigError>) -> Vec<String> {
+    errors.into_iter().map(|error| error.key).collect()
+}
+
+#[test]
+fn test_nothing_layered_gives_defaults() {
+    let settings = Settings::layered(None, vec![], &[]).unwrap();
+    assert_eq!(settings, Settings::default());
+}
+
+#[test]
+fn test_layers_override_in_order() {
+    let file = r#"
+        [flocking]
+        max_speed = 600.0
+        cohesion_weight = 2
+
+        [physics]
+        projectile_lifetime = 1.5
+    "#;
+
+    let from_file = Settings::layered(Some(("test.toml", file)), vec![], &[]).unwrap();
+    assert_eq!(from_file.flocking.max_speed, 600.0);
+    assert_eq!(from_file.flocking.cohesion_weight, 2.0);
+    assert_eq!(from_file.physics.projectile_lifetime.as_secs_f32(), 1.5);
+
+    let vars = env(&[("BOID_WARS_FLOCKING__MAX_SPEED", "700")]);
+    let from_env = Settings::layered(Some(("test.toml", file)), vars.clone(), &[]).unwrap();
+    assert_eq!(from_env.flocking.max_speed, 700.0);
+
+    let cli = [parse_override("flocking.max_speed=800").unwrap()];
+    let from_cli = Settings::layered(Some(("test.toml", file)), vars, &cli).unwrap();
+    assert_eq!(from_cli.flocking.max_speed, 800.0);
+    assert_eq!(from_cli.flocking.cohesion_weight, 2.0);
+}
+
+#[test]
+fn test_legacy_env_names_still_work() {
+    let vars = env(&[
+        ("BOID_WARS_MAX_PLAYERS", "8"),
+        ("BOID_WARS_SERVER_BIND_ADDR", "127.0.0.1:9000"),
+        ("BOID_WARS_DEV_KEY", &"ab".repeat(32)),
+        ("UNRELATED", "ignored"),
+    ]);
+    let settings = Settings::layered(None, vars, &[]).unwrap();
+
+    assert_eq!(settings.game.max_players, 8);
+    assert_eq!(settings.network.server_bind_addr, "127.0.0.1:9000");
+    assert_eq!(settings.network.dev_key, [0xab; 32]);
+}
+
+#[test]
+fn test_bad_values_are_reported_not_defaulted() {
+    let file = r#"
+        [flocking]
+        max_sped = 600.0
+
+        [physics]
+        player_max_speed = -5.0
+
+        [weather]
+        rain = true
+    "#;
+    let vars = env(&[
+        ("BOID_WARS_GAME_WIDTH", "wide"),
+        ("BOID_WARS_MAX_PLAYERS", "1"),
+        ("BOID_WARS_DEV_KEY", "not-a-key"),
+    ]);
+    let cli = [parse_override("groups.lod_far_distance=100").unwrap()];
+
+    let errors = error_keys(Settings::layered(Some(("test.toml", file)), vars, &cli).unwrap_err());
+    for expected in [
+        "flocking.max_sped",
+        "physics.player_max_speed",
+        "weather",
+        "game.game_width",
+        "game.max_players",
+        "network",
+        "groups.lod_medium_distance",
+    ] {
+        assert!(
+            errors.iter().any(|key| key == expected),
+            "missing {expected} in {errors:?}"
+        );
+    }
+}
+
+#[test]
+fn test_arena_must_fit_the_wire_encoding() {
+    let cli = [
+        parse_override("game.game_width=5000").unwrap(),
+        parse_override("game.game_height=4096").unwrap(),
+    ];
+    let errors = error_keys(Settings::layered(None, vec![], &cli).unwrap_err());
+    assert_eq!(errors, vec!["game.game_width"]);
+}
+
+#[test]
+fn test_malformed_file_and_overrides() {
+    let errors = Settings::layered(
+        Some(("broken.toml", "[flocking\nmax_speed = ")),
+        vec![],
+        &[],
+    )
+    .unwrap_err();
+    assert_eq!(error_keys(errors), vec!["broken.toml"]);
+
+    assert!(parse_override("max_speed=3").is_err());
+    assert!(parse_override("flocking.max_speed").is_err());
+}
+
+#[test]
+fn test_example_settings_file_is_valid() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("settings.example.toml");
+    Settings::load(Some(&path), vec![], &[]).unwrap();
+}
@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use boid_wars_server::spatial_grid::{
+    update_spatial_grid, LayerMask, SpatialGrid, SpatialGridPlugin,
+};
+use boid_wars_shared::{Boid, Player, Position};
+use std::time::Duration;
+
+fn entity(n: u32) -> Entity {
+    Entity::from_raw(n)
+}
+
+fn entities(entries: impl Iterator<Item = Entity>) -> Vec<Entity> {
+    let mut entities: Vec<Entity> = entries.collect();
+    entities.sort();
+    entities
+}
+
+#[test]
+fn test_radius_queries_filter_by_layer_and_distance() {
+    let mut grid = SpatialGrid::new(1000.0, 1000.0, 100.0);
+    grid.insert(entity(1), Vec2::new(500.0, 500.0), LayerMask::BOID);
+    grid.insert(entity(2), Vec2::new(540.0, 500.0), LayerMask::PLAYER);
+    grid.insert(entity(3), Vec2::new(620.0, 500.0), LayerMask::BOID);
+    grid.insert(entity(4), Vec2::new(900.0, 900.0), LayerMask::BOID);
+
+    let boids = grid.within_radius(Vec2::new(500.0, 500.0), 150.0, LayerMask::BOID);
+    assert_eq!(
+        entities(boids.map(|e| e.entity)),
+        vec![entity(1), entity(3)]
+    );
+
+    let near = grid.within_radius(
+        Vec2::new(500.0, 500.0),
+        50.0,
+        LayerMask::BOID | LayerMask::PLAYER,
+    );
+    assert_eq!(entities(near.map(|e| e.entity)), vec![entity(1), entity(2)]);
+
+    let entry = grid.get(entity(2)).unwrap();
+    assert_eq!(entry.position, Vec2::new(540.0, 500.0));
+    assert_eq!(entry.layers, LayerMask::PLAYER);
+}
+
+#[test]
+fn test_k_nearest_orders_by_distance() {
+    let mut grid = SpatialGrid::new(2000.0, 2000.0, 100.0);
+    for (n, x) in [
+        (1, 1030.0),
+        (2, 1000.0),
+        (3, 1450.0),
+        (4, 1210.0),
+        (5, 1100.0),
+    ] {
+        grid.insert(entity(n), Vec2::new(x, 1000.0), LayerMask::BOID);
+    }
+    grid.insert(entity(9), Vec2::new(1010.0, 1000.0), LayerMask::OBSTACLE);
+
+    let nearest: Vec<Entity> = grid
+        .k_nearest(Vec2::new(1000.0, 1000.0), 3, 1000.0, LayerMask::BOID)
+        .iter()
+        .map(|e| e.entity)
+        .collect();
+    assert_eq!(nearest, vec![entity(2), entity(1), entity(5)]);
+
+    // Range caps the search even when fewer than k are found
+    let in_range = grid.k_nearest(Vec2::new(1000.0, 1000.0), 10, 250.0, LayerMask::BOID);
+    assert_eq!(in_range.len(), 4);
+    assert!(grid
+        .k_nearest(Vec2::new(0.0, 0.0), 1, 500.0, LayerMask::BOID)
+        .is_empty());
+
+    // Unbounded ranges search the whole grid instead of overflowing
+    for range in [f32::INFINITY, f32::MAX] {
+        let all = grid.k_nearest(Vec2::new(0.0, 0.0), 10, range, LayerMask::BOID);
+        assert_eq!(all.len(), 5);
+    }
+}
+
+#[test]
+fn test_raycast_finds_first_hit_along_the_ray() {
+    let mut grid = SpatialGrid::new(1000.0, 1000.0, 50.0);
+    grid.insert(entity(1), Vec2::new(400.0, 105.0), LayerMask::PLAYER);
+    grid.insert(entity(2), Vec2::new(250.0, 98.0), LayerMask::PLAYER);
+    grid.insert(entity(3), Vec2::new(200.0, 100.0), LayerMask::OBSTACLE);
+    grid.insert(entity(4), Vec2::new(300.0, 160.0), LayerMask::PLAYER);
+
+    let hit = grid
+        .raycast(
+            Vec2::new(100.0, 100.0),
+            Vec2::X,
+            500.0,
+            10.0,
+            LayerMask::PLAYER,
+        )
+        .unwrap();
+    assert_eq!(hit.entry.entity, entity(2));
+    // 2 units off the ray, so it touches 150 - sqrt(10^2 - 2^2) along
+    assert!((hit.distance - (150.0 - 96f32.sqrt())).abs() < 0.01);
+
+    let hits = grid.segment_query(
+        Vec2::new(100.0, 100.0),
+        Vec2::new(500.0, 100.0),
+        10.0,
+        LayerMask::PLAYER | LayerMask::OBSTACLE,
+    );
+    let order: Vec<Entity> = hits.iter().map(|hit| hit.entry.entity).collect();
+    assert_eq!(order, vec![entity(3), entity(2), entity(1)]);
+
+    // Too short to reach anything
+    assert!(grid
+        .raycast(
+            Vec2::new(100.0, 100.0),
+            Vec2::X,
+            80.0,
+            10.0,
+            LayerMask::PLAYER
+        )
+        .is_none());
+}
+
+#[test]
+fn test_moves_update_in_place_or_change_cells() {
+    let mut grid = SpatialGrid::new(1000.0, 1000.0, 100.0);
+    grid.insert(entity(1), Vec2::new(110.0, 110.0), LayerMask::BOID);
+    grid.insert(entity(2), Vec2::new(120.0, 120.0), LayerMask::BOID);
+
+    // Same cell
+    grid.insert(entity(1), Vec2::new(150.0, 150.0), LayerMask::BOID);
+    assert_eq!(grid.len(), 2);
+    assert_eq!(
+        grid.get(entity(1)).unwrap().position,
+        Vec2::new(150.0, 150.0)
+    );
+
+    // New cell, then the entity left behind is still found
+    grid.insert(entity(1), Vec2::new(750.0, 750.0), LayerMask::BOID);
+    assert_eq!(grid.len(), 2);
+    assert_eq!(
+        grid.get(entity(2)).unwrap().position,
+        Vec2::new(120.0, 120.0)
+    );
+    let near_old: Vec<Entity> = grid.get_nearby_entities(Vec2::new(150.0, 150.0), 10.0);
+    assert_eq!(near_old, vec![entity(2)]);
+
+    // Off the grid removes it
+    grid.insert(entity(1), Vec2::new(-50.0, 10.0), LayerMask::BOID);
+    assert!(!grid.contains(entity(1)));
+    assert_eq!(grid.get_stats().total_entities, 1);
+}
+
+#[test]
+fn test_system_tracks_moves_layers_and_despawns() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(SpatialGrid::new(1000.0, 1000.0, 100.0))
+        .add_systems(Update, update_spatial_grid);
+
+    let boid = app
+        .world_mut()
+        .spawn((Boid { id: 1 }, Position(Vec2::new(100.0, 100.0))))
+        .id();
+    let player = app
+        .world_mut()
+        .spawn((
+            Player {
+                id: 1,
+                name: "pilot".to_string(),
+            },
+            Position(Vec2::new(300.0, 300.0)),
+        ))
+        .id();
+    app.update();
+
+    let grid = app.world().resource::<SpatialGrid>();
+    assert_eq!(grid.get(boid).unwrap().layers, LayerMask::BOID);
+    assert_eq!(grid.get(player).unwrap().layers, LayerMask::PLAYER);
+
+    app.world_mut().get_mut::<Position>(boid).unwrap().0 = Vec2::new(800.0, 800.0);
+    app.world_mut().despawn(player);
+    app.update();
+
+    let grid = app.world().resource::<SpatialGrid>();
+    assert_eq!(grid.get(boid).unwrap().position, Vec2::new(800.0, 800.0));
+    assert!(!grid.contains(player));
+    assert_eq!(grid.len(), 1);
+
+    // Becoming a boid changes the layer without a move
+    let rock = app
+        .world_mut()
+        .spawn(Position(Vec2::new(500.0, 500.0)))
+        .id();
+    app.update();
+    assert_eq!(
+        app.world()
+            .resource::<SpatialGrid>()
+            .get(rock)
+            .unwrap()
+            .layers,
+        LayerMask::OTHER
+    );
+    app.world_mut().entity_mut(rock).insert(Boid { id: 2 });
+    app.update();
+    assert_eq!(
+        app.world()
+            .resource::<SpatialGrid>()
+            .get(rock)
+            .unwrap()
+            .layers,
+        LayerMask::BOID
+    );
+}
+
+#[test]
+fn test_plugin_forgets_despawns_between_fixed_ticks() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(SpatialGridPlugin)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+    app.update();
+
+    let boid = app
+        .world_mut()
+        .spawn((Boid { id: 1 }, Position(Vec2::new(100.0, 100.0))))
+        .id();
+    app.world_mut().resource_mut::<SpatialGrid>().insert(
+        boid,
+        Vec2::new(100.0, 100.0),
+        LayerMask::BOID,
+    );
+    app.world_mut().despawn(boid);
+
+    // The clock is stopped, so no fixed tick runs in any of these frames
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(app.world().resource::<SpatialGrid>().is_empty());
+}
+
+#[test]
+fn test_plugin_still_initializes_grid() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(SpatialGridPlugin);
+    app.update();
+    assert!(app.world().resource::<SpatialGrid>().is_empty());
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use boid_wars_server::targeting::{intercept_time, AccuracyProfile, AimMode, TargetingConfig};
+use boid_wars_shared::GroupArchetype;
+
+const SHOT_SPEED: f32 = 400.0;
+
+fn recon() -> GroupArchetype {
+    GroupArchetype::Recon {
+        detection_range: 400.0,
+        flee_speed_bonus: 1.3,
+    }
+}
+
+fn assault() -> GroupArchetype {
+    GroupArchetype::Assault {
+        aggression_multiplier: 1.0,
+        preferred_range: 150.0,
+    }
+}
+
+fn defensive() -> GroupArchetype {
+    GroupArchetype::Defensive {
+        protection_radius: 400.0,
+        retreat_threshold: 0.4,
+    }
+}
+
+#[test]
+fn test_intercept_meets_a_crossing_target() {
+    let shooter = Vec2::ZERO;
+    let target = Vec2::new(300.0, 0.0);
+    let velocity = Vec2::new(0.0, 200.0);
+
+    let time = intercept_time(shooter, target, velocity, SHOT_SPEED).unwrap();
+    let meeting = target + velocity * time;
+    assert!((meeting.length() - SHOT_SPEED * time).abs() < 0.01);
+
+    // A target standing still is met by aiming straight at it
+    let still = intercept_time(shooter, target, Vec2::ZERO, SHOT_SPEED).unwrap();
+    assert!((still - 300.0 / SHOT_SPEED).abs() < 0.0001);
+}
+
+#[test]
+fn test_intercept_gives_up_on_targets_it_cannot_catch() {
+    let shooter = Vec2::ZERO;
+    let target = Vec2::new(300.0, 0.0);
+
+    // Running away faster than the shot
+    let fleeing = Vec2::new(SHOT_SPEED * 2.0, 0.0);
+    assert_eq!(intercept_time(shooter, target, fleeing, SHOT_SPEED), None);
+
+    // Exactly as fast: only closing in can be met
+    let closing = Vec2::new(-SHOT_SPEED, 0.0);
+    let time = intercept_time(shooter, target, closing, SHOT_SPEED).unwrap();
+    assert!((time - 300.0 / (2.0 * SHOT_SPEED)).abs() < 0.0001);
+    let receding = Vec2::new(SHOT_SPEED, 0.0);
+    assert_eq!(intercept_time(shooter, target, receding, SHOT_SPEED), None);
+}
+
+#[test]
+fn test_archetypes_aim_differently() {
+    let config = TargetingConfig::default();
+    let shooter = Vec2::ZERO;
+    let target = Vec2::new(300.0, 0.0);
+    let velocity = Vec2::new(0.0, 200.0);
+
+    let recon = config.profile(&recon());
+    assert_eq!(recon.spread_multiplier, 0.0);
+    let perfect = recon.aim_point(shooter, target, velocity, SHOT_SPEED);
+    let time = intercept_time(shooter, target, velocity, SHOT_SPEED).unwrap();
+    assert!(perfect.distance(target + velocity * time) < 0.01);
+
+    // Assault leads less and sprays more
+    let assault = config.profile(&assault());
+    let sloppy = assault.aim_point(shooter, target, velocity, SHOT_SPEED);
+    assert!(sloppy.y > target.y && sloppy.y < perfect.y);
+    assert!(assault.spread_multiplier > recon.spread_multiplier);
+
+    // Defensive fires where the target is heading
+    let defensive = config.profile(&defensive());
+    assert_eq!(
+        defensive.aim_point(shooter, target, velocity, SHOT_SPEED),
+        target + velocity * config.defensive_suppression_lead_time
+    );
+}
+
+#[test]
+fn test_ungrouped_boids_shoot_straight() {
+    let profile = AccuracyProfile::UNGROUPED;
+    assert_eq!(profile.mode, AimMode::Direct);
+    let target = Vec2::new(300.0, 0.0);
+    assert_eq!(
+        profile.aim_point(Vec2::ZERO, target, Vec2::new(0.0, 200.0), SHOT_SPEED),
+        target
+    );
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use boid_wars_shared::wire::{
+    apply_position_delta, apply_velocity_delta, dequantize_angle, position_delta, quantize_angle,
+    read_angle, read_position, read_velocity, velocity_delta, write_angle, write_position,
+    write_velocity, PositionDelta, QuantizedDelta, VelocityDelta, MAX_ARENA_EXTENT,
+};
+
+#[test]
+fn test_full_encodings_round_trip() {
+    let position = Vec2::new(812.37, 455.5);
+    let velocity = Vec2::new(-143.2, 87.9);
+
+    let mut buffer = Vec::new();
+    write_position(&mut buffer, position).unwrap();
+    write_velocity(&mut buffer, velocity).unwrap();
+    write_angle(&mut buffer, 1.25).unwrap();
+    assert_eq!(buffer.len(), 10);
+
+    let mut reader = buffer.as_slice();
+    assert!(read_position(&mut reader).unwrap().distance(position) < 0.05);
+    assert!(read_velocity(&mut reader).unwrap().distance(velocity) < 0.05);
+    assert!((read_angle(&mut reader).unwrap() - 1.25).abs() < 0.001);
+}
+
+#[test]
+fn test_positions_outside_bounds_saturate() {
+    let mut buffer = Vec::new();
+    write_position(&mut buffer, Vec2::new(-10_000.0, 10_000.0)).unwrap();
+    let decoded = read_position(&mut buffer.as_slice()).unwrap();
+
+    assert!(decoded.x < 0.0);
+    assert!(decoded.y > 1000.0);
+}
+
+#[test]
+fn test_positions_span_the_protocol_range() {
+    // Independent of the local game config, so larger arenas decode the same on both ends
+    let far_corner = Vec2::splat(MAX_ARENA_EXTENT);
+    let mut buffer = Vec::new();
+    write_position(&mut buffer, far_corner).unwrap();
+    let decoded = read_position(&mut buffer.as_slice()).unwrap();
+    assert!(decoded.distance(far_corner) < 0.05);
+}
+
+#[test]
+fn test_angles_wrap() {
+    use std::f32::consts::{PI, TAU};
+
+    let a = dequantize_angle(quantize_angle(0.5 + TAU));
+    assert!((a - 0.5).abs() < 0.001);
+
+    // π and -π are the same heading
+    assert_eq!(quantize_angle(PI), quantize_angle(-PI));
+}
+
+#[test]
+fn test_deltas_reconstruct_quantized_state() {
+    let base = Vec2::new(400.0, 300.0);
+    let next = Vec2::new(401.7, 298.9);
+
+    let delta = position_delta(base, next);
+    assert!(apply_position_delta(base, &delta).distance(next) < 0.05);
+
+    let velocity = Vec2::new(120.0, -40.0);
+    let steered = Vec2::new(118.5, -43.25);
+    let delta = velocity_delta(velocity, steered);
+    assert!(apply_velocity_delta(velocity, &delta).distance(steered) < 0.05);
+}
+
+#[test]
+fn test_delta_messages_round_trip_through_bincode() {
+    let config = bincode::config::standard();
+
+    // A tick of boid movement fits in a few bytes
+    let step = PositionDelta(position_delta(
+        Vec2::new(400.0, 300.0),
+        Vec2::new(401.7, 298.9),
+    ));
+    let bytes = bincode::serde::encode_to_vec(step, config).unwrap();
+    assert!(bytes.len() <= 4);
+    let (decoded, _): (PositionDelta, _) =
+        bincode::serde::decode_from_slice(&bytes, config).unwrap();
+    assert_eq!(decoded, step);
+
+    let jump = VelocityDelta(QuantizedDelta {
+        dx: 70_000,
+        dy: -70_000,
+    });
+    let bytes = bincode::serde::encode_to_vec(jump, config).unwrap();
+    let (decoded, _): (VelocityDelta, _) =
+        bincode::serde::decode_from_slice(&bytes, config).unwrap();
+    assert_eq!(decoded, jump);
+}
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use boid_wars_server::zone::{ZoneConfig, ZonePhase, ZoneStage, ZoneState};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn short_config() -> ZoneConfig {
+    ZoneConfig {
+        enabled: true,
+        phases: vec![
+            ZonePhase {
+                wait_secs: 1.0,
+                shrink_secs: 2.0,
+                radius_fraction: 0.5,
+                damage_per_second: 5.0,
+            },
+            ZonePhase {
+                wait_secs: 1.0,
+                shrink_secs: 1.0,
+                radius_fraction: 0.25,
+                damage_per_second: 20.0,
+            },
+        ],
+        replication_interval: 0.1,
+    }
+}
+
+#[test]
+fn test_zone_progresses_through_phases() {
+    let config = short_config();
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut zone = ZoneState::default();
+
+    assert!(!zone.is_active());
+    assert_eq!(zone.damage_per_second(&config), 0.0);
+
+    zone.start(&config, Vec2::new(1200.0, 900.0), &mut rng);
+    assert_eq!(zone.stage, ZoneStage::Waiting);
+    assert_eq!(zone.center, Vec2::new(600.0, 450.0));
+    let initial_radius = zone.radius;
+    assert!((zone.next_radius - initial_radius * 0.5).abs() < 0.001);
+
+    // Wait out the first phase
+    zone.tick(&config, 1.0, &mut rng);
+    assert_eq!(zone.stage, ZoneStage::Shrinking);
+
+    // Halfway through the shrink
+    zone.tick(&config, 1.0, &mut rng);
+    assert!(zone.radius < initial_radius);
+    assert!(zone.radius > zone.next_radius);
+
+    // Shrink finishes and the next phase begins
+    zone.tick(&config, 1.0, &mut rng);
+    assert_eq!(zone.stage, ZoneStage::Waiting);
+    assert_eq!(zone.phase_index, 1);
+    assert!((zone.radius - initial_radius * 0.5).abs() < 0.001);
+    assert_eq!(zone.damage_per_second(&config), 20.0);
+
+    zone.tick(&config, 1.0, &mut rng);
+    zone.tick(&config, 1.0, &mut rng);
+    assert_eq!(zone.stage, ZoneStage::Final);
+    assert!((zone.radius - initial_radius * 0.25).abs() < 0.001);
+
+    // Final zone keeps dealing the last phase's damage
+    assert_eq!(zone.damage_per_second(&config), 20.0);
+
+    zone.reset();
+    assert!(!zone.is_active());
+}
+
+#[test]
+fn test_next_zone_inside_current() {
+    let config = short_config();
+
+    for seed in 0..50 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut zone = ZoneState::default();
+        zone.start(&config, Vec2::new(1200.0, 900.0), &mut rng);
+
+        assert!(
+            zone.next_center.distance(zone.center) + zone.next_radius <= zone.radius + 0.01,
+            "seed {seed}: next zone escapes current zone"
+        );
+    }
+}
+
+#[test]
+fn test_zone_contains() {
+    let config = short_config();
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut zone = ZoneState::default();
+
+    // Inactive zone is safe everywhere
+    assert!(zone.contains(Vec2::new(-10_000.0, 0.0)));
+
+    zone.start(&config, Vec2::new(1200.0, 900.0), &mut rng);
+    assert!(zone.contains(zone.center));
+    assert!(!zone.contains(zone.center + Vec2::X * (zone.radius + 1.0)));
+
+    let replicated = zone.to_replicated(&config);
+    assert_eq!(replicated.radius, zone.radius);
+    assert!(!replicated.shrinking);
+    assert_eq!(replicated.seconds_remaining, 1.0);
+}
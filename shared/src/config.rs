@@ -1,32 +1,320 @@
-use std::env;
+//! Layered configuration: built-in defaults < TOML file < environment < command line.
+//!
+//! Every settings struct is a [`ConfigSection`] that maps to one `[section]` table.
+//! [`Layers`] stacks the sources on a TOML table, checks each override against the
+//! default value's type, then deserializes and validates the sections, collecting
+//! every problem as a [`ConfigError`] instead of falling back to a default.
+
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::sync::{LazyLock, OnceLock};
+
+use crate::movement::PLAYER_MOVE_SPEED;
+use crate::wire::MAX_ARENA_EXTENT;
+
+/// Prefix shared by every environment variable the game reads
+pub const ENV_PREFIX: &str = "BOID_WARS_";
+
+/// Environment variables that predate the layered config and the settings they map to.
+/// Any other setting can be set as `BOID_WARS_<SECTION>__<FIELD>`.
+pub const ENV_ALIASES: &[(&str, &str)] = &[
+    ("BOID_WARS_SERVER_BIND_ADDR", "network.server_bind_addr"),
+    (
+        "BOID_WARS_CLIENT_CONNECT_ADDR",
+        "network.client_connect_addr",
+    ),
+    ("BOID_WARS_PROTOCOL_ID", "network.protocol_id"),
+    ("BOID_WARS_DEV_KEY", "network.dev_key"),
+    ("BOID_WARS_GAME_WIDTH", "game.game_width"),
+    ("BOID_WARS_GAME_HEIGHT", "game.game_height"),
+    ("BOID_WARS_PLAYER_SPEED", "game.player_speed"),
+    ("BOID_WARS_BOID_SPEED", "game.boid_speed"),
+    ("BOID_WARS_DEFAULT_HEALTH", "game.default_health"),
+    ("BOID_WARS_SPAWN_X", "game.spawn_x"),
+    ("BOID_WARS_SPAWN_Y", "game.spawn_y"),
+    ("BOID_WARS_MAX_PLAYERS", "game.max_players"),
+    ("BOID_WARS_MIN_PLAYERS", "game.min_players"),
+    ("BOID_WARS_MAX_SPECTATORS", "game.max_spectators"),
+    (
+        "BOID_WARS_STATUS_LOG_INTERVAL",
+        "server.status_log_interval",
+    ),
+    (
+        "BOID_WARS_PERFORMANCE_LOG_INTERVAL",
+        "client.performance_log_interval",
+    ),
+];
+
+/// A setting that could not be applied, named by its `section.field` key
+/// (or by the file it came from when the file itself is malformed)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// One `[section]` of the config file
+pub trait ConfigSection: Serialize + DeserializeOwned + Default + Clone {
+    /// Table name in the config file and first part of every key
+    const SECTION: &'static str;
+
+    /// Report values that parse but make no sense
+    fn validate(&self, _check: &mut Validator<'_>) {}
+}
+
+/// Collects validation failures for one section
+pub struct Validator<'a> {
+    section: &'static str,
+    errors: &'a mut Vec<ConfigError>,
+}
+
+impl Validator<'_> {
+    /// Record an error for `field` unless `ok` holds
+    pub fn check(&mut self, field: &str, ok: bool, message: impl Into<String>) {
+        if !ok {
+            self.errors.push(ConfigError {
+                key: format!("{}.{}", self.section, field),
+                message: message.into(),
+            });
+        }
+    }
+
+    pub fn positive(&mut self, field: &str, value: f32) {
+        self.check(
+            field,
+            value > 0.0,
+            format!("must be greater than 0, got {value}"),
+        );
+    }
+
+    pub fn non_negative(&mut self, field: &str, value: f32) {
+        self.check(
+            field,
+            value >= 0.0,
+            format!("must not be negative, got {value}"),
+        );
+    }
+}
+
+/// Settings sources stacked on top of each other, lowest priority first
+#[derive(Debug, Default)]
+pub struct Layers {
+    table: toml::Table,
+    errors: Vec<ConfigError>,
+}
+
+impl Layers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a section with its built-in defaults as the bottom layer
+    pub fn with_defaults<T: ConfigSection>(mut self) -> Self {
+        let defaults = toml::Value::try_from(T::default())
+            .unwrap_or_else(|e| panic!("[{}] defaults are not representable: {}", T::SECTION, e));
+        self.table.insert(T::SECTION.to_string(), defaults);
+        self
+    }
+
+    /// Layer a TOML document; `origin` names it in errors
+    pub fn merge_file(&mut self, origin: &str, text: &str) {
+        let file: toml::Table = match text.parse() {
+            Ok(file) => file,
+            Err(e) => {
+                self.error(origin, e.to_string().trim_end());
+                return;
+            }
+        };
+
+        for (section, fields) in file {
+            if !self.table.contains_key(&section) {
+                self.error(&section, "unknown section");
+                continue;
+            }
+            let toml::Value::Table(fields) = fields else {
+                self.error(&section, "expected a table");
+                continue;
+            };
+            for (field, value) in fields {
+                self.set_value(&format!("{section}.{field}"), value);
+            }
+        }
+    }
+
+    /// Layer `BOID_WARS_*` variables that name a setting in one of the layered sections
+    pub fn merge_env(&mut self, vars: impl IntoIterator<Item = (String, String)>) {
+        for (name, raw) in vars {
+            let Some(key) = env_key(&name) else {
+                continue;
+            };
+            let section = key.split('.').next().unwrap_or_default();
+            if self.table.contains_key(section) {
+                self.set(&key, &raw);
+            }
+        }
+    }
+
+    /// Override one `section.field` with a raw string, as given on the environment or command line
+    pub fn set(&mut self, key: &str, raw: &str) {
+        let value = match self.existing(key) {
+            // Text settings take the raw string as-is, so addresses need no quoting
+            Some(toml::Value::String(_)) => toml::Value::String(raw.to_string()),
+            _ => parse_raw(raw),
+        };
+        self.set_value(key, value);
+    }
+
+    /// Deserialize and validate a section; on failure the error is kept and defaults are returned
+    pub fn section<T: ConfigSection>(&mut self) -> T {
+        let Some(value) = self.table.get(T::SECTION).cloned() else {
+            self.error(T::SECTION, "section was not layered");
+            return T::default();
+        };
+
+        match value.try_into::<T>() {
+            Ok(section) => {
+                section.validate(&mut Validator {
+                    section: T::SECTION,
+                    errors: &mut self.errors,
+                });
+                section
+            }
+            Err(e) => {
+                self.error(T::SECTION, e.to_string().trim_end());
+                T::default()
+            }
+        }
+    }
+
+    /// Everything that went wrong while layering, or `value` if nothing did
+    pub fn finish<T>(self, value: T) -> Result<T, Vec<ConfigError>> {
+        if self.errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn existing(&self, key: &str) -> Option<&toml::Value> {
+        let (section, field) = key.split_once('.')?;
+        self.table.get(section)?.get(field)
+    }
+
+    fn set_value(&mut self, key: &str, value: toml::Value) {
+        let Some(existing) = self.existing(key) else {
+            self.error(key, "unknown setting");
+            return;
+        };
+
+        let value = match (existing, value) {
+            (toml::Value::Float(_), toml::Value::Integer(i)) => toml::Value::Float(i as f64),
+            // Arrays (the dev key) also accept strings; the section's deserializer decides
+            (toml::Value::Array(_), value) => value,
+            (existing, value) if existing.type_str() == value.type_str() => value,
+            (existing, value) => {
+                let message = format!("expected {}, got {}", existing.type_str(), value);
+                self.error(key, message);
+                return;
+            }
+        };
+
+        let (section, field) = key.split_once('.').expect("existing keys are dotted");
+        if let Some(toml::Value::Table(fields)) = self.table.get_mut(section) {
+            fields.insert(field.to_string(), value);
+        }
+    }
+
+    fn error(&mut self, key: &str, message: impl Into<String>) {
+        self.errors.push(ConfigError {
+            key: key.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+/// The `section.field` an environment variable overrides, if any
+pub fn env_key(name: &str) -> Option<String> {
+    if let Some((_, key)) = ENV_ALIASES.iter().find(|(alias, _)| *alias == name) {
+        return Some(key.to_string());
+    }
+    let (section, field) = name.strip_prefix(ENV_PREFIX)?.split_once("__")?;
+    Some(format!(
+        "{}.{}",
+        section.to_lowercase(),
+        field.to_lowercase()
+    ))
+}
+
+/// Read a raw override as a TOML value, treating anything unparseable as a string
+fn parse_raw(raw: &str) -> toml::Value {
+    format!("value = {raw}")
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Format errors one per line for logs and panic messages
+pub fn error_report(errors: &[ConfigError]) -> String {
+    errors
+        .iter()
+        .map(|error| format!("  {error}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
 /// Network configuration shared between client and server
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NetworkConfig {
     pub server_bind_addr: String,    // Server binds to this address
     pub client_connect_addr: String, // Client connects to this address
     pub protocol_id: u64,
+    #[serde(deserialize_with = "deserialize_dev_key")]
     pub dev_key: [u8; 32],
 }
 
 impl Default for NetworkConfig {
     fn default() -> Self {
         Self {
-            server_bind_addr: env::var("BOID_WARS_SERVER_BIND_ADDR")
-                .unwrap_or_else(|_| "0.0.0.0:8080".to_string()),
-            client_connect_addr: env::var("BOID_WARS_CLIENT_CONNECT_ADDR")
-                .unwrap_or_else(|_| "127.0.0.1:8080".to_string()),
-            protocol_id: env::var("BOID_WARS_PROTOCOL_ID")
-                .unwrap_or_else(|_| "12345".to_string())
-                .parse()
-                .unwrap_or(12345),
-            dev_key: parse_dev_key(),
+            server_bind_addr: "0.0.0.0:8080".to_string(),
+            client_connect_addr: "127.0.0.1:8080".to_string(),
+            protocol_id: 12345,
+            // Default secure dev key (better than all zeros)
+            dev_key: [
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23,
+                24, 25, 26, 27, 28, 29, 30, 31, 32,
+            ],
         }
     }
 }
 
+impl ConfigSection for NetworkConfig {
+    const SECTION: &'static str = "network";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        check.check(
+            "server_bind_addr",
+            self.server_bind_addr
+                .parse::<std::net::SocketAddr>()
+                .is_ok(),
+            format!("'{}' is not an ip:port address", self.server_bind_addr),
+        );
+    }
+}
+
 /// Game configuration shared between client and server
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GameConfig {
     pub game_width: f32,
     pub game_height: f32,
@@ -35,62 +323,117 @@ pub struct GameConfig {
     pub default_health: f32,
     pub spawn_x: f32,
     pub spawn_y: f32,
+    pub max_players: u8,
+    pub min_players: u8,
+    /// Connections allowed to watch beyond the player slots
+    pub max_spectators: u8,
 }
 
 impl Default for GameConfig {
     fn default() -> Self {
         Self {
-            game_width: env::var("BOID_WARS_GAME_WIDTH")
-                .unwrap_or_else(|_| "1600.0".to_string())
-                .parse()
-                .unwrap_or(1600.0),
-            game_height: env::var("BOID_WARS_GAME_HEIGHT")
-                .unwrap_or_else(|_| "1200.0".to_string())
-                .parse()
-                .unwrap_or(1200.0),
-            player_speed: env::var("BOID_WARS_PLAYER_SPEED")
-                .unwrap_or_else(|_| "200.0".to_string())
-                .parse()
-                .unwrap_or(200.0),
-            boid_speed: env::var("BOID_WARS_BOID_SPEED")
-                .unwrap_or_else(|_| "150.0".to_string())
-                .parse()
-                .unwrap_or(150.0),
-            default_health: env::var("BOID_WARS_DEFAULT_HEALTH")
-                .unwrap_or_else(|_| "1000.0".to_string())
-                .parse()
-                .unwrap_or(1000.0),
-            spawn_x: env::var("BOID_WARS_SPAWN_X")
-                .unwrap_or_else(|_| "800.0".to_string())
-                .parse()
-                .unwrap_or(800.0),
-            spawn_y: env::var("BOID_WARS_SPAWN_Y")
-                .unwrap_or_else(|_| "600.0".to_string())
-                .parse()
-                .unwrap_or(600.0),
+            game_width: 1600.0,
+            game_height: 1200.0,
+            player_speed: PLAYER_MOVE_SPEED,
+            boid_speed: 150.0,
+            default_health: 1000.0,
+            spawn_x: 800.0,
+            spawn_y: 600.0,
+            max_players: 16,
+            min_players: 2,
+            max_spectators: 32,
+        }
+    }
+}
+
+impl ConfigSection for GameConfig {
+    const SECTION: &'static str = "game";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        check.positive("game_width", self.game_width);
+        check.positive("game_height", self.game_height);
+        // Positions are quantized over a fixed range on the wire
+        for (field, extent) in [
+            ("game_width", self.game_width),
+            ("game_height", self.game_height),
+        ] {
+            check.check(
+                field,
+                extent <= MAX_ARENA_EXTENT,
+                format!("must be at most {MAX_ARENA_EXTENT}, got {extent}"),
+            );
         }
+        check.positive("player_speed", self.player_speed);
+        check.positive("boid_speed", self.boid_speed);
+        check.positive("default_health", self.default_health);
+        check.check(
+            "spawn_x",
+            (0.0..=self.game_width).contains(&self.spawn_x),
+            format!("{} is outside the arena", self.spawn_x),
+        );
+        check.check(
+            "spawn_y",
+            (0.0..=self.game_height).contains(&self.spawn_y),
+            format!("{} is outside the arena", self.spawn_y),
+        );
+        // Lobby supports 2-64 player sessions
+        check.check(
+            "max_players",
+            (2..=64).contains(&self.max_players),
+            format!("must be between 2 and 64, got {}", self.max_players),
+        );
+        check.check(
+            "min_players",
+            (1..=self.max_players).contains(&self.min_players),
+            format!(
+                "must be between 1 and max_players ({}), got {}",
+                self.max_players, self.min_players
+            ),
+        );
     }
 }
 
 /// Server-specific configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ServerConfig {
     pub status_log_interval: f32,
+    /// Health and metrics HTTP endpoint; empty to disable
+    pub health_bind_addr: String,
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
-            status_log_interval: env::var("BOID_WARS_STATUS_LOG_INTERVAL")
-                .unwrap_or_else(|_| "5.0".to_string())
-                .parse()
-                .unwrap_or(5.0),
+            status_log_interval: 5.0,
+            health_bind_addr: "0.0.0.0:9090".to_string(),
         }
     }
 }
 
+impl ConfigSection for ServerConfig {
+    const SECTION: &'static str = "server";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        check.positive("status_log_interval", self.status_log_interval);
+        check.check(
+            "health_bind_addr",
+            self.health_bind_addr.is_empty()
+                || self
+                    .health_bind_addr
+                    .parse::<std::net::SocketAddr>()
+                    .is_ok(),
+            format!(
+                "'{}' is not an ip:port address (leave empty to disable)",
+                self.health_bind_addr
+            ),
+        );
+    }
+}
+
 /// Client-specific configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ClientConfig {
     pub performance_log_interval: f32,
 }
@@ -98,51 +441,153 @@ pub struct ClientConfig {
 impl Default for ClientConfig {
     fn default() -> Self {
         Self {
-            performance_log_interval: env::var("BOID_WARS_PERFORMANCE_LOG_INTERVAL")
-                .unwrap_or_else(|_| "5.0".to_string())
-                .parse()
-                .unwrap_or(5.0),
+            performance_log_interval: 5.0,
         }
     }
 }
 
-/// Parse development key from environment or use secure default
-fn parse_dev_key() -> [u8; 32] {
-    if let Ok(key_str) = env::var("BOID_WARS_DEV_KEY") {
-        // Try to parse as hex string
-        if key_str.len() == 64 {
-            let mut key = [0u8; 32];
-            if hex::decode_to_slice(&key_str, &mut key).is_ok() {
-                return key;
-            }
-        }
-        // Try to parse as comma-separated bytes
-        if let Ok(bytes) = key_str
-            .split(',')
-            .map(|s| s.trim().parse::<u8>())
-            .collect::<Result<Vec<_>, _>>()
-        {
-            if bytes.len() == 32 {
-                let mut key = [0u8; 32];
-                key.copy_from_slice(&bytes);
-                return key;
-            }
+impl ConfigSection for ClientConfig {
+    const SECTION: &'static str = "client";
+
+    fn validate(&self, check: &mut Validator<'_>) {
+        check.positive("performance_log_interval", self.performance_log_interval);
+    }
+}
+
+/// Accept the dev key as 32 bytes, a 64-character hex string or comma-separated bytes
+fn deserialize_dev_key<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DevKey {
+        Bytes([u8; 32]),
+        Text(String),
+    }
+
+    match DevKey::deserialize(deserializer)? {
+        DevKey::Bytes(key) => Ok(key),
+        DevKey::Text(text) => parse_dev_key(&text).ok_or_else(|| {
+            de::Error::custom("dev_key must be 64 hex characters or 32 comma-separated bytes")
+        }),
+    }
+}
+
+fn parse_dev_key(key_str: &str) -> Option<[u8; 32]> {
+    let mut key = [0u8; 32];
+
+    // Try to parse as hex string
+    if key_str.len() == 64 && hex::decode_to_slice(key_str, &mut key).is_ok() {
+        return Some(key);
+    }
+
+    // Try to parse as comma-separated bytes
+    let bytes = key_str
+        .split(',')
+        .map(|s| s.trim().parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    if bytes.len() != 32 {
+        return None;
+    }
+    key.copy_from_slice(&bytes);
+    Some(key)
+}
+
+/// Defaults plus environment for one section, for globals read before anything was
+/// installed. Binaries call [`install_from_env`] or [`install_shared`] at startup so a bad
+/// variable is reported there; this only panics for code that skipped both.
+fn from_env<T: ConfigSection>() -> T {
+    let mut layers = Layers::new().with_defaults::<T>();
+    layers.merge_env(std::env::vars());
+    let section = layers.section::<T>();
+    layers.finish(section).unwrap_or_else(|errors| {
+        panic!(
+            "invalid [{}] configuration:\n{}",
+            T::SECTION,
+            error_report(&errors)
+        )
+    })
+}
+
+// The globals read whatever was installed first: binaries install at startup, anything
+// else gets defaults plus environment on first use.
+static NETWORK_SLOT: OnceLock<NetworkConfig> = OnceLock::new();
+static GAME_SLOT: OnceLock<GameConfig> = OnceLock::new();
+static SERVER_SLOT: OnceLock<ServerConfig> = OnceLock::new();
+static CLIENT_SLOT: OnceLock<ClientConfig> = OnceLock::new();
+
+/// Make layered settings the values behind the global configs.
+///
+/// Installs all of them or none: fails if any was already read, since that reader saw
+/// different values.
+pub fn install_shared(
+    network: NetworkConfig,
+    game: GameConfig,
+    server: ServerConfig,
+    client: ClientConfig,
+) -> Result<(), Vec<ConfigError>> {
+    fn read_early(section: &str) -> ConfigError {
+        ConfigError {
+            key: section.to_string(),
+            message: "was read before the layered config was installed".to_string(),
         }
     }
 
-    // Default secure dev key (better than all zeros)
-    [
-        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
-        26, 27, 28, 29, 30, 31, 32,
+    let taken: Vec<ConfigError> = [
+        (NetworkConfig::SECTION, NETWORK_SLOT.get().is_some()),
+        (GameConfig::SECTION, GAME_SLOT.get().is_some()),
+        (ServerConfig::SECTION, SERVER_SLOT.get().is_some()),
+        (ClientConfig::SECTION, CLIENT_SLOT.get().is_some()),
     ]
-}
+    .into_iter()
+    .filter(|(_, taken)| *taken)
+    .map(|(section, _)| read_early(section))
+    .collect();
+    if !taken.is_empty() {
+        return Err(taken);
+    }
 
-// Remove circular reference - LazyLock instances below provide global access
+    let mut errors = Vec::new();
+    if NETWORK_SLOT.set(network).is_err() {
+        errors.push(read_early(NetworkConfig::SECTION));
+    }
+    if GAME_SLOT.set(game).is_err() {
+        errors.push(read_early(GameConfig::SECTION));
+    }
+    if SERVER_SLOT.set(server).is_err() {
+        errors.push(read_early(ServerConfig::SECTION));
+    }
+    if CLIENT_SLOT.set(client).is_err() {
+        errors.push(read_early(ClientConfig::SECTION));
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
 
-// Lazy static instances for global access
-use std::sync::LazyLock;
+/// Layer defaults and environment for every shared section and install the result,
+/// for binaries without a settings file of their own
+pub fn install_from_env() -> Result<(), Vec<ConfigError>> {
+    let mut layers = Layers::new()
+        .with_defaults::<NetworkConfig>()
+        .with_defaults::<GameConfig>()
+        .with_defaults::<ServerConfig>()
+        .with_defaults::<ClientConfig>();
+    layers.merge_env(std::env::vars());
+    let network = layers.section();
+    let game = layers.section();
+    let server = layers.section();
+    let client = layers.section();
+    layers.finish(())?;
+    install_shared(network, game, server, client)
+}
 
-pub static NETWORK_CONFIG: LazyLock<NetworkConfig> = LazyLock::new(NetworkConfig::default);
-pub static GAME_CONFIG: LazyLock<GameConfig> = LazyLock::new(GameConfig::default);
-pub static SERVER_CONFIG: LazyLock<ServerConfig> = LazyLock::new(ServerConfig::default);
-pub static CLIENT_CONFIG: LazyLock<ClientConfig> = LazyLock::new(ClientConfig::default);
+pub static NETWORK_CONFIG: LazyLock<NetworkConfig> =
+    LazyLock::new(|| NETWORK_SLOT.get_or_init(from_env).clone());
+pub static GAME_CONFIG: LazyLock<GameConfig> =
+    LazyLock::new(|| GAME_SLOT.get_or_init(from_env).clone());
+pub static SERVER_CONFIG: LazyLock<ServerConfig> =
+    LazyLock::new(|| SERVER_SLOT.get_or_init(from_env).clone());
+pub static CLIENT_CONFIG: LazyLock<ClientConfig> =
+    LazyLock::new(|| CLIENT_SLOT.get_or_init(from_env).clone());
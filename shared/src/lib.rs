@@ -1,7 +1,12 @@
 // Shared types between server and client
 
 pub mod config;
+pub mod formation;
+pub mod movement;
 pub mod protocol;
+pub mod wire;
 
 pub use config::*;
+pub use formation::*;
+pub use movement::*;
 pub use protocol::*;
@@ -0,0 +1,99 @@
+//! Ship movement rules shared by the server simulation and client prediction.
+//!
+//! Both sides must produce the same result for the same input, so anything
+//! that changes how a ship responds to `PlayerInput` belongs here.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{GameConfig, GAME_CONFIG};
+
+/// Default ship speed while a movement key is held (pixels/second);
+/// the running value is `MovementRules::player_speed`
+pub const PLAYER_MOVE_SPEED: f32 = 200.0;
+
+/// Default per-tick velocity damping applied after the input sets velocity
+pub const PLAYER_DAMPING_FACTOR: f32 = 0.98;
+
+/// Default distance the ship's center stays from the arena walls (collider half-size)
+pub const PLAYER_WALL_MARGIN: f32 = 31.2;
+
+/// Thrust for a movement vector: full while any direction is held
+pub fn input_thrust(movement: Vec2) -> f32 {
+    if movement.length() > 0.0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// The values a ship's response to input depends on.
+///
+/// The server tunes some of them at runtime, so it sends its running values to
+/// clients in `GameStateUpdate` and prediction never uses the client's own config.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct MovementRules {
+    /// Ship speed while a movement key is held (pixels/second)
+    pub player_speed: f32,
+    /// Per-tick velocity damping applied after the input sets velocity
+    pub damping_factor: f32,
+    /// Distance the ship's center stays from the arena walls (collider half-size)
+    pub wall_margin: f32,
+    /// Arena width and height
+    pub arena_size: Vec2,
+}
+
+impl Default for MovementRules {
+    fn default() -> Self {
+        Self::new(&GAME_CONFIG, PLAYER_DAMPING_FACTOR, PLAYER_WALL_MARGIN)
+    }
+}
+
+impl MovementRules {
+    pub fn new(game_config: &GameConfig, damping_factor: f32, wall_margin: f32) -> Self {
+        Self {
+            player_speed: game_config.player_speed,
+            damping_factor,
+            wall_margin,
+            arena_size: Vec2::new(game_config.game_width, game_config.game_height),
+        }
+    }
+
+    /// Velocity set directly from the input, before damping
+    pub fn input_velocity(&self, movement: Vec2, thrust: f32) -> Vec2 {
+        if thrust > 0.0 {
+            movement.normalize_or_zero() * self.player_speed
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    /// Velocity the ship actually travels at for an input
+    pub fn player_velocity(&self, movement: Vec2) -> Vec2 {
+        self.input_velocity(movement, input_thrust(movement)) * self.damping_factor
+    }
+
+    /// Advance a ship position by `dt` seconds of `movement`, kept inside the arena
+    pub fn step_player_position(&self, position: Vec2, movement: Vec2, dt: f32) -> Vec2 {
+        let next = position + self.player_velocity(movement) * dt;
+        let margin = Vec2::splat(self.wall_margin);
+        next.clamp(margin, self.arena_size - margin)
+    }
+}
+
+/// Sprite angle for an aim direction (sprites point up at angle 0)
+pub fn aim_angle(aim: Vec2) -> f32 {
+    aim.y.atan2(aim.x) - std::f32::consts::FRAC_PI_2
+}
+
+/// Signed shortest rotation from `current` to `target`, in [-π, π]
+pub fn shortest_angle_delta(current: f32, target: f32) -> f32 {
+    let mut delta = target - current;
+    while delta > std::f32::consts::PI {
+        delta -= std::f32::consts::TAU;
+    }
+    while delta < -std::f32::consts::PI {
+        delta += std::f32::consts::TAU;
+    }
+    delta
+}
@@ -1,22 +1,23 @@
+use bevy::diagnostic::{Diagnostic, DiagnosticsStore};
 use bevy::ecs::entity::MapEntities;
 use bevy::prelude::*;
 use lightyear::prelude::*;
+use lightyear::transport::io::IoDiagnosticsPlugin;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-use crate::GAME_CONFIG;
+use crate::{wire, MovementRules, GAME_CONFIG};
 
 // Re-export Vec2 for use in other crates
 pub use bevy::prelude::Vec2;
 
 // Components
 
-/// Player number to distinguish between players
-#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub enum PlayerNumber {
-    Player1,
-    Player2,
-}
+/// Player number to distinguish between players (1-based lobby slot)
+#[derive(
+    Component, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+pub struct PlayerNumber(pub u8);
 
 /// Player entity component
 #[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -25,6 +26,15 @@ pub struct Player {
     pub name: String,
 }
 
+/// Last input the server applied to this ship, for client reconciliation (replicated)
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct InputAck {
+    /// `PlayerInput::sequence` of the input in effect
+    pub sequence: u32,
+    /// Seconds of movement simulated with that input so far
+    pub applied_secs: f32,
+}
+
 /// Position component (replicated)
 #[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq, Deref, DerefMut)]
 pub struct Position(pub Vec2);
@@ -138,6 +148,29 @@ impl Default for Health {
     }
 }
 
+/// Battle-royale safe zone (replicated)
+///
+/// Entities outside the circle take `damage_per_second` until they move back in.
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SafeZone {
+    pub center: Vec2,
+    pub radius: f32,
+    /// Where the zone will end up after the next shrink
+    pub next_center: Vec2,
+    pub next_radius: f32,
+    /// True while the circle is moving towards the next zone
+    pub shrinking: bool,
+    /// Seconds until the current wait or shrink stage ends
+    pub seconds_remaining: f32,
+    pub damage_per_second: f32,
+}
+
+impl SafeZone {
+    pub fn contains(&self, position: Vec2) -> bool {
+        position.distance_squared(self.center) <= self.radius * self.radius
+    }
+}
+
 // Group System Components
 
 /// Core group component for managing boid groups
@@ -283,9 +316,13 @@ pub enum ArenaZone {
 pub struct GroupVelocity(pub Vec2);
 
 /// Replicated group data for network optimization
-#[derive(Component, Serialize, Deserialize, Clone, PartialEq)]
+///
+/// Sent instead of the individual boids while a group is far from every
+/// player; clients lay out a stand-in swarm from the formation.
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct ReplicatedGroup {
     pub id: u32,
+    /// Group center
     pub position: Vec2,
     pub formation: Formation,
     pub member_count: u32,
@@ -303,6 +340,8 @@ pub struct PlayerInput {
     pub aim: Vec2,
     /// Is firing
     pub fire: bool,
+    /// Increasing per-client counter, echoed back in `InputAck`
+    pub sequence: u32,
 }
 
 impl PlayerInput {
@@ -312,8 +351,15 @@ impl PlayerInput {
             movement,
             aim,
             fire,
+            sequence: 0,
         }
     }
+
+    /// Tag the input with its sequence number
+    pub fn with_sequence(mut self, sequence: u32) -> Self {
+        self.sequence = sequence;
+        self
+    }
 }
 
 // Messages
@@ -324,6 +370,7 @@ impl Default for PlayerInput {
             movement: Vec2::ZERO,
             aim: Vec2::ZERO,
             fire: false,
+            sequence: 0,
         }
     }
 }
@@ -396,6 +443,28 @@ impl MapEntities for ServerFullMessage {
     fn map_entities<M: EntityMapper>(&mut self, _entity_mapper: &mut M) {}
 }
 
+/// Why a client is watching instead of playing
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Reflect)]
+pub enum SpectateReason {
+    /// All player slots were taken when the client connected
+    ServerFull,
+    /// The client connected while a round was running
+    MatchInProgress,
+    /// The client's ship was destroyed this round
+    Eliminated,
+}
+
+/// Sent to a client when it starts or stops spectating
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Reflect)]
+pub struct SpectatorStatus {
+    /// Why the client is spectating, `None` once it holds a player slot again
+    pub reason: Option<SpectateReason>,
+}
+
+impl MapEntities for SpectatorStatus {
+    fn map_entities<M: EntityMapper>(&mut self, _entity_mapper: &mut M) {}
+}
+
 /// Represents the current phase of the game session
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Reflect)]
 pub enum GamePhase {
@@ -405,6 +474,10 @@ pub enum GamePhase {
     Lobby,
     /// Game is active and playable
     InGame,
+    /// A win condition was met, the arena is settling before results
+    GameOver,
+    /// End-of-round results are shown before returning to the lobby
+    Results,
 }
 
 /// Message sent by a client to indicate they are ready to start the game
@@ -415,6 +488,50 @@ impl MapEntities for PlayerReady {
     fn map_entities<M: EntityMapper>(&mut self, _entity_mapper: &mut M) {}
 }
 
+/// Seconds between a client's [`ClientBandwidth`] reports
+pub const BANDWIDTH_REPORT_INTERVAL_SECS: f32 = 1.0;
+
+/// Transport throughput a client measured on its own connection, reported so the server
+/// can expose per-client bandwidth (lightyear only counts the server socket as a whole)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub struct ClientBandwidth {
+    /// Bytes per second received from the server
+    pub received_bytes_per_sec: f32,
+    /// Bytes per second sent to the server
+    pub sent_bytes_per_sec: f32,
+}
+
+impl ClientBandwidth {
+    /// Smoothed rates from lightyear's client IO diagnostics, once it has measured any
+    pub fn from_diagnostics(diagnostics: &DiagnosticsStore) -> Option<Self> {
+        let bytes_per_sec = |path| {
+            diagnostics
+                .get(path)
+                .and_then(Diagnostic::smoothed)
+                .map(|kb| (kb * 1000.0) as f32)
+        };
+        Some(Self {
+            received_bytes_per_sec: bytes_per_sec(&IoDiagnosticsPlugin::BYTES_IN)?,
+            sent_bytes_per_sec: bytes_per_sec(&IoDiagnosticsPlugin::BYTES_OUT)?,
+        })
+    }
+}
+
+impl MapEntities for ClientBandwidth {
+    fn map_entities<M: EntityMapper>(&mut self, _entity_mapper: &mut M) {}
+}
+
+/// Ready status of a single lobby slot
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Reflect)]
+pub struct LobbyPlayerStatus {
+    /// Client ID of the player in this slot
+    pub player_id: u64,
+    /// Player number assigned to the slot
+    pub player_number: u8,
+    /// Whether the player has indicated they are ready
+    pub ready: bool,
+}
+
 /// Server broadcast containing the current game state and player statuses
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Reflect)]
 pub struct GameStateUpdate {
@@ -422,16 +539,64 @@ pub struct GameStateUpdate {
     pub phase: GamePhase,
     /// Number of connected players
     pub player_count: u8,
-    /// Whether player 1 has indicated they are ready
-    pub player1_ready: bool,
-    /// Whether player 2 has indicated they are ready
-    pub player2_ready: bool,
+    /// Maximum number of players allowed
+    pub max_players: u8,
+    /// Ready status for every connected player, ordered by player number
+    pub players: Vec<LobbyPlayerStatus>,
+    /// Number of connected spectators without a player slot
+    pub spectator_count: u8,
+    /// Movement rules the server simulates ships with, for client prediction
+    pub movement: MovementRules,
 }
 
 impl MapEntities for GameStateUpdate {
     fn map_entities<M: EntityMapper>(&mut self, _entity_mapper: &mut M) {}
 }
 
+/// Why a round ended
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Reflect)]
+pub enum MatchEndReason {
+    /// Only one player (or none) is left alive
+    LastPlayerStanding,
+    /// The round time limit expired
+    TimeLimit,
+}
+
+/// End-of-round stats for a single player
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Reflect)]
+pub struct PlayerMatchStats {
+    /// Client ID of the player
+    pub player_id: u64,
+    /// Player number the player had this round
+    pub player_number: u8,
+    /// Final placement, 1 is the winner
+    pub placement: u8,
+    /// Other players eliminated by this player
+    pub kills: u32,
+    /// Total damage dealt to players and boids
+    pub damage_dealt: f32,
+    /// Seconds survived since the round started
+    pub survival_secs: f32,
+}
+
+/// Server broadcast sent when a round ends
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Reflect)]
+pub struct MatchResults {
+    /// Client ID of the winner, if anyone survived
+    pub winner: Option<u64>,
+    pub reason: MatchEndReason,
+    /// Length of the round in seconds
+    pub duration_secs: f32,
+    /// Stats for every player in the round, ordered by placement
+    pub players: Vec<PlayerMatchStats>,
+    /// Seconds until the server returns to the lobby
+    pub next_round_in: f32,
+}
+
+impl MapEntities for MatchResults {
+    fn map_entities<M: EntityMapper>(&mut self, _entity_mapper: &mut M) {}
+}
+
 // Bundles
 
 #[derive(Bundle)]
@@ -504,18 +669,39 @@ impl Plugin for ProtocolPlugin {
         app.register_type::<ProjectileDespawnEvent>();
         app.register_type::<HealthChangeEvent>();
         app.register_type::<ServerFullMessage>();
+        app.register_type::<SpectateReason>();
+        app.register_type::<SpectatorStatus>();
         app.register_type::<GamePhase>();
         app.register_type::<PlayerReady>();
+        app.register_type::<ClientBandwidth>();
+        app.register_type::<LobbyPlayerStatus>();
         app.register_type::<GameStateUpdate>();
+        app.register_type::<MatchEndReason>();
+        app.register_type::<PlayerMatchStats>();
+        app.register_type::<MatchResults>();
 
         // Register components for replication using correct Lightyear 0.20 API
         // Server-authoritative components (unidirectional to save bandwidth)
-        app.register_component::<Position>(ChannelDirection::ServerToClient);
-        app.register_component::<Rotation>(ChannelDirection::ServerToClient);
-        app.register_component::<Velocity>(ChannelDirection::ServerToClient);
+        // Hot movement components use the quantized encodings in `wire`, with
+        // position and velocity sent as deltas against the last acked state
+        app.register_component_custom_serde::<Position>(
+            ChannelDirection::ServerToClient,
+            wire::position_serialize_fns(),
+        )
+        .add_delta_compression();
+        app.register_component_custom_serde::<Rotation>(
+            ChannelDirection::ServerToClient,
+            wire::rotation_serialize_fns(),
+        );
+        app.register_component_custom_serde::<Velocity>(
+            ChannelDirection::ServerToClient,
+            wire::velocity_serialize_fns(),
+        )
+        .add_delta_compression();
         // Health is sent via HealthChangeEvent instead of continuous replication
         app.register_component::<Player>(ChannelDirection::ServerToClient);
         app.register_component::<PlayerNumber>(ChannelDirection::ServerToClient);
+        app.register_component::<InputAck>(ChannelDirection::ServerToClient);
         app.register_component::<Boid>(ChannelDirection::ServerToClient);
         app.register_component::<BoidSpriteGroup>(ChannelDirection::ServerToClient);
         app.register_component::<BoidSize>(ChannelDirection::ServerToClient);
@@ -523,9 +709,11 @@ impl Plugin for ProtocolPlugin {
         // BoidCombatState is server-only and not registered for replication
         app.register_component::<Obstacle>(ChannelDirection::ServerToClient);
         app.register_component::<Projectile>(ChannelDirection::ServerToClient);
+        app.register_component::<SafeZone>(ChannelDirection::ServerToClient);
 
-        // Group system components - NOT replicated to save bandwidth
-        // Groups are server-side only for AI coordination
+        // Group AI components are server-side only; distant groups are
+        // summarized by ReplicatedGroup instead of their individual boids
+        app.register_component::<ReplicatedGroup>(ChannelDirection::ServerToClient);
 
         // Register messages
         app.register_message::<PlayerInput>(ChannelDirection::ClientToServer);
@@ -533,8 +721,11 @@ impl Plugin for ProtocolPlugin {
         app.register_message::<ProjectileDespawnEvent>(ChannelDirection::ServerToClient);
         app.register_message::<HealthChangeEvent>(ChannelDirection::ServerToClient);
         app.register_message::<ServerFullMessage>(ChannelDirection::ServerToClient);
+        app.register_message::<SpectatorStatus>(ChannelDirection::ServerToClient);
         app.register_message::<PlayerReady>(ChannelDirection::ClientToServer);
+        app.register_message::<ClientBandwidth>(ChannelDirection::ClientToServer);
         app.register_message::<GameStateUpdate>(ChannelDirection::ServerToClient);
+        app.register_message::<MatchResults>(ChannelDirection::ServerToClient);
 
         // Register channels using correct Lightyear 0.20 API
         app.add_channel::<UnreliableChannel>(ChannelSettings {
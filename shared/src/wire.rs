@@ -0,0 +1,273 @@
+//! Compact wire encodings for the hot replicated components.
+//!
+//! `Position`, `Velocity` and `Rotation` change on every boid every tick, so
+//! they are sent quantized instead of as raw `f32`s:
+//!
+//! - positions as 16-bit fixed point over `MAX_ARENA_EXTENT` (plus a margin),
+//! - velocities as 16-bit fixed point in 1/16 px/s steps,
+//! - angles as 16-bit fractions of a turn.
+//!
+//! Position and velocity updates of entities carrying
+//! `movement_delta_compression()` are also delta-encoded against the last
+//! state the client acknowledged, in quantization steps, which lightyear's
+//! bincode encoding sends as small varints for boids that move a little per
+//! tick.
+//!
+//! The position range is a protocol constant rather than the local
+//! `GameConfig`, so server and client decode the same bytes to the same
+//! place whatever arena each was configured with.
+
+use bevy::prelude::*;
+use lightyear::prelude::DeltaCompression;
+use lightyear::protocol::SerializeFns;
+use lightyear::serialize::SerializationError;
+use lightyear::shared::replication::delta::Diffable;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use std::io::{self, Read, Write};
+
+use crate::{Position, Rotation, Velocity};
+
+/// Largest arena width or height positions can be encoded over
+pub const MAX_ARENA_EXTENT: f32 = 4096.0;
+
+/// Distance outside the arena positions can still be encoded (walls sit just outside)
+pub const POSITION_MARGIN: f32 = 128.0;
+
+/// Velocity quantization steps per px/s, giving a range of about ±2048 px/s
+pub const VELOCITY_STEPS_PER_UNIT: f32 = 16.0;
+
+/// Encodable span of one position axis, about 0.07px per step
+const POSITION_RANGE: f32 = MAX_ARENA_EXTENT + POSITION_MARGIN * 2.0;
+
+/// Quantize one axis of a position into the protocol range
+fn quantize_axis(value: f32) -> u16 {
+    let normalized = ((value + POSITION_MARGIN) / POSITION_RANGE).clamp(0.0, 1.0);
+    (normalized * u16::MAX as f32).round() as u16
+}
+
+fn dequantize_axis(value: u16) -> f32 {
+    value as f32 / u16::MAX as f32 * POSITION_RANGE - POSITION_MARGIN
+}
+
+/// Position as 16-bit fixed point over the protocol range
+pub fn quantize_position(position: Vec2) -> [u16; 2] {
+    [quantize_axis(position.x), quantize_axis(position.y)]
+}
+
+pub fn dequantize_position(quantized: [u16; 2]) -> Vec2 {
+    Vec2::new(dequantize_axis(quantized[0]), dequantize_axis(quantized[1]))
+}
+
+/// Velocity as 16-bit fixed point, saturating at the representable range
+pub fn quantize_velocity(velocity: Vec2) -> [i16; 2] {
+    let step = |value: f32| {
+        (value * VELOCITY_STEPS_PER_UNIT)
+            .round()
+            .clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    };
+    [step(velocity.x), step(velocity.y)]
+}
+
+pub fn dequantize_velocity(quantized: [i16; 2]) -> Vec2 {
+    Vec2::new(quantized[0] as f32, quantized[1] as f32) / VELOCITY_STEPS_PER_UNIT
+}
+
+/// Angle as a 16-bit fraction of a full turn
+pub fn quantize_angle(angle: f32) -> i16 {
+    let wrapped = (angle + PI).rem_euclid(std::f32::consts::TAU) - PI;
+    ((wrapped / PI * 32768.0).round() as i32) as i16
+}
+
+/// Angle in [-π, π)
+pub fn dequantize_angle(quantized: i16) -> f32 {
+    quantized as f32 / 32768.0 * PI
+}
+
+/// Change between two quantized states, in quantization steps
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuantizedDelta {
+    pub dx: i32,
+    pub dy: i32,
+}
+
+impl QuantizedDelta {
+    pub fn between(from: [i32; 2], to: [i32; 2]) -> Self {
+        Self {
+            dx: to[0] - from[0],
+            dy: to[1] - from[1],
+        }
+    }
+
+    pub fn apply(&self, base: [i32; 2]) -> [i32; 2] {
+        [base[0] + self.dx, base[1] + self.dy]
+    }
+}
+
+/// Delta-encode `Position` and `Velocity` updates of a replicated entity
+pub fn movement_delta_compression() -> DeltaCompression {
+    DeltaCompression::default()
+        .add::<Position>()
+        .add::<Velocity>()
+}
+
+/// Replicated delta of a `Position`.
+///
+/// Lightyear registers one delta message per delta type, so each component gets its own.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PositionDelta(pub QuantizedDelta);
+
+/// Replicated delta of a `Velocity`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VelocityDelta(pub QuantizedDelta);
+
+fn position_steps(position: Vec2) -> [i32; 2] {
+    quantize_position(position).map(i32::from)
+}
+
+fn steps_to_position(steps: [i32; 2]) -> Vec2 {
+    dequantize_position(steps.map(|step| step.clamp(0, u16::MAX as i32) as u16))
+}
+
+fn velocity_steps(velocity: Vec2) -> [i32; 2] {
+    quantize_velocity(velocity).map(i32::from)
+}
+
+fn steps_to_velocity(steps: [i32; 2]) -> Vec2 {
+    dequantize_velocity(steps.map(|step| step.clamp(i16::MIN as i32, i16::MAX as i32) as i16))
+}
+
+/// Delta from one position to another, in position quantization steps
+pub fn position_delta(from: Vec2, to: Vec2) -> QuantizedDelta {
+    QuantizedDelta::between(position_steps(from), position_steps(to))
+}
+
+pub fn apply_position_delta(base: Vec2, delta: &QuantizedDelta) -> Vec2 {
+    steps_to_position(delta.apply(position_steps(base)))
+}
+
+/// Delta from one velocity to another, in velocity quantization steps
+pub fn velocity_delta(from: Vec2, to: Vec2) -> QuantizedDelta {
+    QuantizedDelta::between(velocity_steps(from), velocity_steps(to))
+}
+
+pub fn apply_velocity_delta(base: Vec2, delta: &QuantizedDelta) -> Vec2 {
+    steps_to_velocity(delta.apply(velocity_steps(base)))
+}
+
+/// Write a full position (4 bytes)
+pub fn write_position(writer: &mut impl Write, position: Vec2) -> io::Result<()> {
+    for axis in quantize_position(position) {
+        writer.write_all(&axis.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn read_position(reader: &mut impl Read) -> io::Result<Vec2> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(dequantize_position([
+        u16::from_le_bytes([bytes[0], bytes[1]]),
+        u16::from_le_bytes([bytes[2], bytes[3]]),
+    ]))
+}
+
+/// Write a full velocity (4 bytes)
+pub fn write_velocity(writer: &mut impl Write, velocity: Vec2) -> io::Result<()> {
+    for axis in quantize_velocity(velocity) {
+        writer.write_all(&axis.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn read_velocity(reader: &mut impl Read) -> io::Result<Vec2> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(dequantize_velocity([
+        i16::from_le_bytes([bytes[0], bytes[1]]),
+        i16::from_le_bytes([bytes[2], bytes[3]]),
+    ]))
+}
+
+/// Write an angle (2 bytes)
+pub fn write_angle(writer: &mut impl Write, angle: f32) -> io::Result<()> {
+    writer.write_all(&quantize_angle(angle).to_le_bytes())
+}
+
+pub fn read_angle(reader: &mut impl Read) -> io::Result<f32> {
+    let mut bytes = [0u8; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(dequantize_angle(i16::from_le_bytes(bytes)))
+}
+
+// Replication hooks
+//
+// Lightyear's reader and writer types are private, so the hooks below leave them to
+// inference and only use their `Read`/`Write` impls.
+
+impl Diffable for Position {
+    type Delta = PositionDelta;
+
+    fn base_value() -> Self {
+        Position(Vec2::ZERO)
+    }
+
+    fn diff(&self, new: &Self) -> Self::Delta {
+        PositionDelta(position_delta(self.0, new.0))
+    }
+
+    fn apply_diff(&mut self, delta: &Self::Delta) {
+        self.0 = apply_position_delta(self.0, &delta.0);
+    }
+}
+
+impl Diffable for Velocity {
+    type Delta = VelocityDelta;
+
+    fn base_value() -> Self {
+        Velocity(Vec2::ZERO)
+    }
+
+    fn diff(&self, new: &Self) -> Self::Delta {
+        VelocityDelta(velocity_delta(self.0, new.0))
+    }
+
+    fn apply_diff(&mut self, delta: &Self::Delta) {
+        self.0 = apply_velocity_delta(self.0, &delta.0);
+    }
+}
+
+pub(crate) fn position_serialize_fns() -> SerializeFns<Position> {
+    SerializeFns {
+        serialize: |position, writer| -> Result<(), SerializationError> {
+            Ok(write_position(writer, position.0)?)
+        },
+        deserialize: |reader| -> Result<Position, SerializationError> {
+            Ok(Position(read_position(reader)?))
+        },
+    }
+}
+
+pub(crate) fn velocity_serialize_fns() -> SerializeFns<Velocity> {
+    SerializeFns {
+        serialize: |velocity, writer| -> Result<(), SerializationError> {
+            Ok(write_velocity(writer, velocity.0)?)
+        },
+        deserialize: |reader| -> Result<Velocity, SerializationError> {
+            Ok(Velocity(read_velocity(reader)?))
+        },
+    }
+}
+
+pub(crate) fn rotation_serialize_fns() -> SerializeFns<Rotation> {
+    SerializeFns {
+        serialize: |rotation, writer| -> Result<(), SerializationError> {
+            Ok(write_angle(writer, rotation.angle)?)
+        },
+        deserialize: |reader| -> Result<Rotation, SerializationError> {
+            Ok(Rotation {
+                angle: read_angle(reader)?,
+            })
+        },
+    }
+}